@@ -1,138 +1,279 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType, Value};
 
+use crate::calendar::CalendarRegistry;
+use crate::constraints;
+use crate::datetime::DateTimeExpr;
 use crate::error::AnalyzerError;
+use crate::event_schema::EventSchema;
+use crate::graph::DependencyGraph;
+use crate::policy::PolicySet;
+use crate::scoring::{self, ScoringFormula};
+use crate::timezone::Timezones;
+use crate::typecheck::TypeChecker;
+
+/// Entry of the symbol table resolved for a model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    /// Position of the evaluation in `LrolModel::evaluations`
+    pub index: usize,
+    pub evaluation_type: EvaluationType,
+    pub weight: Option<i32>,
+}
 
-#[derive(Debug)]
-pub enum DurationUnit {
-    Minutes,
-    Hours,
-    Days,
-    Weeks,
-    Months,
-    Years,
+/// Evaluation names declared by a model.
+///
+/// When a name is declared more than once the first declaration wins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    by_name: HashMap<String, usize>,
 }
 
-#[derive(Debug)]
-pub struct Duration {
-    pub value: i64,
-    pub unit: DurationUnit,
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a symbol, returning `false` if the name was already declared
+    pub fn insert(&mut self, symbol: Symbol) -> bool {
+        if self.by_name.contains_key(&symbol.name) {
+            return false;
+        }
+        self.by_name.insert(symbol.name.clone(), self.symbols.len());
+        self.symbols.push(symbol);
+        true
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.by_name.get(name).map(|&i| &self.symbols[i])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Symbols in declaration order
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter()
+    }
+}
+
+/// Outcome of analyzing a single model
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisResult {
+    pub symbols: SymbolTable,
+    pub dependency_graph: DependencyGraph,
+    pub errors: Vec<AnalyzerError>,
+    /// Findings that do not make the model invalid
+    pub warnings: Vec<AnalyzerError>,
 }
 
-#[derive(Debug)]
-pub enum DateTimeRef {
-    Now,
-    Timestamp(String),
-    Duration(Duration),
+impl AnalysisResult {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
 }
 
+/// Semantic analyzer for LROL models.
+///
+/// The analyzer holds no per-model state: every call to [`RuleAnalyzer::analyze`]
+/// builds its own symbol table and dependency graph, so a single instance can be
+/// reused across models and shared between threads.
+///
+/// When an event schema is configured, comparisons are also type-checked
+/// against it; see [`TypeChecker`]. Without one, `@` references, literals and
+/// function calls are still checked, but reported as warnings. Thresholds and weights are checked under
+/// the [`ScoringFormula`] the model names in `scoring`, or the configured one.
+#[derive(Debug, Clone, Default)]
 pub struct RuleAnalyzer {
-    evaluation_names: HashSet<String>,
-    dependency_graph: HashMap<String, Vec<String>>,
+    event_schema: Option<EventSchema>,
+    scoring_formula: ScoringFormula,
+    calendars: Option<CalendarRegistry>,
+    policies: Option<PolicySet>,
 }
 
 impl RuleAnalyzer {
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Analyzer that type-checks models against `schema`.
+    ///
+    /// A schema declared inline in a model's `event_schema` is merged on top,
+    /// overriding fields declared here.
+    pub fn with_event_schema(schema: EventSchema) -> Self {
         Self {
-            evaluation_names: HashSet::new(),
-            dependency_graph: HashMap::new(),
+            event_schema: Some(schema),
+            ..Self::default()
         }
     }
 
-    // Parse duration strings like "2 hours", "-3 days", etc.
-    fn parse_duration(duration_str: &str) -> Result<Duration, String> {
-        let trimmed = duration_str.trim().trim_matches('\'').trim_matches('"');
-        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+    pub fn event_schema(&self) -> Option<&EventSchema> {
+        self.event_schema.as_ref()
+    }
+
+    /// Formula used to check whether thresholds are reachable, for models
+    /// without a `scoring` field of their own
+    pub fn set_scoring_formula(&mut self, formula: ScoringFormula) {
+        self.scoring_formula = formula;
+    }
 
-        if parts.len() != 2 {
-            return Err("Duration must contain a number and a unit".to_string());
-        }
+    pub fn scoring_formula(&self) -> ScoringFormula {
+        self.scoring_formula
+    }
 
-        let value = parts[0]
-            .parse::<i64>()
-            .map_err(|_| "Invalid duration value")?;
-
-        let unit = match parts[1].to_lowercase().as_str() {
-            "minute" | "minutes" | "min" | "mins" => DurationUnit::Minutes,
-            "hour" | "hours" | "hr" | "hrs" => DurationUnit::Hours,
-            "day" | "days" => DurationUnit::Days,
-            "week" | "weeks" => DurationUnit::Weeks,
-            "month" | "months" => DurationUnit::Months,
-            "year" | "years" => DurationUnit::Years,
-            _ => return Err("Invalid duration unit".to_string()),
-        };
+    /// Business calendars that datetime expressions may name.
+    ///
+    /// Without them calendar names are not checked.
+    pub fn set_calendars(&mut self, calendars: CalendarRegistry) {
+        self.calendars = Some(calendars);
+    }
+
+    pub fn calendars(&self) -> Option<&CalendarRegistry> {
+        self.calendars.as_ref()
+    }
+
+    /// Organization policies every model must follow, reported as errors
+    pub fn set_policies(&mut self, policies: PolicySet) {
+        self.policies = Some(policies);
+    }
 
-        Ok(Duration { value, unit })
+    pub fn policies(&self) -> Option<&PolicySet> {
+        self.policies.as_ref()
     }
 
-    pub fn analyze(&mut self, model: &LrolModel) -> Result<(), Vec<AnalyzerError>> {
+    pub fn analyze(&self, model: &LrolModel) -> AnalysisResult {
         let mut errors = Vec::new();
 
         self.validate_schema_requirements(model, &mut errors);
+        Timezones::check_model(model, &mut errors);
 
         // First pass: collect all evaluation names and validate uniqueness
-        for evaluation in &model.evaluations {
-            if !self.evaluation_names.insert(evaluation.name.clone()) {
+        let mut symbols = SymbolTable::new();
+        for (index, evaluation) in model.evaluations.iter().enumerate() {
+            let declared = symbols.insert(Symbol {
+                name: evaluation.name.clone(),
+                index,
+                evaluation_type: evaluation.evaluation_type.clone(),
+                weight: evaluation.weight,
+            });
+            if !declared {
                 errors.push(AnalyzerError::DuplicateEvaluationName(
                     evaluation.name.clone(),
                 ));
             }
         }
 
-        // Second pass: validate evaluations and build dependency graph
+        // Second pass: validate evaluations against the symbol table
         for evaluation in &model.evaluations {
-            // Validate evaluation-specific rules
-            self.validate_evaluation(evaluation, &mut errors);
+            self.validate_evaluation(evaluation, &symbols, &mut errors);
+        }
 
-            // Build dependency graph for logical evaluations and string references
-            let mut dependencies = Vec::new();
+        // Third pass: build the dependency graph and report every cycle in it
+        let dependency_graph = DependencyGraph::from_model(model);
+        for cycle in dependency_graph.cycles() {
+            errors.push(AnalyzerError::CircularDependency {
+                evaluation_name: cycle.members[0].clone(),
+                dependency_chain: cycle.path,
+                edges: cycle.edges,
+            });
+        }
 
-            // Add logical operand dependencies
-            if let Some(operands) = &evaluation.operands {
-                dependencies.extend(operands.clone());
-            }
+        // Fourth pass: type-check operands, including event fields if the events
+        // are described. Without a schema the types are partly guessed, so the
+        // findings are only warnings
+        let mut unchecked = Vec::new();
+        match self.resolve_event_schema(model, &mut errors) {
+            Some(schema) => TypeChecker::new(&schema, &symbols).check_model(model, &mut errors),
+            None => TypeChecker::without_schema(&symbols).check_model(model, &mut unchecked),
+        }
 
-            // Add string reference dependencies
-            self.collect_string_references(evaluation, &mut dependencies);
+        if let Some(ref policies) = self.policies {
+            policies.check_model(model, &mut errors);
+        }
 
-            if !dependencies.is_empty() {
-                self.dependency_graph
-                    .insert(evaluation.name.clone(), dependencies);
+        // Finally, look for logical evaluations that can never or always fire
+        // and for thresholds the weights cannot reach
+        let mut warnings = Vec::new();
+        constraints::check_logical_evaluations(model, &mut warnings);
+        let formula = ScoringFormula::for_model(model, self.scoring_formula);
+        scoring::check_scoring(model, formula, &mut warnings);
+        Self::check_numeric_strings(model, &mut warnings);
+        for finding in unchecked {
+            // A number written as a string is already reported as such
+            let reported = matches!(finding, AnalyzerError::TypeMismatch { .. })
+                && warnings.iter().any(|warning| {
+                    matches!(warning, AnalyzerError::NumericString { .. })
+                        && warning.location() == finding.location()
+                });
+            if !reported {
+                warnings.push(finding);
             }
         }
+        Self::check_missing_weights(model, &mut warnings);
 
-        // Third pass: check for circular dependencies
-        if let Err(cycle) = self.check_circular_dependencies() {
-            errors.push(cycle);
+        AnalysisResult {
+            symbols,
+            dependency_graph,
+            errors,
+            warnings,
         }
+    }
 
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+    // Combine the configured schema with the one declared inline in the model
+    fn resolve_event_schema(
+        &self,
+        model: &LrolModel,
+        errors: &mut Vec<AnalyzerError>,
+    ) -> Option<EventSchema> {
+        let inline = match model.event_schema {
+            Some(ref value) => match EventSchema::from_lrol_value(value) {
+                Ok(schema) => Some(schema),
+                Err(reason) => {
+                    errors.push(AnalyzerError::InvalidEventSchema { reason });
+                    None
+                }
+            },
+            None => None,
+        };
+
+        match (&self.event_schema, inline) {
+            (Some(configured), Some(inline)) => {
+                let mut schema = configured.clone();
+                schema.merge(&inline);
+                Some(schema)
+            }
+            (Some(configured), None) => Some(configured.clone()),
+            (None, inline) => inline,
         }
     }
 
-    fn validate_evaluation(&self, evaluation: &Evaluation, errors: &mut Vec<AnalyzerError>) {
+    fn validate_evaluation(
+        &self,
+        evaluation: &Evaluation,
+        symbols: &SymbolTable,
+        errors: &mut Vec<AnalyzerError>,
+    ) {
         // Add datetime validation
         self.validate_datetime_expressions(evaluation, errors);
 
         // Validate string references
-        self.validate_string_references(evaluation, errors);
+        self.validate_string_references(evaluation, symbols, errors);
 
         // Validate weight range (1-5)
         if let Some(weight) = evaluation.weight {
-            if weight < 1 || weight > 5 {
-                errors.push(AnalyzerError::InvalidWeight {
-                    evaluation_name: evaluation.name.clone(),
-                    weight,
-                });
-            }
-        }
-        // Validate weight range (1-5)
-        if let Some(weight) = evaluation.weight {
-            if weight < 1 || weight > 5 {
+            if !(1..=5).contains(&weight) {
                 errors.push(AnalyzerError::InvalidWeight {
                     evaluation_name: evaluation.name.clone(),
                     weight,
@@ -166,7 +307,7 @@ impl RuleAnalyzer {
                         }
                         // Check if all operands reference existing evaluations
                         for operand in operands {
-                            if !self.evaluation_names.contains(operand) {
+                            if !symbols.contains(operand) {
                                 errors.push(AnalyzerError::MissingOperandReference {
                                     evaluation_name: evaluation.name.clone(),
                                     missing_operand: operand.clone(),
@@ -208,36 +349,84 @@ impl RuleAnalyzer {
         }
     }
 
-    // Helper function to extract evaluation names from string references
-    pub fn extract_references(value: &str) -> Vec<String> {
-        let mut refs = Vec::new();
-        for word in value.split_whitespace() {
-            if word.starts_with('@') {
-                // Remove @ and collect reference
-                refs.push(word[1..].to_string());
+    // Quoted numbers compared with an ordering operator are compared as text
+    fn check_numeric_strings(model: &LrolModel, warnings: &mut Vec<AnalyzerError>) {
+        const ORDERING_OPERATORS: [&str; 6] = [">", "<", ">=", "<=", "=<", "=>"];
+
+        for evaluation in &model.evaluations {
+            let conditions = evaluation
+                .conditions
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(i, c)| {
+                    (
+                        format!("conditions[{}].right", i),
+                        c.operator.as_deref(),
+                        c.right.as_ref(),
+                    )
+                });
+            let compared = std::iter::once((
+                "right".to_string(),
+                evaluation.operator.as_deref(),
+                evaluation.right.as_ref(),
+            ))
+            .chain(conditions);
+
+            for (field_name, operator, right) in compared {
+                let (Some(operator), Some(Value::String(value))) = (operator, right) else {
+                    continue;
+                };
+                if ORDERING_OPERATORS.contains(&operator.trim()) && is_plain_number(value.trim()) {
+                    warnings.push(AnalyzerError::NumericString {
+                        evaluation_name: evaluation.name.clone(),
+                        field_name,
+                        value: value.clone(),
+                    });
+                }
             }
         }
-        refs
     }
 
-    // Collect all string references from an evaluation
-    fn collect_string_references(&self, evaluation: &Evaluation, dependencies: &mut Vec<String>) {
-        if let Some(ref left) = evaluation.left {
-            dependencies.extend(Self::extract_references(left));
+    // An unweighted evaluation in an otherwise weighted rule is usually an oversight
+    fn check_missing_weights(model: &LrolModel, warnings: &mut Vec<AnalyzerError>) {
+        if model.evaluations.iter().all(|e| e.weight.is_none()) {
+            return;
         }
-
-        if let Some(Value::String(ref right)) = evaluation.right {
-            dependencies.extend(Self::extract_references(right));
+        // Aggregations never fire, so they need no weight
+        for evaluation in model
+            .evaluations
+            .iter()
+            .filter(|e| e.weight.is_none() && scoring::can_fire(e))
+        {
+            warnings.push(AnalyzerError::MissingWeight {
+                evaluation_name: evaluation.name.clone(),
+            });
         }
+    }
 
-        // Add more fields that might contain references here
+    // Helper function to extract evaluation names from string references
+    pub fn extract_references(value: &str) -> Vec<String> {
+        let mut refs = Vec::new();
+        for word in value.split_whitespace() {
+            // Remove @ and collect reference
+            if let Some(reference) = word.strip_prefix('@') {
+                refs.push(reference.to_string());
+            }
+        }
+        refs
     }
 
-    fn validate_string_references(&self, evaluation: &Evaluation, errors: &mut Vec<AnalyzerError>) {
+    fn validate_string_references(
+        &self,
+        evaluation: &Evaluation,
+        symbols: &SymbolTable,
+        errors: &mut Vec<AnalyzerError>,
+    ) {
         // Validate left operand references
         if let Some(ref left) = evaluation.left {
             for reference in Self::extract_references(left) {
-                if !self.evaluation_names.contains(&reference) {
+                if !symbols.contains(&reference) {
                     errors.push(AnalyzerError::InvalidStringReference {
                         evaluation_name: evaluation.name.clone(),
                         field_name: "left".to_string(),
@@ -250,7 +439,7 @@ impl RuleAnalyzer {
         // Validate right operand references if it's a string
         if let Some(Value::String(ref right)) = evaluation.right {
             for reference in Self::extract_references(right) {
-                if !self.evaluation_names.contains(&reference) {
+                if !symbols.contains(&reference) {
                     errors.push(AnalyzerError::InvalidStringReference {
                         evaluation_name: evaluation.name.clone(),
                         field_name: "right".to_string(),
@@ -261,90 +450,9 @@ impl RuleAnalyzer {
         }
     }
 
-    fn check_circular_dependencies(&self) -> Result<(), AnalyzerError> {
-        let mut visited = HashSet::new();
-        let mut path = Vec::new();
-
-        for start_node in self.dependency_graph.keys() {
-            if !visited.contains(start_node) {
-                if let Some(cycle) = self.detect_cycle(start_node, &mut visited, &mut path) {
-                    return Err(AnalyzerError::CircularDependency {
-                        evaluation_name: start_node.clone(),
-                        dependency_chain: cycle,
-                    });
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    fn detect_cycle(
-        &self,
-        node: &str,
-        visited: &mut HashSet<String>,
-        path: &mut Vec<String>,
-    ) -> Option<Vec<String>> {
-        if path.contains(&node.to_string()) {
-            let cycle_start = path.iter().position(|x| x == node).unwrap();
-            return Some(path[cycle_start..].to_vec());
-        }
-
-        if visited.contains(node) {
-            return None;
-        }
-
-        visited.insert(node.to_string());
-        path.push(node.to_string());
-
-        if let Some(dependencies) = self.dependency_graph.get(node) {
-            for dep in dependencies {
-                if let Some(cycle) = self.detect_cycle(dep, visited, path) {
-                    return Some(cycle);
-                }
-            }
-        }
-
-        path.pop();
-        None
-    }
-
     // Parse datetime expressions like datetime(now, '-2 hours')
-    fn parse_datetime_expression(expr: &str) -> Result<(DateTimeRef, Option<DateTimeRef>), String> {
-        // Basic syntax check
-        if !expr.starts_with("datetime(") || !expr.ends_with(')') {
-            return Err("Invalid datetime function syntax".to_string());
-        }
-
-        // Extract arguments
-        let args_str = &expr[9..expr.len() - 1];
-        let args: Vec<&str> = args_str.split(',').map(|s| s.trim()).collect();
-
-        if args.is_empty() || args.len() > 2 {
-            return Err("datetime() requires 1 or 2 arguments".to_string());
-        }
-
-        // Parse first argument
-        let first_arg = match args[0] {
-            "now" => DateTimeRef::Now,
-            ts if ts.starts_with('\'') || ts.starts_with('"') => {
-                DateTimeRef::Timestamp(ts.trim_matches('\'').trim_matches('"').to_string())
-            }
-            _ => return Err("Invalid first argument".to_string()),
-        };
-
-        // Parse optional second argument (duration)
-        let second_arg = if args.len() == 2 {
-            let duration_str = args[1].trim_matches('\'').trim_matches('"');
-            if duration_str.is_empty() {
-                return Err("Empty duration string".to_string());
-            }
-            Some(DateTimeRef::Duration(Self::parse_duration(duration_str)?))
-        } else {
-            None
-        };
-
-        Ok((first_arg, second_arg))
+    fn parse_datetime_expression(expr: &str) -> Result<DateTimeExpr, String> {
+        DateTimeExpr::parse(expr)
     }
 
     fn validate_datetime_expressions(
@@ -352,46 +460,72 @@ impl RuleAnalyzer {
         evaluation: &Evaluation,
         errors: &mut Vec<AnalyzerError>,
     ) {
-        // Validate left operand
+        let mut expressions: Vec<(String, &str)> = Vec::new();
         if let Some(ref left) = evaluation.left {
-            if left.starts_with("datetime(") {
-                match Self::parse_datetime_expression(left) {
-                    Err(reason) => {
-                        errors.push(AnalyzerError::InvalidDateTimeExpression {
-                            evaluation_name: evaluation.name.clone(),
-                            field_name: "left".to_string(),
-                            expression: left.clone(),
-                            reason,
-                        });
-                    }
-                    Ok(_) => {} // Valid datetime expression
-                }
+            expressions.push(("left".to_string(), left));
+        }
+        if let Some(Value::String(ref right)) = evaluation.right {
+            expressions.push(("right".to_string(), right));
+        }
+        if let Some(ref field) = evaluation.field {
+            expressions.push(("field".to_string(), field));
+        }
+        for (i, condition) in evaluation.conditions.iter().flatten().enumerate() {
+            if let Some(ref left) = condition.left {
+                expressions.push((format!("conditions[{}].left", i), left));
+            }
+            if let Some(Value::String(ref right)) = condition.right {
+                expressions.push((format!("conditions[{}].right", i), right));
             }
         }
 
-        // Validate right operand if it's a string
-        if let Some(Value::String(ref right)) = evaluation.right {
-            if right.starts_with("datetime(") {
-                match Self::parse_datetime_expression(right) {
-                    Err(reason) => {
-                        errors.push(AnalyzerError::InvalidDateTimeExpression {
-                            evaluation_name: evaluation.name.clone(),
-                            field_name: "right".to_string(),
-                            expression: right.clone(),
-                            reason,
-                        });
-                    }
-                    Ok(_) => {} // Valid datetime expression
+        for (field_name, expression) in expressions {
+            if !DateTimeExpr::is_call(expression) {
+                continue;
+            }
+            let parsed = match Self::parse_datetime_expression(expression) {
+                Ok(parsed) => parsed,
+                Err(reason) => {
+                    errors.push(AnalyzerError::InvalidDateTimeExpression {
+                        evaluation_name: evaluation.name.clone(),
+                        field_name,
+                        expression: expression.to_string(),
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            let Some(ref calendars) = self.calendars else {
+                continue;
+            };
+            for calendar in parsed.calendars() {
+                if calendars.get(calendar).is_none() {
+                    errors.push(AnalyzerError::UnknownCalendar {
+                        evaluation_name: evaluation.name.clone(),
+                        field_name: field_name.clone(),
+                        calendar: calendar.to_string(),
+                    });
                 }
             }
         }
     }
 }
 
+// Number written the way LROL accepts it unquoted, e.g. `-12` or `30000.50`
+fn is_plain_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    is_digits(whole) && fraction.is_none_or(is_digits)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lrol_parser::types::{Action, Evaluation, EvaluationType, Metadata, Value};
+    use lrol_parser::types::{Action, Evaluation, EvaluationType, Metadata, TimezoneSpec, Value};
 
     fn create_test_model() -> LrolModel {
         LrolModel {
@@ -409,6 +543,8 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
                 Evaluation {
                     name: "risk_check".to_string(),
@@ -419,27 +555,32 @@ mod tests {
                     operands: Some(vec!["amount_check".to_string()]),
                     weight: Some(4),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
             ],
-            actions: vec![
-                Action {
+            actions: vec![Action {
                 action_type: "flag_transaction".to_string(),
                 reason: "High risk transaction".to_string(),
-            }
-            ],
-            metadata: Some(Metadata{
+            }],
+            metadata: Some(Metadata {
                 created_by: Some("test_user".to_owned()),
                 created_at: Some("2024-01-01T12:00:00Z".to_owned()),
                 last_updated: Some("2024-01-01T12:00:00Z".to_owned()),
-                notes: Some("Test notes".to_owned())
-            })
+                notes: Some("Test notes".to_owned()),
+                allow_lints: None,
+            }),
+            event_schema: None,
+            timezone: None,
+            field_timezones: None,
+            scoring: None,
         }
     }
 
     #[test]
     fn test_valid_model() {
         let model = create_test_model();
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let res = analyzer.analyze(&model);
         println!("{:?}", res);
         assert!(res.is_ok());
@@ -457,14 +598,16 @@ mod tests {
             operands: None,
             weight: Some(3),
             aggregation: None,
+            field: None,
+            conditions: None,
         });
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e, AnalyzerError::DuplicateEvaluationName(_)))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::DuplicateEvaluationName(_))));
     }
 
     #[test]
@@ -479,15 +622,17 @@ mod tests {
             operands: Some(vec!["non_existent".to_string()]),
             weight: Some(3),
             aggregation: None,
+            field: None,
+            conditions: None,
         });
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
 
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e, AnalyzerError::MissingOperandReference { .. }))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::MissingOperandReference { .. })));
     }
 
     #[test]
@@ -503,6 +648,8 @@ mod tests {
                 operands: Some(vec!["eval2".to_string()]),
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             },
             Evaluation {
                 name: "eval2".to_string(),
@@ -513,15 +660,58 @@ mod tests {
                 operands: Some(vec!["eval1".to_string()]),
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             },
         ];
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e, AnalyzerError::CircularDependency { .. }))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::CircularDependency { .. })));
+    }
+
+    #[test]
+    fn test_every_cycle_reported() {
+        let mut model = create_test_model();
+        let logical = |name: &str, operands: &[&str]| Evaluation {
+            name: name.to_string(),
+            evaluation_type: EvaluationType::Logical,
+            left: None,
+            operator: Some("AND".to_string()),
+            right: None,
+            operands: Some(operands.iter().map(|o| o.to_string()).collect()),
+            weight: Some(3),
+            aggregation: None,
+            field: None,
+            conditions: None,
+        };
+        model.evaluations = vec![
+            logical("eval1", &["eval2"]),
+            logical("eval2", &["eval1"]),
+            logical("eval3", &["eval3"]),
+        ];
+
+        let result = RuleAnalyzer::new().analyze(&model);
+        let cycles: Vec<_> = result
+            .errors
+            .iter()
+            .filter_map(|e| match e {
+                AnalyzerError::CircularDependency {
+                    evaluation_name,
+                    dependency_chain,
+                    edges,
+                } => Some((
+                    evaluation_name.as_str(),
+                    dependency_chain.len(),
+                    edges.len(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cycles, vec![("eval1", 2, 2), ("eval3", 1, 1)]);
     }
 
     #[test]
@@ -536,14 +726,16 @@ mod tests {
             operands: None,
             weight: Some(6), // Invalid weight > 5
             aggregation: None,
+            field: None,
+            conditions: None,
         });
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e, AnalyzerError::InvalidWeight { .. }))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::InvalidWeight { .. })));
     }
 
     #[test]
@@ -563,6 +755,8 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
                 Evaluation {
                     name: "reference_check".to_string(),
@@ -573,21 +767,28 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
             ],
             actions: vec![Action {
                 action_type: "flag_transaction".to_owned(),
                 reason: "High amount detected".to_owned(),
             }],
-            metadata: Some(Metadata{
+            metadata: Some(Metadata {
                 created_by: Some("test_user".to_owned()),
                 created_at: Some("2024-01-01T12:00:00Z".to_owned()),
                 last_updated: Some("2024-01-01T12:00:00Z".to_owned()),
-                notes: Some("Test notes".to_owned())
-            })
+                notes: Some("Test notes".to_owned()),
+                allow_lints: None,
+            }),
+            event_schema: None,
+            timezone: None,
+            field_timezones: None,
+            scoring: None,
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         assert!(analyzer.analyze(&model).is_ok());
     }
 
@@ -607,30 +808,34 @@ mod tests {
                 operands: None,
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             }],
             actions: vec![Action {
                 action_type: "flag_transaction".to_owned(),
                 reason: "High amount detected".to_owned(),
             }],
-            metadata: Some(Metadata{
+            metadata: Some(Metadata {
                 created_by: Some("test_user".to_owned()),
                 created_at: Some("2024-01-01T12:00:00Z".to_owned()),
                 last_updated: Some("2024-01-01T12:00:00Z".to_owned()),
-                notes: Some("Test notes".to_owned())
-            })
+                notes: Some("Test notes".to_owned()),
+                allow_lints: None,
+            }),
+            event_schema: None,
+            timezone: None,
+            field_timezones: None,
+            scoring: None,
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e,
-                AnalyzerError::InvalidStringReference {
-                    reference,
-                    ..
-                } if reference == "non_existent_check"
-            ))
-        ));
+        assert!(result.errors.iter().any(|e| matches!(e,
+            AnalyzerError::InvalidStringReference {
+                reference,
+                ..
+            } if reference == "non_existent_check"
+        )));
     }
 
     #[test]
@@ -650,6 +855,8 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
                 Evaluation {
                     name: "eval2".to_string(),
@@ -660,26 +867,33 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
             ],
             actions: vec![Action {
                 action_type: "flag_transaction".to_owned(),
                 reason: "High amount detected".to_owned(),
             }],
-            metadata: Some(Metadata{
+            metadata: Some(Metadata {
                 created_by: Some("test_user".to_owned()),
                 created_at: Some("2024-01-01T12:00:00Z".to_owned()),
                 last_updated: Some("2024-01-01T12:00:00Z".to_owned()),
-                notes: Some("Test notes".to_owned())
-            })
+                notes: Some("Test notes".to_owned()),
+                allow_lints: None,
+            }),
+            event_schema: None,
+            timezone: None,
+            field_timezones: None,
+            scoring: None,
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e, AnalyzerError::CircularDependency { .. }))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::CircularDependency { .. })));
     }
 
     #[test]
@@ -699,6 +913,8 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
                 Evaluation {
                     name: "eval2".to_string(),
@@ -709,6 +925,8 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
                 Evaluation {
                     name: "combined_check".to_string(),
@@ -719,21 +937,28 @@ mod tests {
                     operands: None,
                     weight: Some(3),
                     aggregation: None,
+                    field: None,
+                    conditions: None,
                 },
             ],
             actions: vec![Action {
                 action_type: "flag_transaction".to_owned(),
                 reason: "High amount detected".to_owned(),
             }],
-            metadata: Some(Metadata{
+            metadata: Some(Metadata {
                 created_by: Some("test_user".to_owned()),
                 created_at: Some("2024-01-01T12:00:00Z".to_owned()),
                 last_updated: Some("2024-01-01T12:00:00Z".to_owned()),
-                notes: Some("Test notes".to_owned())
-            })
+                notes: Some("Test notes".to_owned()),
+                allow_lints: None,
+            }),
+            event_schema: None,
+            timezone: None,
+            field_timezones: None,
+            scoring: None,
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         assert!(analyzer.analyze(&model).is_ok());
     }
 
@@ -753,6 +978,8 @@ mod tests {
                 operands: None,
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             }],
             actions: vec![Action {
                 action_type: "flag_transaction".to_string(),
@@ -761,7 +988,7 @@ mod tests {
             ..Default::default()
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let res = analyzer.analyze(&model);
         assert!(res.is_ok());
     }
@@ -782,6 +1009,8 @@ mod tests {
                 operands: None,
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             }],
             actions: vec![Action {
                 action_type: "flag_transaction".to_string(),
@@ -790,14 +1019,12 @@ mod tests {
             ..Default::default()
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e,
-                AnalyzerError::InvalidDateTimeExpression { .. }
-            ))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::InvalidDateTimeExpression { .. })));
     }
 
     #[test]
@@ -818,6 +1045,8 @@ mod tests {
                 operands: None,
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             }],
             actions: vec![Action {
                 action_type: "flag_transaction".to_string(),
@@ -826,14 +1055,12 @@ mod tests {
             ..Default::default()
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         let result = analyzer.analyze(&model);
-        assert!(matches!(
-            result,
-            Err(ref errors) if errors.iter().any(|e| matches!(e,
-                AnalyzerError::InvalidDateTimeExpression { .. }
-            ))
-        ));
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::InvalidDateTimeExpression { .. })));
     }
 
     #[test]
@@ -845,6 +1072,12 @@ mod tests {
             "datetime(now, '2 weeks')",
             "datetime(now, '-3 months')",
             "datetime(now, '1 year')",
+            "datetime(now, '-45 seconds')",
+            "datetime(now, '-1 day 2 hours')",
+            "datetime(now, '-PT2H')",
+            "datetime(transaction.timestamp, 'P30D')",
+            "hour_of_day(transaction.timestamp)",
+            "day_of_week(datetime(now, '-1 day'))",
         ];
 
         for expr in test_cases {
@@ -874,6 +1107,8 @@ mod tests {
                 operands: None,
                 weight: Some(3),
                 aggregation: None,
+                field: None,
+                conditions: None,
             }],
             actions: vec![Action {
                 action_type: "flag_transaction".to_string(),
@@ -882,7 +1117,145 @@ mod tests {
             ..Default::default()
         };
 
-        let mut analyzer = RuleAnalyzer::new();
+        let analyzer = RuleAnalyzer::new();
         assert!(analyzer.analyze(&model).is_ok());
     }
+
+    #[test]
+    fn test_analyzer_is_reusable() {
+        let analyzer = RuleAnalyzer::new();
+        let model = create_test_model();
+
+        // Analyzing the same names twice must not report duplicates
+        assert!(analyzer.analyze(&model).is_ok());
+        assert!(analyzer.analyze(&model).is_ok());
+    }
+
+    #[test]
+    fn test_analysis_result_contents() {
+        let analyzer = RuleAnalyzer::new();
+        let result = analyzer.analyze(&create_test_model());
+
+        assert_eq!(result.symbols.len(), 2);
+        let symbol = result.symbols.get("risk_check").unwrap();
+        assert_eq!(symbol.index, 1);
+        assert_eq!(symbol.evaluation_type, EvaluationType::Logical);
+        assert_eq!(
+            result.dependency_graph.dependencies("risk_check"),
+            vec!["amount_check"]
+        );
+    }
+
+    #[test]
+    fn test_inline_event_schema() {
+        let mut model = create_test_model();
+        model.event_schema = Some(Value::Object(vec![(
+            "fields".to_string(),
+            Value::Object(vec![(
+                "amount".to_string(),
+                Value::String("string".to_string()),
+            )]),
+        )]));
+
+        let result = RuleAnalyzer::new().analyze(&model);
+        assert!(result.errors.iter().any(|e| matches!(e,
+            AnalyzerError::TypeMismatch { evaluation_name, .. } if evaluation_name == "amount_check"
+        )));
+
+        model.event_schema = Some(Value::Object(vec![(
+            "fields".to_string(),
+            Value::Object(vec![(
+                "amount".to_string(),
+                Value::String("money".to_string()),
+            )]),
+        )]));
+        let result = RuleAnalyzer::new().analyze(&model);
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| matches!(e, AnalyzerError::InvalidEventSchema { .. })));
+    }
+
+    #[test]
+    fn test_inline_schema_overrides_configured() {
+        use crate::event_schema::{FieldSpec, FieldType};
+
+        let mut schema = EventSchema::new();
+        schema.insert("amount", FieldSpec::new(FieldType::String));
+        let analyzer = RuleAnalyzer::with_event_schema(schema);
+
+        let mut model = create_test_model();
+        assert!(!analyzer.analyze(&model).is_ok());
+
+        model.event_schema = Some(Value::Object(vec![(
+            "fields".to_string(),
+            Value::Object(vec![(
+                "amount".to_string(),
+                Value::String("number".to_string()),
+            )]),
+        )]));
+        assert!(analyzer.analyze(&model).is_ok());
+    }
+
+    #[test]
+    fn test_timezones_and_calendars() {
+        let mut model = create_test_model();
+        model.timezone = Some(TimezoneSpec::Zone("America/New_Yrok".to_string()));
+        model.evaluations[0].left =
+            Some("is_business_hours(transaction.timestamp, 'uk_banking')".to_string());
+
+        // Calendar names are only checked against configured calendars
+        let result = RuleAnalyzer::new().analyze(&model);
+        assert!(matches!(&result.errors[..],
+            [AnalyzerError::InvalidTimezone { field: None, reason }]
+                if reason.contains("America/New_Yrok")
+        ));
+
+        let mut analyzer = RuleAnalyzer::new();
+        let mut calendars = CalendarRegistry::new();
+        calendars.insert(crate::calendar::BusinessCalendar::new("us_banking"));
+        analyzer.set_calendars(calendars);
+        model.timezone = Some(TimezoneSpec::Zone("America/New_York".to_string()));
+        let result = analyzer.analyze(&model);
+        assert!(matches!(&result.errors[..],
+            [AnalyzerError::UnknownCalendar { field_name, calendar, .. }]
+                if field_name == "left" && calendar == "uk_banking"
+        ));
+    }
+
+    #[test]
+    fn test_policies() {
+        let model = create_test_model();
+        let mut analyzer = RuleAnalyzer::new();
+        analyzer.set_policies(
+            PolicySet::from_json(
+                r#"{"policies": [{"id": "POL-001", "require": {"min_threshold": 0.99}}]}"#,
+            )
+            .unwrap(),
+        );
+        let result = analyzer.analyze(&model);
+        assert!(matches!(&result.errors[..],
+            [AnalyzerError::PolicyViolation { policy_id, field_name, .. }]
+                if policy_id == "POL-001" && field_name == "threshold"
+        ));
+    }
+
+    #[test]
+    fn test_analyzer_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RuleAnalyzer>();
+        assert_send_sync::<AnalysisResult>();
+
+        let analyzer = std::sync::Arc::new(RuleAnalyzer::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let analyzer = analyzer.clone();
+                std::thread::spawn(move || analyzer.analyze(&create_test_model()).is_ok())
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
 }
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use crate::timezone::parse_zone;
+
+/// Named set of weekend days, business hours and holidays.
+///
+/// Calendars are loaded from JSON files such as:
+///
+/// ```json
+/// {
+///   "name": "us_banking",
+///   "timezone": "America/New_York",
+///   "weekend": ["saturday", "sunday"],
+///   "business_hours": {"start": "09:00", "end": "17:00"},
+///   "holidays": ["2024-12-25", {"date": "2024-07-04", "name": "Independence Day"}]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusinessCalendar {
+    pub name: String,
+    /// Zone the calendar's days and hours are in; without one the zone of the
+    /// datetime being checked is used
+    pub timezone: Option<Tz>,
+    pub weekend: Vec<Weekday>,
+    pub opens: NaiveTime,
+    /// End of business hours; before `opens` for hours that span midnight
+    pub closes: NaiveTime,
+    /// Holiday dates with their names, which may be empty
+    pub holidays: BTreeMap<NaiveDate, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CalendarFile {
+    name: Option<String>,
+    timezone: Option<String>,
+    weekend: Option<Vec<String>>,
+    business_hours: Option<BusinessHoursFile>,
+    #[serde(default)]
+    holidays: Vec<HolidayFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BusinessHoursFile {
+    start: String,
+    end: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HolidayFile {
+    Date(String),
+    Named { date: String, name: String },
+}
+
+impl BusinessCalendar {
+    /// Calendar with a Saturday and Sunday weekend, 09:00 to 17:00 business
+    /// hours and no holidays
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            timezone: None,
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            opens: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            closes: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+            holidays: BTreeMap::new(),
+        }
+    }
+
+    /// Reads a calendar, named `default_name` unless the file names it
+    pub fn from_json(json: &str, default_name: &str) -> Result<Self, String> {
+        let file: CalendarFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid calendar: {}", e))?;
+
+        let mut calendar = BusinessCalendar::new(file.name.as_deref().unwrap_or(default_name));
+        calendar.timezone = file.timezone.as_deref().map(parse_zone).transpose()?;
+        if let Some(weekend) = file.weekend {
+            calendar.weekend = weekend
+                .iter()
+                .map(|day| {
+                    day.parse::<Weekday>()
+                        .map_err(|_| format!("Invalid weekday '{}'", day))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(hours) = file.business_hours {
+            calendar.opens = parse_time(&hours.start)?;
+            calendar.closes = parse_time(&hours.end)?;
+        }
+        for holiday in file.holidays {
+            let (date, name) = match holiday {
+                HolidayFile::Date(date) => (date, String::new()),
+                HolidayFile::Named { date, name } => (date, name),
+            };
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid holiday date '{}'", date))?;
+            calendar.holidays.insert(date, name);
+        }
+        Ok(calendar)
+    }
+
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&date.weekday())
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains_key(&date)
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_holiday(date)
+    }
+
+    /// Whether a local time falls within business hours.
+    ///
+    /// Hours that span midnight belong to the business day they start on.
+    pub fn is_business_hours(&self, datetime: NaiveDateTime) -> bool {
+        let time = datetime.time();
+        if self.opens <= self.closes {
+            self.is_business_day(datetime.date()) && self.opens <= time && time < self.closes
+        } else if time >= self.opens {
+            self.is_business_day(datetime.date())
+        } else {
+            time < self.closes
+                && datetime
+                    .date()
+                    .pred_opt()
+                    .is_some_and(|day| self.is_business_day(day))
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+        .map_err(|_| format!("Invalid time '{}': expected HH:MM", time))
+}
+
+/// Business calendars by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalendarRegistry {
+    calendars: BTreeMap<String, BusinessCalendar>,
+}
+
+impl CalendarRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a calendar file, or every `.json` file in a directory.
+    ///
+    /// Calendars that do not name themselves are named after their file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let files = if path.is_dir() {
+            let mut files: Vec<_> = std::fs::read_dir(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut registry = CalendarRegistry::new();
+        for file in files {
+            let json = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            let stem = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let calendar = BusinessCalendar::from_json(&json, &stem)
+                .map_err(|e| format!("{}: {}", file.display(), e))?;
+            if registry.get(&calendar.name).is_some() {
+                return Err(format!(
+                    "{}: calendar '{}' is declared twice",
+                    file.display(),
+                    calendar.name
+                ));
+            }
+            registry.insert(calendar);
+        }
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, calendar: BusinessCalendar) {
+        self.calendars.insert(calendar.name.clone(), calendar);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BusinessCalendar> {
+        self.calendars.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.calendars.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn at(datetime: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_calendar_from_json() {
+        let calendar = BusinessCalendar::from_json(
+            r#"{
+                "timezone": "Asia/Dubai",
+                "weekend": ["Saturday", "sun"],
+                "business_hours": {"start": "08:30", "end": "16:00"},
+                "holidays": ["2024-12-02", {"date": "2024-12-03", "name": "National Day"}]
+            }"#,
+            "uae",
+        )
+        .unwrap();
+
+        assert_eq!(calendar.name, "uae");
+        assert_eq!(calendar.timezone, Some(Tz::Asia__Dubai));
+        assert!(calendar.is_weekend(date("2024-12-01")));
+        assert!(calendar.is_holiday(date("2024-12-03")));
+        assert!(!calendar.is_business_day(date("2024-12-02")));
+        assert!(calendar.is_business_day(date("2024-12-04")));
+        assert!(calendar.is_business_hours(at("2024-12-04 08:30")));
+        assert!(!calendar.is_business_hours(at("2024-12-04 16:00")));
+
+        for invalid in [
+            r#"{"weekend": ["Caturday"]}"#,
+            r#"{"timezone": "Nowhere"}"#,
+            r#"{"holidays": ["25/12/2024"]}"#,
+            r#"{"business_hours": {"start": "9am", "end": "17:00"}}"#,
+            r#"{"weekends": []}"#,
+        ] {
+            assert!(
+                BusinessCalendar::from_json(invalid, "x").is_err(),
+                "{} should not load",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_overnight_business_hours() {
+        let mut calendar = BusinessCalendar::new("night_shift");
+        calendar.opens = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        calendar.closes = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
+
+        // Friday night into Saturday morning is a Friday shift
+        assert!(calendar.is_business_hours(at("2024-03-01 23:00")));
+        assert!(calendar.is_business_hours(at("2024-03-02 05:00")));
+        // Saturday night into Sunday morning is not
+        assert!(!calendar.is_business_hours(at("2024-03-02 23:00")));
+        assert!(!calendar.is_business_hours(at("2024-03-03 05:00")));
+        assert!(!calendar.is_business_hours(at("2024-03-04 12:00")));
+    }
+}
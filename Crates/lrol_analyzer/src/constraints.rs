@@ -0,0 +1,899 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType, Value};
+
+use crate::error::AnalyzerError;
+use crate::operand::Operand;
+
+/// Logical trees larger than this many conjunctions in normal form are not analyzed
+pub const MAX_CONJUNCTIONS: usize = 256;
+
+/// Constant an event field is compared with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scalar {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn from_value(value: &Value) -> Option<Scalar> {
+        match value {
+            Value::Number(n) => Some(Scalar::Number(*n)),
+            Value::String(s) => Some(Scalar::Text(s.clone())),
+            Value::Bool(b) => Some(Scalar::Bool(*b)),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> ScalarKind {
+        match self {
+            Scalar::Number(_) => ScalarKind::Number,
+            Scalar::Text(_) => ScalarKind::Text,
+            Scalar::Bool(_) => ScalarKind::Bool,
+        }
+    }
+
+    // Key used for set membership of text and boolean values
+    fn key(&self) -> String {
+        match self {
+            Scalar::Number(n) => n.to_string(),
+            Scalar::Text(s) => s.clone(),
+            Scalar::Bool(b) => b.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scalar::Number(n) => write!(f, "{}", n),
+            Scalar::Text(s) => write!(f, "\"{}\"", s),
+            Scalar::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScalarKind {
+    Number,
+    Text,
+    Bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    pub fn parse(operator: &str) -> Option<CompareOp> {
+        match operator.trim() {
+            "==" => Some(CompareOp::Eq),
+            "!=" => Some(CompareOp::Ne),
+            "<" => Some(CompareOp::Lt),
+            "<=" => Some(CompareOp::Le),
+            ">" => Some(CompareOp::Gt),
+            ">=" => Some(CompareOp::Ge),
+            _ => None,
+        }
+    }
+
+    pub fn negate(self) -> CompareOp {
+        match self {
+            CompareOp::Eq => CompareOp::Ne,
+            CompareOp::Ne => CompareOp::Eq,
+            CompareOp::Lt => CompareOp::Ge,
+            CompareOp::Le => CompareOp::Gt,
+            CompareOp::Gt => CompareOp::Le,
+            CompareOp::Ge => CompareOp::Lt,
+        }
+    }
+
+    pub fn is_ordering(self) -> bool {
+        !matches!(self, CompareOp::Eq | CompareOp::Ne)
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Compare(CompareOp, Scalar),
+    In(Vec<Scalar>),
+    NotIn(Vec<Scalar>),
+}
+
+impl Predicate {
+    pub fn negate(&self) -> Predicate {
+        match self {
+            Predicate::Compare(op, value) => Predicate::Compare(op.negate(), value.clone()),
+            Predicate::In(values) => Predicate::NotIn(values.clone()),
+            Predicate::NotIn(values) => Predicate::In(values.clone()),
+        }
+    }
+
+    /// Kind of the constants the predicate compares with, if they all agree
+    pub fn kind(&self) -> Option<ScalarKind> {
+        match self {
+            Predicate::Compare(_, value) => Some(value.kind()),
+            Predicate::In(values) | Predicate::NotIn(values) => {
+                let mut kinds = values.iter().map(Scalar::kind);
+                let first = kinds.next()?;
+                kinds.all(|k| k == first).then_some(first)
+            }
+        }
+    }
+}
+
+/// Comparison of an event field with constants, taken from one evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Atom {
+    /// Evaluation the comparison was declared in
+    pub evaluation: String,
+    pub field: String,
+    pub predicate: Predicate,
+}
+
+impl Atom {
+    pub fn negate(&self) -> Atom {
+        Atom {
+            evaluation: self.evaluation.clone(),
+            field: self.field.clone(),
+            predicate: self.predicate.negate(),
+        }
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |values: &[Scalar]| {
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match &self.predicate {
+            Predicate::Compare(op, value) => write!(f, "{} {} {}", self.field, op, value),
+            Predicate::In(values) => write!(f, "{} IN [{}]", self.field, list(values)),
+            Predicate::NotIn(values) => write!(f, "{} NOT IN [{}]", self.field, list(values)),
+        }
+    }
+}
+
+/// Leaf of a [`Formula`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Atom(Atom),
+    /// Evaluation whose result cannot be analyzed, e.g. an aggregation, kept
+    /// as an independent boolean
+    Opaque {
+        evaluation: String,
+        negated: bool,
+    },
+}
+
+impl Literal {
+    pub fn evaluation(&self) -> &str {
+        match self {
+            Literal::Atom(atom) => &atom.evaluation,
+            Literal::Opaque { evaluation, .. } => evaluation,
+        }
+    }
+
+    pub fn negate(&self) -> Literal {
+        match self {
+            Literal::Atom(atom) => Literal::Atom(atom.negate()),
+            Literal::Opaque {
+                evaluation,
+                negated,
+            } => Literal::Opaque {
+                evaluation: evaluation.clone(),
+                negated: !negated,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Atom(atom) => write!(f, "{}", atom),
+            Literal::Opaque {
+                evaluation,
+                negated: false,
+            } => write!(f, "@{}", evaluation),
+            Literal::Opaque {
+                evaluation,
+                negated: true,
+            } => write!(f, "NOT @{}", evaluation),
+        }
+    }
+}
+
+/// Boolean structure of an evaluation over field comparisons
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    Literal(Literal),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+}
+
+impl Formula {
+    /// Builds the formula of the evaluation called `name`, expanding logical operands
+    pub fn from_model(model: &LrolModel, name: &str) -> Option<Formula> {
+        let evaluations: HashMap<&str, &Evaluation> = model
+            .evaluations
+            .iter()
+            .rev()
+            .map(|e| (e.name.as_str(), e))
+            .collect();
+        let mut visiting = Vec::new();
+        evaluations
+            .get(name)
+            .map(|evaluation| Self::build(evaluation, &evaluations, &mut visiting))
+    }
+
+    fn build<'a>(
+        evaluation: &'a Evaluation,
+        evaluations: &HashMap<&str, &'a Evaluation>,
+        visiting: &mut Vec<&'a str>,
+    ) -> Formula {
+        let opaque = || {
+            Formula::Literal(Literal::Opaque {
+                evaluation: evaluation.name.clone(),
+                negated: false,
+            })
+        };
+
+        match evaluation.evaluation_type {
+            EvaluationType::Comparison => match Self::atom(evaluation) {
+                Some(atom) => Formula::Literal(Literal::Atom(atom)),
+                None => opaque(),
+            },
+            EvaluationType::Logical => {
+                let operands = match evaluation.operands {
+                    Some(ref operands) if !operands.is_empty() => operands,
+                    _ => return opaque(),
+                };
+                // Cycles are reported elsewhere; break them by treating the evaluation as opaque
+                if visiting.contains(&evaluation.name.as_str()) {
+                    return opaque();
+                }
+
+                visiting.push(&evaluation.name);
+                let children = operands
+                    .iter()
+                    .map(|operand| match evaluations.get(operand.as_str()) {
+                        Some(child) => Self::build(child, evaluations, visiting),
+                        None => Formula::Literal(Literal::Opaque {
+                            evaluation: operand.clone(),
+                            negated: false,
+                        }),
+                    })
+                    .collect();
+                visiting.pop();
+
+                match evaluation.operator.as_deref() {
+                    Some("AND") => Formula::And(children),
+                    Some("OR") => Formula::Or(children),
+                    _ => opaque(),
+                }
+            }
+            _ => opaque(),
+        }
+    }
+
+    // A comparison of an event field with constants
+    fn atom(evaluation: &Evaluation) -> Option<Atom> {
+        let Operand::Field(field) = Operand::parse(evaluation.left.as_deref()?) else {
+            return None;
+        };
+        let operator = evaluation.operator.as_deref()?.trim();
+        let right = evaluation.right.as_ref()?;
+
+        let predicate = match (operator, right) {
+            ("IN", Value::Array(items)) => Predicate::In(
+                items
+                    .iter()
+                    .map(Scalar::from_value)
+                    .collect::<Option<_>>()?,
+            ),
+            ("NOT IN", Value::Array(items)) => Predicate::NotIn(
+                items
+                    .iter()
+                    .map(Scalar::from_value)
+                    .collect::<Option<_>>()?,
+            ),
+            (operator, value) => {
+                let op = CompareOp::parse(operator)?;
+                // Strings naming fields or calling functions are not constants
+                if let Value::String(s) = value {
+                    if !matches!(Operand::from_value(value, |_| false), Operand::Literal(_))
+                        || s.contains('.')
+                    {
+                        return None;
+                    }
+                }
+                let value = Scalar::from_value(value)?;
+                if op.is_ordering() && value.kind() != ScalarKind::Number {
+                    return None;
+                }
+                Predicate::Compare(op, value)
+            }
+        };
+
+        predicate.kind()?;
+        Some(Atom {
+            evaluation: evaluation.name.clone(),
+            field,
+            predicate,
+        })
+    }
+
+    pub fn negate(&self) -> Formula {
+        match self {
+            Formula::Literal(literal) => Formula::Literal(literal.negate()),
+            Formula::And(children) => Formula::Or(children.iter().map(Formula::negate).collect()),
+            Formula::Or(children) => Formula::And(children.iter().map(Formula::negate).collect()),
+        }
+    }
+
+    /// Disjunctive normal form, or `None` if it has more than [`MAX_CONJUNCTIONS`] terms
+    pub fn dnf(&self) -> Option<Vec<Vec<Literal>>> {
+        match self {
+            Formula::Literal(literal) => Some(vec![vec![literal.clone()]]),
+            Formula::Or(children) => {
+                let mut terms = Vec::new();
+                for child in children {
+                    terms.extend(child.dnf()?);
+                    if terms.len() > MAX_CONJUNCTIONS {
+                        return None;
+                    }
+                }
+                Some(terms)
+            }
+            Formula::And(children) => {
+                let mut terms = vec![Vec::new()];
+                for child in children {
+                    let child_terms = child.dnf()?;
+                    if terms.len() * child_terms.len() > MAX_CONJUNCTIONS {
+                        return None;
+                    }
+                    terms = terms
+                        .iter()
+                        .flat_map(|term| {
+                            child_terms.iter().map(move |child_term| {
+                                let mut combined = term.clone();
+                                combined.extend(child_term.iter().cloned());
+                                combined
+                            })
+                        })
+                        .collect();
+                }
+                Some(terms)
+            }
+        }
+    }
+
+    /// Whether no event satisfies the formula; `None` if it is too large to decide
+    pub fn is_unsatisfiable(&self) -> Option<bool> {
+        Some(self.dnf()?.iter().all(|term| conflict(term).is_some()))
+    }
+
+    /// Whether every event satisfies the formula; `None` if it is too large to decide
+    pub fn is_tautology(&self) -> Option<bool> {
+        self.negate().is_unsatisfiable()
+    }
+
+    /// Whether every event satisfying `self` also satisfies `other`
+    pub fn implies(&self, other: &Formula) -> Option<bool> {
+        Formula::And(vec![self.clone(), other.negate()]).is_unsatisfiable()
+    }
+}
+
+/// Literals of a conjunction that cannot hold together, reduced to a minimal set
+pub fn conflict(term: &[Literal]) -> Option<Vec<Literal>> {
+    for (i, literal) in term.iter().enumerate() {
+        if let Literal::Opaque { .. } = literal {
+            let negated = literal.negate();
+            if term[i + 1..].contains(&negated) {
+                return Some(vec![literal.clone(), negated]);
+            }
+        }
+    }
+
+    let mut groups: Vec<((&str, ScalarKind), Vec<&Atom>)> = Vec::new();
+    for literal in term {
+        if let Literal::Atom(atom) = literal {
+            let key = (atom.field.as_str(), atom.predicate.kind()?);
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, atoms)) => atoms.push(atom),
+                None => groups.push((key, vec![atom])),
+            }
+        }
+    }
+
+    for ((_, kind), mut atoms) in groups {
+        if !Domain::from_atoms(kind, &atoms).is_empty() {
+            continue;
+        }
+        // Drop atoms that are not needed for the conflict
+        let mut i = 0;
+        while i < atoms.len() {
+            let mut rest = atoms.clone();
+            rest.remove(i);
+            if Domain::from_atoms(kind, &rest).is_empty() {
+                atoms = rest;
+            } else {
+                i += 1;
+            }
+        }
+        return Some(atoms.into_iter().cloned().map(Literal::Atom).collect());
+    }
+
+    None
+}
+
+/// Values of a single field allowed by a set of predicates
+#[derive(Debug, Clone, PartialEq)]
+pub enum Domain {
+    Numeric {
+        /// Lower bound and whether it is inclusive
+        lower: Option<(f64, bool)>,
+        upper: Option<(f64, bool)>,
+        /// Closed set of allowed values, from `==` or `IN`
+        allowed: Option<Vec<f64>>,
+        excluded: Vec<f64>,
+    },
+    Discrete {
+        allowed: Option<BTreeSet<String>>,
+        excluded: BTreeSet<String>,
+    },
+}
+
+impl Domain {
+    pub fn full(kind: ScalarKind) -> Domain {
+        match kind {
+            ScalarKind::Number => Domain::Numeric {
+                lower: None,
+                upper: None,
+                allowed: None,
+                excluded: Vec::new(),
+            },
+            ScalarKind::Text => Domain::Discrete {
+                allowed: None,
+                excluded: BTreeSet::new(),
+            },
+            ScalarKind::Bool => Domain::Discrete {
+                allowed: Some(["false".to_string(), "true".to_string()].into()),
+                excluded: BTreeSet::new(),
+            },
+        }
+    }
+
+    pub fn from_atoms(kind: ScalarKind, atoms: &[&Atom]) -> Domain {
+        let mut domain = Domain::full(kind);
+        for atom in atoms {
+            domain.restrict(&atom.predicate);
+        }
+        domain
+    }
+
+    /// Intersects the domain with the values satisfying `predicate`
+    pub fn restrict(&mut self, predicate: &Predicate) {
+        match self {
+            Domain::Numeric {
+                lower,
+                upper,
+                allowed,
+                excluded,
+            } => {
+                let numbers = |values: &[Scalar]| -> Vec<f64> {
+                    values
+                        .iter()
+                        .filter_map(|v| match v {
+                            Scalar::Number(n) => Some(*n),
+                            _ => None,
+                        })
+                        .collect()
+                };
+                match predicate {
+                    Predicate::Compare(op, Scalar::Number(n)) => match op {
+                        CompareOp::Eq => Self::allow_only(allowed, &[*n]),
+                        CompareOp::Ne => excluded.push(*n),
+                        CompareOp::Gt => Self::raise(lower, (*n, false)),
+                        CompareOp::Ge => Self::raise(lower, (*n, true)),
+                        CompareOp::Lt => Self::lower(upper, (*n, false)),
+                        CompareOp::Le => Self::lower(upper, (*n, true)),
+                    },
+                    Predicate::In(values) => Self::allow_only(allowed, &numbers(values)),
+                    Predicate::NotIn(values) => excluded.extend(numbers(values)),
+                    _ => {}
+                }
+            }
+            Domain::Discrete { allowed, excluded } => {
+                let keys = |values: &[Scalar]| -> BTreeSet<String> {
+                    values.iter().map(Scalar::key).collect()
+                };
+                let allow_only = |allowed: &mut Option<BTreeSet<String>>,
+                                  keys: BTreeSet<String>| {
+                    *allowed = Some(match allowed.take() {
+                        Some(current) => current.intersection(&keys).cloned().collect(),
+                        None => keys,
+                    });
+                };
+                match predicate {
+                    Predicate::Compare(CompareOp::Eq, value) => {
+                        allow_only(allowed, [value.key()].into())
+                    }
+                    Predicate::Compare(CompareOp::Ne, value) => {
+                        excluded.insert(value.key());
+                    }
+                    Predicate::In(values) => allow_only(allowed, keys(values)),
+                    Predicate::NotIn(values) => excluded.extend(keys(values)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn allow_only(allowed: &mut Option<Vec<f64>>, values: &[f64]) {
+        *allowed = Some(match allowed.take() {
+            Some(current) => current.into_iter().filter(|v| values.contains(v)).collect(),
+            None => values.to_vec(),
+        });
+    }
+
+    // Keep the tighter of two lower bounds
+    fn raise(lower: &mut Option<(f64, bool)>, bound: (f64, bool)) {
+        *lower = Some(match *lower {
+            Some(current) if current.0 > bound.0 || (current.0 == bound.0 && !current.1) => current,
+            _ => bound,
+        });
+    }
+
+    // Keep the tighter of two upper bounds
+    fn lower(upper: &mut Option<(f64, bool)>, bound: (f64, bool)) {
+        *upper = Some(match *upper {
+            Some(current) if current.0 < bound.0 || (current.0 == bound.0 && !current.1) => current,
+            _ => bound,
+        });
+    }
+
+    /// Whether the numeric bounds admit `value`
+    fn within(lower: Option<(f64, bool)>, upper: Option<(f64, bool)>, value: f64) -> bool {
+        lower.is_none_or(|(l, inclusive)| value > l || (inclusive && value == l))
+            && upper.is_none_or(|(u, inclusive)| value < u || (inclusive && value == u))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Domain::Numeric {
+                lower,
+                upper,
+                allowed,
+                excluded,
+            } => {
+                if let Some(allowed) = allowed {
+                    return !allowed
+                        .iter()
+                        .any(|v| Self::within(*lower, *upper, *v) && !excluded.contains(v));
+                }
+                match (lower, upper) {
+                    (Some((l, l_inclusive)), Some((u, u_inclusive))) => {
+                        l > u || (l == u && (!l_inclusive || !u_inclusive || excluded.contains(l)))
+                    }
+                    _ => false,
+                }
+            }
+            Domain::Discrete { allowed, excluded } => allowed
+                .as_ref()
+                .is_some_and(|allowed| allowed.iter().all(|v| excluded.contains(v))),
+        }
+    }
+}
+
+/// Reports logical evaluations that can never fire, always fire, or carry
+/// operands implied by their siblings.
+///
+/// Only comparisons of event fields with constants are interpreted; any other
+/// evaluation is an independent boolean, so findings never depend on values
+/// the analyzer cannot see.
+pub fn check_logical_evaluations(model: &LrolModel, warnings: &mut Vec<AnalyzerError>) {
+    for evaluation in &model.evaluations {
+        if evaluation.evaluation_type != EvaluationType::Logical {
+            continue;
+        }
+        let Some(formula) = Formula::from_model(model, &evaluation.name) else {
+            continue;
+        };
+        let children = match formula {
+            Formula::And(ref children) | Formula::Or(ref children) => children,
+            Formula::Literal(_) => continue,
+        };
+
+        // Properties inherited from an operand are reported on that operand
+        let inherited = |property: fn(&Formula) -> Option<bool>| {
+            children.iter().any(|child| property(child) == Some(true))
+        };
+
+        if formula.is_unsatisfiable() == Some(true) {
+            if !inherited(Formula::is_unsatisfiable) {
+                warnings.push(unsatisfiable(evaluation, &formula));
+            }
+            continue;
+        }
+
+        if formula.is_tautology() == Some(true) {
+            if !inherited(Formula::is_tautology) {
+                warnings.push(tautological(evaluation, &formula));
+            }
+            continue;
+        }
+
+        check_redundant_operands(evaluation, children, warnings);
+    }
+}
+
+fn unsatisfiable(evaluation: &Evaluation, formula: &Formula) -> AnalyzerError {
+    let term = formula
+        .dnf()
+        .and_then(|terms| terms.first().and_then(|term| conflict(term)))
+        .unwrap_or_default();
+    AnalyzerError::UnsatisfiableEvaluation {
+        evaluation_name: evaluation.name.clone(),
+        conflicting_evaluations: evaluation_names(&term),
+        reason: describe(&term, " and ", "cannot all hold"),
+    }
+}
+
+fn tautological(evaluation: &Evaluation, formula: &Formula) -> AnalyzerError {
+    let term = formula
+        .negate()
+        .dnf()
+        .and_then(|terms| terms.first().and_then(|term| conflict(term)))
+        .unwrap_or_default();
+    let negated: Vec<Literal> = term.iter().map(Literal::negate).collect();
+    AnalyzerError::TautologicalEvaluation {
+        evaluation_name: evaluation.name.clone(),
+        complementary_evaluations: evaluation_names(&negated),
+        reason: describe(&negated, " or ", "always holds"),
+    }
+}
+
+fn check_redundant_operands(
+    evaluation: &Evaluation,
+    children: &[Formula],
+    warnings: &mut Vec<AnalyzerError>,
+) {
+    let Some(ref operands) = evaluation.operands else {
+        return;
+    };
+    let conjunction = evaluation.operator.as_deref() == Some("AND");
+    let mut redundant = vec![false; children.len()];
+
+    for i in 0..children.len() {
+        for j in 0..children.len() {
+            if i == j || redundant[j] || operands[i] == operands[j] {
+                continue;
+            }
+            // Under AND an operand implied by a sibling adds nothing; under OR
+            // an operand implying a sibling does
+            let (stronger, weaker) = if conjunction { (j, i) } else { (i, j) };
+            if children[stronger].implies(&children[weaker]) == Some(true) {
+                redundant[i] = true;
+                warnings.push(AnalyzerError::RedundantOperand {
+                    evaluation_name: evaluation.name.clone(),
+                    operand: operands[i].clone(),
+                    subsumed_by: operands[j].clone(),
+                });
+                break;
+            }
+        }
+    }
+}
+
+fn evaluation_names(literals: &[Literal]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for literal in literals {
+        if !names.iter().any(|n| n == literal.evaluation()) {
+            names.push(literal.evaluation().to_string());
+        }
+    }
+    names
+}
+
+fn describe(literals: &[Literal], separator: &str, verdict: &str) -> String {
+    let parts: Vec<String> = literals.iter().map(ToString::to_string).collect();
+    format!("{} {}", parts.join(separator), verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, logical};
+
+    fn check(evaluations: Vec<Evaluation>) -> Vec<AnalyzerError> {
+        let model = LrolModel {
+            evaluations,
+            ..Default::default()
+        };
+        let mut warnings = Vec::new();
+        check_logical_evaluations(&model, &mut warnings);
+        warnings
+    }
+
+    fn strings(values: &[&str]) -> Value {
+        Value::Array(
+            values
+                .iter()
+                .map(|v| Value::String(v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_contradictory_bounds() {
+        let warnings = check(vec![
+            comparison("Large", "amount", ">", Value::Number(10000.0)),
+            comparison("Small", "amount", "<", Value::Number(500.0)),
+            comparison("Usd", "currency", "==", Value::String("USD".to_string())),
+            logical("Never", "AND", &["Usd", "Large", "Small"]),
+        ]);
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        match &warnings[0] {
+            AnalyzerError::UnsatisfiableEvaluation {
+                evaluation_name,
+                conflicting_evaluations,
+                reason,
+            } => {
+                assert_eq!(evaluation_name, "Never");
+                assert_eq!(conflicting_evaluations, &vec!["Large", "Small"]);
+                assert_eq!(reason, "amount > 10000 and amount < 500 cannot all hold");
+            }
+            other => panic!("unexpected warning {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_contradictory_sets() {
+        let warnings = check(vec![
+            comparison("Risky", "industry", "IN", strings(&["Gambling", "Crypto"])),
+            comparison(
+                "Safe",
+                "industry",
+                "NOT IN",
+                strings(&["Gambling", "Crypto", "Retail"]),
+            ),
+            logical("Never", "AND", &["Risky", "Safe"]),
+            comparison(
+                "Open",
+                "card_state",
+                "==",
+                Value::String("OPEN".to_string()),
+            ),
+            comparison(
+                "Closed",
+                "card_state",
+                "==",
+                Value::String("CLOSED".to_string()),
+            ),
+            logical("Both_States", "AND", &["Open", "Closed"]),
+        ]);
+
+        let unsat: Vec<_> = warnings
+            .iter()
+            .filter_map(|w| match w {
+                AnalyzerError::UnsatisfiableEvaluation {
+                    evaluation_name, ..
+                } => Some(evaluation_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unsat, vec!["Never", "Both_States"]);
+    }
+
+    #[test]
+    fn test_complementary_or_is_tautology() {
+        let warnings = check(vec![
+            comparison("Large", "amount", ">", Value::Number(1000.0)),
+            comparison("Not_Large", "amount", "<=", Value::Number(1000.0)),
+            logical("Always", "OR", &["Large", "Not_Large"]),
+        ]);
+
+        assert!(
+            matches!(&warnings[..], [AnalyzerError::TautologicalEvaluation {
+            evaluation_name, complementary_evaluations, ..
+        }] if evaluation_name == "Always" && complementary_evaluations.len() == 2)
+        );
+    }
+
+    #[test]
+    fn test_redundant_operands() {
+        let warnings = check(vec![
+            comparison("Over_100", "amount", ">", Value::Number(100.0)),
+            comparison("Over_1000", "amount", ">", Value::Number(1000.0)),
+            logical("Both", "AND", &["Over_100", "Over_1000"]),
+            logical("Either", "OR", &["Over_100", "Over_1000"]),
+        ]);
+
+        assert!(warnings.iter().any(|w| matches!(w,
+            AnalyzerError::RedundantOperand { evaluation_name, operand, subsumed_by }
+                if evaluation_name == "Both" && operand == "Over_100" && subsumed_by == "Over_1000"
+        )));
+        assert!(warnings.iter().any(|w| matches!(w,
+            AnalyzerError::RedundantOperand { evaluation_name, operand, .. }
+                if evaluation_name == "Either" && operand == "Over_1000"
+        )));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_nested_and_opaque_operands() {
+        let mut count = comparison("Count", "amount", ">", Value::Number(0.0));
+        count.evaluation_type = EvaluationType::Aggregation;
+
+        let warnings = check(vec![
+            count,
+            comparison("Large", "amount", ">", Value::Number(10000.0)),
+            comparison("Small", "amount", "<", Value::Number(500.0)),
+            logical("Inner", "OR", &["Small", "Count"]),
+            logical("Outer", "AND", &["Large", "Inner"]),
+            comparison("Ref", "@Count", ">", Value::Number(5.0)),
+            logical("Opaque_Only", "AND", &["Ref", "Count"]),
+        ]);
+
+        // `Outer` still fires when `Count` does
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_unsatisfiable_operand_reported_once() {
+        let warnings = check(vec![
+            comparison("Large", "amount", ">", Value::Number(10000.0)),
+            comparison("Small", "amount", "<", Value::Number(500.0)),
+            comparison("Usd", "currency", "==", Value::String("USD".to_string())),
+            logical("Never", "AND", &["Large", "Small"]),
+            logical("Also_Never", "AND", &["Never", "Usd"]),
+        ]);
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let warnings = check(vec![
+            comparison("At_Least", "amount", ">=", Value::Number(500.0)),
+            comparison("At_Most", "amount", "<=", Value::Number(500.0)),
+            comparison("Not_500", "amount", "!=", Value::Number(500.0)),
+            logical("Exactly_500", "AND", &["At_Least", "At_Most"]),
+            logical("Nothing", "AND", &["At_Least", "At_Most", "Not_500"]),
+        ]);
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(matches!(&warnings[0],
+            AnalyzerError::UnsatisfiableEvaluation { evaluation_name, .. } if evaluation_name == "Nothing"
+        ));
+    }
+}
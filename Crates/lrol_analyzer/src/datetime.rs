@@ -0,0 +1,873 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::calendar::{BusinessCalendar, CalendarRegistry};
+use crate::event_schema::FieldType;
+use crate::operand::{is_field_path, split_arguments};
+use crate::timezone::{parse_zone, Timezones};
+
+/// Functions that take or produce datetimes
+pub const DATETIME_FUNCTIONS: [&str; 8] = [
+    "datetime",
+    "strftime",
+    "hour_of_day",
+    "day_of_week",
+    "is_weekend",
+    "is_holiday",
+    "is_business_day",
+    "is_business_hours",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationUnit {
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl DurationUnit {
+    fn parse(unit: &str) -> Option<Self> {
+        let unit = match unit.to_lowercase().as_str() {
+            "millisecond" | "milliseconds" | "ms" => DurationUnit::Milliseconds,
+            "second" | "seconds" | "sec" | "secs" => DurationUnit::Seconds,
+            "minute" | "minutes" | "min" | "mins" => DurationUnit::Minutes,
+            "hour" | "hours" | "hr" | "hrs" => DurationUnit::Hours,
+            "day" | "days" => DurationUnit::Days,
+            "week" | "weeks" => DurationUnit::Weeks,
+            "month" | "months" => DurationUnit::Months,
+            "year" | "years" => DurationUnit::Years,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+/// Signed span of calendar time.
+///
+/// Months and days are kept apart from clock time, so that adding a month to
+/// January 31st lands on the last day of February and adding a day always
+/// keeps the time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Duration {
+    pub months: i64,
+    pub days: i64,
+    pub milliseconds: i64,
+}
+
+impl Duration {
+    /// Parses `'-2 hours'`, compound durations such as `'-1 day 2 hours'` and
+    /// ISO-8601 durations such as `PT2H` or `-P30D`.
+    ///
+    /// A leading sign applies to the whole duration.
+    pub fn parse(duration: &str) -> Result<Self, String> {
+        let trimmed = duration.trim().trim_matches('\'').trim_matches('"').trim();
+        let (negative, unsigned) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (
+                false,
+                trimmed.strip_prefix('+').unwrap_or(trimmed).trim_start(),
+            ),
+        };
+        if unsigned.is_empty() {
+            return Err("Empty duration string".to_string());
+        }
+
+        let duration = if unsigned.starts_with(['P', 'p']) {
+            Self::parse_iso(&unsigned[1..])?
+        } else {
+            Self::parse_compound(unsigned)?
+        };
+        Ok(if negative {
+            duration.negate()
+        } else {
+            duration
+        })
+    }
+
+    pub fn add(&mut self, value: i64, unit: DurationUnit) {
+        match unit {
+            DurationUnit::Milliseconds => self.milliseconds += value,
+            DurationUnit::Seconds => self.milliseconds += value * 1_000,
+            DurationUnit::Minutes => self.milliseconds += value * 60_000,
+            DurationUnit::Hours => self.milliseconds += value * 3_600_000,
+            DurationUnit::Days => self.days += value,
+            DurationUnit::Weeks => self.days += value * 7,
+            DurationUnit::Months => self.months += value,
+            DurationUnit::Years => self.months += value * 12,
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        Duration {
+            months: -self.months,
+            days: -self.days,
+            milliseconds: -self.milliseconds,
+        }
+    }
+
+    /// Shifts a datetime by this duration: months first, then days, then clock time.
+    ///
+    /// Months and days are added to the local date in the datetime's timezone.
+    pub fn apply_to<Z: TimeZone>(&self, datetime: DateTime<Z>) -> Option<DateTime<Z>> {
+        let months = Months::new(u32::try_from(self.months.unsigned_abs()).ok()?);
+        let shifted = if self.months < 0 {
+            datetime.checked_sub_months(months)?
+        } else {
+            datetime.checked_add_months(months)?
+        };
+        let days = Days::new(self.days.unsigned_abs());
+        let shifted = if self.days < 0 {
+            shifted.checked_sub_days(days)?
+        } else {
+            shifted.checked_add_days(days)?
+        };
+        shifted.checked_add_signed(TimeDelta::try_milliseconds(self.milliseconds)?)
+    }
+
+    // `1 day 2 hours`, `1 day, 2 hours` or `30 minutes`
+    fn parse_compound(duration: &str) -> Result<Self, String> {
+        let tokens: Vec<&str> = duration
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .collect();
+        if !tokens.len().is_multiple_of(2) {
+            return Err("Duration must contain a number and a unit".to_string());
+        }
+
+        let mut result = Duration::default();
+        for (i, pair) in tokens.chunks(2).enumerate() {
+            if i > 0 && pair[0].starts_with(['-', '+']) {
+                return Err("Only the start of a duration may carry a sign".to_string());
+            }
+            let value = pair[0]
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid duration value '{}'", pair[0]))?;
+            let unit = DurationUnit::parse(pair[1])
+                .ok_or_else(|| format!("Invalid duration unit '{}'", pair[1]))?;
+            result.add(value, unit);
+        }
+        Ok(result)
+    }
+
+    // The part of an ISO-8601 duration after `P`, e.g. `1DT2H30M` or `1.5S`
+    fn parse_iso(duration: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ISO-8601 duration 'P{}'", duration);
+        let (date, time) = match duration.split_once(['T', 't']) {
+            Some((_, "")) => return Err(invalid()),
+            Some((date, time)) => (date, Some(time)),
+            None => (duration, None),
+        };
+        if date.is_empty() && time.is_none() {
+            return Err(invalid());
+        }
+
+        let mut result = Duration::default();
+        for (part, in_time) in [(date, false), (time.unwrap_or_default(), true)] {
+            let mut number = String::new();
+            for c in part.chars() {
+                if c.is_ascii_digit() || (c == '.' && in_time) {
+                    number.push(c);
+                    continue;
+                }
+                if number.is_empty() {
+                    return Err(invalid());
+                }
+                match (c.to_ascii_uppercase(), in_time) {
+                    ('S', true) => {
+                        let seconds: f64 = number.parse().map_err(|_| invalid())?;
+                        result.milliseconds += (seconds * 1_000.0).round() as i64;
+                    }
+                    (designator, _) => {
+                        let value: i64 = number.parse().map_err(|_| invalid())?;
+                        let unit = match (designator, in_time) {
+                            ('Y', false) => DurationUnit::Years,
+                            ('M', false) => DurationUnit::Months,
+                            ('W', false) => DurationUnit::Weeks,
+                            ('D', false) => DurationUnit::Days,
+                            ('H', true) => DurationUnit::Hours,
+                            ('M', true) => DurationUnit::Minutes,
+                            _ => return Err(invalid()),
+                        };
+                        result.add(value, unit);
+                    }
+                }
+                number.clear();
+            }
+            if !number.is_empty() {
+                return Err(invalid());
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Parsed datetime function call, e.g. `datetime(transaction.timestamp, '-1 hour')`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateTimeExpr {
+    Now,
+    /// Quoted timestamp such as `'2024-01-01'`
+    Timestamp(DateTime<Utc>),
+    /// Event field holding a datetime
+    Field(String),
+    /// `datetime(base, duration)`
+    Shifted {
+        base: Box<DateTimeExpr>,
+        duration: Duration,
+    },
+    /// `datetime(base, 'start of day')`, also of a week (Monday), month or year
+    StartOf {
+        base: Box<DateTimeExpr>,
+        unit: DurationUnit,
+    },
+    /// `strftime(format, value[, zone])`, formatted with chrono's specifiers
+    Strftime {
+        format: String,
+        value: Box<DateTimeExpr>,
+        zone: Option<Tz>,
+    },
+    /// `hour_of_day(value[, zone])`, from 0 to 23
+    HourOfDay {
+        value: Box<DateTimeExpr>,
+        zone: Option<Tz>,
+    },
+    /// `day_of_week(value[, zone])`, the ISO weekday from 1 (Monday) to 7 (Sunday)
+    DayOfWeek {
+        value: Box<DateTimeExpr>,
+        zone: Option<Tz>,
+    },
+    /// `is_business_day(value[, calendar])` and the other calendar checks
+    Calendar {
+        check: CalendarCheck,
+        value: Box<DateTimeExpr>,
+        calendar: Option<String>,
+    },
+}
+
+/// Question a [`DateTimeExpr::Calendar`] asks of a business calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarCheck {
+    Weekend,
+    Holiday,
+    BusinessDay,
+    BusinessHours,
+}
+
+impl CalendarCheck {
+    pub fn function(&self) -> &'static str {
+        match self {
+            CalendarCheck::Weekend => "is_weekend",
+            CalendarCheck::Holiday => "is_holiday",
+            CalendarCheck::BusinessDay => "is_business_day",
+            CalendarCheck::BusinessHours => "is_business_hours",
+        }
+    }
+
+    fn from_function(name: &str) -> Option<Self> {
+        [
+            CalendarCheck::Weekend,
+            CalendarCheck::Holiday,
+            CalendarCheck::BusinessDay,
+            CalendarCheck::BusinessHours,
+        ]
+        .into_iter()
+        .find(|check| check.function() == name)
+    }
+}
+
+/// Result of evaluating a [`DateTimeExpr`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateTimeValue {
+    DateTime(DateTime<Utc>),
+    Text(String),
+    Number(i64),
+    Bool(bool),
+}
+
+/// Event and configuration a [`DateTimeExpr`] is evaluated against
+pub struct DateTimeContext<'a> {
+    pub now: DateTime<Utc>,
+    /// Reads an event field as text; datetimes are parsed with [`parse_timestamp`]
+    pub field: &'a dyn Fn(&str) -> Option<String>,
+    pub timezones: &'a Timezones,
+    pub calendars: &'a CalendarRegistry,
+}
+
+impl DateTimeExpr {
+    /// Whether `expr` is written as a call to one of the [`DATETIME_FUNCTIONS`],
+    /// even if the call is malformed
+    pub fn is_call(expr: &str) -> bool {
+        DATETIME_FUNCTIONS.iter().any(|name| {
+            expr.trim()
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start().starts_with('('))
+        })
+    }
+
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let (name, args) =
+            call(expr).ok_or_else(|| "Invalid datetime function syntax".to_string())?;
+
+        let arity = |min: usize, max: usize| {
+            if args.len() < min || args.len() > max || args.iter().any(|arg| arg.is_empty()) {
+                let expected = if min == max {
+                    format!("{}", min)
+                } else {
+                    format!("{} or {}", min, max)
+                };
+                Err(format!("{}() requires {} arguments", name, expected))
+            } else {
+                Ok(())
+            }
+        };
+        let zone = |i: usize| args.get(i).map(|arg| parse_zone_argument(arg)).transpose();
+
+        if let Some(check) = CalendarCheck::from_function(name) {
+            // A holiday only means something with a calendar that lists it
+            let min = if check == CalendarCheck::Holiday {
+                2
+            } else {
+                1
+            };
+            arity(min, 2)?;
+            let calendar = args
+                .get(1)
+                .map(|arg| {
+                    unquote(arg)
+                        .filter(|name| !name.trim().is_empty())
+                        .map(|name| name.trim().to_string())
+                        .ok_or_else(|| format!("{}() calendar must be a quoted name", name))
+                })
+                .transpose()?;
+            return Ok(DateTimeExpr::Calendar {
+                check,
+                value: Box::new(Self::parse_base(args[0])?),
+                calendar,
+            });
+        }
+
+        match name {
+            "datetime" => {
+                arity(1, 2)?;
+                let base = Self::parse_base(args[0])?;
+                let start_of = args.get(1).and_then(|arg| {
+                    let modifier = unquote(arg)?.trim().to_lowercase();
+                    Some(modifier.strip_prefix("start of ")?.trim().to_string())
+                });
+                if let Some(unit) = start_of {
+                    let unit = match DurationUnit::parse(&unit) {
+                        Some(
+                            unit @ (DurationUnit::Days
+                            | DurationUnit::Weeks
+                            | DurationUnit::Months
+                            | DurationUnit::Years),
+                        ) => unit,
+                        _ => return Err(format!("Invalid modifier 'start of {}'", unit)),
+                    };
+                    return Ok(DateTimeExpr::StartOf {
+                        base: Box::new(base),
+                        unit,
+                    });
+                }
+                match args.get(1) {
+                    Some(duration) => Ok(DateTimeExpr::Shifted {
+                        base: Box::new(base),
+                        duration: Duration::parse(duration)?,
+                    }),
+                    None => Ok(base),
+                }
+            }
+            "strftime" => {
+                arity(2, 3)?;
+                let format = unquote(args[0])
+                    .ok_or_else(|| "strftime() format must be a quoted string".to_string())?;
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    return Err(format!("Invalid strftime format '{}'", format));
+                }
+                Ok(DateTimeExpr::Strftime {
+                    format: format.to_string(),
+                    value: Box::new(Self::parse_base(args[1])?),
+                    zone: zone(2)?,
+                })
+            }
+            "hour_of_day" => {
+                arity(1, 2)?;
+                Ok(DateTimeExpr::HourOfDay {
+                    value: Box::new(Self::parse_base(args[0])?),
+                    zone: zone(1)?,
+                })
+            }
+            "day_of_week" => {
+                arity(1, 2)?;
+                Ok(DateTimeExpr::DayOfWeek {
+                    value: Box::new(Self::parse_base(args[0])?),
+                    zone: zone(1)?,
+                })
+            }
+            _ => Err(format!("Unknown datetime function {}()", name)),
+        }
+    }
+
+    // Argument that denotes a datetime: `now`, a timestamp, a field or a nested call
+    fn parse_base(arg: &str) -> Result<Self, String> {
+        if arg == "now" {
+            return Ok(DateTimeExpr::Now);
+        }
+        if let Some(timestamp) = unquote(arg) {
+            return parse_timestamp(timestamp)
+                .map(DateTimeExpr::Timestamp)
+                .ok_or_else(|| format!("Invalid timestamp '{}'", timestamp));
+        }
+        if call(arg).is_some() {
+            let nested = Self::parse(arg)?;
+            if nested.result_type() != FieldType::DateTime {
+                return Err(format!("{} does not produce a datetime", arg));
+            }
+            return Ok(nested);
+        }
+        if is_field_path(arg) {
+            return Ok(DateTimeExpr::Field(arg.to_string()));
+        }
+        Err(format!("Invalid datetime argument '{}'", arg))
+    }
+
+    /// Type of the value the expression produces
+    pub fn result_type(&self) -> FieldType {
+        match self {
+            DateTimeExpr::Strftime { .. } => FieldType::String,
+            DateTimeExpr::HourOfDay { .. } | DateTimeExpr::DayOfWeek { .. } => FieldType::Number,
+            DateTimeExpr::Calendar { .. } => FieldType::Boolean,
+            _ => FieldType::DateTime,
+        }
+    }
+
+    /// Datetime the expression is computed from, if it is not a datetime itself
+    fn inner(&self) -> Option<&DateTimeExpr> {
+        match self {
+            DateTimeExpr::Shifted { base, .. } | DateTimeExpr::StartOf { base, .. } => Some(base),
+            DateTimeExpr::Strftime { value, .. }
+            | DateTimeExpr::HourOfDay { value, .. }
+            | DateTimeExpr::DayOfWeek { value, .. }
+            | DateTimeExpr::Calendar { value, .. } => Some(value),
+            DateTimeExpr::Now | DateTimeExpr::Timestamp(_) | DateTimeExpr::Field(_) => None,
+        }
+    }
+
+    /// Event fields the expression reads
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            DateTimeExpr::Field(field) => vec![field.as_str()],
+            _ => self.inner().map(DateTimeExpr::fields).unwrap_or_default(),
+        }
+    }
+
+    /// Business calendars the expression refers to by name
+    pub fn calendars(&self) -> Vec<&str> {
+        let mut calendars = self
+            .inner()
+            .map(DateTimeExpr::calendars)
+            .unwrap_or_default();
+        if let DateTimeExpr::Calendar {
+            calendar: Some(ref name),
+            ..
+        } = self
+        {
+            calendars.push(name);
+        }
+        calendars
+    }
+
+    /// Timezone the local time of this datetime is read in by default: that of
+    /// the field it comes from, otherwise the rule's, otherwise UTC
+    fn zone(&self, context: &DateTimeContext) -> Tz {
+        let timezone = match self {
+            DateTimeExpr::Field(name) => context.timezones.for_field(name),
+            DateTimeExpr::Shifted { base, .. } | DateTimeExpr::StartOf { base, .. } => {
+                return base.zone(context)
+            }
+            _ => context.timezones.default_timezone(),
+        };
+        timezone
+            .and_then(|timezone| timezone.resolve(context.field))
+            .unwrap_or(Tz::UTC)
+    }
+
+    pub fn evaluate(&self, context: &DateTimeContext) -> Result<DateTimeValue, String> {
+        let datetime = |expr: &DateTimeExpr| match expr.evaluate(context)? {
+            DateTimeValue::DateTime(datetime) => Ok(datetime),
+            other => Err(format!("Expected a datetime, found {:?}", other)),
+        };
+        let local = |expr: &DateTimeExpr, zone: &Option<Tz>| {
+            let zone = zone.unwrap_or_else(|| expr.zone(context));
+            datetime(expr).map(|datetime| datetime.with_timezone(&zone))
+        };
+
+        let value = match self {
+            DateTimeExpr::Now => DateTimeValue::DateTime(context.now),
+            DateTimeExpr::Timestamp(timestamp) => DateTimeValue::DateTime(*timestamp),
+            DateTimeExpr::Field(name) => DateTimeValue::DateTime(
+                (context.field)(name)
+                    .as_deref()
+                    .and_then(parse_timestamp)
+                    .ok_or_else(|| format!("Field {} is not a datetime", name))?,
+            ),
+            DateTimeExpr::Shifted { base, duration } => DateTimeValue::DateTime(
+                duration
+                    .apply_to(local(base, &None)?)
+                    .ok_or_else(|| "Datetime out of range".to_string())?
+                    .with_timezone(&Utc),
+            ),
+            DateTimeExpr::StartOf { base, unit } => {
+                let local = local(base, &None)?;
+                let date = local.date_naive();
+                let start = match unit {
+                    DurationUnit::Weeks => {
+                        date - Days::new(date.weekday().num_days_from_monday().into())
+                    }
+                    DurationUnit::Months => date.with_day(1).unwrap_or(date),
+                    DurationUnit::Years => date.with_ordinal(1).unwrap_or(date),
+                    _ => date,
+                };
+                // Where a transition skips midnight the day starts at the first valid hour
+                let start = (0..24)
+                    .filter_map(|hour| start.and_hms_opt(hour, 0, 0))
+                    .find_map(|start| local.timezone().from_local_datetime(&start).earliest())
+                    .ok_or_else(|| "Datetime out of range".to_string())?;
+                DateTimeValue::DateTime(start.with_timezone(&Utc))
+            }
+            DateTimeExpr::Strftime {
+                format,
+                value,
+                zone,
+            } => DateTimeValue::Text(local(value, zone)?.format(format).to_string()),
+            DateTimeExpr::HourOfDay { value, zone } => {
+                DateTimeValue::Number(local(value, zone)?.hour().into())
+            }
+            DateTimeExpr::DayOfWeek { value, zone } => {
+                DateTimeValue::Number(local(value, zone)?.weekday().number_from_monday().into())
+            }
+            DateTimeExpr::Calendar {
+                check,
+                value,
+                calendar,
+            } => {
+                let default = BusinessCalendar::new("default");
+                let calendar = match calendar {
+                    Some(name) => context
+                        .calendars
+                        .get(name)
+                        .ok_or_else(|| format!("Unknown business calendar '{}'", name))?,
+                    None => &default,
+                };
+                let local = local(value, &calendar.timezone)?.naive_local();
+                DateTimeValue::Bool(match check {
+                    CalendarCheck::Weekend => calendar.is_weekend(local.date()),
+                    CalendarCheck::Holiday => calendar.is_holiday(local.date()),
+                    CalendarCheck::BusinessDay => calendar.is_business_day(local.date()),
+                    CalendarCheck::BusinessHours => calendar.is_business_hours(local),
+                })
+            }
+        };
+        Ok(value)
+    }
+}
+
+fn parse_zone_argument(arg: &str) -> Result<Tz, String> {
+    unquote(arg)
+        .ok_or_else(|| format!("Timezone {} must be a quoted zone name", arg))
+        .and_then(parse_zone)
+}
+
+/// Parses an RFC 3339 timestamp, or a date and optional time read as UTC
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(timestamp, format) {
+            return Some(datetime.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(timestamp, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc())
+}
+
+fn unquote(arg: &str) -> Option<&str> {
+    let bytes = arg.as_bytes();
+    if bytes.len() >= 2
+        && (bytes[0] == b'\'' || bytes[0] == b'"')
+        && bytes[bytes.len() - 1] == bytes[0]
+    {
+        Some(&arg[1..arg.len() - 1])
+    } else {
+        None
+    }
+}
+
+// Split `name(a, b)` into the function name and its top-level arguments
+fn call(expr: &str) -> Option<(&str, Vec<&str>)> {
+    let open = expr.find('(')?;
+    let name = expr[..open].trim_end();
+    if name.is_empty()
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || !expr.ends_with(')')
+    {
+        return None;
+    }
+    Some((name, split_arguments(&expr[open + 1..expr.len() - 1])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timezone::Timezone;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        parse_timestamp(timestamp).unwrap()
+    }
+
+    fn evaluate_in(
+        expr: &str,
+        now: &str,
+        timezones: &Timezones,
+        calendars: &CalendarRegistry,
+    ) -> DateTimeValue {
+        let event = |field: &str| match field {
+            "transaction.timestamp" => Some("2024-02-29T23:30:00Z".to_string()),
+            "merchant_country" => Some("JP".to_string()),
+            _ => None,
+        };
+        let context = DateTimeContext {
+            now: at(now),
+            field: &event,
+            timezones,
+            calendars,
+        };
+        DateTimeExpr::parse(expr)
+            .unwrap()
+            .evaluate(&context)
+            .unwrap()
+    }
+
+    fn evaluate(expr: &str, now: &str) -> DateTimeValue {
+        evaluate_in(expr, now, &Timezones::new(), &CalendarRegistry::new())
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        let hours = |h: i64| Duration {
+            milliseconds: h * 3_600_000,
+            ..Default::default()
+        };
+        assert_eq!(Duration::parse("'-2 hours'"), Ok(hours(-2)));
+        assert_eq!(Duration::parse("PT2H"), Ok(hours(2)));
+        assert_eq!(
+            Duration::parse("-1 day 2 hours"),
+            Ok(Duration {
+                months: 0,
+                days: -1,
+                milliseconds: -7_200_000
+            })
+        );
+        assert_eq!(Duration::parse("1 day, 2 hours"), Duration::parse("P1DT2H"));
+        assert_eq!(
+            Duration::parse("P1Y2M3W4DT5M1.5S"),
+            Ok(Duration {
+                months: 14,
+                days: 25,
+                milliseconds: 301_500
+            })
+        );
+        assert_eq!(Duration::parse("250 ms").unwrap().milliseconds, 250);
+        assert_eq!(Duration::parse("30 seconds").unwrap().milliseconds, 30_000);
+
+        for invalid in [
+            "",
+            "-",
+            "2h",
+            "2 fortnights",
+            "1 day -2 hours",
+            "P",
+            "PT",
+            "P1H",
+            "PT1D",
+            "P1.5D",
+        ] {
+            assert!(
+                Duration::parse(invalid).is_err(),
+                "{} should not parse",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_calendar_arithmetic() {
+        let month = Duration::parse("1 month").unwrap();
+        assert_eq!(month.apply_to(at("2024-01-31")), Some(at("2024-02-29")));
+        assert_eq!(month.apply_to(at("2023-01-31")), Some(at("2023-02-28")));
+        assert_eq!(
+            Duration::parse("-1 year")
+                .unwrap()
+                .apply_to(at("2024-02-29")),
+            Some(at("2023-02-28"))
+        );
+        assert_eq!(
+            Duration::parse("P1M1D")
+                .unwrap()
+                .apply_to(at("2024-01-31T10:00:00Z")),
+            Some(at("2024-03-01T10:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        assert_eq!(
+            DateTimeExpr::parse("datetime(transaction.timestamp)"),
+            Ok(DateTimeExpr::Field("transaction.timestamp".to_string()))
+        );
+        assert_eq!(
+            DateTimeExpr::parse("hour_of_day(datetime(now, 'PT30M'))")
+                .unwrap()
+                .result_type(),
+            FieldType::Number
+        );
+        assert_eq!(
+            DateTimeExpr::parse("strftime('%H', transaction_date)")
+                .unwrap()
+                .fields(),
+            vec!["transaction_date"]
+        );
+        assert_eq!(
+            DateTimeExpr::parse("is_business_hours(created, 'us_banking')")
+                .unwrap()
+                .calendars(),
+            vec!["us_banking"]
+        );
+        assert!(DateTimeExpr::is_call("day_of_week(now)"));
+        assert!(!DateTimeExpr::is_call("sum(amount)"));
+
+        for invalid in [
+            "datetime(now - 2 hours)",
+            "datetime(now, 'start of hour')",
+            "datetime('2024-13-01')",
+            "datetime(now, '-2 hours', '1 day')",
+            "datetime()",
+            "strftime(%H, now)",
+            "strftime('%Q', now)",
+            "hour_of_day(strftime('%H', now))",
+            "datetime(now",
+            "hour_of_day(now, 'Mars/Olympus_Mons')",
+            "hour_of_day(now, UTC)",
+            "is_holiday(now)",
+            "is_business_day(now, '')",
+        ] {
+            assert!(
+                DateTimeExpr::parse(invalid).is_err(),
+                "{} should not parse",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let now = "2024-03-31T12:00:00Z";
+        assert_eq!(
+            evaluate("datetime(now, '-1 month')", now),
+            DateTimeValue::DateTime(at("2024-02-29T12:00:00Z"))
+        );
+        assert_eq!(
+            evaluate("datetime(transaction.timestamp, 'PT45M')", now),
+            DateTimeValue::DateTime(at("2024-03-01T00:15:00Z"))
+        );
+        assert_eq!(
+            evaluate("strftime('%H:%M', transaction.timestamp)", now),
+            DateTimeValue::Text("23:30".to_string())
+        );
+        assert_eq!(
+            evaluate("datetime(now, 'start of week')", now),
+            DateTimeValue::DateTime(at("2024-03-25"))
+        );
+        assert_eq!(
+            evaluate("datetime(transaction.timestamp, 'start of month')", now),
+            DateTimeValue::DateTime(at("2024-02-01"))
+        );
+        assert_eq!(evaluate("hour_of_day(now)", now), DateTimeValue::Number(12));
+        // 2024-03-31 is a Sunday
+        assert_eq!(evaluate("day_of_week(now)", now), DateTimeValue::Number(7));
+    }
+
+    #[test]
+    fn test_timezones_and_calendars() {
+        let mut timezones = Timezones::new();
+        timezones.set_default(Timezone::Zone(Tz::America__New_York));
+        timezones.insert(
+            "transaction.timestamp",
+            Timezone::Field {
+                field: "merchant_country".to_string(),
+                zones: [("JP".to_string(), Tz::Asia__Tokyo)].into(),
+                default: None,
+            },
+        );
+        let mut calendars = CalendarRegistry::new();
+        let mut banking = BusinessCalendar::new("us_banking");
+        banking.timezone = Some(Tz::America__New_York);
+        banking
+            .holidays
+            .insert(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(), String::new());
+        calendars.insert(banking);
+
+        let now = "2024-03-10T12:00:00Z";
+        let evaluate = |expr: &str| evaluate_in(expr, now, &timezones, &calendars);
+        // 23:30 UTC on Thursday is 08:30 on Friday in Tokyo
+        assert_eq!(
+            evaluate("hour_of_day(transaction.timestamp)"),
+            DateTimeValue::Number(8)
+        );
+        assert_eq!(
+            evaluate("strftime('%H', transaction.timestamp, 'UTC')"),
+            DateTimeValue::Text("23".to_string())
+        );
+        assert_eq!(evaluate("hour_of_day(now)"), DateTimeValue::Number(8));
+        // New York moved to daylight saving time at 02:00 that morning
+        assert_eq!(
+            evaluate("datetime(now, 'start of day')"),
+            DateTimeValue::DateTime(at("2024-03-10T05:00:00Z"))
+        );
+        assert_eq!(
+            evaluate("datetime(now, '-1 day')"),
+            DateTimeValue::DateTime(at("2024-03-09T13:00:00Z"))
+        );
+
+        assert_eq!(evaluate("is_weekend(now)"), DateTimeValue::Bool(true));
+        // The calendar reads the Tokyo timestamp as 18:30 on Thursday in New York
+        assert_eq!(
+            evaluate("is_business_hours(transaction.timestamp, 'us_banking')"),
+            DateTimeValue::Bool(false)
+        );
+        assert_eq!(
+            evaluate("is_business_day(transaction.timestamp, 'us_banking')"),
+            DateTimeValue::Bool(true)
+        );
+        assert_eq!(
+            evaluate("is_holiday(datetime(transaction.timestamp, '1 day'), 'us_banking')"),
+            DateTimeValue::Bool(true)
+        );
+    }
+}
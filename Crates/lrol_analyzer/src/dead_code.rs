@@ -0,0 +1,246 @@
+use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{DependencyGraph, DependencyKind};
+use crate::insights::WarningSeverity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadCodeKind {
+    /// Weighted evaluation outside the logical tree, contributing only to the score
+    UnreferencedEvaluation,
+    /// Evaluation with neither a weight nor anything depending on it
+    UnweightedEvaluation,
+    /// More than one logical evaluation that nothing depends on
+    MultipleRootLogicals,
+    /// Aggregation whose value is never compared with `@Name`
+    UncomparedAggregation,
+}
+
+/// Evaluation that does not, or may not, contribute to the rule's outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadCode {
+    pub kind: DeadCodeKind,
+    /// Evaluations concerned, in declaration order
+    pub evaluations: Vec<String>,
+    pub severity: WarningSeverity,
+    pub message: String,
+    pub suggestion: String,
+}
+
+/// Finds evaluations that are unused or only partly used by a model.
+///
+/// The graph must have been built from the same model; see
+/// [`DependencyGraph::from_model`].
+pub fn find_dead_code(model: &LrolModel, graph: &DependencyGraph) -> Vec<DeadCode> {
+    let mut findings = Vec::new();
+    let declared = |name: &str| model.evaluations.iter().find(|e| e.name == name);
+
+    let roots: Vec<&Evaluation> = graph.roots().into_iter().filter_map(declared).collect();
+    let logical_roots: Vec<&str> = roots
+        .iter()
+        .filter(|e| e.evaluation_type == EvaluationType::Logical)
+        .map(|e| e.name.as_str())
+        .collect();
+    let root_list = logical_roots.join(", ");
+
+    if logical_roots.len() > 1 {
+        findings.push(DeadCode {
+            kind: DeadCodeKind::MultipleRootLogicals,
+            evaluations: logical_roots.iter().map(|n| n.to_string()).collect(),
+            severity: WarningSeverity::Medium,
+            message: format!(
+                "The rule has {} independent logical evaluations",
+                logical_roots.len()
+            ),
+            suggestion: format!(
+                "Combine {} under a single AND/OR evaluation, or split them into separate rules",
+                root_list
+            ),
+        });
+    }
+
+    for evaluation in &roots {
+        let name = &evaluation.name;
+        match evaluation.evaluation_type {
+            EvaluationType::Logical => {}
+            EvaluationType::Aggregation => findings.push(DeadCode {
+                kind: DeadCodeKind::UncomparedAggregation,
+                evaluations: vec![name.clone()],
+                severity: WarningSeverity::Medium,
+                message: format!("Aggregation {} is computed but never used", name),
+                suggestion: compare_suggestion(name, &root_list),
+            }),
+            _ if evaluation.weight.is_none() => findings.push(DeadCode {
+                kind: DeadCodeKind::UnweightedEvaluation,
+                evaluations: vec![name.clone()],
+                severity: WarningSeverity::High,
+                message: format!(
+                    "{} has no weight and nothing depends on it, so it never affects the outcome",
+                    name
+                ),
+                suggestion: if logical_roots.is_empty() {
+                    format!("Give {} a weight, or remove it", name)
+                } else {
+                    format!(
+                        "Add {} to the operands of {}, give it a weight, or remove it",
+                        name, root_list
+                    )
+                },
+            }),
+            _ if !logical_roots.is_empty() => findings.push(DeadCode {
+                kind: DeadCodeKind::UnreferencedEvaluation,
+                evaluations: vec![name.clone()],
+                severity: WarningSeverity::Medium,
+                message: format!(
+                    "{} is not referenced by {} and only contributes through its weight",
+                    name, root_list
+                ),
+                suggestion: format!(
+                    "Add {} to the operands of {}, or remove it if it is obsolete",
+                    name, root_list
+                ),
+            }),
+            _ => {}
+        }
+    }
+
+    // Aggregations used directly as logical operands are treated as booleans
+    for evaluation in &model.evaluations {
+        let name = &evaluation.name;
+        if evaluation.evaluation_type != EvaluationType::Aggregation
+            || roots.iter().any(|root| root.name == *name)
+        {
+            continue;
+        }
+        let compared = graph
+            .incoming(name)
+            .any(|edge| edge.kind != DependencyKind::Operand);
+        if !compared {
+            let users = graph.dependents(name).join(", ");
+            findings.push(DeadCode {
+                kind: DeadCodeKind::UncomparedAggregation,
+                evaluations: vec![name.clone()],
+                severity: WarningSeverity::Low,
+                message: format!(
+                    "Aggregation {} is used as an operand of {} without being compared to a threshold",
+                    name, users
+                ),
+                suggestion: compare_suggestion(name, &users),
+            });
+        }
+    }
+
+    findings
+}
+
+fn compare_suggestion(name: &str, users: &str) -> String {
+    let target = if users.is_empty() {
+        String::new()
+    } else {
+        format!(" and use it in {}", users)
+    };
+    format!(
+        "Add a comparison such as {{\"left\": \"@{}\", \"operator\": \">\", \"right\": ...}}{}",
+        name, target
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, evaluation, logical};
+    use lrol_parser::Value;
+
+    fn findings(evaluations: Vec<Evaluation>) -> Vec<DeadCode> {
+        let model = LrolModel {
+            evaluations,
+            ..Default::default()
+        };
+        find_dead_code(&model, &DependencyGraph::from_model(&model))
+    }
+
+    #[test]
+    fn test_unreferenced_evaluation() {
+        // Shape of R015: the industry check is outside the logical tree
+        let findings = findings(vec![
+            comparison(
+                "High_Risk_Industry_Check",
+                "industry",
+                ">",
+                Value::Number(15.0),
+            ),
+            evaluation("Industry_Transaction_Count", EvaluationType::Aggregation),
+            comparison(
+                "Count_Exceeded",
+                "@Industry_Transaction_Count",
+                ">",
+                Value::Number(15.0),
+            ),
+            logical("High_Risk_Industry_Logic", "OR", &["Count_Exceeded"]),
+        ]);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, DeadCodeKind::UnreferencedEvaluation);
+        assert_eq!(findings[0].evaluations, vec!["High_Risk_Industry_Check"]);
+        assert_eq!(
+            findings[0].suggestion,
+            "Add High_Risk_Industry_Check to the operands of High_Risk_Industry_Logic, or remove it if it is obsolete"
+        );
+    }
+
+    #[test]
+    fn test_unweighted_and_multiple_roots() {
+        let mut unweighted = comparison("Unused", "amount", ">", Value::Number(15.0));
+        unweighted.weight = None;
+
+        let findings = findings(vec![
+            comparison("A", "amount", ">", Value::Number(15.0)),
+            comparison("B", "country_risk", ">", Value::Number(15.0)),
+            unweighted,
+            logical("First", "OR", &["A"]),
+            logical("Second", "OR", &["B"]),
+        ]);
+
+        let kinds: Vec<_> = findings.iter().map(|f| (f.kind, f.severity)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (DeadCodeKind::MultipleRootLogicals, WarningSeverity::Medium),
+                (DeadCodeKind::UnweightedEvaluation, WarningSeverity::High),
+            ]
+        );
+        assert_eq!(findings[0].evaluations, vec!["First", "Second"]);
+    }
+
+    #[test]
+    fn test_uncompared_aggregations() {
+        let findings = findings(vec![
+            evaluation("Count", EvaluationType::Aggregation),
+            evaluation("Total", EvaluationType::Aggregation),
+            evaluation("Unused_Sum", EvaluationType::Aggregation),
+            comparison("Total_Exceeded", "@Total", ">", Value::Number(15.0)),
+            logical("Logic", "OR", &["Count", "Total_Exceeded"]),
+        ]);
+
+        let found: Vec<_> = findings
+            .iter()
+            .map(|f| (f.kind, f.evaluations[0].as_str(), f.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    DeadCodeKind::UncomparedAggregation,
+                    "Unused_Sum",
+                    WarningSeverity::Medium
+                ),
+                (
+                    DeadCodeKind::UncomparedAggregation,
+                    "Count",
+                    WarningSeverity::Low
+                ),
+            ]
+        );
+    }
+}
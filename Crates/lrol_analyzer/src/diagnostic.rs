@@ -0,0 +1,830 @@
+use std::fmt;
+
+use lrol_parser::{parser::LrolModel, ParserError, SourceMap, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AnalyzerError;
+use crate::fix::Fix;
+use crate::scoring::ScoringFormula;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Source range annotated with a short message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Parser or analyzer finding with everything needed to render it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// Stable code such as `LROL0022`, see [`crate::error_codes::explain`]
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Where the problem is, when it can be located in the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<Label>,
+    /// Related locations, e.g. the first declaration of a duplicate
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary: Vec<Label>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub help: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    /// Diagnostic for a parser error, located with the source map when given
+    pub fn from_parser_error(error: &ParserError, source_map: Option<&SourceMap>) -> Self {
+        let (message, primary, help) = match error {
+            ParserError::InvalidSyntax {
+                line,
+                column,
+                message,
+            } => (
+                format!("Invalid syntax: {}", message),
+                source_map.and_then(|map| {
+                    let offset = map.offset(*line, *column)?;
+                    Some(Label {
+                        span: map.span(offset, offset + 1),
+                        message: "the parser stopped here".to_string(),
+                    })
+                }),
+                None,
+            ),
+            ParserError::MissingField { field } => (
+                error.to_string(),
+                None,
+                Some(format!("add a \"{}\" field to the rule", field)),
+            ),
+            ParserError::InvalidValue {
+                field, expected, ..
+            } => (
+                error.to_string(),
+                source_map
+                    .and_then(|map| map.get(&format!("/{}", field)))
+                    .map(|span| Label {
+                        span,
+                        message: format!("expected {}", expected),
+                    }),
+                None,
+            ),
+        };
+
+        Diagnostic {
+            code: error.code().to_string(),
+            severity: Severity::Error,
+            message,
+            primary,
+            secondary: Vec::new(),
+            help: help.into_iter().collect(),
+            fix: None,
+        }
+    }
+
+    /// Diagnostic for an analyzer error found in `model`.
+    ///
+    /// `occurrence` counts earlier errors of the same kind for the same
+    /// evaluation, which tells duplicate declarations apart.
+    pub fn from_analyzer_error(
+        error: &AnalyzerError,
+        severity: Severity,
+        model: Option<&LrolModel>,
+        source_map: Option<&SourceMap>,
+        occurrence: usize,
+    ) -> Self {
+        let locator = Locator { model, source_map };
+        let mut diagnostic = Diagnostic {
+            code: error.code().to_string(),
+            severity,
+            message: error.to_string(),
+            primary: None,
+            secondary: Vec::new(),
+            help: Vec::new(),
+            fix: None,
+        };
+        describe(error, &locator, occurrence, &mut diagnostic);
+        diagnostic
+    }
+
+    /// Renders the diagnostic with the offending source lines underlined
+    pub fn render(&self, source: Option<&str>, path: Option<&str>) -> String {
+        let mut output = format!("{}[{}]: {}\n", self.severity, self.code, self.message);
+
+        let lines: Vec<&str> = source
+            .map(|source| source.split('\n').collect())
+            .unwrap_or_default();
+        let mut labels: Vec<(&Label, bool)> = self
+            .primary
+            .iter()
+            .map(|label| (label, true))
+            .chain(self.secondary.iter().map(|label| (label, false)))
+            .filter(|(label, _)| label.span.line <= lines.len())
+            .collect();
+        labels.sort_by_key(|(label, _)| (label.span.line, label.span.column));
+
+        let width = labels
+            .iter()
+            .map(|(label, _)| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+
+        match (&self.primary, path) {
+            (Some(primary), path) => output.push_str(&format!(
+                "{}--> {}:{}:{}\n",
+                gutter,
+                path.unwrap_or("<source>"),
+                primary.span.line,
+                primary.span.column
+            )),
+            (None, Some(path)) => output.push_str(&format!("{}--> {}\n", gutter, path)),
+            (None, None) => {}
+        }
+
+        if !labels.is_empty() {
+            output.push_str(&format!("{} |\n", gutter));
+            let mut previous_line: Option<usize> = None;
+            for (label, primary) in &labels {
+                let line = label.span.line;
+                let text = lines[line - 1].trim_end_matches('\r');
+                if previous_line != Some(line) {
+                    if previous_line.is_some_and(|previous| line > previous + 1) {
+                        output.push_str("...\n");
+                    }
+                    output.push_str(&format!("{:>width$} | {}\n", line, text, width = width));
+                    previous_line = Some(line);
+                }
+                output.push_str(&format!(
+                    "{} | {}\n",
+                    gutter,
+                    underline(text, label, *primary)
+                ));
+            }
+            output.push_str(&format!("{} |\n", gutter));
+        }
+
+        for help in &self.help {
+            output.push_str(&format!("{} = help: {}\n", gutter, help));
+        }
+        if let Some(ref fix) = self.fix {
+            let command = if fix.is_machine_applicable() {
+                "lrol fix"
+            } else {
+                "lrol fix --unsafe-fixes"
+            };
+            output.push_str(&format!(
+                "{} = fix: {} (run `{}`)\n",
+                gutter, fix.message, command
+            ));
+        }
+
+        output
+    }
+}
+
+// Marks the part of the label's span on its first line
+fn underline(text: &str, label: &Label, primary: bool) -> String {
+    let start = (label.span.column - 1).min(text.len());
+    let line_start = label.span.start + 1 - label.span.column;
+    let end = (label.span.end.saturating_sub(line_start))
+        .min(text.len())
+        .max(start);
+
+    let indent: String = text[..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let marker = if primary { "^" } else { "-" };
+    let marks = marker.repeat(text[start..end].chars().count().max(1));
+    if label.message.is_empty() {
+        format!("{}{}", indent, marks)
+    } else {
+        format!("{}{} {}", indent, marks, label.message)
+    }
+}
+
+struct Locator<'a> {
+    model: Option<&'a LrolModel>,
+    source_map: Option<&'a SourceMap>,
+}
+
+impl Locator<'_> {
+    fn declarations(&self, name: &str) -> Vec<usize> {
+        self.model
+            .map(|model| {
+                model
+                    .evaluations
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.name == name)
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // The named field of an evaluation, falling back to its name
+    fn field(&self, name: &str, field: Option<&str>) -> Option<Span> {
+        let index = *self.declarations(name).first()?;
+        self.field_at(index, field)
+    }
+
+    fn field_at(&self, index: usize, field: Option<&str>) -> Option<Span> {
+        let map = self.source_map?;
+        field
+            .and_then(|field| map.evaluation_field(index, field))
+            .or_else(|| map.evaluation_field(index, "name"))
+            .or_else(|| map.evaluation(index))
+    }
+
+    fn pointer(&self, pointer: &str) -> Option<Span> {
+        self.source_map?.get(pointer)
+    }
+
+    // Field of the first action of the given type
+    fn action(&self, action_type: &str, field: &str) -> Option<Span> {
+        let index = self
+            .model?
+            .actions
+            .iter()
+            .position(|a| a.action_type == action_type)?;
+        self.pointer(&format!("/actions/{}/{}", index, field))
+            .or_else(|| self.pointer(&format!("/actions/{}", index)))
+    }
+
+    fn closest_name(&self, name: &str) -> Option<&str> {
+        self.model?
+            .evaluations
+            .iter()
+            .map(|e| (edit_distance(&e.name, name), e.name.as_str()))
+            .filter(|(distance, _)| *distance <= 2.max(name.len() / 4))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+fn label(span: Option<Span>, message: impl Into<String>) -> Option<Label> {
+    span.map(|span| Label {
+        span,
+        message: message.into(),
+    })
+}
+
+// Fill in the location, related locations and help for an analyzer error
+fn describe(error: &AnalyzerError, at: &Locator, occurrence: usize, d: &mut Diagnostic) {
+    let evaluation_label = |name: &str, message: String| label(at.field(name, None), message);
+
+    match error {
+        AnalyzerError::DuplicateEvaluationName(name) => {
+            let declarations = at.declarations(name);
+            let duplicate = declarations.get(occurrence + 1).or(declarations.last());
+            d.primary = label(
+                duplicate.and_then(|&i| at.field_at(i, Some("name"))),
+                "declared again here",
+            );
+            d.secondary
+                .extend(evaluation_label(name, "first declared here".to_string()));
+            d.help
+                .push("evaluation names must be unique within a rule".to_string());
+        }
+        AnalyzerError::MissingOperandReference {
+            evaluation_name,
+            missing_operand,
+        } => {
+            let position = at
+                .model
+                .and_then(|m| m.evaluations.iter().find(|e| e.name == *evaluation_name))
+                .and_then(|e| e.operands.as_ref())
+                .and_then(|operands| operands.iter().position(|o| o == missing_operand));
+            let field = position.map(|i| format!("operands[{}]", i));
+            d.primary = label(
+                at.field(
+                    evaluation_name,
+                    Some(field.as_deref().unwrap_or("operands")),
+                ),
+                "no evaluation with this name",
+            );
+            match at.closest_name(missing_operand) {
+                Some(candidate) => d.help.push(format!("did you mean {}?", candidate)),
+                None => d.help.push(format!(
+                    "declare an evaluation named {}, or remove it from the operands",
+                    missing_operand
+                )),
+            }
+        }
+        AnalyzerError::CircularDependency {
+            evaluation_name,
+            dependency_chain,
+            ..
+        } => {
+            let next = |i: usize| {
+                dependency_chain
+                    .get(i + 1)
+                    .unwrap_or(&dependency_chain[0])
+                    .clone()
+            };
+            d.primary = evaluation_label(evaluation_name, format!("depends on {}", next(0)));
+            for (i, member) in dependency_chain.iter().enumerate().skip(1) {
+                d.secondary
+                    .extend(evaluation_label(member, format!("depends on {}", next(i))));
+            }
+            d.help
+                .push("remove one of the dependencies so that evaluations form a tree".to_string());
+        }
+        AnalyzerError::InvalidWeight {
+            evaluation_name, ..
+        }
+        | AnalyzerError::InvalidWeightRange {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("weight")),
+                "weight out of range",
+            );
+            d.help.push("weights range from 1 to 5".to_string());
+        }
+        AnalyzerError::MissingRequiredField {
+            evaluation_name,
+            field_name,
+        } => {
+            d.primary = evaluation_label(evaluation_name, format!("has no '{}'", field_name));
+            d.help.push(format!(
+                "add a \"{}\" field to {}",
+                field_name, evaluation_name
+            ));
+        }
+        AnalyzerError::InvalidLogicalOperator {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("operator")),
+                "not a logical operator",
+            );
+            d.help
+                .push("logical evaluations combine operands with AND or OR".to_string());
+        }
+        AnalyzerError::EmptyOperands(name) => {
+            d.primary = label(at.field(name, Some("operands")), "no operands");
+            d.help
+                .push("list the evaluations to combine in \"operands\"".to_string());
+        }
+        AnalyzerError::InvalidStringReference {
+            evaluation_name,
+            field_name,
+            reference,
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("@{} is not declared", reference),
+            );
+            if let Some(candidate) = at.closest_name(reference) {
+                d.help.push(format!("did you mean @{}?", candidate));
+            }
+        }
+        AnalyzerError::InvalidDateTimeExpression {
+            evaluation_name,
+            field_name,
+            ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                "invalid datetime expression",
+            );
+            d.help.push(
+                "write it as e.g. datetime(now, '-2 hours') or hour_of_day(transaction.timestamp)"
+                    .to_string(),
+            );
+        }
+        AnalyzerError::InvalidDurationFormat {
+            evaluation_name,
+            field_name,
+            ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                "invalid duration",
+            );
+            d.help
+                .push("write durations as e.g. '-2 hours', '1 day 6 hours' or 'PT2H'".to_string());
+        }
+        AnalyzerError::InvalidThreshold { .. } => {
+            d.primary = label(at.pointer("/threshold"), "out of range");
+            d.help.push("thresholds range from 0 to 1".to_string());
+        }
+        AnalyzerError::InvalidEvaluationType {
+            evaluation_name, ..
+        } => {
+            d.primary = label(at.field(evaluation_name, Some("type")), "unknown type");
+            d.help.push(
+                "evaluation types are comparison, aggregation, logical, time-based and conditional"
+                    .to_string(),
+            );
+        }
+        AnalyzerError::InvalidComparisonOperator {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("operator")),
+                "not a comparison operator",
+            );
+            d.help.push(
+                "comparison operators are >, <, >=, <=, ==, !=, IN, NOT IN, LIKE and NOT LIKE"
+                    .to_string(),
+            );
+        }
+        AnalyzerError::InvalidAggregationType {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("aggregation")),
+                "unknown aggregation",
+            );
+            d.help
+                .push("aggregations are SUM, COUNT, AVG, MIN, MAX and STDDEV".to_string());
+        }
+        AnalyzerError::InvalidActionType { action_type } => {
+            d.primary = label(at.action(action_type, "type"), "invalid action type");
+            d.help
+                .push("give the action a type such as flag_transaction".to_string());
+        }
+        AnalyzerError::MissingActionReason { action_type } => {
+            d.primary = label(at.action(action_type, "reason"), "empty reason");
+            d.help
+                .push("explain why the action is taken in \"reason\"".to_string());
+        }
+        AnalyzerError::InvalidMetadataFormat { field, .. } => {
+            d.primary = label(at.pointer(&format!("/metadata/{}", field)), "invalid value");
+        }
+        AnalyzerError::MissingRequiredSchemaField { field } => {
+            d.primary = label(at.pointer(&format!("/{}", field)), "empty");
+            d.help.push(match field.as_str() {
+                "evaluations" | "actions" => {
+                    format!("every rule needs at least one entry in \"{}\"", field)
+                }
+                _ => format!("give the rule a non-empty \"{}\"", field),
+            });
+        }
+        AnalyzerError::InvalidEventSchema { .. } => {
+            d.primary = label(at.pointer("/event_schema"), "invalid schema");
+        }
+        AnalyzerError::UnknownField {
+            evaluation_name,
+            field_name,
+            field,
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("{} is not in the event schema", field),
+            );
+            d.help
+                .push("declare the field in the event schema, or check its spelling".to_string());
+        }
+        AnalyzerError::TypeMismatch {
+            evaluation_name,
+            field_name,
+            expected,
+            found,
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("expected {}, found {}", expected, found),
+            );
+        }
+        AnalyzerError::IncompatibleOperator {
+            evaluation_name,
+            field_name,
+            operator,
+            operand_type,
+            ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("{} cannot use {}", operand_type, operator),
+            );
+        }
+        AnalyzerError::IncompatibleReference {
+            evaluation_name,
+            field_name,
+            reference,
+            found,
+            expected,
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("expected {}, found {}", expected, found),
+            );
+            d.secondary.extend(label(
+                at.field(reference, None),
+                format!("{} produces a {}", reference, found),
+            ));
+            d.help.push(match found.as_str() {
+                "boolean" => format!(
+                    "{} is true or false; combine it in a logical evaluation instead",
+                    reference
+                ),
+                "number" => format!("compare {} with a number in a comparison first", reference),
+                _ => format!(
+                    "compare {} with one of its results in a comparison first",
+                    reference
+                ),
+            });
+        }
+        AnalyzerError::UnsatisfiableEvaluation {
+            evaluation_name,
+            conflicting_evaluations,
+            ..
+        } => {
+            d.primary = evaluation_label(evaluation_name, "can never fire".to_string());
+            for other in conflicting_evaluations {
+                d.secondary.extend(evaluation_label(
+                    other,
+                    "conflicts with the others".to_string(),
+                ));
+            }
+            d.help
+                .push("remove or relax one of the conflicting conditions".to_string());
+        }
+        AnalyzerError::TautologicalEvaluation {
+            evaluation_name,
+            complementary_evaluations,
+            ..
+        } => {
+            d.primary = evaluation_label(evaluation_name, "always fires".to_string());
+            for other in complementary_evaluations {
+                d.secondary.extend(evaluation_label(
+                    other,
+                    "covers the remaining cases".to_string(),
+                ));
+            }
+            d.help
+                .push("a rule that always fires flags every event; check the operands".to_string());
+        }
+        AnalyzerError::RedundantOperand {
+            evaluation_name,
+            operand,
+            subsumed_by,
+        } => {
+            d.primary = evaluation_label(evaluation_name, format!("{} is redundant", operand));
+            d.secondary.extend(evaluation_label(
+                subsumed_by,
+                format!("already implied by {}", subsumed_by),
+            ));
+            d.help.push(format!("remove {} from the operands", operand));
+        }
+        AnalyzerError::UnreachableThreshold { max_score, .. } => {
+            d.primary = label(at.pointer("/threshold"), "can never be reached");
+            d.help.push(format!(
+                "lower the threshold to at most {:.2}, or raise the weights",
+                max_score
+            ));
+        }
+        AnalyzerError::ThresholdAlwaysReached { min_score, .. } => {
+            d.primary = label(at.pointer("/threshold"), "always reached");
+            d.help
+                .push(format!("raise the threshold above {:.2}", min_score));
+        }
+        AnalyzerError::LowWeightTrigger {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("weight")),
+                "enough to trigger the rule alone",
+            );
+            d.secondary
+                .extend(label(at.pointer("/threshold"), "threshold"));
+            d.help.push(format!(
+                "if {} should not trigger the rule by itself, lower its weight or raise the threshold",
+                evaluation_name
+            ));
+        }
+        AnalyzerError::IneffectiveWeight {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("weight")),
+                "never changes the outcome",
+            );
+        }
+        AnalyzerError::NumericString {
+            evaluation_name,
+            field_name,
+            ..
+        } => {
+            d.primary = label(at.field(evaluation_name, Some(field_name)), "quoted number");
+        }
+        AnalyzerError::MissingWeight { evaluation_name } => {
+            d.primary = evaluation_label(evaluation_name, "no weight".to_string());
+            d.help.push("give it a weight between 1 and 5".to_string());
+        }
+        AnalyzerError::InvalidTimezone { field, .. } => {
+            let pointer = match field {
+                Some(field) => format!("/field_timezones/{}", field),
+                None => "/timezone".to_string(),
+            };
+            d.primary = label(at.pointer(&pointer), "unknown timezone");
+            d.help.push(
+                "use an IANA zone name such as America/New_York, Europe/London or UTC".to_string(),
+            );
+        }
+        AnalyzerError::UnknownCalendar {
+            evaluation_name,
+            field_name,
+            calendar,
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some(field_name)),
+                format!("no calendar named {}", calendar),
+            );
+            d.help.push(
+                "load the calendar with --calendars, or check the spelling of its name".to_string(),
+            );
+        }
+        AnalyzerError::NonCanonicalAggregation {
+            evaluation_name, ..
+        } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("aggregation")),
+                "not upper case",
+            );
+        }
+        AnalyzerError::UnboundedAggregationWindow { evaluation_name } => {
+            d.primary = label(
+                at.field(evaluation_name, Some("conditions")),
+                "no time window",
+            );
+            d.help.push(
+                "add a condition such as transaction_date >= datetime(now, '-30 days')".to_string(),
+            );
+        }
+        AnalyzerError::StateBudgetExceeded {
+            largest_evaluation, ..
+        } => {
+            d.primary = label(
+                at.field(largest_evaluation, Some("conditions")),
+                "largest window",
+            );
+            d.help.push(format!(
+                "shorten the window of {}, or raise the budget",
+                largest_evaluation
+            ));
+        }
+        AnalyzerError::PolicyViolation {
+            policy_id,
+            description,
+            evaluation_name,
+            field_name,
+            ..
+        } => {
+            let span = match evaluation_name {
+                Some(evaluation_name) => at.field(evaluation_name, Some(field_name)),
+                // A missing field, such as metadata, is pointed at the rule's id
+                None => at
+                    .pointer(&format!("/{}", field_name))
+                    .or_else(|| at.pointer("/model_id")),
+            };
+            d.primary = label(span, format!("violates {}", policy_id));
+            d.help.extend(description.clone());
+        }
+        AnalyzerError::InvalidScoring { .. } => {
+            d.primary = label(at.pointer("/scoring"), "unknown strategy");
+            d.help.push(format!(
+                "expected one of {}",
+                ScoringFormula::ALL
+                    .iter()
+                    .map(ScoringFormula::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::RuleValidator;
+
+    const SOURCE: &str = r#"{
+  "model_id": "R950",
+  "name": "Broken",
+  "threshold": 0.5,
+  "evaluations": [
+    {
+      "name": "Amount_Check",
+      "type": "comparison",
+      "left": "amount",
+      "operator": "=<",
+      "right": 100,
+      "weight": 2
+    },
+    {
+      "name": "Logic",
+      "type": "logical",
+      "operator": "OR",
+      "operands": ["Amount_Check", "Amount_Chek"],
+      "weight": 2
+    },
+    {
+      "name": "Amount_Check",
+      "type": "comparison",
+      "left": "amount",
+      "operator": ">",
+      "right": 5,
+      "weight": 2
+    }
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Large amount"}]
+}"#;
+
+    fn diagnostic(code: &str) -> Diagnostic {
+        let report = RuleValidator::new().validate_with_report(SOURCE);
+        report
+            .diagnostics()
+            .into_iter()
+            .find(|d| d.code == code)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_with_fix() {
+        let rendered = diagnostic("LROL0022").render(Some(SOURCE), Some("rules/R950.json"));
+        assert_eq!(
+            rendered,
+            "error[LROL0022]: Invalid comparison operator '=<' in Amount_Check
+  --> rules/R950.json:10:19
+   |
+10 |       \"operator\": \"=<\",
+   |                   ^^^^ not a comparison operator
+   |
+   = help: comparison operators are >, <, >=, <=, ==, !=, IN, NOT IN, LIKE and NOT LIKE
+   = fix: Replace operator '=<' with '<=' (run `lrol fix`)
+"
+        );
+    }
+
+    #[test]
+    fn test_secondary_labels_and_suggestions() {
+        let duplicate = diagnostic("LROL0010");
+        assert_eq!(duplicate.primary.as_ref().unwrap().span.line, 22);
+        assert_eq!(duplicate.secondary[0].span.line, 7);
+        let rendered = duplicate.render(Some(SOURCE), None);
+        assert!(rendered.contains("  --> <source>:22:15\n"));
+        assert!(rendered.contains(" 7 |       \"name\": \"Amount_Check\",\n   |               -------------- first declared here\n...\n22 |"));
+
+        let missing = diagnostic("LROL0011");
+        assert_eq!(missing.help, vec!["did you mean Amount_Check?"]);
+        let span = missing.primary.unwrap().span;
+        assert_eq!(&SOURCE[span.start..span.end], "\"Amount_Chek\"");
+    }
+
+    #[test]
+    fn test_parser_diagnostic() {
+        let source = "{\n  \"model_id\": \"R951\",\n  \"threshold\": \"high\"\n}";
+        let report = RuleValidator::new().validate_with_report(source);
+        let diagnostics = report.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "LROL0001");
+        assert!(diagnostics[0].primary.is_some());
+        assert!(diagnostics[0]
+            .render(Some(source), None)
+            .starts_with("error[LROL0001]: Invalid syntax: "));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("Amount_Check", "Amount_Chek"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use lrol_parser::{parser::LrolModel, Action, Evaluation};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::event_schema::lrol_to_json;
+use crate::graph::{DependencyEdge, DependencyGraph};
+
+/// Old and new value of a field, `None` on the side where it is absent
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Change to the evaluation with a given name, wherever it is declared
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvaluationDiff {
+    pub name: String,
+    pub change: ChangeKind,
+    /// Every field of an added or removed evaluation, the changed ones otherwise
+    pub fields: Vec<FieldChange>,
+}
+
+impl EvaluationDiff {
+    pub fn field(&self, field: &str) -> Option<&FieldChange> {
+        self.fields.iter().find(|change| change.field == field)
+    }
+}
+
+/// Change to the action of a given type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionChange {
+    pub action_type: String,
+    pub change: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_reason: Option<String>,
+}
+
+/// Dependencies between evaluations that only one version has
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DependencyChanges {
+    pub added: Vec<DependencyEdge>,
+    pub removed: Vec<DependencyEdge>,
+}
+
+/// Semantic difference between two versions of a rule.
+///
+/// Evaluations are matched by name, so moving one around changes nothing but
+/// [`RuleDiff::evaluation_order_changed`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleDiff {
+    /// Changes to top-level fields such as `threshold`
+    pub rule: Vec<FieldChange>,
+    pub evaluations: Vec<EvaluationDiff>,
+    /// Whether evaluations in both versions are declared in another order
+    pub evaluation_order_changed: bool,
+    pub actions: Vec<ActionChange>,
+    pub dependencies: DependencyChanges,
+}
+
+impl RuleDiff {
+    pub fn between(old: &LrolModel, new: &LrolModel) -> Self {
+        let rule = changed_fields(rule_fields(old), rule_fields(new));
+
+        let old_evaluations = first_by_name(&old.evaluations);
+        let new_evaluations = first_by_name(&new.evaluations);
+        let mut evaluations = Vec::new();
+        for evaluation in &new.evaluations {
+            if !std::ptr::eq(new_evaluations[evaluation.name.as_str()], evaluation) {
+                continue;
+            }
+            match old_evaluations.get(evaluation.name.as_str()) {
+                None => evaluations.push(EvaluationDiff {
+                    name: evaluation.name.clone(),
+                    change: ChangeKind::Added,
+                    fields: changed_fields(Vec::new(), evaluation_fields(evaluation)),
+                }),
+                Some(previous) => {
+                    let fields =
+                        changed_fields(evaluation_fields(previous), evaluation_fields(evaluation));
+                    if !fields.is_empty() {
+                        evaluations.push(EvaluationDiff {
+                            name: evaluation.name.clone(),
+                            change: ChangeKind::Modified,
+                            fields,
+                        });
+                    }
+                }
+            }
+        }
+        for evaluation in &old.evaluations {
+            if std::ptr::eq(old_evaluations[evaluation.name.as_str()], evaluation)
+                && !new_evaluations.contains_key(evaluation.name.as_str())
+            {
+                evaluations.push(EvaluationDiff {
+                    name: evaluation.name.clone(),
+                    change: ChangeKind::Removed,
+                    fields: changed_fields(evaluation_fields(evaluation), Vec::new()),
+                });
+            }
+        }
+
+        let common = |model: &LrolModel, other: &HashMap<&str, &Evaluation>| -> Vec<String> {
+            let mut names: Vec<String> = Vec::new();
+            for evaluation in &model.evaluations {
+                if other.contains_key(evaluation.name.as_str()) && !names.contains(&evaluation.name)
+                {
+                    names.push(evaluation.name.clone());
+                }
+            }
+            names
+        };
+        let evaluation_order_changed =
+            common(old, &new_evaluations) != common(new, &old_evaluations);
+
+        let old_graph = DependencyGraph::from_model(old);
+        let new_graph = DependencyGraph::from_model(new);
+        let only_in = |graph: &DependencyGraph, other: &DependencyGraph| -> Vec<DependencyEdge> {
+            graph
+                .edges()
+                .iter()
+                .filter(|edge| !other.edges().contains(edge))
+                .cloned()
+                .collect()
+        };
+
+        RuleDiff {
+            rule,
+            evaluations,
+            evaluation_order_changed,
+            actions: action_changes(&old.actions, &new.actions),
+            dependencies: DependencyChanges {
+                added: only_in(&new_graph, &old_graph),
+                removed: only_in(&old_graph, &new_graph),
+            },
+        }
+    }
+
+    /// Whether both versions behave and read the same, apart from evaluation order
+    pub fn is_empty(&self) -> bool {
+        self.rule.is_empty()
+            && self.evaluations.is_empty()
+            && self.actions.is_empty()
+            && self.dependencies.added.is_empty()
+            && self.dependencies.removed.is_empty()
+    }
+
+    pub fn evaluation(&self, name: &str) -> Option<&EvaluationDiff> {
+        self.evaluations.iter().find(|diff| diff.name == name)
+    }
+}
+
+// Later declarations of a duplicated name are ignored, as the analyzer reports them
+fn first_by_name(evaluations: &[Evaluation]) -> HashMap<&str, &Evaluation> {
+    let mut by_name = HashMap::new();
+    for evaluation in evaluations {
+        by_name
+            .entry(evaluation.name.as_str())
+            .or_insert(evaluation);
+    }
+    by_name
+}
+
+type Fields = Vec<(&'static str, serde_json::Value)>;
+
+fn rule_fields(model: &LrolModel) -> Fields {
+    let mut fields = vec![
+        ("model_id", json!(model.model_id)),
+        ("name", json!(model.name)),
+    ];
+    if let Some(description) = &model.description {
+        fields.push(("description", json!(description)));
+    }
+    fields.push(("threshold", json!(model.threshold)));
+    if let Some(scoring) = &model.scoring {
+        fields.push(("scoring", json!(scoring)));
+    }
+    if let Some(timezone) = &model.timezone {
+        fields.push(("timezone", json!(timezone)));
+    }
+    if let Some(field_timezones) = &model.field_timezones {
+        fields.push(("field_timezones", json!(field_timezones)));
+    }
+    if let Some(event_schema) = &model.event_schema {
+        fields.push(("event_schema", lrol_to_json(event_schema)));
+    }
+    if let Some(metadata) = &model.metadata {
+        fields.push(("metadata", json!(metadata)));
+    }
+    fields
+}
+
+/// Fields of an evaluation as written in LROL, in their usual order
+fn evaluation_fields(evaluation: &Evaluation) -> Fields {
+    let mut fields = vec![("type", json!(evaluation.evaluation_type.to_string()))];
+    if let Some(left) = &evaluation.left {
+        fields.push(("left", json!(left)));
+    }
+    if let Some(operator) = &evaluation.operator {
+        fields.push(("operator", json!(operator)));
+    }
+    if let Some(right) = &evaluation.right {
+        fields.push(("right", lrol_to_json(right)));
+    }
+    if let Some(operands) = &evaluation.operands {
+        fields.push(("operands", json!(operands)));
+    }
+    if let Some(aggregation) = &evaluation.aggregation {
+        fields.push(("aggregation", json!(aggregation.to_string())));
+    }
+    if let Some(field) = &evaluation.field {
+        fields.push(("field", json!(field)));
+    }
+    if let Some(conditions) = &evaluation.conditions {
+        let conditions: Vec<serde_json::Value> = conditions
+            .iter()
+            .map(|condition| {
+                json!({
+                    "left": condition.left,
+                    "operator": condition.operator,
+                    "right": condition.right.as_ref().map(lrol_to_json),
+                })
+            })
+            .collect();
+        fields.push(("conditions", json!(conditions)));
+    }
+    if let Some(weight) = evaluation.weight {
+        fields.push(("weight", json!(weight)));
+    }
+    fields
+}
+
+fn changed_fields(old: Fields, new: Fields) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = Vec::new();
+    for (field, value) in &new {
+        let previous = old.iter().find(|(name, _)| name == field).map(|(_, v)| v);
+        if previous != Some(value) {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                old: previous.cloned(),
+                new: Some(value.clone()),
+            });
+        }
+    }
+    for (field, value) in old {
+        if !new.iter().any(|(name, _)| *name == field) {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                old: Some(value),
+                new: None,
+            });
+        }
+    }
+    changes
+}
+
+/// Actions are matched by type; among several of one type, by position
+fn action_changes(old: &[Action], new: &[Action]) -> Vec<ActionChange> {
+    let of_type = |actions: &[Action], action_type: &str| -> Vec<String> {
+        actions
+            .iter()
+            .filter(|action| action.action_type == action_type)
+            .map(|action| action.reason.clone())
+            .collect()
+    };
+
+    let mut types: Vec<&str> = Vec::new();
+    for action in old.iter().chain(new) {
+        if !types.contains(&action.action_type.as_str()) {
+            types.push(&action.action_type);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for action_type in types {
+        let old_reasons = of_type(old, action_type);
+        let new_reasons = of_type(new, action_type);
+        for i in 0..old_reasons.len().max(new_reasons.len()) {
+            let (old_reason, new_reason) = (old_reasons.get(i), new_reasons.get(i));
+            let change = match (old_reason, new_reason) {
+                (Some(old), Some(new)) if old == new => continue,
+                (Some(_), Some(_)) => ChangeKind::Modified,
+                (Some(_), None) => ChangeKind::Removed,
+                _ => ChangeKind::Added,
+            };
+            changes.push(ActionChange {
+                action_type: action_type.to_string(),
+                change,
+                old_reason: old_reason.cloned(),
+                new_reason: new_reason.cloned(),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DependencyKind;
+
+    fn model(threshold: f64, evaluations: &str, actions: &str) -> LrolModel {
+        lrol_parser::parse_str(&format!(
+            r#"{{
+  "model_id": "D001",
+  "name": "Diff",
+  "threshold": {},
+  "evaluations": [{}],
+  "actions": [{}]
+}}"#,
+            threshold, evaluations, actions
+        ))
+        .unwrap()
+    }
+
+    const AMOUNT: &str = r#"{"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 10000, "weight": 3}"#;
+    const COUNTRY: &str = r#"{"name": "Country_Check", "type": "comparison", "left": "country", "operator": "==", "right": "XX", "weight": 2}"#;
+    const FLAG: &str = r#"{"type": "flag_transaction", "reason": "Risky"}"#;
+
+    #[test]
+    fn test_reordering_is_not_a_change() {
+        let old = model(0.5, &format!("{}, {}", AMOUNT, COUNTRY), FLAG);
+        let new = model(0.5, &format!("{}, {}", COUNTRY, AMOUNT), FLAG);
+        let diff = RuleDiff::between(&old, &new);
+        assert!(diff.is_empty());
+        assert!(diff.evaluation_order_changed);
+        assert!(RuleDiff::between(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_changes() {
+        let old = model(0.5, &format!("{}, {}", AMOUNT, COUNTRY), FLAG);
+        let new = model(
+            0.7,
+            &format!(
+                "{}, {}",
+                AMOUNT
+                    .replace("10000", "5000")
+                    .replace(r#""weight": 3"#, r#""weight": 4"#),
+                r#"{"name": "Both", "type": "logical", "operator": "AND", "operands": ["Amount_Check", "Missing"], "weight": 1}"#
+            ),
+            r#"{"type": "flag_transaction", "reason": "Very risky"}, {"type": "send_alert", "reason": "Alert"}"#,
+        );
+        let diff = RuleDiff::between(&old, &new);
+
+        assert_eq!(
+            diff.rule,
+            vec![FieldChange {
+                field: "threshold".to_string(),
+                old: Some(json!(0.5)),
+                new: Some(json!(0.7)),
+            }]
+        );
+
+        let amount = diff.evaluation("Amount_Check").unwrap();
+        assert_eq!(amount.change, ChangeKind::Modified);
+        assert_eq!(
+            amount
+                .fields
+                .iter()
+                .map(|f| f.field.as_str())
+                .collect::<Vec<_>>(),
+            vec!["right", "weight"]
+        );
+        assert_eq!(amount.field("weight").unwrap().new, Some(json!(4)));
+        assert_eq!(diff.evaluation("Both").unwrap().change, ChangeKind::Added);
+        let country = diff.evaluation("Country_Check").unwrap();
+        assert_eq!(country.change, ChangeKind::Removed);
+        assert_eq!(country.field("right").unwrap().old, Some(json!("XX")));
+        assert_eq!(country.field("right").unwrap().new, None);
+
+        assert_eq!(
+            diff.actions
+                .iter()
+                .map(|a| (a.action_type.as_str(), a.change))
+                .collect::<Vec<_>>(),
+            vec![
+                ("flag_transaction", ChangeKind::Modified),
+                ("send_alert", ChangeKind::Added)
+            ]
+        );
+
+        assert!(diff.dependencies.removed.is_empty());
+        assert_eq!(diff.dependencies.added.len(), 2);
+        assert_eq!(diff.dependencies.added[0].kind, DependencyKind::Operand);
+        assert_eq!(diff.dependencies.added[1].to, "Missing");
+    }
+}
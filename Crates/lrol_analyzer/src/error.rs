@@ -1,8 +1,11 @@
+use std::fmt;
+
 use lrol_parser::ParserError;
 
+use crate::graph::DependencyEdge;
+use crate::state_cost::format_bytes;
 use crate::validator::ValidationReport;
 
-
 #[derive(Debug, Clone)]
 pub enum AnalyzerError {
     DuplicateEvaluationName(String),
@@ -12,7 +15,10 @@ pub enum AnalyzerError {
     },
     CircularDependency {
         evaluation_name: String,
+        /// Evaluations on the shortest cycle, starting at `evaluation_name`
         dependency_chain: Vec<String>,
+        /// Every dependency between the evaluations of the cycle
+        edges: Vec<DependencyEdge>,
     },
     InvalidWeight {
         evaluation_name: String,
@@ -50,6 +56,10 @@ pub enum AnalyzerError {
         value: f64,
         reason: String,
     },
+    /// The model's `scoring` field names no known strategy
+    InvalidScoring {
+        scoring: String,
+    },
 
     // Evaluation-specific errors
     InvalidEvaluationType {
@@ -87,8 +97,596 @@ pub enum AnalyzerError {
     MissingRequiredSchemaField {
         field: String,
     },
+
+    // Event schema errors
+    InvalidEventSchema {
+        reason: String,
+    },
+    UnknownField {
+        evaluation_name: String,
+        field_name: String,
+        field: String,
+    },
+    TypeMismatch {
+        evaluation_name: String,
+        field_name: String,
+        expected: String,
+        found: String,
+    },
+    IncompatibleOperator {
+        evaluation_name: String,
+        field_name: String,
+        operator: String,
+        operand_type: String,
+        reason: String,
+    },
+    /// `@` reference or logical operand naming an evaluation whose output does
+    /// not fit where it is used, e.g. a comparison in arithmetic
+    IncompatibleReference {
+        evaluation_name: String,
+        field_name: String,
+        reference: String,
+        /// Output type of the referenced evaluation
+        found: String,
+        expected: String,
+    },
+
+    // Constraint analysis warnings
+    UnsatisfiableEvaluation {
+        evaluation_name: String,
+        conflicting_evaluations: Vec<String>,
+        reason: String,
+    },
+    TautologicalEvaluation {
+        evaluation_name: String,
+        complementary_evaluations: Vec<String>,
+        reason: String,
+    },
+    RedundantOperand {
+        evaluation_name: String,
+        operand: String,
+        subsumed_by: String,
+    },
+
+    // Scoring analysis warnings
+    UnreachableThreshold {
+        threshold: f64,
+        max_score: f64,
+        formula: String,
+    },
+    ThresholdAlwaysReached {
+        threshold: f64,
+        min_score: f64,
+        formula: String,
+    },
+    LowWeightTrigger {
+        evaluation_name: String,
+        weight: i32,
+        threshold: f64,
+    },
+    IneffectiveWeight {
+        evaluation_name: String,
+        weight: i32,
+        reason: String,
+    },
+
+    // Warnings with a mechanical fix
+    NumericString {
+        evaluation_name: String,
+        field_name: String,
+        value: String,
+    },
+    MissingWeight {
+        evaluation_name: String,
+    },
+    NonCanonicalAggregation {
+        evaluation_name: String,
+        aggregation: String,
+    },
+
+    // Timezones and business calendars
+    InvalidTimezone {
+        /// Field the timezone is declared for, `None` for the rule-level timezone
+        field: Option<String>,
+        reason: String,
+    },
+    UnknownCalendar {
+        evaluation_name: String,
+        field_name: String,
+        calendar: String,
+    },
+
+    // State cost warnings
+    UnboundedAggregationWindow {
+        evaluation_name: String,
+    },
+    StateBudgetExceeded {
+        memory_bytes: u64,
+        budget_bytes: u64,
+        /// Aggregation holding the most state
+        largest_evaluation: String,
+    },
+
+    /// A rule breaks one of the organization's policies
+    PolicyViolation {
+        policy_id: String,
+        description: Option<String>,
+        /// Evaluation breaking the policy, `None` for the rule as a whole
+        evaluation_name: Option<String>,
+        /// Field of the evaluation, or of the rule, that breaks it
+        field_name: String,
+        message: String,
+    },
 }
 
+impl AnalyzerError {
+    /// Evaluation the error was found in and, when known, the field within it
+    pub fn location(&self) -> Option<(&str, Option<&str>)> {
+        match self {
+            AnalyzerError::DuplicateEvaluationName(name) | AnalyzerError::EmptyOperands(name) => {
+                Some((name, None))
+            }
+            AnalyzerError::MissingOperandReference {
+                evaluation_name, ..
+            }
+            | AnalyzerError::RedundantOperand {
+                evaluation_name, ..
+            } => Some((evaluation_name, Some("operands"))),
+            AnalyzerError::InvalidWeight {
+                evaluation_name, ..
+            }
+            | AnalyzerError::InvalidWeightRange {
+                evaluation_name, ..
+            }
+            | AnalyzerError::LowWeightTrigger {
+                evaluation_name, ..
+            }
+            | AnalyzerError::IneffectiveWeight {
+                evaluation_name, ..
+            } => Some((evaluation_name, Some("weight"))),
+            AnalyzerError::InvalidLogicalOperator {
+                evaluation_name, ..
+            }
+            | AnalyzerError::InvalidComparisonOperator {
+                evaluation_name, ..
+            } => Some((evaluation_name, Some("operator"))),
+            AnalyzerError::InvalidEvaluationType {
+                evaluation_name, ..
+            } => Some((evaluation_name, Some("type"))),
+            AnalyzerError::InvalidAggregationType {
+                evaluation_name, ..
+            }
+            | AnalyzerError::NonCanonicalAggregation {
+                evaluation_name, ..
+            } => Some((evaluation_name, Some("aggregation"))),
+            AnalyzerError::CircularDependency {
+                evaluation_name, ..
+            }
+            | AnalyzerError::UnsatisfiableEvaluation {
+                evaluation_name, ..
+            }
+            | AnalyzerError::TautologicalEvaluation {
+                evaluation_name, ..
+            } => Some((evaluation_name, None)),
+            AnalyzerError::MissingRequiredField {
+                evaluation_name, ..
+            }
+            | AnalyzerError::MissingWeight { evaluation_name } => Some((evaluation_name, None)),
+            AnalyzerError::InvalidStringReference {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::InvalidDateTimeExpression {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::InvalidDurationFormat {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::UnknownField {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::TypeMismatch {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::IncompatibleOperator {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::IncompatibleReference {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::NumericString {
+                evaluation_name,
+                field_name,
+                ..
+            }
+            | AnalyzerError::UnknownCalendar {
+                evaluation_name,
+                field_name,
+                ..
+            } => Some((evaluation_name, Some(field_name))),
+            AnalyzerError::UnboundedAggregationWindow { evaluation_name }
+            | AnalyzerError::StateBudgetExceeded {
+                largest_evaluation: evaluation_name,
+                ..
+            } => Some((evaluation_name, Some("conditions"))),
+            AnalyzerError::PolicyViolation {
+                evaluation_name: Some(evaluation_name),
+                field_name,
+                ..
+            } => Some((evaluation_name, Some(field_name))),
+            _ => None,
+        }
+    }
+}
+
+impl AnalyzerError {
+    /// Stable diagnostic code, explained by `lrol explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            AnalyzerError::DuplicateEvaluationName(_) => "LROL0010",
+            AnalyzerError::MissingOperandReference { .. } => "LROL0011",
+            AnalyzerError::CircularDependency { .. } => "LROL0012",
+            AnalyzerError::InvalidWeight { .. } => "LROL0013",
+            AnalyzerError::MissingRequiredField { .. } => "LROL0014",
+            AnalyzerError::InvalidLogicalOperator { .. } => "LROL0015",
+            AnalyzerError::EmptyOperands(_) => "LROL0016",
+            AnalyzerError::InvalidStringReference { .. } => "LROL0017",
+            AnalyzerError::InvalidDateTimeExpression { .. } => "LROL0018",
+            AnalyzerError::InvalidDurationFormat { .. } => "LROL0019",
+            AnalyzerError::InvalidThreshold { .. } => "LROL0020",
+            AnalyzerError::InvalidEvaluationType { .. } => "LROL0021",
+            AnalyzerError::InvalidComparisonOperator { .. } => "LROL0022",
+            AnalyzerError::InvalidAggregationType { .. } => "LROL0023",
+            AnalyzerError::InvalidWeightRange { .. } => "LROL0024",
+            AnalyzerError::InvalidActionType { .. } => "LROL0025",
+            AnalyzerError::MissingActionReason { .. } => "LROL0026",
+            AnalyzerError::InvalidMetadataFormat { .. } => "LROL0027",
+            AnalyzerError::MissingRequiredSchemaField { .. } => "LROL0028",
+            AnalyzerError::InvalidEventSchema { .. } => "LROL0029",
+            AnalyzerError::UnknownField { .. } => "LROL0030",
+            AnalyzerError::TypeMismatch { .. } => "LROL0031",
+            AnalyzerError::IncompatibleOperator { .. } => "LROL0032",
+            AnalyzerError::UnsatisfiableEvaluation { .. } => "LROL0033",
+            AnalyzerError::TautologicalEvaluation { .. } => "LROL0034",
+            AnalyzerError::RedundantOperand { .. } => "LROL0035",
+            AnalyzerError::UnreachableThreshold { .. } => "LROL0036",
+            AnalyzerError::ThresholdAlwaysReached { .. } => "LROL0037",
+            AnalyzerError::LowWeightTrigger { .. } => "LROL0038",
+            AnalyzerError::IneffectiveWeight { .. } => "LROL0039",
+            AnalyzerError::NumericString { .. } => "LROL0040",
+            AnalyzerError::MissingWeight { .. } => "LROL0041",
+            AnalyzerError::NonCanonicalAggregation { .. } => "LROL0042",
+            AnalyzerError::InvalidTimezone { .. } => "LROL0043",
+            AnalyzerError::UnknownCalendar { .. } => "LROL0044",
+            AnalyzerError::IncompatibleReference { .. } => "LROL0045",
+            AnalyzerError::UnboundedAggregationWindow { .. } => "LROL0046",
+            AnalyzerError::StateBudgetExceeded { .. } => "LROL0047",
+            AnalyzerError::PolicyViolation { .. } => "LROL0048",
+            AnalyzerError::InvalidScoring { .. } => "LROL0049",
+        }
+    }
+}
+
+impl fmt::Display for AnalyzerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalyzerError::DuplicateEvaluationName(name) => {
+                write!(f, "Duplicate evaluation name: {}", name)
+            }
+            AnalyzerError::MissingOperandReference {
+                evaluation_name,
+                missing_operand,
+            } => write!(
+                f,
+                "Missing operand reference in {}: {}",
+                evaluation_name, missing_operand
+            ),
+            AnalyzerError::CircularDependency {
+                evaluation_name,
+                dependency_chain,
+                edges,
+            } => {
+                let edges: Vec<String> = edges.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "Circular dependency detected in {}: {} → {} (via {})",
+                    evaluation_name,
+                    dependency_chain.join(" → "),
+                    evaluation_name,
+                    edges.join(", ")
+                )
+            }
+            AnalyzerError::InvalidWeight {
+                evaluation_name,
+                weight,
+            }
+            | AnalyzerError::InvalidWeightRange {
+                evaluation_name,
+                weight,
+            } => write!(
+                f,
+                "Weight {} of {} is outside the range 1 to 5",
+                weight, evaluation_name
+            ),
+            AnalyzerError::MissingRequiredField {
+                evaluation_name,
+                field_name,
+            } => write!(
+                f,
+                "{} is missing the required field '{}'",
+                evaluation_name, field_name
+            ),
+            AnalyzerError::InvalidLogicalOperator {
+                evaluation_name,
+                operator,
+            } => write!(
+                f,
+                "Invalid logical operator '{}' in {}",
+                operator, evaluation_name
+            ),
+            AnalyzerError::EmptyOperands(name) => write!(f, "{} has no operands", name),
+            AnalyzerError::InvalidStringReference {
+                evaluation_name,
+                field_name,
+                reference,
+            } => write!(
+                f,
+                "Reference @{} in {} ({}) does not name an evaluation",
+                reference, evaluation_name, field_name
+            ),
+            AnalyzerError::InvalidDateTimeExpression {
+                evaluation_name,
+                field_name,
+                expression,
+                reason,
+            } => write!(
+                f,
+                "Invalid datetime in {} ({}): {} - {}",
+                evaluation_name, field_name, expression, reason
+            ),
+            AnalyzerError::InvalidDurationFormat {
+                evaluation_name,
+                field_name,
+                duration,
+                reason,
+            } => write!(
+                f,
+                "Invalid duration in {} ({}): {} - {}",
+                evaluation_name, field_name, duration, reason
+            ),
+            AnalyzerError::InvalidThreshold { value, reason } => {
+                write!(f, "Invalid threshold {}: {}", value, reason)
+            }
+            AnalyzerError::InvalidEvaluationType {
+                evaluation_name,
+                found_type,
+            } => write!(
+                f,
+                "Invalid evaluation type '{}' in {}",
+                found_type, evaluation_name
+            ),
+            AnalyzerError::InvalidComparisonOperator {
+                evaluation_name,
+                operator,
+            } => write!(
+                f,
+                "Invalid comparison operator '{}' in {}",
+                operator, evaluation_name
+            ),
+            AnalyzerError::InvalidAggregationType {
+                evaluation_name,
+                aggregation,
+            } => write!(
+                f,
+                "Invalid aggregation '{}' in {}",
+                aggregation, evaluation_name
+            ),
+            AnalyzerError::InvalidActionType { action_type } => {
+                write!(f, "Invalid action type '{}'", action_type)
+            }
+            AnalyzerError::MissingActionReason { action_type } => {
+                write!(f, "Action '{}' has no reason", action_type)
+            }
+            AnalyzerError::InvalidMetadataFormat { field, reason } => {
+                write!(f, "Invalid metadata field '{}': {}", field, reason)
+            }
+            AnalyzerError::MissingRequiredSchemaField { field } => {
+                write!(f, "Missing required field: {}", field)
+            }
+            AnalyzerError::InvalidEventSchema { reason } => {
+                write!(f, "Invalid event schema: {}", reason)
+            }
+            AnalyzerError::UnknownField {
+                evaluation_name,
+                field_name,
+                field,
+            } => write!(
+                f,
+                "Unknown event field in {} ({}): {}",
+                evaluation_name, field_name, field
+            ),
+            AnalyzerError::TypeMismatch {
+                evaluation_name,
+                field_name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Type mismatch in {} ({}): expected {}, found {}",
+                evaluation_name, field_name, expected, found
+            ),
+            AnalyzerError::IncompatibleOperator {
+                evaluation_name,
+                field_name,
+                operator,
+                operand_type,
+                reason,
+            } => write!(
+                f,
+                "Operator {} cannot be applied to {} in {} ({}): {}",
+                operator, operand_type, evaluation_name, field_name, reason
+            ),
+            AnalyzerError::IncompatibleReference {
+                evaluation_name,
+                field_name,
+                reference,
+                found,
+                expected,
+            } => write!(
+                f,
+                "{} produces a {} but is used as a {} in {} ({})",
+                reference, found, expected, evaluation_name, field_name
+            ),
+            AnalyzerError::UnsatisfiableEvaluation {
+                evaluation_name,
+                conflicting_evaluations,
+                reason,
+            } => write!(
+                f,
+                "{} can never fire because of {}: {}",
+                evaluation_name,
+                conflicting_evaluations.join(", "),
+                reason
+            ),
+            AnalyzerError::TautologicalEvaluation {
+                evaluation_name,
+                complementary_evaluations,
+                reason,
+            } => write!(
+                f,
+                "{} always fires because of {}: {}",
+                evaluation_name,
+                complementary_evaluations.join(", "),
+                reason
+            ),
+            AnalyzerError::RedundantOperand {
+                evaluation_name,
+                operand,
+                subsumed_by,
+            } => write!(
+                f,
+                "Operand {} of {} is redundant given {}",
+                operand, evaluation_name, subsumed_by
+            ),
+            AnalyzerError::UnreachableThreshold {
+                threshold,
+                max_score,
+                formula,
+            } => write!(
+                f,
+                "Threshold {} can never be reached: the highest {} score is {:.2}",
+                threshold, formula, max_score
+            ),
+            AnalyzerError::ThresholdAlwaysReached {
+                threshold,
+                min_score,
+                formula,
+            } => write!(
+                f,
+                "Threshold {} is always reached: the lowest {} score is {:.2}",
+                threshold, formula, min_score
+            ),
+            AnalyzerError::LowWeightTrigger {
+                evaluation_name,
+                weight,
+                threshold,
+            } => write!(
+                f,
+                "{} alone reaches threshold {} with weight {}",
+                evaluation_name, threshold, weight
+            ),
+            AnalyzerError::IneffectiveWeight {
+                evaluation_name,
+                weight,
+                reason,
+            } => write!(
+                f,
+                "Weight {} of {} never changes the outcome: {}",
+                weight, evaluation_name, reason
+            ),
+            AnalyzerError::NumericString {
+                evaluation_name,
+                field_name,
+                value,
+            } => write!(
+                f,
+                "{}.{} compares with the string \"{}\", which is ordered as text, not as a number",
+                evaluation_name, field_name, value
+            ),
+            AnalyzerError::MissingWeight { evaluation_name } => write!(
+                f,
+                "{} has no weight while other evaluations of the rule do",
+                evaluation_name
+            ),
+            AnalyzerError::NonCanonicalAggregation {
+                evaluation_name,
+                aggregation,
+            } => write!(
+                f,
+                "Aggregation {} of {} should be written in upper case",
+                aggregation, evaluation_name
+            ),
+            AnalyzerError::InvalidTimezone { field, reason } => match field {
+                Some(field) => write!(f, "Invalid timezone for {}: {}", field, reason),
+                None => write!(f, "Invalid rule timezone: {}", reason),
+            },
+            AnalyzerError::UnknownCalendar {
+                evaluation_name,
+                field_name,
+                calendar,
+            } => write!(
+                f,
+                "Unknown business calendar '{}' in {} ({})",
+                calendar, evaluation_name, field_name
+            ),
+            AnalyzerError::UnboundedAggregationWindow { evaluation_name } => write!(
+                f,
+                "Aggregation {} has no time window, so its state grows without bound",
+                evaluation_name
+            ),
+            AnalyzerError::StateBudgetExceeded {
+                memory_bytes,
+                budget_bytes,
+                largest_evaluation,
+            } => write!(
+                f,
+                "Aggregation state of about {} exceeds the budget of {}; {} holds the most",
+                format_bytes(*memory_bytes),
+                format_bytes(*budget_bytes),
+                largest_evaluation
+            ),
+            AnalyzerError::PolicyViolation {
+                policy_id,
+                evaluation_name,
+                message,
+                ..
+            } => match evaluation_name {
+                Some(evaluation_name) => write!(
+                    f,
+                    "Policy {} violated by {}: {}",
+                    policy_id, evaluation_name, message
+                ),
+                None => write!(f, "Policy {} violated: {}", policy_id, message),
+            },
+            AnalyzerError::InvalidScoring { scoring } => {
+                write!(f, "Unknown scoring strategy '{}'", scoring)
+            }
+        }
+    }
+}
 
 // Combined error type to handle both parser and analyzer errors
 #[derive(Debug)]
@@ -113,19 +711,17 @@ impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ValidationError::Parser(e) => write!(f, "Parser error: {}", e),
-            ValidationError::Analyzer(e) => write!(f, "Analyzer error: {:?}", e),
+            ValidationError::Analyzer(e) => write!(f, "Analyzer error: {}", e),
         }
     }
 }
 
-
-
 #[derive(Debug)]
 pub enum FileValidationError {
     FileNotFound(String),
     FileReadError { path: String, error: std::io::Error },
     InvalidUtf8 { path: String },
-    ValidationErrors(ValidationReport),
+    ValidationErrors(Box<ValidationReport>),
 }
 
 impl std::fmt::Display for FileValidationError {
@@ -147,7 +743,6 @@ impl std::fmt::Display for FileValidationError {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use crate::validator::RuleValidator;
@@ -193,7 +788,7 @@ mod tests {
         create_test_file(temp_dir.path(), "valid_rule.json", valid_rule).unwrap();
         let file_path = temp_dir.path().join("valid_rule.json");
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let result = validator.validate_with_report_from_file(&file_path);
         assert!(result.is_ok());
         assert!(result.unwrap().is_valid());
@@ -213,15 +808,18 @@ mod tests {
         create_test_file(temp_dir.path(), "invalid_rule.json", invalid_rule).unwrap();
         let file_path = temp_dir.path().join("invalid_rule.json");
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let result = validator.validate_with_report_from_file(&file_path);
-        assert!(matches!(result, Err(FileValidationError::ValidationErrors(_))));
+        assert!(matches!(
+            result,
+            Err(FileValidationError::ValidationErrors(_))
+        ));
     }
 
     #[test]
     fn test_validate_directory() {
         let temp_dir = TempDir::new().unwrap();
-        
+
         // Create multiple test files
         let valid_rule = r#"{
             "model_id": "TEST001",
@@ -257,34 +855,77 @@ mod tests {
         create_test_file(temp_dir.path(), "failed_rule.json", invalid_rule).unwrap();
         create_test_file(temp_dir.path(), "not_json.txt", "not a json file").unwrap();
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let results = validator.validate_directory(temp_dir.path());
 
         // Should only process .json files
         assert_eq!(results.len(), 2);
-        
+
         // Check results
         let mut valid_found = false;
         let mut invalid_found = false;
 
-        
-
         for (file_name, result) in results {
             if file_name.ends_with("valid_rule.json") {
                 valid_found = true;
                 assert!(result.is_ok());
             } else if file_name.ends_with("failed_rule.json") {
                 invalid_found = true;
-                assert!(matches!(result, Err(FileValidationError::ValidationErrors(_))));
+                assert!(matches!(
+                    result,
+                    Err(FileValidationError::ValidationErrors(_))
+                ));
             }
         }
 
         assert!(valid_found && invalid_found);
     }
 
+    #[test]
+    fn test_validate_directory_with_shared_evaluation_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let rule = |model_id: &str| {
+            format!(
+                r#"{{
+            "model_id": "{}",
+            "name": "Shared Names",
+            "threshold": 0.9,
+            "evaluations": [
+                {{
+                    "name": "amount_check",
+                    "type": "comparison",
+                    "left": "transaction_amount",
+                    "operator": ">",
+                    "right": 1000,
+                    "weight": 3
+                }}
+            ],
+            "actions": [
+                {{
+                    "type": "flag_transaction",
+                    "reason": "High amount transaction"
+                }}
+            ]
+        }}"#,
+                model_id
+            )
+        };
+
+        create_test_file(temp_dir.path(), "first.json", &rule("TEST001")).unwrap();
+        create_test_file(temp_dir.path(), "second.json", &rule("TEST002")).unwrap();
+
+        let validator = RuleValidator::new();
+        let results = validator.validate_directory(temp_dir.path());
+
+        assert_eq!(results.len(), 2);
+        for (file_name, result) in results {
+            assert!(result.is_ok(), "{} failed validation", file_name);
+        }
+    }
+
     #[test]
     fn test_file_not_found() {
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let result = validator.validate_with_report_from_file("nonexistent.json");
         assert!(matches!(result, Err(FileValidationError::FileNotFound(_))));
     }
@@ -293,13 +934,16 @@ mod tests {
     fn test_invalid_utf8_file() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("invalid_utf8.json");
-        
+
         // Create file with invalid UTF-8
         let mut file = File::create(&file_path).unwrap();
         file.write_all(&[0xFF, 0xFF, 0xFF, 0xFF]).unwrap();
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let result = validator.validate_with_report_from_file(&file_path);
-        assert!(matches!(result, Err(FileValidationError::FileReadError { .. })));
+        assert!(matches!(
+            result,
+            Err(FileValidationError::FileReadError { .. })
+        ));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,765 @@
+/// Long-form description of a diagnostic code, shown by `lrol explain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+/// Every code a parser or analyzer diagnostic can carry, in numeric order
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "LROL0001",
+        title: "invalid syntax",
+        explanation: r#"The rule is not valid JSON, or a value has a shape the parser does not
+accept, such as a threshold written as a string.
+
+Erroneous example:
+
+    {
+      "model_id": "R001",
+      "threshold": "high",
+    }
+
+Make sure the document is well-formed JSON and that every field has the
+type the LROL schema expects:
+
+    {
+      "model_id": "R001",
+      "threshold": 0.7
+    }"#,
+    },
+    ErrorCode {
+        code: "LROL0002",
+        title: "missing field",
+        explanation: r#"A field the parser needs to build the rule is absent.
+
+Erroneous example:
+
+    { "name": "Large Transfers", "threshold": 0.7 }
+
+Every rule has a model_id, a name, a threshold, evaluations and actions:
+
+    {
+      "model_id": "R001",
+      "name": "Large Transfers",
+      "threshold": 0.7,
+      "evaluations": [...],
+      "actions": [...]
+    }"#,
+    },
+    ErrorCode {
+        code: "LROL0003",
+        title: "invalid value",
+        explanation: r#"A field has a value of the right type that the parser cannot interpret,
+for example a threshold outside of the representable range.
+
+Replace the value with one of the form named in the diagnostic."#,
+    },
+    ErrorCode {
+        code: "LROL0010",
+        title: "duplicate evaluation name",
+        explanation: r#"Two evaluations in the same rule share a name. Logical evaluations and
+@references refer to evaluations by name, so names must be unique.
+
+Erroneous example:
+
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": ">", "right": 1000 },
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": "<", "right": 5 }
+
+Rename one of them and update its references:
+
+    { "name": "Amount_Check", ... },
+    { "name": "Small_Amount_Check", ... }
+
+`lrol fix --unsafe-fixes` renames later declarations to Name_2, Name_3 and
+so on. References keep reading the first declaration, except in evaluations
+whose use of the name only fits a renamed one, such as `@Name > 5` when the
+first declaration is a comparison and a later one an aggregation."#,
+    },
+    ErrorCode {
+        code: "LROL0011",
+        title: "missing operand reference",
+        explanation: r#"A logical evaluation lists an operand that is not the name of any
+evaluation in the rule. This is usually a typo or a renamed evaluation.
+
+Erroneous example:
+
+    { "name": "Combined", "type": "logical", "operator": "AND",
+      "operands": ["Amount_Check", "Velocity_Chek"] }
+
+Use the exact name of an existing evaluation:
+
+    "operands": ["Amount_Check", "Velocity_Check"]"#,
+    },
+    ErrorCode {
+        code: "LROL0012",
+        title: "circular dependency",
+        explanation: r#"Evaluations depend on each other in a cycle, so none of them can be
+computed first.
+
+Erroneous example:
+
+    { "name": "A", "type": "logical", "operator": "AND", "operands": ["B"] },
+    { "name": "B", "type": "logical", "operator": "OR", "operands": ["A"] }
+
+Break the cycle by removing one of the dependencies; operands must form
+a tree rooted at the top-level evaluations."#,
+    },
+    ErrorCode {
+        code: "LROL0013",
+        title: "invalid weight",
+        explanation: r#"An evaluation's weight is outside of the allowed range. Weights are
+integers from 1 to 5.
+
+Erroneous example:
+
+    { "name": "Amount_Check", ..., "weight": 8 }
+
+Scale the weights of the rule down so that they fit:
+
+    { "name": "Amount_Check", ..., "weight": 5 }"#,
+    },
+    ErrorCode {
+        code: "LROL0014",
+        title: "missing required field",
+        explanation: r#"An evaluation lacks a field its type needs: comparisons need left,
+operator and right, aggregations need an aggregation function, logical
+evaluations need an operator and operands.
+
+Erroneous example:
+
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": ">" }
+
+Add the missing field:
+
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": ">", "right": 1000 }"#,
+    },
+    ErrorCode {
+        code: "LROL0015",
+        title: "invalid logical operator",
+        explanation: r#"A logical evaluation uses an operator other than AND or OR.
+
+Erroneous example:
+
+    { "name": "Combined", "type": "logical", "operator": "&&",
+      "operands": ["A", "B"] }
+
+Use AND or OR, in upper case:
+
+    { "name": "Combined", "type": "logical", "operator": "AND",
+      "operands": ["A", "B"] }
+
+`lrol fix` rewrites common spellings such as `and`, `&&` and `||`."#,
+    },
+    ErrorCode {
+        code: "LROL0016",
+        title: "empty operands",
+        explanation: r#"A logical evaluation has no operands, so it has nothing to combine.
+
+Erroneous example:
+
+    { "name": "Combined", "type": "logical", "operator": "OR",
+      "operands": [] }
+
+List the evaluations it combines, or remove it."#,
+    },
+    ErrorCode {
+        code: "LROL0017",
+        title: "invalid string reference",
+        explanation: r#"A value of the form @Name refers to an evaluation that does not exist.
+
+Erroneous example:
+
+    { "name": "Same_Country", "type": "comparison", "left": "country",
+      "operator": "==", "right": "@Home_Contry" }
+
+Refer to an evaluation declared in the rule:
+
+    "right": "@Home_Country""#,
+    },
+    ErrorCode {
+        code: "LROL0018",
+        title: "invalid datetime expression",
+        explanation: r#"A datetime function call cannot be parsed. datetime() takes `now`, a
+quoted timestamp, an event field or another datetime() call, optionally
+followed by a quoted duration. strftime(format, value), hour_of_day(value)
+and day_of_week(value) take the same kinds of values.
+
+Erroneous example:
+
+    "left": "datetime(now - 2 hours)"
+
+Write the offset as a duration argument:
+
+    "left": "datetime(now, '-2 hours')"
+    "left": "datetime(transaction.timestamp, 'PT2H')"
+    "left": "datetime('2024-01-01T00:00:00Z')"
+
+Durations are described under LROL0019."#,
+    },
+    ErrorCode {
+        code: "LROL0019",
+        title: "invalid duration format",
+        explanation: r#"A duration cannot be parsed. Durations are one or more whole numbers
+followed by a unit, such as '-1 day 2 hours', or ISO-8601 durations such
+as 'PT2H' or 'P30D'. A leading sign applies to the whole duration. Units
+are milliseconds (ms), seconds, minutes, hours, days, weeks, months and
+years, singular or plural. Months and years follow the calendar, so one
+month after January 31st is the last day of February.
+
+Erroneous example:
+
+    "left": "datetime(now, '-2h')"
+
+Separate the number from the unit and spell the unit out:
+
+    "left": "datetime(now, '-2 hours')""#,
+    },
+    ErrorCode {
+        code: "LROL0020",
+        title: "invalid threshold",
+        explanation: r#"The rule's threshold is outside of the range 0 to 1. The threshold is
+compared with the weighted score of the evaluations, which is normalised
+to that range.
+
+Erroneous example:
+
+    "threshold": 70
+
+Express it as a fraction:
+
+    "threshold": 0.7"#,
+    },
+    ErrorCode {
+        code: "LROL0021",
+        title: "invalid evaluation type",
+        explanation: r#"An evaluation has a type the validator does not know. Types are
+comparison, aggregation, logical, time-based and conditional.
+
+Erroneous example:
+
+    { "name": "Amount_Check", "type": "compare", ... }
+
+Use one of the known types:
+
+    { "name": "Amount_Check", "type": "comparison", ... }"#,
+    },
+    ErrorCode {
+        code: "LROL0022",
+        title: "invalid comparison operator",
+        explanation: r#"A comparison uses an operator that is not one of >, <, >=, <=, ==, !=,
+IN, NOT IN, LIKE and NOT LIKE.
+
+Erroneous example:
+
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": "=<", "right": 1000 }
+
+Use the canonical spelling:
+
+    "operator": "<="
+
+`lrol fix` rewrites common alternatives such as =<, => and =."#,
+    },
+    ErrorCode {
+        code: "LROL0023",
+        title: "invalid aggregation type",
+        explanation: r#"An aggregation evaluation uses a function other than SUM, COUNT, AVG,
+MIN, MAX and STDDEV.
+
+Erroneous example:
+
+    { "name": "Daily_Total", "type": "aggregation", "aggregation": "TOTAL",
+      "left": "amount", "operator": ">", "right": 10000 }
+
+Use one of the supported functions:
+
+    "aggregation": "SUM""#,
+    },
+    ErrorCode {
+        code: "LROL0024",
+        title: "weight out of range",
+        explanation: r#"An evaluation's weight is outside of the range 1 to 5. This is the
+schema-level counterpart of LROL0013.
+
+Erroneous example:
+
+    { "name": "Amount_Check", ..., "weight": 0 }
+
+Give the evaluation a weight from 1 to 5, or remove the weight to leave
+the evaluation out of the score."#,
+    },
+    ErrorCode {
+        code: "LROL0025",
+        title: "invalid action type",
+        explanation: r#"An action has an empty type, so the engine cannot tell what to do when
+the rule fires.
+
+Erroneous example:
+
+    "actions": [{ "type": "", "reason": "Large amount" }]
+
+Name the action:
+
+    "actions": [{ "type": "flag_transaction", "reason": "Large amount" }]"#,
+    },
+    ErrorCode {
+        code: "LROL0026",
+        title: "missing action reason",
+        explanation: r#"An action has no reason. Reasons are shown to analysts reviewing the
+flagged event and are required.
+
+Erroneous example:
+
+    "actions": [{ "type": "block_transaction", "reason": "" }]
+
+Explain why the action is taken:
+
+    "actions": [{ "type": "block_transaction",
+                  "reason": "Transfer to a sanctioned country" }]"#,
+    },
+    ErrorCode {
+        code: "LROL0027",
+        title: "invalid metadata format",
+        explanation: r#"A metadata field has a value in the wrong format. created_at and
+last_updated are RFC 3339 timestamps.
+
+Erroneous example:
+
+    "metadata": { "created_at": "yesterday" }
+
+Use a full timestamp:
+
+    "metadata": { "created_at": "2024-03-01T09:00:00Z" }"#,
+    },
+    ErrorCode {
+        code: "LROL0028",
+        title: "missing required schema field",
+        explanation: r#"A top-level field of the rule is empty: model_id and name must not be
+blank, and evaluations and actions need at least one entry.
+
+Erroneous example:
+
+    { "model_id": "", "name": "Large Transfers", ... }
+
+Fill in the field:
+
+    { "model_id": "R001", "name": "Large Transfers", ... }"#,
+    },
+    ErrorCode {
+        code: "LROL0029",
+        title: "invalid event schema",
+        explanation: r#"The rule's event_schema cannot be read. It maps field names to types
+(string, number, boolean, datetime, or a nested object).
+
+Erroneous example:
+
+    "event_schema": { "amount": "decimal" }
+
+Use a supported type:
+
+    "event_schema": { "amount": "number" }"#,
+    },
+    ErrorCode {
+        code: "LROL0030",
+        title: "unknown field",
+        explanation: r#"An evaluation refers to an event field that is not declared in the
+event schema. With a schema in place, unknown fields are usually typos.
+
+Erroneous example:
+
+    "event_schema": { "amount": "number" },
+    ...
+    { "name": "Amount_Check", "left": "ammount", "operator": ">",
+      "right": 1000 }
+
+Fix the spelling or declare the field in the schema."#,
+    },
+    ErrorCode {
+        code: "LROL0031",
+        title: "type mismatch",
+        explanation: r#"A value is compared with a field of a different type, for example a
+number field with a string.
+
+Erroneous example:
+
+    "event_schema": { "amount": "number" },
+    ...
+    { "name": "Amount_Check", "left": "amount", "operator": "==",
+      "right": "large" }
+
+Compare values of the same type:
+
+    { "name": "Amount_Check", "left": "amount", "operator": ">",
+      "right": 10000 }"#,
+    },
+    ErrorCode {
+        code: "LROL0032",
+        title: "incompatible operator",
+        explanation: r#"An operator is used with operands it does not apply to, such as an
+ordering operator on strings or LIKE on numbers.
+
+Erroneous example:
+
+    { "name": "Country_Check", "left": "country", "operator": ">",
+      "right": "DE" }
+
+Use an operator that applies to the operand type:
+
+    { "name": "Country_Check", "left": "country", "operator": "IN",
+      "right": ["DE", "FR"] }"#,
+    },
+    ErrorCode {
+        code: "LROL0033",
+        title: "unsatisfiable evaluation",
+        explanation: r#"The conditions of an AND evaluation contradict each other, so it can
+never be true.
+
+Erroneous example:
+
+    { "name": "Large", "left": "amount", "operator": ">", "right": 1000 },
+    { "name": "Small", "left": "amount", "operator": "<", "right": 100 },
+    { "name": "Both", "type": "logical", "operator": "AND",
+      "operands": ["Large", "Small"] }
+
+Relax or remove one of the conflicting conditions, or combine them with
+OR if either should fire."#,
+    },
+    ErrorCode {
+        code: "LROL0034",
+        title: "tautological evaluation",
+        explanation: r#"The operands of an OR evaluation cover every possible value, so it is
+always true and the rule flags every event.
+
+Erroneous example:
+
+    { "name": "Large", "left": "amount", "operator": ">=", "right": 100 },
+    { "name": "Small", "left": "amount", "operator": "<", "right": 100 },
+    { "name": "Either", "type": "logical", "operator": "OR",
+      "operands": ["Large", "Small"] }
+
+Check the bounds of the operands; they usually overlap by mistake."#,
+    },
+    ErrorCode {
+        code: "LROL0035",
+        title: "redundant operand",
+        explanation: r#"An operand of a logical evaluation is implied by another operand and
+does not change its result.
+
+Erroneous example:
+
+    { "name": "Over_1000", "left": "amount", "operator": ">", "right": 1000 },
+    { "name": "Over_5000", "left": "amount", "operator": ">", "right": 5000 },
+    { "name": "Large", "type": "logical", "operator": "AND",
+      "operands": ["Over_1000", "Over_5000"] }
+
+Remove the redundant operand (Over_1000 here)."#,
+    },
+    ErrorCode {
+        code: "LROL0036",
+        title: "unreachable threshold",
+        explanation: r#"Even when every weighted evaluation is true, the score stays below the
+threshold, so the rule can never fire.
+
+Erroneous example:
+
+    "threshold": 0.9, with weights arranged so that the evaluations
+    which can be true together score at most 0.6
+
+Lower the threshold to at most the reported maximum score, or raise the
+weights of evaluations that can hold at the same time."#,
+    },
+    ErrorCode {
+        code: "LROL0037",
+        title: "threshold always reached",
+        explanation: r#"Even when no weighted evaluation is true, the score reaches the
+threshold, so the rule fires on every event.
+
+Erroneous example:
+
+    "threshold": 0.0
+
+Raise the threshold above the reported minimum score."#,
+    },
+    ErrorCode {
+        code: "LROL0038",
+        title: "low weight trigger",
+        explanation: r#"A single low-weight evaluation is enough to reach the threshold on its
+own. This is a warning: it is often intended, but a minor signal
+triggering the rule alone is usually a mistake.
+
+Example:
+
+    "threshold": 0.2,
+    { "name": "Night_Time", ..., "weight": 1 }
+    { "name": "Large_Amount", ..., "weight": 4 }
+
+Raise the threshold or lower the weight if Night_Time should only
+contribute to the score."#,
+    },
+    ErrorCode {
+        code: "LROL0039",
+        title: "ineffective weight",
+        explanation: r#"An evaluation's weight never changes whether the rule fires: the
+threshold is reached or missed regardless of its result.
+
+Remove the evaluation, or adjust its weight or the threshold so that it
+matters."#,
+    },
+    ErrorCode {
+        code: "LROL0040",
+        title: "numeric string",
+        explanation: r#"A number is written as a string and compared with an ordering
+operator. Strings compare lexicographically, so "900" > "1000".
+
+Erroneous example:
+
+    { "name": "Amount_Check", "left": "amount", "operator": ">",
+      "right": "1000" }
+
+Remove the quotes:
+
+    "right": 1000
+
+`lrol fix` unquotes the value."#,
+    },
+    ErrorCode {
+        code: "LROL0041",
+        title: "missing weight",
+        explanation: r#"An evaluation has no weight while other evaluations of the rule do, so
+it does not contribute to the score.
+
+Example:
+
+    { "name": "Amount_Check", ..., "weight": 3 },
+    { "name": "Country_Check", ... }
+
+Give it a weight, or make it an operand of a logical evaluation if it is
+only a building block. `lrol fix --unsafe-fixes` inserts a weight of 1."#,
+    },
+    ErrorCode {
+        code: "LROL0042",
+        title: "non-canonical aggregation",
+        explanation: r#"An aggregation function is not written in upper case. It is accepted,
+but the schema spells aggregations in upper case.
+
+Example:
+
+    "aggregation": "sum"
+
+Write it in upper case:
+
+    "aggregation": "SUM"
+
+`lrol fix` rewrites the value."#,
+    },
+    ErrorCode {
+        code: "LROL0043",
+        title: "invalid timezone",
+        explanation: r#"The rule's timezone, or the timezone of one of its fields, names a zone
+that does not exist. Zones are IANA names such as America/New_York,
+Europe/London or UTC; abbreviations such as EST are ambiguous and not
+accepted.
+
+Erroneous example:
+
+    "timezone": "EST",
+    "field_timezones": {
+      "merchant_time": {
+        "field": "merchant_country",
+        "zones": { "US": "America/NewYork" }
+      }
+    }
+
+Use the full zone names:
+
+    "timezone": "America/New_York",
+    "field_timezones": {
+      "merchant_time": {
+        "field": "merchant_country",
+        "zones": { "US": "America/New_York" }
+      }
+    }"#,
+    },
+    ErrorCode {
+        code: "LROL0044",
+        title: "unknown business calendar",
+        explanation: r#"A calendar function such as is_business_day() names a calendar that is
+not among the calendars passed to the analyzer. Calendars are JSON files
+loaded with `--calendars <file or directory>`; a calendar is named by its
+"name" field, or after its file.
+
+Erroneous example, with only us_banking.json loaded:
+
+    "left": "is_business_day(transaction_date, 'uk_banking')"
+
+Load the missing calendar, or refer to one that is loaded:
+
+    "left": "is_business_day(transaction_date, 'us_banking')""#,
+    },
+    ErrorCode {
+        code: "LROL0045",
+        title: "incompatible reference",
+        explanation: r#"An evaluation is referenced where its result cannot be used.
+Comparisons, logical and time-based evaluations produce true or false,
+aggregations produce a number and conditionals produce one of their
+results. Logical operands must produce true or false, arithmetic needs
+numbers and ordering operators cannot be applied to true or false.
+
+Erroneous example:
+
+    { "name": "Amount_Check", "type": "comparison", "left": "amount",
+      "operator": ">", "right": 10000 },
+    { "name": "Amount_Doubled", "type": "comparison",
+      "left": "@Amount_Check * 2", "operator": ">", "right": 20000 }
+
+Reference an evaluation that produces the expected kind of value:
+
+    { "name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM",
+      "field": "amount" },
+    { "name": "Amount_Doubled", "type": "comparison",
+      "left": "@Amount_Sum * 2", "operator": ">", "right": 20000 }"#,
+    },
+    ErrorCode {
+        code: "LROL0046",
+        title: "unbounded aggregation window",
+        explanation: r#"An aggregation has no condition limiting how far back its events go, so a
+streaming engine has to keep every event it has ever seen for each entity.
+Reported by `lrol cost`.
+
+Erroneous example:
+
+    { "name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM",
+      "field": "amount" }
+
+Bound the events with a condition on their time:
+
+    { "name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM",
+      "field": "amount",
+      "conditions": [{ "left": "transaction_date", "operator": ">=",
+                       "right": "datetime(now, '-30 days')" }] }"#,
+    },
+    ErrorCode {
+        code: "LROL0047",
+        title: "state budget exceeded",
+        explanation: r#"The state a streaming engine keeps for the rule's aggregations is
+estimated to exceed the budget of the state cost configuration. Every
+aggregation keeps the events of its window, so the state grows with the
+event rate times the window length.
+
+Erroneous example, with 2000 events per second and a 1 GiB budget:
+
+    "conditions": [{ "left": "transaction_date", "operator": ">=",
+                     "right": "datetime(now, '-30 days')" }]
+
+Shorten the windows, narrow the conditions, or raise `budget_bytes`:
+
+    "conditions": [{ "left": "transaction_date", "operator": ">=",
+                     "right": "datetime(now, '-1 hour')" }]"#,
+    },
+    ErrorCode {
+        code: "LROL0048",
+        title: "policy violation",
+        explanation: r#"A rule breaks one of the organization's policies, loaded with
+`lrol validate --policy <file>`. The message names the policy and the
+requirement that is not met.
+
+Erroneous example, under a policy requiring blocking rules to have a
+threshold of at least 0.9:
+
+    { "id": "POL-001", "applies_to": { "actions": ["block_transaction"] },
+      "require": { "min_threshold": 0.9 } }
+
+    "threshold": 0.8,
+    "actions": [{ "type": "block_transaction", "reason": "Fraud" }]
+
+Change the rule to meet the policy:
+
+    "threshold": 0.9,
+    "actions": [{ "type": "block_transaction", "reason": "Fraud" }]"#,
+    },
+    ErrorCode {
+        code: "LROL0049",
+        title: "unknown scoring strategy",
+        explanation: r#"The rule's `scoring` field names a strategy that does not exist. The
+strategy decides how the weights of firing evaluations combine into the
+score compared with the threshold:
+
+    weighted_ratio       firing weight over total weight (the default)
+    root_weighted_ratio  the same, counting only evaluations nothing depends on
+    max_weight           highest firing weight over 5
+    noisy_or             1 - product of (1 - weight / 5) over firing evaluations
+    logistic             1 / (1 + e^-(firing weight - total weight / 2))
+
+Erroneous example:
+
+    "scoring": "average",
+
+Name one of the strategies above:
+
+    "scoring": "noisy_or","#,
+    },
+];
+
+/// Looks up the explanation of a code, ignoring case
+pub fn explain(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES
+        .iter()
+        .find(|error_code| error_code.code.eq_ignore_ascii_case(code.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_explain() {
+        assert_eq!(
+            explain("lrol0022").unwrap().title,
+            "invalid comparison operator"
+        );
+        assert_eq!(explain(" LROL0001 ").unwrap().code, "LROL0001");
+        assert!(explain("LROL9999").is_none());
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_sorted() {
+        let codes: Vec<&str> = ERROR_CODES.iter().map(|c| c.code).collect();
+        let mut sorted = codes.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(codes, sorted);
+
+        let explanations: HashSet<&str> = ERROR_CODES.iter().map(|c| c.explanation).collect();
+        assert_eq!(explanations.len(), ERROR_CODES.len());
+    }
+
+    #[test]
+    fn test_every_error_is_explained() {
+        use crate::error::AnalyzerError;
+        use lrol_parser::ParserError;
+
+        let errors = [
+            ParserError::MissingField {
+                field: "name".to_string(),
+            }
+            .code(),
+            AnalyzerError::DuplicateEvaluationName("A".to_string()).code(),
+            AnalyzerError::NonCanonicalAggregation {
+                evaluation_name: "A".to_string(),
+                aggregation: "sum".to_string(),
+            }
+            .code(),
+        ];
+        for code in errors {
+            assert!(explain(code).is_some(), "{} is not explained", code);
+        }
+        assert_eq!(ERROR_CODES.len(), 3 + 40);
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use lrol_parser::Value;
+use serde::{Deserialize, Serialize};
+
+/// Type of an event field or of an operand expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FieldType {
+    String,
+    Number,
+    Boolean,
+    DateTime,
+    Duration,
+    Object,
+    Array(Box<FieldType>),
+    /// Type is unknown; compatible with everything
+    Any,
+}
+
+impl FieldType {
+    /// Whether values of the two types can be compared with each other
+    pub fn is_compatible_with(&self, other: &FieldType) -> bool {
+        match (self, other) {
+            (FieldType::Any, _) | (_, FieldType::Any) => true,
+            (FieldType::Array(a), FieldType::Array(b)) => a.is_compatible_with(b),
+            (a, b) => a == b,
+        }
+    }
+
+    /// Whether the type supports `<`, `>`, `<=` and `>=`
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self,
+            FieldType::Number
+                | FieldType::String
+                | FieldType::DateTime
+                | FieldType::Duration
+                | FieldType::Any
+        )
+    }
+
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, FieldType::Array(_) | FieldType::Object)
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::String => write!(f, "string"),
+            FieldType::Number => write!(f, "number"),
+            FieldType::Boolean => write!(f, "boolean"),
+            FieldType::DateTime => write!(f, "datetime"),
+            FieldType::Duration => write!(f, "duration"),
+            FieldType::Object => write!(f, "object"),
+            FieldType::Array(item) => write!(f, "array<{}>", item),
+            FieldType::Any => write!(f, "any"),
+        }
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(item) = s
+            .strip_prefix("array<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return Ok(FieldType::Array(Box::new(item.parse()?)));
+        }
+
+        match s.to_lowercase().as_str() {
+            "string" => Ok(FieldType::String),
+            "number" | "integer" => Ok(FieldType::Number),
+            "boolean" | "bool" => Ok(FieldType::Boolean),
+            "datetime" | "date-time" | "timestamp" => Ok(FieldType::DateTime),
+            "duration" => Ok(FieldType::Duration),
+            "object" => Ok(FieldType::Object),
+            "array" => Ok(FieldType::Array(Box::new(FieldType::Any))),
+            "any" => Ok(FieldType::Any),
+            _ => Err(format!("Unknown field type '{}'", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for FieldType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FieldType> for String {
+    fn from(value: FieldType) -> Self {
+        value.to_string()
+    }
+}
+
+/// Declaration of a single event field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "FieldSpecRepr")]
+pub struct FieldSpec {
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    /// Field may be absent or null on some events
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Closed set of values the field takes, if known
+    #[serde(default, rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+}
+
+impl FieldSpec {
+    pub fn new(field_type: FieldType) -> Self {
+        Self {
+            field_type,
+            optional: false,
+            values: None,
+        }
+    }
+}
+
+// Fields may be declared with just their type, e.g. `"amount": "number"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FieldSpecRepr {
+    Short(FieldType),
+    Full {
+        #[serde(rename = "type")]
+        field_type: FieldType,
+        #[serde(default)]
+        optional: bool,
+        #[serde(default, rename = "enum")]
+        values: Option<Vec<String>>,
+    },
+}
+
+impl From<FieldSpecRepr> for FieldSpec {
+    fn from(repr: FieldSpecRepr) -> Self {
+        match repr {
+            FieldSpecRepr::Short(field_type) => FieldSpec::new(field_type),
+            FieldSpecRepr::Full {
+                field_type,
+                optional,
+                values,
+            } => FieldSpec {
+                field_type,
+                optional,
+                values,
+            },
+        }
+    }
+}
+
+/// Fields available on the events a rule is evaluated against.
+///
+/// Nested fields are declared with dotted paths such as `profile.available_balance`.
+/// A schema is written either in the LROL format:
+///
+/// ```json
+/// { "fields": { "amount": "number", "merchant_state": { "type": "string", "optional": true } } }
+/// ```
+///
+/// or as a JSON Schema document, see [`EventSchema::from_json_schema`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub fields: BTreeMap<String, FieldSpec>,
+}
+
+impl EventSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<S: Into<String>>(&mut self, path: S, spec: FieldSpec) {
+        self.fields.insert(path.into(), spec);
+    }
+
+    pub fn get(&self, path: &str) -> Option<&FieldSpec> {
+        self.fields.get(path)
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.fields.contains_key(path)
+    }
+
+    /// Type of the field at `path`.
+    ///
+    /// Paths below a field declared as `object` or `any` resolve to
+    /// [`FieldType::Any`]; paths that are not covered by the schema resolve to `None`.
+    pub fn resolve(&self, path: &str) -> Option<FieldType> {
+        if let Some(spec) = self.fields.get(path) {
+            return Some(spec.field_type.clone());
+        }
+
+        let mut prefix = path;
+        while let Some((parent, _)) = prefix.rsplit_once('.') {
+            if let Some(spec) = self.fields.get(parent) {
+                return match spec.field_type {
+                    FieldType::Object | FieldType::Any => Some(FieldType::Any),
+                    _ => None,
+                };
+            }
+            prefix = parent;
+        }
+
+        None
+    }
+
+    /// Adds every field of `other`, replacing declarations of the same path
+    pub fn merge(&mut self, other: &EventSchema) {
+        for (path, spec) in &other.fields {
+            self.fields.insert(path.clone(), spec.clone());
+        }
+    }
+
+    /// Reads a schema from either the LROL format or a JSON Schema document
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        if value.get("fields").is_some() {
+            serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid event schema: {}", e))
+        } else if value.get("properties").is_some() || value.get("$schema").is_some() {
+            Self::from_json_schema(value)
+        } else {
+            Err("Event schema must declare 'fields' or JSON Schema 'properties'".to_string())
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read event schema {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Event schema is not valid JSON: {}", e))?;
+        Self::from_json(&value)
+    }
+
+    /// Reads a schema declared inline in a rule's `event_schema` field
+    pub fn from_lrol_value(value: &Value) -> Result<Self, String> {
+        Self::from_json(&lrol_to_json(value))
+    }
+
+    /// Imports the `properties` of a JSON Schema document.
+    ///
+    /// Nested objects are flattened to dotted paths, `integer` becomes `number`,
+    /// strings with `format: date-time` become `datetime`, properties missing from
+    /// `required` or allowing `null` are optional and string `enum`s are kept.
+    pub fn from_json_schema(document: &serde_json::Value) -> Result<Self, String> {
+        let mut schema = EventSchema::new();
+        match document.get("properties") {
+            Some(serde_json::Value::Object(_)) => {
+                Self::import_properties(document, "", &mut schema);
+                Ok(schema)
+            }
+            _ => Err("JSON Schema document has no 'properties' object".to_string()),
+        }
+    }
+
+    fn import_properties(node: &serde_json::Value, prefix: &str, schema: &mut EventSchema) {
+        let Some(serde_json::Value::Object(properties)) = node.get("properties") else {
+            return;
+        };
+        let required: Vec<&str> = node
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        for (name, property) in properties {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+
+            let (field_type, nullable) = Self::json_schema_type(property);
+            let values = property.get("enum").and_then(|e| e.as_array()).map(|e| {
+                e.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            });
+
+            schema.insert(
+                path.clone(),
+                FieldSpec {
+                    field_type: field_type.clone(),
+                    optional: nullable || !required.contains(&name.as_str()),
+                    values: values.filter(|v| !v.is_empty()),
+                },
+            );
+
+            if field_type == FieldType::Object {
+                Self::import_properties(property, &path, schema);
+            }
+        }
+    }
+
+    // Type of a JSON Schema node and whether it allows `null`
+    fn json_schema_type(node: &serde_json::Value) -> (FieldType, bool) {
+        let mut nullable = false;
+        let type_name = match node.get("type") {
+            Some(serde_json::Value::String(t)) => Some(t.as_str()),
+            Some(serde_json::Value::Array(types)) => {
+                let mut names = types.iter().filter_map(|t| t.as_str());
+                let mut found = None;
+                for name in names.by_ref() {
+                    if name == "null" {
+                        nullable = true;
+                    } else if found.is_none() {
+                        found = Some(name);
+                    }
+                }
+                found
+            }
+            _ => None,
+        };
+
+        let field_type = match type_name {
+            Some("string") => match node.get("format").and_then(|f| f.as_str()) {
+                Some("date-time") | Some("date") => FieldType::DateTime,
+                Some("duration") => FieldType::Duration,
+                _ => FieldType::String,
+            },
+            Some("number") | Some("integer") => FieldType::Number,
+            Some("boolean") => FieldType::Boolean,
+            Some("object") => FieldType::Object,
+            Some("array") => {
+                let item = node
+                    .get("items")
+                    .map(|items| Self::json_schema_type(items).0)
+                    .unwrap_or(FieldType::Any);
+                FieldType::Array(Box::new(item))
+            }
+            _ if node.get("properties").is_some() => FieldType::Object,
+            _ => FieldType::Any,
+        };
+
+        (field_type, nullable)
+    }
+}
+
+/// Converts a parsed LROL value into its JSON equivalent
+pub fn lrol_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::Number(n) => serde_json::Number::from_f64(*n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Array(items) => serde_json::Value::Array(items.iter().map(lrol_to_json).collect()),
+        Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), lrol_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_lrol_schema() {
+        let schema = EventSchema::from_json(&json!({
+            "fields": {
+                "amount": "number",
+                "merchant_state": { "type": "string", "optional": true, "enum": ["NY", "CT"] },
+                "recent_ips": "array<string>",
+                "profile": "object"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(schema.resolve("amount"), Some(FieldType::Number));
+        assert_eq!(
+            schema.resolve("recent_ips"),
+            Some(FieldType::Array(Box::new(FieldType::String)))
+        );
+        assert!(schema.get("merchant_state").unwrap().optional);
+        assert_eq!(
+            schema.resolve("profile.available_balance"),
+            Some(FieldType::Any)
+        );
+        assert_eq!(schema.resolve("amount.cents"), None);
+        assert_eq!(schema.resolve("unknown"), None);
+    }
+
+    #[test]
+    fn test_import_json_schema() {
+        let schema = EventSchema::from_json(&json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "required": ["amount", "created"],
+            "properties": {
+                "amount": { "type": "integer" },
+                "created": { "type": "string", "format": "date-time" },
+                "avs_address": { "type": ["string", "null"] },
+                "status": { "type": "string", "enum": ["AUTHORIZATION", "FINANCIAL_AUTHORIZATION"] },
+                "merchant": {
+                    "type": "object",
+                    "properties": { "state": { "type": "string" } }
+                },
+                "events": { "type": "array", "items": { "type": "object" } }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(schema.resolve("amount"), Some(FieldType::Number));
+        assert!(!schema.get("amount").unwrap().optional);
+        assert_eq!(schema.resolve("created"), Some(FieldType::DateTime));
+        assert!(schema.get("avs_address").unwrap().optional);
+        assert_eq!(
+            schema.get("status").unwrap().values.as_ref().unwrap().len(),
+            2
+        );
+        assert_eq!(schema.resolve("merchant.state"), Some(FieldType::String));
+        assert_eq!(
+            schema.resolve("events"),
+            Some(FieldType::Array(Box::new(FieldType::Object)))
+        );
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(EventSchema::from_json(&json!({ "amount": "number" })).is_err());
+        assert!(EventSchema::from_json(&json!({ "fields": { "amount": "money" } })).is_err());
+    }
+
+    #[test]
+    fn test_schema_roundtrip() {
+        let mut schema = EventSchema::new();
+        schema.insert("amount", FieldSpec::new(FieldType::Number));
+        schema.insert(
+            "tags",
+            FieldSpec::new(FieldType::Array(Box::new(FieldType::String))),
+        );
+
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["fields"]["tags"]["type"], "array<string>");
+        assert_eq!(EventSchema::from_json(&json).unwrap(), schema);
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use lrol_parser::{parser::LrolModel, EvaluationType};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::DependencyGraph;
+use crate::library::{LibraryRule, RuleRef};
+use crate::operand::{path_matches, referenced_fields};
+
+/// Categories of sensitive event fields, usually loaded from a JSON file:
+///
+/// ```json
+/// {
+///   "fields": {
+///     "card_number": ["payment_credential"],
+///     "customer.date_of_birth": ["pii", "protected_attribute"],
+///     "customer.address": ["pii"]
+///   }
+/// }
+/// ```
+///
+/// Field names match as in [`path_matches`], so `customer.address` also
+/// classifies `customer.address.city`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldClassifications {
+    pub fields: BTreeMap<String, Vec<String>>,
+}
+
+impl FieldClassifications {
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content).map_err(|e| format!("Invalid field classification: {}", e))
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read field classification {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Self::from_json(&content)
+    }
+
+    /// Categories of every classification naming `path`, sorted
+    pub fn categories(&self, path: &str) -> Vec<String> {
+        let categories: BTreeSet<&String> = self
+            .fields
+            .iter()
+            .filter(|(pattern, _)| path_matches(path, pattern))
+            .flat_map(|(_, categories)| categories)
+            .collect();
+        categories.into_iter().cloned().collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    /// Read by the evaluation itself
+    Direct,
+    /// Aggregated over, or used to filter the aggregated events
+    Aggregated,
+    /// Read by an evaluation this one depends on through operands or `@` references
+    Referenced,
+}
+
+/// Classified field an evaluation depends on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldUsage {
+    pub evaluation_name: String,
+    pub field: String,
+    pub categories: Vec<String>,
+    pub usage: UsageKind,
+    /// Evaluation field the event field is read in, e.g. `left` or
+    /// `conditions[0].right`, for direct and aggregated usage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Dependency chain from this evaluation to the one reading the field,
+    /// for referenced usage
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub via: Vec<String>,
+}
+
+/// Classified fields used by one rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleAudit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub model_id: String,
+    pub name: String,
+    /// Every category the rule depends on, sorted
+    pub categories: Vec<String>,
+    pub usages: Vec<FieldUsage>,
+}
+
+impl RuleAudit {
+    pub fn from_model(model: &LrolModel, classifications: &FieldClassifications) -> Self {
+        let is_classified = |path: &str| !classifications.categories(path).is_empty();
+
+        // Fields each evaluation reads itself, in declaration order
+        let mut direct: Vec<FieldUsage> = Vec::new();
+        for reference in referenced_fields(model, is_classified) {
+            let categories = classifications.categories(&reference.field);
+            if categories.is_empty() {
+                continue;
+            }
+            let aggregated = model
+                .evaluations
+                .iter()
+                .find(|e| e.name == reference.evaluation_name)
+                .is_some_and(|e| e.evaluation_type == EvaluationType::Aggregation);
+            direct.push(FieldUsage {
+                evaluation_name: reference.evaluation_name,
+                field: reference.field,
+                categories,
+                usage: if aggregated {
+                    UsageKind::Aggregated
+                } else {
+                    UsageKind::Direct
+                },
+                location: Some(reference.field_name),
+                via: Vec::new(),
+            });
+        }
+
+        let graph = DependencyGraph::from_model(model);
+        let mut usages = Vec::new();
+        let mut audited = BTreeSet::new();
+        for evaluation in &model.evaluations {
+            if !audited.insert(evaluation.name.as_str()) {
+                continue;
+            }
+            usages.extend(
+                direct
+                    .iter()
+                    .filter(|usage| usage.evaluation_name == evaluation.name)
+                    .cloned(),
+            );
+
+            // Shortest chain to every evaluation this one depends on
+            let mut chains: HashMap<&str, Vec<String>> = HashMap::new();
+            let mut queue = VecDeque::from([evaluation.name.as_str()]);
+            chains.insert(&evaluation.name, vec![evaluation.name.clone()]);
+            while let Some(name) = queue.pop_front() {
+                for dependency in graph.dependencies(name) {
+                    if !chains.contains_key(dependency) {
+                        let mut chain = chains[name].clone();
+                        chain.push(dependency.to_string());
+                        chains.insert(dependency, chain);
+                        queue.push_back(dependency);
+                    }
+                }
+            }
+
+            let mut seen = BTreeSet::new();
+            for usage in &direct {
+                if usage.evaluation_name == evaluation.name {
+                    continue;
+                }
+                let Some(chain) = chains.get(usage.evaluation_name.as_str()) else {
+                    continue;
+                };
+                if seen.insert((usage.field.as_str(), usage.evaluation_name.as_str())) {
+                    usages.push(FieldUsage {
+                        evaluation_name: evaluation.name.clone(),
+                        field: usage.field.clone(),
+                        categories: usage.categories.clone(),
+                        usage: UsageKind::Referenced,
+                        location: None,
+                        via: chain[1..].to_vec(),
+                    });
+                }
+            }
+        }
+
+        let categories: BTreeSet<&String> = usages.iter().flat_map(|u| &u.categories).collect();
+        RuleAudit {
+            source: None,
+            model_id: model.model_id.clone(),
+            name: model.name.clone(),
+            categories: categories.into_iter().cloned().collect(),
+            usages,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Distinct classified fields the rule depends on, sorted
+    pub fn fields(&self) -> Vec<&str> {
+        let fields: BTreeSet<&str> = self.usages.iter().map(|u| u.field.as_str()).collect();
+        fields.into_iter().collect()
+    }
+}
+
+/// Classified fields used across a library of rules
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LibraryAudit {
+    pub rules: Vec<RuleAudit>,
+    /// Rules depending on each category
+    pub categories: BTreeMap<String, Vec<RuleRef>>,
+    /// Rules depending on each classified field
+    pub fields: BTreeMap<String, Vec<RuleRef>>,
+}
+
+impl LibraryAudit {
+    pub fn from_library(rules: &[LibraryRule], classifications: &FieldClassifications) -> Self {
+        let mut audit = LibraryAudit {
+            rules: Vec::new(),
+            categories: BTreeMap::new(),
+            fields: BTreeMap::new(),
+        };
+        for rule in rules {
+            let rule_audit =
+                RuleAudit::from_model(&rule.model, classifications).with_source(&rule.source);
+            let rule_ref = RuleRef {
+                source: rule.source.clone(),
+                model_id: rule.model.model_id.clone(),
+            };
+            for category in &rule_audit.categories {
+                audit
+                    .categories
+                    .entry(category.clone())
+                    .or_default()
+                    .push(rule_ref.clone());
+            }
+            for field in rule_audit.fields() {
+                audit
+                    .fields
+                    .entry(field.to_string())
+                    .or_default()
+                    .push(rule_ref.clone());
+            }
+            audit.rules.push(rule_audit);
+        }
+        audit
+    }
+
+    /// One line per usage, with a header, for spreadsheets
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("source,model_id,evaluation,field,categories,usage,location,via\n");
+        for rule in &self.rules {
+            for usage in &rule.usages {
+                let usage_kind = serde_json::to_value(usage.usage)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                let row = [
+                    rule.source.clone().unwrap_or_default(),
+                    rule.model_id.clone(),
+                    usage.evaluation_name.clone(),
+                    usage.field.clone(),
+                    usage.categories.join(";"),
+                    usage_kind,
+                    usage.location.clone().unwrap_or_default(),
+                    usage.via.join(" -> "),
+                ];
+                let row: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+        }
+        csv
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classifications() -> FieldClassifications {
+        FieldClassifications::from_json(
+            r#"{"fields": {
+  "card_number": ["payment_credential"],
+  "customer.date_of_birth": ["pii", "protected_attribute"],
+  "customer": ["pii"]
+}}"#,
+        )
+        .unwrap()
+    }
+
+    fn model() -> LrolModel {
+        lrol_parser::parse_str(
+            r#"{
+  "model_id": "A001",
+  "name": "Audit",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "Age_Check", "type": "comparison", "left": "timediff(now, customer.date_of_birth)", "operator": "<", "right": 6570},
+    {"name": "Card_Count", "type": "aggregation", "aggregation": "COUNT", "field": "amount",
+     "conditions": [{"left": "payment.card_number", "operator": "==", "right": "4111"}]},
+    {"name": "Card_Check", "type": "comparison", "left": "@Card_Count", "operator": ">", "right": 3},
+    {"name": "Logic", "type": "logical", "operator": "AND", "operands": ["Age_Check", "Card_Check"], "weight": 3},
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 100, "weight": 1}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Test"}]
+}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rule_audit() {
+        let audit = RuleAudit::from_model(&model(), &classifications());
+        assert_eq!(
+            audit.categories,
+            vec!["payment_credential", "pii", "protected_attribute"]
+        );
+        assert_eq!(
+            audit.fields(),
+            vec!["customer.date_of_birth", "payment.card_number"]
+        );
+
+        let usages: Vec<(&str, &str, UsageKind, Vec<String>)> = audit
+            .usages
+            .iter()
+            .map(|u| {
+                (
+                    u.evaluation_name.as_str(),
+                    u.field.as_str(),
+                    u.usage,
+                    u.via.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            usages,
+            vec![
+                (
+                    "Age_Check",
+                    "customer.date_of_birth",
+                    UsageKind::Direct,
+                    vec![]
+                ),
+                (
+                    "Card_Count",
+                    "payment.card_number",
+                    UsageKind::Aggregated,
+                    vec![]
+                ),
+                (
+                    "Card_Check",
+                    "payment.card_number",
+                    UsageKind::Referenced,
+                    vec!["Card_Count".to_string()]
+                ),
+                (
+                    "Logic",
+                    "customer.date_of_birth",
+                    UsageKind::Referenced,
+                    vec!["Age_Check".to_string()]
+                ),
+                (
+                    "Logic",
+                    "payment.card_number",
+                    UsageKind::Referenced,
+                    vec!["Card_Check".to_string(), "Card_Count".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            audit.usages[0].categories,
+            vec!["pii", "protected_attribute"]
+        );
+        assert_eq!(
+            audit.usages[1].location.as_deref(),
+            Some("conditions[0].left")
+        );
+    }
+
+    #[test]
+    fn test_library_audit() {
+        let mut clean = model();
+        clean.model_id = "A002".to_string();
+        clean.evaluations.truncate(0);
+        let rules = vec![
+            LibraryRule {
+                source: "a.json".to_string(),
+                model: model(),
+            },
+            LibraryRule {
+                source: "b.json".to_string(),
+                model: clean,
+            },
+        ];
+        let audit = LibraryAudit::from_library(&rules, &classifications());
+
+        assert_eq!(audit.rules.len(), 2);
+        assert!(audit.rules[1].usages.is_empty());
+        assert_eq!(
+            audit.categories["pii"],
+            vec![RuleRef {
+                source: "a.json".to_string(),
+                model_id: "A001".to_string()
+            }]
+        );
+        assert_eq!(audit.fields.len(), 2);
+
+        let csv = audit.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 5);
+        assert!(csv.contains(
+            "a.json,A001,Logic,payment.card_number,payment_credential,referenced,,Card_Check -> Card_Count\n"
+        ));
+        assert!(csv.contains(",aggregated,conditions[0].left,\n"));
+        assert_eq!(csv_cell("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+}
@@ -0,0 +1,594 @@
+use lrol_parser::{parser::LrolModel, SourceMap, Span};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{Symbol, SymbolTable};
+use crate::error::AnalyzerError;
+use crate::typecheck::TypeChecker;
+use crate::validator::RuleValidator;
+
+/// Fixing one issue can reveal another, so fixes are applied over a few passes
+pub const MAX_FIX_PASSES: usize = 8;
+
+/// Whether a fix can be applied without review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Applicability {
+    /// The fix keeps the author's intent and is applied by `lrol fix`
+    MachineApplicable,
+    /// The fix is plausible but changes behavior, so it needs review
+    MaybeIncorrect,
+}
+
+/// Replacement of a byte range of the source; an empty range inserts text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// Edits resolving a single diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    pub message: String,
+    pub applicability: Applicability,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+/// Source after fixing, with the fixes that were applied in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixOutcome {
+    pub source: String,
+    pub applied: Vec<Fix>,
+}
+
+/// Suggests a fix for a diagnostic found in `source`.
+///
+/// `model` must have been parsed from `source`, and `source_map` built from it.
+pub fn suggest_fix(
+    error: &AnalyzerError,
+    model: &LrolModel,
+    source: &str,
+    source_map: &SourceMap,
+) -> Option<Fix> {
+    // Diagnostics name their evaluation. When the name is declared more than
+    // once, fix the one declaration the diagnostic describes, and nothing when
+    // several could be meant.
+    let index_of = |name: &str, describes: &dyn Fn(usize) -> bool| {
+        let mut matching = (0..model.evaluations.len())
+            .filter(|&i| model.evaluations[i].name == name && describes(i));
+        let index = matching.next()?;
+        matching.next().is_none().then_some(index)
+    };
+    let written = |index: usize, field: &str| {
+        let span = source_map.evaluation_field(index, field)?;
+        Some(source[span.start..span.end].trim_matches('"'))
+    };
+
+    match error {
+        AnalyzerError::InvalidComparisonOperator {
+            evaluation_name,
+            operator,
+        } => {
+            let canonical = canonical_comparison_operator(operator)?;
+            let index = index_of(evaluation_name, &|i| {
+                written(i, "operator") == Some(operator.as_str())
+            })?;
+            let span = source_map.evaluation_field(index, "operator")?;
+            Some(Fix {
+                message: format!("Replace operator '{}' with '{}'", operator, canonical),
+                applicability: Applicability::MachineApplicable,
+                edits: vec![replace_with_string(span, canonical)],
+            })
+        }
+        AnalyzerError::InvalidLogicalOperator {
+            evaluation_name,
+            operator,
+        } => {
+            let canonical = match operator.trim().to_ascii_uppercase().as_str() {
+                "AND" | "&&" => "AND",
+                "OR" | "||" => "OR",
+                _ => return None,
+            };
+            let index = index_of(evaluation_name, &|i| {
+                written(i, "operator") == Some(operator.as_str())
+            })?;
+            let span = source_map.evaluation_field(index, "operator")?;
+            Some(Fix {
+                message: format!("Replace operator '{}' with '{}'", operator, canonical),
+                applicability: Applicability::MachineApplicable,
+                edits: vec![replace_with_string(span, canonical)],
+            })
+        }
+        AnalyzerError::NumericString {
+            evaluation_name,
+            field_name,
+            value,
+        } => {
+            let index = index_of(evaluation_name, &|i| {
+                written(i, field_name) == Some(value.as_str())
+            })?;
+            let span = source_map.evaluation_field(index, field_name)?;
+            Some(Fix {
+                message: format!("Write \"{}\" as the number {}", value, value.trim()),
+                applicability: Applicability::MachineApplicable,
+                edits: vec![TextEdit {
+                    span,
+                    replacement: value.trim().to_string(),
+                }],
+            })
+        }
+        AnalyzerError::NonCanonicalAggregation {
+            evaluation_name,
+            aggregation,
+        } => {
+            let canonical = aggregation.to_ascii_uppercase();
+            let index = index_of(evaluation_name, &|i| {
+                written(i, "aggregation") == Some(aggregation.as_str())
+            })?;
+            let span = source_map.evaluation_field(index, "aggregation")?;
+            Some(Fix {
+                message: format!("Write aggregation '{}' as '{}'", aggregation, canonical),
+                applicability: Applicability::MachineApplicable,
+                edits: vec![replace_with_string(span, &canonical)],
+            })
+        }
+        AnalyzerError::MissingWeight { evaluation_name } => {
+            let index = index_of(evaluation_name, &|i| model.evaluations[i].weight.is_none())?;
+            let edit = insert_field(source, source_map, index, "\"weight\": 1")?;
+            Some(Fix {
+                message: format!("Give {} the lowest weight, 1", evaluation_name),
+                applicability: Applicability::MaybeIncorrect,
+                edits: vec![edit],
+            })
+        }
+        AnalyzerError::DuplicateEvaluationName(name) => {
+            rename_duplicates(name, model, source, source_map)
+        }
+        _ => None,
+    }
+}
+
+/// Applies every fix whose edits do not overlap those of an earlier fix.
+///
+/// Returns the new source and the fixes that were applied.
+pub fn apply_fixes<'a>(source: &str, fixes: &'a [Fix]) -> (String, Vec<&'a Fix>) {
+    let mut applied: Vec<&Fix> = Vec::new();
+    let mut edits: Vec<&TextEdit> = Vec::new();
+
+    for fix in fixes {
+        let overlaps = fix.edits.iter().any(|edit| {
+            edits.iter().any(|other| {
+                edit.span.start < other.span.end && other.span.start < edit.span.end
+                    || edit.span.start == other.span.start
+            })
+        });
+        if !overlaps {
+            edits.extend(&fix.edits);
+            applied.push(fix);
+        }
+    }
+
+    edits.sort_by_key(|edit| (edit.span.start, edit.span.end));
+    let mut output = String::with_capacity(source.len());
+    let mut position = 0;
+    for edit in edits {
+        output.push_str(&source[position..edit.span.start]);
+        output.push_str(&edit.replacement);
+        position = edit.span.end;
+    }
+    output.push_str(&source[position..]);
+
+    (output, applied)
+}
+
+/// Repeatedly validates `source` and applies the suggested fixes.
+///
+/// Only machine-applicable fixes are used unless `include_maybe_incorrect` is set.
+pub fn fix_source(
+    validator: &RuleValidator,
+    source: &str,
+    include_maybe_incorrect: bool,
+) -> FixOutcome {
+    let mut outcome = FixOutcome {
+        source: source.to_string(),
+        applied: Vec::new(),
+    };
+
+    for _ in 0..MAX_FIX_PASSES {
+        let report = validator.validate_with_report(&outcome.source);
+        let fixes: Vec<Fix> = report
+            .fixes()
+            .into_iter()
+            .filter(|fix| include_maybe_incorrect || fix.is_machine_applicable())
+            .collect();
+
+        let (fixed, applied) = apply_fixes(&outcome.source, &fixes);
+        if applied.is_empty() {
+            break;
+        }
+        outcome.applied.extend(applied.into_iter().cloned());
+        outcome.source = fixed;
+    }
+
+    outcome
+}
+
+fn canonical_comparison_operator(operator: &str) -> Option<&'static str> {
+    let normalized = operator
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_uppercase();
+    let canonical = match normalized.as_str() {
+        "=<" => "<=",
+        "=>" => ">=",
+        "=" | "===" => "==",
+        "<>" | "=!" | "!==" => "!=",
+        ">" => ">",
+        "<" => "<",
+        ">=" => ">=",
+        "<=" => "<=",
+        "==" => "==",
+        "!=" => "!=",
+        "IN" => "IN",
+        "NOT IN" => "NOT IN",
+        "LIKE" => "LIKE",
+        "NOT LIKE" => "NOT LIKE",
+        _ => return None,
+    };
+    (canonical != operator).then_some(canonical)
+}
+
+fn replace_with_string(span: Span, value: &str) -> TextEdit {
+    TextEdit {
+        span,
+        replacement: format!("\"{}\"", value),
+    }
+}
+
+// Insert a field before the closing brace of an evaluation, matching the
+// indentation of its `name` field
+fn insert_field(
+    source: &str,
+    source_map: &SourceMap,
+    index: usize,
+    field: &str,
+) -> Option<TextEdit> {
+    let evaluation = source_map.evaluation(index)?;
+    let body = source.get(evaluation.start..evaluation.end.checked_sub(1)?)?;
+    let last = evaluation.start + body.trim_end().len();
+
+    let name = source_map.evaluation_field(index, "name")?;
+    let line_start = source[..name.start].rfind('\n').map_or(0, |i| i + 1);
+    let replacement = if source[evaluation.start..last].contains('\n') {
+        let key_start = source[line_start..name.start]
+            .find('"')
+            .map_or(name.start, |i| line_start + i);
+        format!(",\n{}{}", &source[line_start..key_start], field)
+    } else {
+        format!(", {}", field)
+    };
+
+    Some(TextEdit {
+        span: source_map.span(last, last),
+        replacement,
+    })
+}
+
+// Rename every later declaration of a duplicated name. References resolve to
+// the first declaration, so an evaluation's references are only pointed at a
+// renamed one when they fit its output and not that of the first, e.g. an
+// ordering comparison of a name declared as a comparison and an aggregation.
+fn rename_duplicates(
+    name: &str,
+    model: &LrolModel,
+    source: &str,
+    source_map: &SourceMap,
+) -> Option<Fix> {
+    let declarations: Vec<usize> = model
+        .evaluations
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.name == name)
+        .map(|(i, _)| i)
+        .collect();
+    if declarations.len() < 2 {
+        return None;
+    }
+
+    let mut taken: Vec<String> = model.evaluations.iter().map(|e| e.name.clone()).collect();
+    let mut edits = Vec::new();
+    let mut renamed = Vec::new();
+    let mut retargeted = Vec::new();
+    let mut resolved = vec![declarations[0]; model.evaluations.len()];
+
+    for &index in declarations.iter().skip(1) {
+        let new_name = (2..)
+            .map(|n| format!("{}_{}", name, n))
+            .find(|candidate| !taken.contains(candidate))?;
+        taken.push(new_name.clone());
+
+        edits.push(replace_with_string(
+            source_map.evaluation_field(index, "name")?,
+            &new_name,
+        ));
+
+        for (referencing, evaluation) in model.evaluations.iter().enumerate() {
+            if evaluation.name == name
+                || resolved[referencing] != declarations[0]
+                || type_errors(model, name, declarations[0], referencing) == 0
+                || type_errors(model, name, index, referencing) > 0
+            {
+                continue;
+            }
+            let references = reference_edits(source, source_map, referencing, name, &new_name);
+            if !references.is_empty() {
+                edits.extend(references);
+                resolved[referencing] = index;
+                retargeted.push(format!("{} at {}", evaluation.name, new_name));
+            }
+        }
+        renamed.push(new_name);
+    }
+
+    let references = if retargeted.is_empty() {
+        "references still read the first".to_string()
+    } else {
+        format!(
+            "point {}; other references still read the first",
+            retargeted.join(", ")
+        )
+    };
+    Some(Fix {
+        message: format!(
+            "Rename the later declarations of {} to {}; {}",
+            name,
+            renamed.join(", "),
+            references
+        ),
+        applicability: Applicability::MaybeIncorrect,
+        edits,
+    })
+}
+
+// Type errors in the evaluation at `referencing` when `name` resolves to the
+// declaration at `declaration`
+fn type_errors(model: &LrolModel, name: &str, declaration: usize, referencing: usize) -> usize {
+    let mut symbols = SymbolTable::new();
+    let declared = std::iter::once(declaration).chain(0..model.evaluations.len());
+    for index in declared {
+        let evaluation = &model.evaluations[index];
+        if evaluation.name == name && index != declaration {
+            continue;
+        }
+        symbols.insert(Symbol {
+            name: evaluation.name.clone(),
+            index,
+            evaluation_type: evaluation.evaluation_type.clone(),
+            weight: evaluation.weight,
+        });
+    }
+
+    let mut errors = Vec::new();
+    TypeChecker::without_schema(&symbols)
+        .check_evaluation(&model.evaluations[referencing], &mut errors);
+    errors.len()
+}
+
+// Edits pointing the references to `name` in one evaluation at `new_name`
+fn reference_edits(
+    source: &str,
+    source_map: &SourceMap,
+    index: usize,
+    name: &str,
+    new_name: &str,
+) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+
+    if let Some(operands) = source_map.evaluation_field(index, "operands") {
+        let quoted = format!("\"{}\"", name);
+        for (offset, _) in source[operands.start..operands.end].match_indices(&quoted) {
+            let start = operands.start + offset;
+            edits.push(replace_with_string(
+                source_map.span(start, start + quoted.len()),
+                new_name,
+            ));
+        }
+    }
+
+    if let Some(evaluation) = source_map.evaluation(index) {
+        let reference = format!("@{}", name);
+        let text = &source[evaluation.start..evaluation.end];
+        for (offset, _) in text.match_indices(&reference) {
+            let end = offset + reference.len();
+            let boundary = text[end..]
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_alphanumeric() || c == '_'));
+            if boundary {
+                let start = evaluation.start + offset + 1;
+                edits.push(TextEdit {
+                    span: source_map.span(start, start + name.len()),
+                    replacement: new_name.to_string(),
+                });
+            }
+        }
+    }
+
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"{
+  "model_id": "R900",
+  "name": "Fixable",
+  "threshold": 0.5,
+  "evaluations": [
+    {
+      "name": "Total",
+      "type": "aggregation",
+      "aggregation": "sum",
+      "field": "amount",
+      "weight": 2
+    },
+    {
+      "name": "Limit_Check",
+      "type": "comparison",
+      "left": "@Total",
+      "operator": "=<",
+      "right": "30000"
+    },
+    {
+      "name": "Logic",
+      "type": "logical",
+      "operator": "and",
+      "operands": ["Limit_Check"],
+      "weight": 3
+    }
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Limit exceeded"}]
+}"#;
+
+    #[test]
+    fn test_fix_source() {
+        let validator = RuleValidator::new();
+        let outcome = fix_source(&validator, SOURCE, false);
+
+        let messages: Vec<_> = outcome.applied.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Replace operator '=<' with '<='",
+                "Replace operator 'and' with 'AND'",
+                "Write \"30000\" as the number 30000",
+                "Write aggregation 'sum' as 'SUM'",
+            ]
+        );
+        assert!(outcome.source.contains(r#""operator": "<=","#));
+        assert!(outcome.source.contains(r#""right": 30000"#));
+        assert!(outcome.source.contains(r#""aggregation": "SUM","#));
+        // Formatting outside the edits is untouched
+        assert_eq!(outcome.source.lines().count(), SOURCE.lines().count());
+
+        let report = validator.validate_with_report(&outcome.source);
+        assert!(report.is_valid());
+        assert!(report
+            .analyzer_warnings
+            .iter()
+            .any(|w| matches!(w, AnalyzerError::MissingWeight { .. })));
+
+        let outcome = fix_source(&validator, &outcome.source, true);
+        assert_eq!(outcome.applied.len(), 1);
+        assert!(outcome
+            .source
+            .contains("      \"right\": 30000,\n      \"weight\": 1\n    },"));
+    }
+
+    #[test]
+    fn test_rename_duplicates() {
+        let source = r#"{
+  "model_id": "R901",
+  "name": "Duplicates",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "Check", "type": "comparison", "left": "amount", "operator": ">", "right": 10, "weight": 2},
+    {"name": "Check", "type": "aggregation", "aggregation": "COUNT", "field": "amount", "weight": 2},
+    {"name": "Count_Check", "type": "comparison", "left": "@Check", "operator": ">", "right": 5, "weight": 2},
+    {"name": "Logic", "type": "logical", "operator": "OR", "operands": ["Check", "Count_Check"], "weight": 2}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Suspicious"}]
+}"#;
+
+        let validator = RuleValidator::new();
+        assert!(fix_source(&validator, source, false).applied.is_empty());
+
+        let outcome = fix_source(&validator, source, true);
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(
+            outcome.applied[0].applicability,
+            Applicability::MaybeIncorrect
+        );
+        assert_eq!(
+            outcome.applied[0].message,
+            "Rename the later declarations of Check to Check_2; point Count_Check at Check_2; other references still read the first"
+        );
+        assert!(outcome
+            .source
+            .contains(r#"{"name": "Check", "type": "comparison""#));
+        assert!(outcome
+            .source
+            .contains(r#"{"name": "Check_2", "type": "aggregation""#));
+        // Ordering the count only fits the aggregation, while the logical
+        // operand fits the comparison it already reads
+        assert!(outcome.source.contains(r#""left": "@Check_2""#));
+        assert!(outcome
+            .source
+            .contains(r#""operands": ["Check", "Count_Check"]"#));
+        assert!(validator.validate_with_report(&outcome.source).is_valid());
+    }
+
+    #[test]
+    fn test_duplicate_without_weight() {
+        let source = r#"{
+  "model_id": "R902",
+  "name": "Duplicate Without Weight",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "Amt", "type": "comparison", "left": "amount", "operator": ">", "right": 10, "weight": 3},
+    {"name": "Amt", "type": "comparison", "left": "amount", "operator": ">", "right": 500},
+    {"name": "L", "type": "logical", "operator": "OR", "operands": ["Amt"], "weight": 2}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Suspicious"}]
+}"#;
+
+        let validator = RuleValidator::new();
+        let report = validator.validate_with_report(source);
+        assert!(report
+            .analyzer_warnings
+            .iter()
+            .any(|w| matches!(w, AnalyzerError::MissingWeight { .. })));
+
+        // The weight goes to the declaration without one, not the first "Amt"
+        let outcome = fix_source(&validator, source, true);
+        assert_eq!(outcome.applied.len(), 2);
+        assert!(outcome.source.contains(r#""right": 10, "weight": 3}"#));
+        assert!(outcome.source.contains(
+            r#"{"name": "Amt_2", "type": "comparison", "left": "amount", "operator": ">", "right": 500, "weight": 1}"#
+        ));
+        assert_eq!(outcome.source.matches("\"weight\"").count(), 3);
+        assert!(outcome.source.contains(r#""operands": ["Amt"]"#));
+        assert!(validator.validate_with_report(&outcome.source).is_valid());
+    }
+
+    #[test]
+    fn test_overlapping_fixes_are_skipped() {
+        let span = |start, end| Span {
+            start,
+            end,
+            line: 1,
+            column: start + 1,
+        };
+        let fix = |start, end, replacement: &str| Fix {
+            message: replacement.to_string(),
+            applicability: Applicability::MachineApplicable,
+            edits: vec![TextEdit {
+                span: span(start, end),
+                replacement: replacement.to_string(),
+            }],
+        };
+
+        let fixes = vec![fix(0, 3, "one"), fix(2, 5, "two"), fix(6, 6, "+")];
+        let (output, applied) = apply_fixes("abcdefg", &fixes);
+        assert_eq!(output, "onedef+g");
+        assert_eq!(applied.len(), 2);
+    }
+}
@@ -0,0 +1,533 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use lrol_parser::{parser::LrolModel, Evaluation, Value};
+use serde::Serialize;
+
+use crate::analyzer::RuleAnalyzer;
+
+/// How one evaluation depends on another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    /// Listed in the `operands` of a logical evaluation
+    Operand,
+    /// Referenced with `@Name` in the `left` field
+    LeftReference,
+    /// Referenced with `@Name` in the `right` field
+    RightReference,
+}
+
+impl DependencyKind {
+    /// Name of the evaluation field the dependency was declared in
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            DependencyKind::Operand => "operands",
+            DependencyKind::LeftReference => "left",
+            DependencyKind::RightReference => "right",
+        }
+    }
+}
+
+impl fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyKind::Operand => write!(f, "operands"),
+            DependencyKind::LeftReference => write!(f, "@left"),
+            DependencyKind::RightReference => write!(f, "@right"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: DependencyKind,
+}
+
+impl fmt::Display for DependencyEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -[{}]-> {}", self.from, self.kind, self.to)
+    }
+}
+
+/// Strongly connected component of the graph that contains a cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// Evaluations in the component, in declaration order
+    pub members: Vec<String>,
+    /// Shortest cycle through the first member, starting and ending before
+    /// returning to it, e.g. `[a, b]` for `a → b → a`
+    pub path: Vec<String>,
+    /// Every edge between members of the component, in declaration order
+    pub edges: Vec<DependencyEdge>,
+}
+
+/// Directed graph of evaluation dependencies.
+///
+/// Nodes are kept in declaration order and edges in the order they appear in
+/// each evaluation, so every traversal over the graph is deterministic.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DependencyGraph {
+    nodes: Vec<String>,
+    edges: Vec<DependencyEdge>,
+    outgoing: HashMap<String, Vec<usize>>,
+    incoming: HashMap<String, Vec<usize>>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the graph from the operands and `@` references of every evaluation
+    pub fn from_model(model: &LrolModel) -> Self {
+        let mut graph = Self::new();
+
+        for evaluation in &model.evaluations {
+            graph.add_node(&evaluation.name);
+        }
+
+        for evaluation in &model.evaluations {
+            for (to, kind) in Self::collect_dependencies(evaluation) {
+                graph.add_edge(&evaluation.name, &to, kind);
+            }
+        }
+
+        graph
+    }
+
+    // Collect operands and string references of a single evaluation
+    fn collect_dependencies(evaluation: &Evaluation) -> Vec<(String, DependencyKind)> {
+        let mut dependencies = Vec::new();
+
+        if let Some(ref operands) = evaluation.operands {
+            for operand in operands {
+                dependencies.push((operand.clone(), DependencyKind::Operand));
+            }
+        }
+
+        if let Some(ref left) = evaluation.left {
+            for reference in RuleAnalyzer::extract_references(left) {
+                dependencies.push((reference, DependencyKind::LeftReference));
+            }
+        }
+
+        if let Some(Value::String(ref right)) = evaluation.right {
+            for reference in RuleAnalyzer::extract_references(right) {
+                dependencies.push((reference, DependencyKind::RightReference));
+            }
+        }
+
+        dependencies
+    }
+
+    pub fn add_node(&mut self, name: &str) {
+        if !self.outgoing.contains_key(name) {
+            self.nodes.push(name.to_string());
+            self.outgoing.insert(name.to_string(), Vec::new());
+            self.incoming.insert(name.to_string(), Vec::new());
+        }
+    }
+
+    /// Adds an edge, creating either endpoint if it is not yet a node
+    pub fn add_edge(&mut self, from: &str, to: &str, kind: DependencyKind) {
+        self.add_node(from);
+        self.add_node(to);
+
+        let index = self.edges.len();
+        self.edges.push(DependencyEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+        });
+        self.outgoing.get_mut(from).unwrap().push(index);
+        self.incoming.get_mut(to).unwrap().push(index);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.outgoing.contains_key(name)
+    }
+
+    /// All nodes in declaration order
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[DependencyEdge] {
+        &self.edges
+    }
+
+    /// Edges leaving `name`, i.e. what it depends on
+    pub fn outgoing(&self, name: &str) -> impl Iterator<Item = &DependencyEdge> {
+        self.outgoing
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(move |&i| &self.edges[i])
+    }
+
+    /// Edges entering `name`, i.e. what depends on it
+    pub fn incoming(&self, name: &str) -> impl Iterator<Item = &DependencyEdge> {
+        self.incoming
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(move |&i| &self.edges[i])
+    }
+
+    /// Names `name` depends on, without duplicates
+    pub fn dependencies(&self, name: &str) -> Vec<&str> {
+        let mut deps: Vec<&str> = Vec::new();
+        for edge in self.outgoing(name) {
+            if !deps.contains(&edge.to.as_str()) {
+                deps.push(&edge.to);
+            }
+        }
+        deps
+    }
+
+    /// Names depending on `name`, without duplicates
+    pub fn dependents(&self, name: &str) -> Vec<&str> {
+        let mut deps: Vec<&str> = Vec::new();
+        for edge in self.incoming(name) {
+            if !deps.contains(&edge.from.as_str()) {
+                deps.push(&edge.from);
+            }
+        }
+        deps
+    }
+
+    /// Nodes nothing else depends on
+    pub fn roots(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|n| self.incoming.get(n.as_str()).is_none_or(Vec::is_empty))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Strongly connected components, ordered by their first declared member.
+    ///
+    /// Members of each component are in declaration order. Every node belongs
+    /// to exactly one component.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        let position: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.as_str(), i))
+            .collect();
+        let mut tarjan = Tarjan {
+            graph: self,
+            position: &position,
+            index: vec![None; self.nodes.len()],
+            lowlink: vec![0; self.nodes.len()],
+            on_stack: vec![false; self.nodes.len()],
+            stack: Vec::new(),
+            next_index: 0,
+            components: Vec::new(),
+        };
+        for i in 0..self.nodes.len() {
+            if tarjan.index[i].is_none() {
+                tarjan.visit(i);
+            }
+        }
+
+        let mut components = tarjan.components;
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        components.sort_unstable();
+        components
+            .into_iter()
+            .map(|c| c.into_iter().map(|i| self.nodes[i].clone()).collect())
+            .collect()
+    }
+
+    /// Every cycle in the graph, one per strongly connected component.
+    ///
+    /// Components of a single node are only cycles when the node depends on
+    /// itself.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter_map(|members| {
+                let edges: Vec<DependencyEdge> = self
+                    .edges
+                    .iter()
+                    .filter(|e| members.contains(&e.from) && members.contains(&e.to))
+                    .cloned()
+                    .collect();
+                if edges.is_empty() {
+                    return None;
+                }
+                let path = self.shortest_cycle(&members[0], &members);
+                Some(Cycle {
+                    members,
+                    path,
+                    edges,
+                })
+            })
+            .collect()
+    }
+
+    // Breadth-first search back to `start` without leaving the component
+    fn shortest_cycle(&self, start: &str, members: &[String]) -> Vec<String> {
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(node) = queue.pop_front() {
+            for dep in self.dependencies(node) {
+                if dep == start {
+                    let mut path = vec![node.to_string()];
+                    let mut current = node;
+                    while let Some(&prev) = previous.get(current) {
+                        path.push(prev.to_string());
+                        current = prev;
+                    }
+                    path.reverse();
+                    return path;
+                }
+                if members.iter().any(|m| m == dep) && !previous.contains_key(dep) {
+                    previous.insert(dep, node);
+                    queue.push_back(dep);
+                }
+            }
+        }
+
+        vec![start.to_string()]
+    }
+
+    /// Longest chain of dependencies starting at `name`, including `name` itself.
+    ///
+    /// Edges closing a cycle are ignored so the chain is always finite.
+    pub fn longest_chain_from(&self, name: &str) -> Vec<String> {
+        let mut memo = HashMap::new();
+        let mut on_path = Vec::new();
+        self.longest_chain_memo(name, &mut memo, &mut on_path).0
+    }
+
+    /// Longest chain of dependencies in the whole graph.
+    ///
+    /// Ties are broken in favour of the node declared first.
+    pub fn longest_chain(&self) -> Vec<String> {
+        let mut memo = HashMap::new();
+        let mut longest = Vec::new();
+
+        for node in &self.nodes {
+            let mut on_path = Vec::new();
+            let (chain, _) = self.longest_chain_memo(node, &mut memo, &mut on_path);
+            if chain.len() > longest.len() {
+                longest = chain;
+            }
+        }
+
+        longest
+    }
+
+    // Returns the chain and whether it was cut short by a cycle. Such chains
+    // depend on the path taken to reach the node, so they are not cached.
+    fn longest_chain_memo(
+        &self,
+        node: &str,
+        memo: &mut HashMap<String, Vec<String>>,
+        on_path: &mut Vec<String>,
+    ) -> (Vec<String>, bool) {
+        if let Some(chain) = memo.get(node) {
+            return (chain.clone(), false);
+        }
+
+        on_path.push(node.to_string());
+        let mut best: Vec<String> = Vec::new();
+        let mut truncated = false;
+        for dep in self.dependencies(node) {
+            if on_path.iter().any(|n| n == dep) {
+                truncated = true;
+                continue;
+            }
+            let (chain, dep_truncated) = self.longest_chain_memo(dep, memo, on_path);
+            truncated |= dep_truncated;
+            if chain.len() > best.len() {
+                best = chain;
+            }
+        }
+        on_path.pop();
+
+        let mut chain = vec![node.to_string()];
+        chain.extend(best);
+
+        if !truncated {
+            memo.insert(node.to_string(), chain.clone());
+        }
+        (chain, truncated)
+    }
+}
+
+// Tarjan's algorithm over node positions, visiting nodes and edges in
+// declaration order
+struct Tarjan<'a> {
+    graph: &'a DependencyGraph,
+    position: &'a HashMap<&'a str, usize>,
+    index: Vec<Option<usize>>,
+    lowlink: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, node: usize) {
+        self.index[node] = Some(self.next_index);
+        self.lowlink[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        let graph = self.graph;
+        for dep in graph.dependencies(&graph.nodes[node]) {
+            let dep = self.position[dep];
+            match self.index[dep] {
+                None => {
+                    self.visit(dep);
+                    self.lowlink[node] = self.lowlink[node].min(self.lowlink[dep]);
+                }
+                Some(index) if self.on_stack[dep] => {
+                    self.lowlink[node] = self.lowlink[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.lowlink[node]) == self.index[node] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lrol_parser::EvaluationType;
+
+    fn evaluation(name: &str, left: Option<&str>, operands: Option<Vec<&str>>) -> Evaluation {
+        Evaluation {
+            name: name.to_string(),
+            evaluation_type: if operands.is_some() {
+                EvaluationType::Logical
+            } else {
+                EvaluationType::Comparison
+            },
+            left: left.map(str::to_string),
+            operator: Some(if operands.is_some() { "AND" } else { ">" }.to_string()),
+            right: Some(Value::Number(1.0)),
+            operands: operands.map(|o| o.into_iter().map(str::to_string).collect()),
+            weight: Some(3),
+            aggregation: None,
+            field: None,
+            conditions: None,
+        }
+    }
+
+    #[test]
+    fn test_graph_from_model() {
+        let model = LrolModel {
+            evaluations: vec![
+                evaluation("a", Some("amount"), None),
+                evaluation("b", Some("@a"), None),
+                evaluation("c", None, Some(vec!["a", "b"])),
+            ],
+            ..Default::default()
+        };
+
+        let graph = DependencyGraph::from_model(&model);
+        assert_eq!(graph.nodes(), &["a", "b", "c"]);
+        assert_eq!(graph.edges().len(), 3);
+        assert_eq!(graph.dependencies("c"), vec!["a", "b"]);
+        assert_eq!(graph.dependents("a"), vec!["b", "c"]);
+        assert_eq!(graph.roots(), vec!["c"]);
+        assert_eq!(
+            graph.outgoing("b").next().map(|e| e.kind),
+            Some(DependencyKind::LeftReference)
+        );
+    }
+
+    #[test]
+    fn test_longest_chain() {
+        let model = LrolModel {
+            evaluations: vec![
+                evaluation("a", Some("amount"), None),
+                evaluation("b", Some("@a"), None),
+                evaluation("c", None, Some(vec!["a", "b"])),
+                evaluation("d", Some("value"), None),
+            ],
+            ..Default::default()
+        };
+
+        let graph = DependencyGraph::from_model(&model);
+        assert_eq!(graph.longest_chain(), vec!["c", "b", "a"]);
+        assert_eq!(graph.longest_chain_from("d"), vec!["d"]);
+    }
+
+    #[test]
+    fn test_longest_chain_with_cycle() {
+        let model = LrolModel {
+            evaluations: vec![
+                evaluation("a", Some("@b"), None),
+                evaluation("b", Some("@a"), None),
+            ],
+            ..Default::default()
+        };
+
+        let graph = DependencyGraph::from_model(&model);
+        assert_eq!(graph.longest_chain(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let model = LrolModel {
+            evaluations: vec![
+                evaluation("a", Some("@c"), None),
+                evaluation("b", None, Some(vec!["a"])),
+                evaluation("c", Some("@b"), None),
+                evaluation("d", Some("amount"), None),
+                evaluation("e", None, Some(vec!["e", "d"])),
+                evaluation("f", None, Some(vec!["g"])),
+                evaluation("g", None, Some(vec!["f"])),
+            ],
+            ..Default::default()
+        };
+
+        let graph = DependencyGraph::from_model(&model);
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec!["a", "b", "c"], vec!["d"], vec!["e"], vec!["f", "g"]]
+        );
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 3);
+        assert_eq!(cycles[0].path, vec!["a", "c", "b"]);
+        assert_eq!(
+            cycles[0]
+                .edges
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["a -[@left]-> c", "b -[operands]-> a", "c -[@left]-> b"]
+        );
+        assert_eq!(cycles[1].members, vec!["e"]);
+        assert_eq!(cycles[1].path, vec!["e"]);
+        assert_eq!(cycles[1].edges.len(), 1);
+        assert_eq!(cycles[2].path, vec!["f", "g"]);
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use lrol_parser::parser::LrolModel;
+use serde::Serialize;
+
+use crate::graph::{DependencyGraph, DependencyKind};
+use crate::library::LibraryRule;
+
+/// Format an [`EvaluationGraph`] is rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// Node and edge lists
+    Json,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "json" => Ok(GraphFormat::Json),
+            other => Err(format!(
+                "Unknown graph format '{}', expected dot, mermaid or json",
+                other
+            )),
+        }
+    }
+}
+
+/// Rule whose evaluations are drawn as one group of nodes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphRule {
+    pub source: Option<String>,
+    pub model_id: String,
+    pub name: String,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphNode {
+    pub id: usize,
+    /// Index of the rule declaring the evaluation in [`EvaluationGraph::rules`]
+    pub rule: usize,
+    pub name: String,
+    /// `None` for an evaluation that is referenced but declared by no rule
+    pub evaluation_type: Option<String>,
+    pub weight: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphEdge {
+    /// Node that depends on `to`
+    pub from: usize,
+    pub to: usize,
+    pub kind: DependencyKind,
+    /// `to` is declared by another rule than `from`
+    pub cross_rule: bool,
+}
+
+/// Evaluations of one or more rules and the dependencies between them, for
+/// rendering as a diagram.
+///
+/// Nodes and edges keep declaration order, so the same rules always render the
+/// same way.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EvaluationGraph {
+    pub rules: Vec<GraphRule>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl EvaluationGraph {
+    pub fn from_model(model: &LrolModel, source: Option<&str>) -> Self {
+        Self::build(&[(source, model)])
+    }
+
+    /// Graph of every rule of a library, in order of `source`.
+    ///
+    /// A rule referencing an evaluation it does not declare itself is linked to
+    /// the first other rule that declares it.
+    pub fn from_library(rules: &[LibraryRule]) -> Self {
+        let mut rules: Vec<&LibraryRule> = rules.iter().collect();
+        rules.sort_by(|a, b| a.source.cmp(&b.source));
+        let rules: Vec<(Option<&str>, &LrolModel)> = rules
+            .iter()
+            .map(|rule| (Some(rule.source.as_str()), &rule.model))
+            .collect();
+        Self::build(&rules)
+    }
+
+    fn build(rules: &[(Option<&str>, &LrolModel)]) -> Self {
+        let mut graph = Self::default();
+
+        // Node of each evaluation name, per rule; the first declaration wins
+        let mut declared: Vec<HashMap<&str, usize>> = Vec::new();
+        for (rule, (source, model)) in rules.iter().enumerate() {
+            graph.rules.push(GraphRule {
+                source: source.map(str::to_string),
+                model_id: model.model_id.clone(),
+                name: model.name.clone(),
+                threshold: model.threshold,
+            });
+            let mut names = HashMap::new();
+            for evaluation in &model.evaluations {
+                let id = graph.add_node(rule, &evaluation.name);
+                graph.nodes[id].evaluation_type = Some(evaluation.evaluation_type.to_string());
+                graph.nodes[id].weight = evaluation.weight;
+                names.entry(evaluation.name.as_str()).or_insert(id);
+            }
+            declared.push(names);
+        }
+
+        let mut missing: HashMap<(usize, String), usize> = HashMap::new();
+        for (rule, (_, model)) in rules.iter().enumerate() {
+            for edge in DependencyGraph::from_model(model).edges() {
+                let from = declared[rule][edge.from.as_str()];
+                let imported = || {
+                    declared
+                        .iter()
+                        .enumerate()
+                        .filter(|(other, _)| *other != rule)
+                        .find_map(|(_, names)| names.get(edge.to.as_str()).copied())
+                };
+                let (to, cross_rule) = match declared[rule].get(edge.to.as_str()) {
+                    Some(&to) => (to, false),
+                    None => match imported() {
+                        Some(to) => (to, true),
+                        None => {
+                            let key = (rule, edge.to.clone());
+                            let to = match missing.get(&key) {
+                                Some(&to) => to,
+                                None => {
+                                    let to = graph.add_node(rule, &edge.to);
+                                    missing.insert(key, to);
+                                    to
+                                }
+                            };
+                            (to, false)
+                        }
+                    },
+                };
+                graph.edges.push(GraphEdge {
+                    from,
+                    to,
+                    kind: edge.kind,
+                    cross_rule,
+                });
+            }
+        }
+
+        graph
+    }
+
+    fn add_node(&mut self, rule: usize, name: &str) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(GraphNode {
+            id,
+            rule,
+            name: name.to_string(),
+            evaluation_type: None,
+            weight: None,
+        });
+        id
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Graphviz DOT, with each rule drawn as a cluster
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph lrol {\n");
+        output.push_str("    rankdir=LR;\n");
+        output
+            .push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+        output.push_str("    edge [fontname=\"Helvetica\", fontsize=10];\n");
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            output.push_str(&format!("    subgraph cluster_{} {{\n", index));
+            output.push_str(&format!(
+                "        label=\"{}\";\n",
+                dot_label(&rule_label(rule))
+            ));
+            for node in self.nodes.iter().filter(|node| node.rule == index) {
+                let style = if node.evaluation_type.is_none() {
+                    ", style=\"rounded,dashed\""
+                } else {
+                    ""
+                };
+                output.push_str(&format!(
+                    "        n{} [label=\"{}\", fillcolor=\"{}\"{}];\n",
+                    node.id,
+                    dot_label(&node_label(node)),
+                    color(node.evaluation_type.as_deref()),
+                    style
+                ));
+            }
+            output.push_str("    }\n");
+        }
+
+        for edge in &self.edges {
+            let style = if edge.cross_rule {
+                ", style=bold"
+            } else if edge.kind == DependencyKind::Operand {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            output.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"{}];\n",
+                edge.from,
+                edge.to,
+                dot_label(&[edge_label(edge)]),
+                style
+            ));
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    /// Mermaid flowchart, with each rule drawn as a subgraph
+    pub fn to_mermaid(&self) -> String {
+        let mut output = String::from("flowchart LR\n");
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            output.push_str(&format!(
+                "    subgraph rule{}[\"{}\"]\n",
+                index,
+                mermaid_label(&rule_label(rule))
+            ));
+            for node in self.nodes.iter().filter(|node| node.rule == index) {
+                output.push_str(&format!(
+                    "        n{}[\"{}\"]\n",
+                    node.id,
+                    mermaid_label(&node_label(node))
+                ));
+            }
+            output.push_str("    end\n");
+        }
+
+        for edge in &self.edges {
+            let arrow = if edge.cross_rule {
+                "==>"
+            } else if edge.kind == DependencyKind::Operand {
+                "-->"
+            } else {
+                "-.->"
+            };
+            output.push_str(&format!(
+                "    n{} {}|\"{}\"| n{}\n",
+                edge.from,
+                arrow,
+                mermaid_label(&[edge_label(edge)]),
+                edge.to
+            ));
+        }
+
+        let mut classes: Vec<(&str, Vec<String>)> = Vec::new();
+        for node in &self.nodes {
+            let class = class_name(node.evaluation_type.as_deref());
+            let id = format!("n{}", node.id);
+            match classes.iter_mut().find(|(name, _)| *name == class) {
+                Some((_, ids)) => ids.push(id),
+                None => classes.push((class, vec![id])),
+            }
+        }
+        for (class, ids) in classes {
+            let evaluation_type = self
+                .nodes
+                .iter()
+                .find(|node| class_name(node.evaluation_type.as_deref()) == class)
+                .and_then(|node| node.evaluation_type.as_deref());
+            let dashed = if evaluation_type.is_none() {
+                ",stroke-dasharray:4"
+            } else {
+                ""
+            };
+            output.push_str(&format!(
+                "    classDef {} fill:{}{}\n",
+                class,
+                color(evaluation_type),
+                dashed
+            ));
+            output.push_str(&format!("    class {} {}\n", ids.join(","), class));
+        }
+
+        output
+    }
+
+    /// Rules, nodes and edges as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+fn rule_label(rule: &GraphRule) -> Vec<String> {
+    vec![
+        format!("{}: {}", rule.model_id, rule.name),
+        format!("threshold {}", rule.threshold),
+    ]
+}
+
+fn node_label(node: &GraphNode) -> Vec<String> {
+    let details = match (&node.evaluation_type, node.weight) {
+        (Some(evaluation_type), Some(weight)) => format!("{}, weight {}", evaluation_type, weight),
+        (Some(evaluation_type), None) => evaluation_type.clone(),
+        (None, _) => "not declared".to_string(),
+    };
+    vec![node.name.clone(), details]
+}
+
+fn edge_label(edge: &GraphEdge) -> String {
+    if edge.cross_rule {
+        format!("{} (import)", edge.kind)
+    } else {
+        edge.kind.to_string()
+    }
+}
+
+fn color(evaluation_type: Option<&str>) -> &'static str {
+    match evaluation_type {
+        Some("comparison") => "#cfe2ff",
+        Some("logical") => "#ffe69c",
+        Some("aggregation") => "#d1e7dd",
+        Some("time-based") => "#e2d9f3",
+        Some("conditional") => "#f8d7da",
+        _ => "#ffffff",
+    }
+}
+
+// Mermaid class names cannot contain `-`
+fn class_name(evaluation_type: Option<&str>) -> &'static str {
+    match evaluation_type {
+        Some("comparison") => "comparison",
+        Some("logical") => "logical",
+        Some("aggregation") => "aggregation",
+        Some("time-based") => "time_based",
+        Some("conditional") => "conditional",
+        _ => "missing",
+    }
+}
+
+fn dot_label(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
+fn mermaid_label(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            line.replace('&', "#amp;")
+                .replace('"', "#quot;")
+                .replace('<', "#lt;")
+                .replace('>', "#gt;")
+        })
+        .collect::<Vec<_>>()
+        .join("<br/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(json: &str) -> LrolModel {
+        lrol_parser::parse_str(json).unwrap()
+    }
+
+    fn high_value() -> LrolModel {
+        let mut model = model(
+            r#"{
+  "model_id": "R001",
+  "name": "High value",
+  "threshold": 0.85,
+  "evaluations": [
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 10000, "weight": 5},
+    {"name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 3},
+    {"name": "Sum_Check", "type": "comparison", "left": "@Amount_Sum", "operator": ">", "right": 50000},
+    {"name": "Logic", "type": "logical", "operator": "AND", "operands": ["Amount_Check", "Sum_Check", "Velocity_Check"], "weight": 4}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "High value"}]
+}"#,
+        );
+        // Quotes cannot be escaped in rule files but may come from other sources
+        model.name = "High \"value\"".to_string();
+        model
+    }
+
+    #[test]
+    fn test_render_model() {
+        let graph = EvaluationGraph::from_model(&high_value(), Some("R001.json"));
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.nodes[4].name, "Velocity_Check");
+        assert_eq!(graph.nodes[4].evaluation_type, None);
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.edges[0].kind, DependencyKind::LeftReference);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains(r#"label="R001: High \"value\"\nthreshold 0.85";"#));
+        assert!(dot
+            .contains(r##"n1 [label="Amount_Sum\naggregation, weight 3", fillcolor="#d1e7dd"];"##));
+        assert!(dot.contains(r#"n2 -> n1 [label="@left", style=dashed];"#));
+        assert!(dot.contains(r#"n3 -> n0 [label="operands"];"#));
+        assert!(dot.contains(r##"n4 [label="Velocity_Check\nnot declared", fillcolor="#ffffff", style="rounded,dashed"];"##));
+
+        let mermaid = graph.render(GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid
+            .contains(r#"subgraph rule0["R001: High #quot;value#quot;<br/>threshold 0.85"]"#));
+        assert!(mermaid.contains(r#"n2 -.->|"@left"| n1"#));
+        assert!(mermaid.contains("class n0,n2 comparison\n"));
+        assert!(mermaid.contains("classDef missing fill:#ffffff,stroke-dasharray:4\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        assert_eq!(json["rules"][0]["threshold"], 0.85);
+        assert_eq!(json["nodes"][3]["evaluation_type"], "logical");
+        assert_eq!(json["edges"][0]["kind"], "left_reference");
+    }
+
+    #[test]
+    fn test_cross_rule_references() {
+        let velocity = model(
+            r#"{
+  "model_id": "R002",
+  "name": "Velocity",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "Velocity_Check", "type": "comparison", "left": "count", "operator": ">", "right": 5, "weight": 2}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Velocity"}]
+}"#,
+        );
+        let graph = EvaluationGraph::from_library(&[
+            LibraryRule {
+                source: "b/R002.json".to_string(),
+                model: velocity,
+            },
+            LibraryRule {
+                source: "a/R001.json".to_string(),
+                model: high_value(),
+            },
+        ]);
+
+        assert_eq!(graph.rules[0].model_id, "R001");
+        assert_eq!(graph.nodes.len(), 5);
+        let import = graph.edges.iter().find(|edge| edge.cross_rule).unwrap();
+        assert_eq!(graph.nodes[import.from].name, "Logic");
+        assert_eq!(graph.nodes[import.to].rule, 1);
+        assert!(graph
+            .to_dot()
+            .contains(r#"n3 -> n4 [label="operands (import)", style=bold];"#));
+    }
+}
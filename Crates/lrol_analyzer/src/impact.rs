@@ -0,0 +1,650 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType};
+use serde::Serialize;
+
+use crate::diff::{ActionChange, ChangeKind, EvaluationDiff, FieldChange, RuleDiff};
+use crate::graph::{DependencyGraph, DependencyKind};
+use crate::scoring::ScoringFormula;
+
+/// Action type that stops a transaction instead of letting it through
+pub const BLOCKING_ACTION: &str = "block_transaction";
+
+/// How a change affects how often the rule fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    /// Fires on every event it fired on before, and possibly more
+    Broadening,
+    /// Fires on no event it did not fire on before, and possibly fewer
+    Narrowing,
+    /// Fires on exactly the same events
+    Neutral,
+    /// May fire on more events and on fewer ones
+    Unknown,
+}
+
+impl Impact {
+    /// Impact of two changes made together
+    pub fn combine(self, other: Impact) -> Impact {
+        match (self, other) {
+            (Impact::Neutral, impact) | (impact, Impact::Neutral) => impact,
+            (a, b) if a == b => a,
+            _ => Impact::Unknown,
+        }
+    }
+
+    fn of_order(ordering: Option<std::cmp::Ordering>) -> Impact {
+        match ordering {
+            Some(std::cmp::Ordering::Greater) => Impact::Broadening,
+            Some(std::cmp::Ordering::Less) => Impact::Narrowing,
+            Some(std::cmp::Ordering::Equal) => Impact::Neutral,
+            None => Impact::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Impact::Broadening => write!(f, "broadening"),
+            Impact::Narrowing => write!(f, "narrowing"),
+            Impact::Neutral => write!(f, "neutral"),
+            Impact::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// One change of a [`RuleDiff`] with its effect on the rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassifiedChange {
+    /// What changed, e.g. `threshold` or `Amount_Check.operator`
+    pub subject: String,
+    pub impact: Impact,
+    /// The rule now blocks transactions it used to let through
+    pub blocking: bool,
+    pub reason: String,
+}
+
+impl ClassifiedChange {
+    /// Broadening and blocking changes need a second approval, and so do
+    /// changes of unknown impact, which may broaden the rule
+    pub fn requires_second_approval(&self) -> bool {
+        self.blocking || matches!(self.impact, Impact::Broadening | Impact::Unknown)
+    }
+}
+
+/// Classification of every change between two versions of a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImpactReport {
+    /// Combined impact of all changes
+    pub impact: Impact,
+    pub requires_second_approval: bool,
+    pub changes: Vec<ClassifiedChange>,
+}
+
+impl ImpactReport {
+    /// Classifies the changes of `diff`, the difference from `old` to `new`.
+    ///
+    /// A change to an evaluation is judged by whether the evaluation holds
+    /// more or less often, then followed through the weights and the AND and
+    /// OR evaluations of `new` to the rule. Values read through `@`
+    /// references can move either way, so changes reaching the rule that way
+    /// are unknown. Weights are judged under the scoring formula `new` names,
+    /// or `formula` when it names none.
+    pub fn classify(
+        old: &LrolModel,
+        new: &LrolModel,
+        diff: &RuleDiff,
+        formula: ScoringFormula,
+    ) -> Self {
+        let mut changes = Vec::new();
+        let graph = DependencyGraph::from_model(new);
+        let formula = ScoringFormula::for_model(new, formula);
+
+        for change in &diff.rule {
+            changes.push(classify_rule_field(change));
+        }
+        for evaluation_diff in &diff.evaluations {
+            let old_evaluation = old
+                .evaluations
+                .iter()
+                .find(|e| e.name == evaluation_diff.name);
+            let new_evaluation = new
+                .evaluations
+                .iter()
+                .find(|e| e.name == evaluation_diff.name);
+            classify_evaluation(
+                evaluation_diff,
+                old_evaluation,
+                new_evaluation,
+                new,
+                &graph,
+                formula,
+                &mut changes,
+            );
+        }
+        for action in &diff.actions {
+            changes.push(classify_action(action));
+        }
+
+        let impact = changes.iter().fold(Impact::Neutral, |impact, change| {
+            impact.combine(change.impact)
+        });
+        ImpactReport {
+            impact,
+            requires_second_approval: changes
+                .iter()
+                .any(ClassifiedChange::requires_second_approval),
+            changes,
+        }
+    }
+
+    /// Changes that need a second approval
+    pub fn second_approvals(&self) -> impl Iterator<Item = &ClassifiedChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.requires_second_approval())
+    }
+}
+
+fn classified(
+    subject: impl Into<String>,
+    impact: Impact,
+    reason: impl Into<String>,
+) -> ClassifiedChange {
+    ClassifiedChange {
+        subject: subject.into(),
+        impact,
+        blocking: false,
+        reason: reason.into(),
+    }
+}
+
+fn classify_rule_field(change: &FieldChange) -> ClassifiedChange {
+    match change.field.as_str() {
+        "threshold" => {
+            let old = change.old.as_ref().and_then(|v| v.as_f64());
+            let new = change.new.as_ref().and_then(|v| v.as_f64());
+            let impact = match (old, new) {
+                (Some(old), Some(new)) => Impact::of_order(old.partial_cmp(&new)),
+                _ => Impact::Unknown,
+            };
+            let reason = match impact {
+                Impact::Broadening => "a lower threshold is reached by more events",
+                Impact::Narrowing => "a higher threshold is reached by fewer events",
+                _ => "the threshold changed",
+            };
+            classified("threshold", impact, reason)
+        }
+        "scoring" => classified(
+            "scoring",
+            Impact::Unknown,
+            "changes how weights combine into the score",
+        ),
+        "timezone" | "field_timezones" | "event_schema" => classified(
+            change.field.as_str(),
+            Impact::Unknown,
+            "changes how event values are read",
+        ),
+        field => classified(field, Impact::Neutral, "does not affect which events fire"),
+    }
+}
+
+fn classify_action(action: &ActionChange) -> ClassifiedChange {
+    let blocking = action.action_type == BLOCKING_ACTION && action.change == ChangeKind::Added;
+    let reason = match (action.change, blocking) {
+        (_, true) => "the rule now blocks the transactions it fires on",
+        (ChangeKind::Removed, _) => "the rule no longer takes this action",
+        (ChangeKind::Added, _) => "the rule now takes this action",
+        (ChangeKind::Modified, _) => "only the reason changed",
+    };
+    ClassifiedChange {
+        subject: format!("action {}", action.action_type),
+        impact: Impact::Neutral,
+        blocking,
+        reason: reason.to_string(),
+    }
+}
+
+fn classify_evaluation(
+    diff: &EvaluationDiff,
+    old: Option<&Evaluation>,
+    new: Option<&Evaluation>,
+    model: &LrolModel,
+    graph: &DependencyGraph,
+    formula: ScoringFormula,
+    changes: &mut Vec<ClassifiedChange>,
+) {
+    let name = diff.name.as_str();
+    let (old, new) = match (diff.change, old, new) {
+        (ChangeKind::Added, _, Some(new)) => {
+            changes.push(classify_scored(name, None, new.weight, formula));
+            return;
+        }
+        (ChangeKind::Removed, Some(old), _) => {
+            changes.push(classify_scored(name, old.weight, None, formula));
+            return;
+        }
+        (ChangeKind::Modified, Some(old), Some(new)) => (old, new),
+        _ => return,
+    };
+
+    // How the change makes the evaluation itself hold, followed to the rule
+    let mut push = |subject: String, holds: Impact, reason: String| {
+        let impact = rule_impact(model, graph, name, holds, &mut HashSet::new());
+        let reason = if impact == holds || holds == Impact::Unknown {
+            reason
+        } else {
+            format!("{}, which reaches the rule as {}", reason, impact)
+        };
+        changes.push(classified(subject, impact, reason));
+    };
+
+    if diff.field("type").is_some() {
+        push(
+            format!("{}.type", name),
+            Impact::Unknown,
+            format!(
+                "changed from {} to {}",
+                old.evaluation_type, new.evaluation_type
+            ),
+        );
+    } else if new.evaluation_type == EvaluationType::Logical {
+        let fields: Vec<&str> = ["operator", "operands"]
+            .into_iter()
+            .filter(|field| diff.field(field).is_some())
+            .collect();
+        if !fields.is_empty() {
+            let (holds, reason) = logical_impact(old, new);
+            push(
+                format!("{}.{}", name, fields.join(" and ")),
+                holds,
+                reason.to_string(),
+            );
+        }
+    } else if new.evaluation_type == EvaluationType::Comparison {
+        for change in &diff.fields {
+            let subject = format!("{}.{}", name, change.field);
+            match change.field.as_str() {
+                "operator" => {
+                    let holds = operator_impact(old.operator.as_deref(), new.operator.as_deref());
+                    push(subject, holds, operator_reason(holds).to_string());
+                }
+                "right" => {
+                    let holds = bound_impact(new.operator.as_deref(), change);
+                    push(subject, holds, bound_reason(holds).to_string());
+                }
+                "weight" => {}
+                _ => push(
+                    subject,
+                    Impact::Unknown,
+                    "compares something else".to_string(),
+                ),
+            }
+        }
+    } else {
+        for change in diff.fields.iter().filter(|change| change.field != "weight") {
+            push(
+                format!("{}.{}", name, change.field),
+                Impact::Unknown,
+                format!(
+                    "changes the result of the {} evaluation",
+                    new.evaluation_type
+                ),
+            );
+        }
+    }
+
+    if diff.field("weight").is_some() {
+        changes.push(classify_scored(name, old.weight, new.weight, formula));
+    }
+}
+
+/// Impact of an evaluation counting towards the score with another weight,
+/// `None` when it does not count
+fn classify_scored(
+    name: &str,
+    old: Option<i32>,
+    new: Option<i32>,
+    formula: ScoringFormula,
+) -> ClassifiedChange {
+    let (old, new) = (old.unwrap_or(0).max(0), new.unwrap_or(0).max(0));
+    let subject = if old == 0 || new == 0 {
+        name.to_string()
+    } else {
+        format!("{}.weight", name)
+    };
+    if old == new {
+        return classified(subject, Impact::Neutral, "does not count towards the score");
+    }
+    match formula {
+        // Weights also make up the total the score is divided by or centred on
+        ScoringFormula::WeightedRatio
+        | ScoringFormula::RootWeightedRatio
+        | ScoringFormula::Logistic => classified(
+            subject,
+            Impact::Unknown,
+            "raises the score when it holds and lowers it otherwise",
+        ),
+        ScoringFormula::MaxWeight | ScoringFormula::NoisyOr => {
+            let impact = Impact::of_order(new.partial_cmp(&old));
+            let reason = match impact {
+                Impact::Broadening => "adds more to the score when it holds",
+                _ => "adds less to the score when it holds",
+            };
+            classified(subject, impact, reason)
+        }
+    }
+}
+
+/// Follows a change in how often `name` holds to the rule's score
+fn rule_impact(
+    model: &LrolModel,
+    graph: &DependencyGraph,
+    name: &str,
+    holds: Impact,
+    visited: &mut HashSet<String>,
+) -> Impact {
+    if !visited.insert(name.to_string()) {
+        return Impact::Unknown;
+    }
+    let evaluation = model.evaluations.iter().find(|e| e.name == name);
+    let mut impact = Impact::Neutral;
+    if evaluation.and_then(|e| e.weight).is_some_and(|w| w > 0) {
+        impact = holds;
+    }
+    for edge in graph.incoming(name) {
+        let parent = match edge.kind {
+            // AND and OR hold more often when an operand does
+            DependencyKind::Operand => rule_impact(model, graph, &edge.from, holds, visited),
+            DependencyKind::LeftReference | DependencyKind::RightReference => Impact::Unknown,
+        };
+        impact = impact.combine(parent);
+    }
+    visited.remove(name);
+    impact
+}
+
+/// Outcomes of comparing left with right that satisfy an operator
+fn outcomes(operator: Option<&str>) -> Option<BTreeSet<std::cmp::Ordering>> {
+    use std::cmp::Ordering::*;
+    let outcomes: &[std::cmp::Ordering] = match operator?.trim() {
+        ">" => &[Greater],
+        ">=" | "=>" => &[Greater, Equal],
+        "<" => &[Less],
+        "<=" | "=<" => &[Less, Equal],
+        "==" | "=" => &[Equal],
+        "!=" => &[Less, Greater],
+        _ => return None,
+    };
+    Some(outcomes.iter().copied().collect())
+}
+
+fn operator_impact(old: Option<&str>, new: Option<&str>) -> Impact {
+    let (Some(old), Some(new)) = (outcomes(old), outcomes(new)) else {
+        return Impact::Unknown;
+    };
+    if old == new {
+        Impact::Neutral
+    } else if new.is_superset(&old) {
+        Impact::Broadening
+    } else if new.is_subset(&old) {
+        Impact::Narrowing
+    } else {
+        Impact::Unknown
+    }
+}
+
+fn operator_reason(holds: Impact) -> &'static str {
+    match holds {
+        Impact::Broadening => "the new operator accepts every value the old one did",
+        Impact::Narrowing => "the new operator accepts only values the old one did",
+        Impact::Neutral => "the operators are equivalent",
+        Impact::Unknown => "the operators accept different values",
+    }
+}
+
+/// Moving the bound of an ordering comparison, such as lowering `right` of `>`
+fn bound_impact(operator: Option<&str>, change: &FieldChange) -> Impact {
+    let old = change.old.as_ref().and_then(|v| v.as_f64());
+    let new = change.new.as_ref().and_then(|v| v.as_f64());
+    let (Some(old), Some(new)) = (old, new) else {
+        return Impact::Unknown;
+    };
+    match operator.map(str::trim) {
+        Some(">" | ">=" | "=>") => Impact::of_order(old.partial_cmp(&new)),
+        Some("<" | "<=" | "=<") => Impact::of_order(new.partial_cmp(&old)),
+        _ => Impact::Unknown,
+    }
+}
+
+fn bound_reason(holds: Impact) -> &'static str {
+    match holds {
+        Impact::Broadening => "the bound moved to accept more values",
+        Impact::Narrowing => "the bound moved to accept fewer values",
+        _ => "the compared value changed",
+    }
+}
+
+fn logical_impact(old: &Evaluation, new: &Evaluation) -> (Impact, &'static str) {
+    let operands = |evaluation: &Evaluation| -> BTreeSet<String> {
+        evaluation.operands.iter().flatten().cloned().collect()
+    };
+    let (old_operands, new_operands) = (operands(old), operands(new));
+    let (old_operator, new_operator) = (
+        old.operator.as_deref().map(str::trim),
+        new.operator.as_deref().map(str::trim),
+    );
+
+    match (old_operator, new_operator) {
+        (Some("AND"), Some("AND")) if new_operands.is_subset(&old_operands) => {
+            (Impact::Broadening, "requires fewer operands to hold")
+        }
+        (Some("AND"), Some("AND")) if new_operands.is_superset(&old_operands) => {
+            (Impact::Narrowing, "requires more operands to hold")
+        }
+        (Some("OR"), Some("OR")) if new_operands.is_superset(&old_operands) => {
+            (Impact::Broadening, "holds when any of more operands holds")
+        }
+        (Some("OR"), Some("OR")) if new_operands.is_subset(&old_operands) => {
+            (Impact::Narrowing, "holds when any of fewer operands holds")
+        }
+        // All operands holding implies any of them, and of any superset
+        (Some("AND"), Some("OR")) if !old_operands.is_disjoint(&new_operands) => (
+            Impact::Broadening,
+            "holds when any operand holds instead of all",
+        ),
+        (Some("OR"), Some("AND")) if !old_operands.is_disjoint(&new_operands) => (
+            Impact::Narrowing,
+            "holds only when all operands hold instead of any",
+        ),
+        _ => (Impact::Unknown, "combines different operands"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(threshold: f64, evaluations: &str, actions: &str) -> LrolModel {
+        lrol_parser::parse_str(&format!(
+            r#"{{
+  "model_id": "I001",
+  "name": "Impact",
+  "threshold": {},
+  "evaluations": [{}],
+  "actions": [{}]
+}}"#,
+            threshold, evaluations, actions
+        ))
+        .unwrap()
+    }
+
+    fn classify(old: &LrolModel, new: &LrolModel, formula: ScoringFormula) -> ImpactReport {
+        ImpactReport::classify(old, new, &RuleDiff::between(old, new), formula)
+    }
+
+    const FLAG: &str = r#"{"type": "flag_transaction", "reason": "Risky"}"#;
+    const BLOCK: &str = r#"{"type": "block_transaction", "reason": "Risky"}"#;
+
+    fn rule(threshold: f64, operator: &str, right: i32, logic: &str, action: &str) -> LrolModel {
+        model(
+            threshold,
+            &format!(
+                r#"
+    {{"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": "{}", "right": {}}},
+    {{"name": "Country_Check", "type": "comparison", "left": "country", "operator": "==", "right": "XX"}},
+    {{"name": "Night_Check", "type": "comparison", "left": "hour", "operator": "<", "right": 6}},
+    {{"name": "Logic", "type": "logical", "operator": "{}", "operands": [{}], "weight": 3}}"#,
+                operator,
+                right,
+                logic.split_once(' ').unwrap().0,
+                logic.split_once(' ').unwrap().1
+            ),
+            action,
+        )
+    }
+
+    #[test]
+    fn test_classify_changes() {
+        let base = rule(
+            0.5,
+            ">",
+            1000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            FLAG,
+        );
+        let impact = |other: &LrolModel| classify(&base, other, ScoringFormula::WeightedRatio);
+
+        let report = impact(&rule(
+            0.4,
+            ">",
+            1000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.impact, Impact::Broadening);
+        assert!(report.requires_second_approval);
+
+        let report = impact(&rule(
+            0.5,
+            ">=",
+            1000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.changes[0].subject, "Amount_Check.operator");
+        assert_eq!(report.impact, Impact::Broadening);
+
+        let report = impact(&rule(
+            0.5,
+            ">",
+            2000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.impact, Impact::Narrowing);
+        assert!(!report.requires_second_approval);
+
+        let report = impact(&rule(
+            0.5,
+            ">",
+            1000,
+            r#"AND "Amount_Check", "Country_Check", "Night_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.impact, Impact::Narrowing);
+
+        let report = impact(&rule(
+            0.5,
+            ">",
+            1000,
+            r#"OR "Amount_Check", "Country_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.changes[0].subject, "Logic.operator");
+        assert_eq!(report.impact, Impact::Broadening);
+
+        // Broadening and narrowing together may go either way
+        let report = impact(&rule(
+            0.6,
+            ">=",
+            1000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            FLAG,
+        ));
+        assert_eq!(report.impact, Impact::Unknown);
+
+        let report = impact(&rule(
+            0.5,
+            ">",
+            1000,
+            r#"AND "Amount_Check", "Country_Check""#,
+            BLOCK,
+        ));
+        assert_eq!(report.impact, Impact::Neutral);
+        assert!(report.requires_second_approval);
+        assert_eq!(
+            report
+                .second_approvals()
+                .map(|c| c.subject.as_str())
+                .collect::<Vec<_>>(),
+            vec!["action block_transaction"]
+        );
+    }
+
+    #[test]
+    fn test_propagation_and_weights() {
+        let old = model(
+            0.5,
+            r#"
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 1000, "weight": 2},
+    {"name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount"},
+    {"name": "Sum_Check", "type": "comparison", "left": "@Amount_Sum", "operator": ">", "right": 5000, "weight": 2}"#,
+            FLAG,
+        );
+        let new = model(
+            0.5,
+            r#"
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 1000, "weight": 4},
+    {"name": "Amount_Sum", "type": "aggregation", "aggregation": "AVG", "field": "amount"},
+    {"name": "Sum_Check", "type": "comparison", "left": "@Amount_Sum", "operator": ">", "right": 5000, "weight": 2}"#,
+            FLAG,
+        );
+
+        let report = classify(&old, &new, ScoringFormula::MaxWeight);
+        let impacts: Vec<(&str, Impact)> = report
+            .changes
+            .iter()
+            .map(|c| (c.subject.as_str(), c.impact))
+            .collect();
+        assert_eq!(
+            impacts,
+            vec![
+                ("Amount_Check.weight", Impact::Broadening),
+                ("Amount_Sum.aggregation", Impact::Unknown),
+            ]
+        );
+        assert_eq!(
+            classify(&old, &new, ScoringFormula::WeightedRatio).changes[0].impact,
+            Impact::Unknown
+        );
+
+        // An evaluation nothing reads and that carries no weight changes nothing
+        let unused = model(
+            0.5,
+            r#"
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 1000, "weight": 2},
+    {"name": "Amount_Sum", "type": "aggregation", "aggregation": "AVG", "field": "amount"}"#,
+            FLAG,
+        );
+        let mut unused_old = unused.clone();
+        unused_old.evaluations[1].aggregation = old.evaluations[1].aggregation.clone();
+        assert_eq!(
+            classify(&unused_old, &unused, ScoringFormula::WeightedRatio).impact,
+            Impact::Neutral
+        );
+    }
+}
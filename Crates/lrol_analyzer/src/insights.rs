@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use lrol_parser::{parser::LrolModel, Evaluation, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::dead_code::find_dead_code;
+use crate::graph::{DependencyGraph, DependencyKind};
+use crate::scoring::ScoringAnalysis;
+
+/// Dependency chains longer than this are reported as a performance warning
+pub const MAX_RECOMMENDED_CHAIN_LENGTH: usize = 3;
+
+/// Models with more evaluations than this are reported as a complexity warning
+pub const MAX_RECOMMENDED_EVALUATIONS: usize = 10;
+
+/// Action types recommended by the LROL schema
+pub const RECOMMENDED_ACTION_TYPES: [&str; 3] =
+    ["flag_transaction", "block_transaction", "send_alert"];
+
+/// Full insight report for a single model
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    pub summary: AnalysisSummary,
+    pub details: AnalysisDetails,
+    pub warnings: Vec<AnalysisWarning>,
+    pub suggestions: Vec<String>,
+    /// Threshold reachability, when computed for a scoring formula
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ScoringAnalysis>,
+}
+
+impl AnalysisReport {
+    pub fn with_file_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.file_path = Some(path.as_ref().to_string_lossy().into_owned());
+        self
+    }
+
+    pub fn with_scoring(mut self, scoring: ScoringAnalysis) -> Self {
+        self.scoring = Some(scoring);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisSummary {
+    pub total_evaluations: usize,
+    /// Number of evaluations per evaluation type
+    pub evaluation_types: BTreeMap<String, usize>,
+    /// Number of evaluations on the longest dependency chain
+    pub max_evaluation_depth: usize,
+    /// Number of evaluations depending on at least one other evaluation
+    pub dependency_count: usize,
+    pub complexity_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisDetails {
+    pub evaluation_dependencies: BTreeMap<String, Vec<String>>,
+    pub datetime_expressions: Vec<String>,
+    /// Longest dependency chain starting at each root evaluation
+    pub reference_chains: Vec<Vec<String>>,
+    /// Longest dependency chain in the model, from dependent to dependency
+    pub longest_dependency_chain: Vec<String>,
+    pub evaluation_weights: BTreeMap<String, i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnalysisWarning {
+    pub severity: WarningSeverity,
+    pub category: WarningCategory,
+    pub message: String,
+    pub context: String,
+    /// How to address the warning, when there is a specific fix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WarningCategory {
+    Complexity,
+    Performance,
+    Maintainability,
+    BestPractice,
+    DeadCode,
+}
+
+/// Builds the insight report for a model.
+///
+/// The model is expected to have passed [`RuleAnalyzer::analyze`]; unresolved
+/// references are still tolerated and simply show up as graph nodes.
+pub fn analyze_model(model: &LrolModel) -> AnalysisReport {
+    let graph = DependencyGraph::from_model(model);
+
+    let mut summary = AnalysisSummary {
+        total_evaluations: model.evaluations.len(),
+        evaluation_types: BTreeMap::new(),
+        max_evaluation_depth: 0,
+        dependency_count: 0,
+        complexity_score: 0.0,
+    };
+
+    let mut details = AnalysisDetails {
+        evaluation_dependencies: BTreeMap::new(),
+        datetime_expressions: Vec::new(),
+        reference_chains: Vec::new(),
+        longest_dependency_chain: Vec::new(),
+        evaluation_weights: BTreeMap::new(),
+    };
+
+    // Analyze evaluation types
+    for eval in &model.evaluations {
+        *summary
+            .evaluation_types
+            .entry(format!("{:?}", eval.evaluation_type))
+            .or_insert(0) += 1;
+
+        // Track weights
+        if let Some(weight) = eval.weight {
+            details.evaluation_weights.insert(eval.name.clone(), weight);
+        }
+
+        // Analyze dependencies
+        let deps = graph.dependencies(&eval.name);
+        if !deps.is_empty() {
+            details.evaluation_dependencies.insert(
+                eval.name.clone(),
+                deps.into_iter().map(str::to_string).collect(),
+            );
+            summary.dependency_count += 1;
+        }
+
+        // Check for datetime expressions
+        if let Some(expr) = find_datetime_expression(eval) {
+            details.datetime_expressions.push(expr);
+        }
+    }
+
+    // Analyze dependency chains
+    for root in graph.roots() {
+        let chain = graph.longest_chain_from(root);
+        if chain.len() > 1 {
+            details.reference_chains.push(chain);
+        }
+    }
+    details.longest_dependency_chain = find_longest_dependency_chain(&graph);
+    summary.max_evaluation_depth = details.longest_dependency_chain.len();
+
+    // Calculate complexity score
+    summary.complexity_score = calculate_complexity_score(model, &graph);
+
+    let mut warnings = Vec::new();
+    check_for_warnings(model, &graph, &mut warnings);
+
+    let mut suggestions = Vec::new();
+    generate_suggestions(model, &mut suggestions);
+
+    AnalysisReport {
+        file_path: None,
+        summary,
+        details,
+        warnings,
+        suggestions,
+        scoring: None,
+    }
+}
+
+fn find_datetime_expression(eval: &Evaluation) -> Option<String> {
+    let check_expr = |s: &str| {
+        if s.starts_with("datetime(") {
+            Some(s.to_string())
+        } else {
+            None
+        }
+    };
+
+    if let Some(ref left) = eval.left {
+        if let Some(expr) = check_expr(left) {
+            return Some(expr);
+        }
+    }
+
+    if let Some(Value::String(ref right)) = eval.right {
+        if let Some(expr) = check_expr(right) {
+            return Some(expr);
+        }
+    }
+
+    None
+}
+
+/// Longest chain of evaluations linked by operands or `@` references.
+///
+/// The chain starts at the evaluation that depends on the others and ends at
+/// one that depends on nothing.
+pub fn find_longest_dependency_chain(graph: &DependencyGraph) -> Vec<String> {
+    graph.longest_chain()
+}
+
+/// Heuristic complexity score: one point per evaluation plus extra weight for
+/// operands, `@` references and datetime expressions.
+pub fn calculate_complexity_score(model: &LrolModel, graph: &DependencyGraph) -> f64 {
+    let mut score = 0.0;
+
+    // Base score from number of evaluations
+    score += model.evaluations.len() as f64 * 1.0;
+
+    // Add complexity for operands and string references
+    for edge in graph.edges() {
+        score += match edge.kind {
+            DependencyKind::Operand => 0.5,
+            _ => 0.3,
+        };
+    }
+
+    // Add complexity for datetime expressions
+    for eval in &model.evaluations {
+        if find_datetime_expression(eval).is_some() {
+            score += 0.5;
+        }
+    }
+
+    score
+}
+
+/// Appends maintainability and best-practice warnings for a model
+pub fn check_for_warnings(
+    model: &LrolModel,
+    graph: &DependencyGraph,
+    warnings: &mut Vec<AnalysisWarning>,
+) {
+    // Check complexity
+    if model.evaluations.len() > MAX_RECOMMENDED_EVALUATIONS {
+        warnings.push(AnalysisWarning {
+            severity: WarningSeverity::Medium,
+            category: WarningCategory::Complexity,
+            message: "High number of evaluations may impact maintainability".to_string(),
+            context: format!("Total evaluations: {}", model.evaluations.len()),
+            suggestion: None,
+        });
+    }
+
+    for action in &model.actions {
+        if !RECOMMENDED_ACTION_TYPES.contains(&action.action_type.as_str()) {
+            warnings.push(AnalysisWarning {
+                severity: WarningSeverity::Medium,
+                category: WarningCategory::BestPractice,
+                message: "While user defined action types are accepted. We recommend that you use one the following action types 'flag_transaction', 'block_transaction' or 'send_alert'".to_string(),
+                context: format!("Action type: '{}'", action.action_type),
+                suggestion: None,
+            });
+        }
+    }
+
+    // Check for deep dependency chains
+    let max_chain = find_longest_dependency_chain(graph);
+    if max_chain.len() > MAX_RECOMMENDED_CHAIN_LENGTH {
+        warnings.push(AnalysisWarning {
+            severity: WarningSeverity::Medium,
+            category: WarningCategory::Performance,
+            message: "Deep dependency chain detected".to_string(),
+            context: format!("Longest chain: {}", max_chain.join(" → ")),
+            suggestion: None,
+        });
+    }
+
+    // Check for evaluations that do not contribute to the outcome
+    for finding in find_dead_code(model, graph) {
+        warnings.push(AnalysisWarning {
+            severity: finding.severity,
+            category: WarningCategory::DeadCode,
+            message: finding.message,
+            context: format!("Evaluations: {}", finding.evaluations.join(", ")),
+            suggestion: Some(finding.suggestion),
+        });
+    }
+}
+
+/// Appends documentation and balancing suggestions for a model
+pub fn generate_suggestions(model: &LrolModel, suggestions: &mut Vec<String>) {
+    // Suggest documentation if description is missing
+    if model.description.is_none() {
+        suggestions.push("Consider adding a description to improve rule documentation".to_string());
+    }
+
+    // Suggest weight normalization
+    let weights: Vec<_> = model.evaluations.iter().filter_map(|e| e.weight).collect();
+    if !weights.is_empty() && weights.iter().any(|&w| w >= 4) {
+        suggestions
+            .push("Consider normalizing evaluation weights to improve rule balance".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, logical, weighted};
+    use lrol_parser::Action;
+
+    fn create_chained_model() -> LrolModel {
+        LrolModel {
+            model_id: "TEST001".to_string(),
+            name: "Chained Model".to_string(),
+            threshold: 0.9,
+            evaluations: vec![
+                comparison("amount_check", "amount", ">", Value::Number(100.0)),
+                comparison("ref_1", "@amount_check", ">", Value::Number(100.0)),
+                comparison("ref_2", "@ref_1", ">", Value::Number(100.0)),
+                weighted(logical("final_logic", "AND", &["ref_2", "amount_check"]), 5),
+            ],
+            actions: vec![Action {
+                action_type: "alert_team".to_string(),
+                reason: "Deep chain".to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_depth_and_chain() {
+        let report = analyze_model(&create_chained_model());
+
+        assert_eq!(report.summary.total_evaluations, 4);
+        assert_eq!(report.summary.max_evaluation_depth, 4);
+        assert_eq!(report.summary.dependency_count, 3);
+        assert_eq!(
+            report.details.longest_dependency_chain,
+            vec!["final_logic", "ref_2", "ref_1", "amount_check"]
+        );
+        assert_eq!(report.details.reference_chains.len(), 1);
+        assert_eq!(
+            report.details.evaluation_dependencies["final_logic"],
+            vec!["ref_2", "amount_check"]
+        );
+        // Four evaluations, two operands and two `@` references
+        assert!((report.summary.complexity_score - 5.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warnings_and_suggestions() {
+        let report = analyze_model(&create_chained_model());
+
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.category == WarningCategory::Performance));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.category == WarningCategory::BestPractice));
+        assert_eq!(report.suggestions.len(), 2);
+        assert!(!report
+            .warnings
+            .iter()
+            .any(|w| w.category == WarningCategory::DeadCode));
+    }
+
+    #[test]
+    fn test_report_serializes() {
+        let report = analyze_model(&create_chained_model()).with_file_path("rules/R001.json");
+        let json = serde_json::to_string(&report).unwrap();
+        let decoded: AnalysisReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, report);
+    }
+}
@@ -1,6 +1,31 @@
 pub mod analyzer;
-pub mod validator;
+pub mod calendar;
+pub mod constraints;
+pub mod datetime;
+pub mod dead_code;
+pub mod diagnostic;
+pub mod diff;
 pub mod error;
-
-
-
+pub mod error_codes;
+pub mod event_schema;
+pub mod field_audit;
+pub mod fix;
+pub mod graph;
+pub mod graph_export;
+pub mod impact;
+pub mod insights;
+pub mod library;
+pub mod lint;
+pub mod lints;
+pub mod operand;
+pub mod plan;
+pub mod policy;
+pub mod schema_inference;
+pub mod scoring;
+pub mod state_cost;
+#[cfg(test)]
+mod test_support;
+pub mod timezone;
+pub mod typecheck;
+pub mod validator;
+pub mod witness;
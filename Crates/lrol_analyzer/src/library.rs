@@ -0,0 +1,632 @@
+use std::collections::{BTreeMap, HashMap};
+
+use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::RuleAnalyzer;
+use crate::constraints::{Formula, Literal};
+use crate::event_schema::lrol_to_json;
+use crate::graph::DependencyGraph;
+use crate::operand::referenced_fields;
+
+/// Action types that stop a transaction
+pub const BLOCKING_ACTION_TYPES: [&str; 3] = [
+    "block_transaction",
+    "decline_transaction",
+    "reject_transaction",
+];
+
+/// Action types that let a transaction through without review
+pub const ALLOWING_ACTION_TYPES: [&str; 2] = ["allow_transaction", "approve_transaction"];
+
+/// Rule of a library, together with where it was loaded from
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryRule {
+    /// File path or any other name identifying the rule in reports
+    pub source: String,
+    pub model: LrolModel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RuleRef {
+    pub source: String,
+    pub model_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationRef {
+    pub source: String,
+    pub model_id: String,
+    pub evaluation_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateModelId {
+    pub model_id: String,
+    pub sources: Vec<String>,
+}
+
+/// Evaluations of different rules computing the same thing under different names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquivalentEvaluations {
+    /// Canonical form of the shared evaluation
+    pub signature: String,
+    pub occurrences: Vec<EvaluationRef>,
+}
+
+/// Rule whose conditions imply those of another rule.
+///
+/// Only the logical conditions are compared, not thresholds and weights, so
+/// the other rule can still score below its threshold when this one fires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubsumedRule {
+    pub rule: RuleRef,
+    pub subsumed_by: RuleRef,
+    /// The conditions of each rule imply those of the other
+    pub equivalent: bool,
+}
+
+/// Rules that can fire on the same event but disagree on what to do with it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictingActions {
+    pub rules: [RuleRef; 2],
+    pub actions: [Vec<String>; 2],
+    /// Event fields and evaluations both rules depend on
+    pub shared_conditions: Vec<String>,
+}
+
+/// Findings across all rules of a library
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryReport {
+    pub rule_count: usize,
+    pub duplicate_model_ids: Vec<DuplicateModelId>,
+    pub equivalent_evaluations: Vec<EquivalentEvaluations>,
+    pub subsumed_rules: Vec<SubsumedRule>,
+    pub conflicting_actions: Vec<ConflictingActions>,
+}
+
+impl LibraryReport {
+    pub fn is_clean(&self) -> bool {
+        self.duplicate_model_ids.is_empty()
+            && self.equivalent_evaluations.is_empty()
+            && self.subsumed_rules.is_empty()
+            && self.conflicting_actions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Disposition {
+    Block,
+    Allow,
+    Review,
+}
+
+/// Compares every rule of a library with every other.
+///
+/// Rules are reported in order of `source`, so the report does not depend on
+/// the order the rules were loaded in.
+pub fn analyze_library(rules: &[LibraryRule]) -> LibraryReport {
+    let mut rules: Vec<&LibraryRule> = rules.iter().collect();
+    rules.sort_by(|a, b| a.source.cmp(&b.source));
+
+    let canonical: Vec<Canonicalizer> = rules.iter().map(|r| Canonicalizer::new(r)).collect();
+    let formulas: Vec<Option<Formula>> = rules
+        .iter()
+        .zip(&canonical)
+        .map(|(rule, canonical)| rule_formula(&rule.model, canonical))
+        .collect();
+
+    LibraryReport {
+        rule_count: rules.len(),
+        duplicate_model_ids: duplicate_model_ids(&rules),
+        equivalent_evaluations: equivalent_evaluations(&rules, &canonical),
+        subsumed_rules: subsumed_rules(&rules, &formulas),
+        conflicting_actions: conflicting_actions(&rules, &canonical, &formulas),
+    }
+}
+
+fn rule_ref(rule: &LibraryRule) -> RuleRef {
+    RuleRef {
+        source: rule.source.clone(),
+        model_id: rule.model.model_id.clone(),
+    }
+}
+
+fn duplicate_model_ids(rules: &[&LibraryRule]) -> Vec<DuplicateModelId> {
+    let mut by_id: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for rule in rules {
+        by_id
+            .entry(&rule.model.model_id)
+            .or_default()
+            .push(rule.source.clone());
+    }
+    by_id
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(model_id, sources)| DuplicateModelId {
+            model_id: model_id.to_string(),
+            sources,
+        })
+        .collect()
+}
+
+fn equivalent_evaluations(
+    rules: &[&LibraryRule],
+    canonical: &[Canonicalizer],
+) -> Vec<EquivalentEvaluations> {
+    let mut by_signature: BTreeMap<String, Vec<EvaluationRef>> = BTreeMap::new();
+    for (rule, canonical) in rules.iter().zip(canonical) {
+        for evaluation in &rule.model.evaluations {
+            by_signature
+                .entry(canonical.key(&evaluation.name))
+                .or_default()
+                .push(EvaluationRef {
+                    source: rule.source.clone(),
+                    model_id: rule.model.model_id.clone(),
+                    evaluation_name: evaluation.name.clone(),
+                });
+        }
+    }
+
+    by_signature
+        .into_iter()
+        .filter(|(_, occurrences)| {
+            occurrences
+                .iter()
+                .any(|o| o.source != occurrences[0].source)
+        })
+        .map(|(signature, occurrences)| EquivalentEvaluations {
+            signature,
+            occurrences,
+        })
+        .collect()
+}
+
+fn subsumed_rules(rules: &[&LibraryRule], formulas: &[Option<Formula>]) -> Vec<SubsumedRule> {
+    let mut subsumed = Vec::new();
+
+    for (i, a) in formulas.iter().enumerate() {
+        let Some(a) = a else { continue };
+        if a.is_unsatisfiable() != Some(false) {
+            continue;
+        }
+        for (j, b) in formulas.iter().enumerate() {
+            let Some(b) = b else { continue };
+            if i == j || a.implies(b) != Some(true) {
+                continue;
+            }
+            let equivalent = b.implies(a) == Some(true);
+            // Report equivalent rules once
+            if equivalent && j < i {
+                continue;
+            }
+            subsumed.push(SubsumedRule {
+                rule: rule_ref(rules[i]),
+                subsumed_by: rule_ref(rules[j]),
+                equivalent,
+            });
+        }
+    }
+
+    subsumed
+}
+
+fn conflicting_actions(
+    rules: &[&LibraryRule],
+    canonical: &[Canonicalizer],
+    formulas: &[Option<Formula>],
+) -> Vec<ConflictingActions> {
+    let dispositions: Vec<Option<Disposition>> =
+        rules.iter().map(|rule| disposition(&rule.model)).collect();
+    let conditions: Vec<Vec<String>> = rules
+        .iter()
+        .zip(canonical)
+        .map(|(rule, canonical)| conditions(&rule.model, canonical))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for i in 0..rules.len() {
+        for j in i + 1..rules.len() {
+            let (Some(a), Some(b)) = (dispositions[i], dispositions[j]) else {
+                continue;
+            };
+            if a == b {
+                continue;
+            }
+            let shared: Vec<String> = conditions[i]
+                .iter()
+                .filter(|c| conditions[j].contains(c))
+                .cloned()
+                .collect();
+            if shared.is_empty() {
+                continue;
+            }
+            // Both must be able to fire on the same event
+            if let (Some(fa), Some(fb)) = (&formulas[i], &formulas[j]) {
+                if Formula::And(vec![fa.clone(), fb.clone()]).is_unsatisfiable() == Some(true) {
+                    continue;
+                }
+            }
+
+            conflicts.push(ConflictingActions {
+                rules: [rule_ref(rules[i]), rule_ref(rules[j])],
+                actions: [action_types(&rules[i].model), action_types(&rules[j].model)],
+                shared_conditions: shared,
+            });
+        }
+    }
+    conflicts
+}
+
+fn action_types(model: &LrolModel) -> Vec<String> {
+    model
+        .actions
+        .iter()
+        .map(|a| a.action_type.clone())
+        .collect()
+}
+
+// The strongest outcome among a rule's actions
+fn disposition(model: &LrolModel) -> Option<Disposition> {
+    let types: Vec<&str> = model
+        .actions
+        .iter()
+        .map(|a| a.action_type.as_str())
+        .collect();
+    if types.iter().any(|t| BLOCKING_ACTION_TYPES.contains(t)) {
+        Some(Disposition::Block)
+    } else if types.iter().any(|t| ALLOWING_ACTION_TYPES.contains(t)) {
+        Some(Disposition::Allow)
+    } else if types.is_empty() {
+        None
+    } else {
+        Some(Disposition::Review)
+    }
+}
+
+// Event fields a rule reads and the canonical form of its evaluations
+fn conditions(model: &LrolModel, canonical: &Canonicalizer) -> Vec<String> {
+    let mut conditions: Vec<String> = referenced_fields(model, |_| false)
+        .into_iter()
+        .map(|reference| format!("field {}", reference.field))
+        .collect();
+    conditions.extend(
+        model
+            .evaluations
+            .iter()
+            .filter(|e| e.evaluation_type != EvaluationType::Logical)
+            .map(|e| canonical.key(&e.name)),
+    );
+    conditions.sort();
+    conditions.dedup();
+    conditions
+}
+
+// Formula of the rule's decision, with evaluations that cannot be analyzed
+// renamed to their canonical form so they compare across rules
+fn rule_formula(model: &LrolModel, canonical: &Canonicalizer) -> Option<Formula> {
+    let graph = DependencyGraph::from_model(model);
+    let roots = graph.roots();
+    let logical: Vec<&str> = roots
+        .iter()
+        .copied()
+        .filter(|name| {
+            model
+                .evaluations
+                .iter()
+                .any(|e| e.name == *name && e.evaluation_type == EvaluationType::Logical)
+        })
+        .collect();
+    let roots = if logical.is_empty() { roots } else { logical };
+
+    let mut formulas: Vec<Formula> = roots
+        .iter()
+        .filter_map(|root| Formula::from_model(model, root))
+        .map(|formula| rename_opaque(formula, canonical))
+        .collect();
+    match formulas.len() {
+        0 => None,
+        1 => formulas.pop(),
+        _ => Some(Formula::Or(formulas)),
+    }
+}
+
+fn rename_opaque(formula: Formula, canonical: &Canonicalizer) -> Formula {
+    match formula {
+        Formula::Literal(Literal::Opaque {
+            evaluation,
+            negated,
+        }) => Formula::Literal(Literal::Opaque {
+            evaluation: canonical.key(&evaluation),
+            negated,
+        }),
+        Formula::Literal(literal) => Formula::Literal(literal),
+        Formula::And(children) => Formula::And(
+            children
+                .into_iter()
+                .map(|c| rename_opaque(c, canonical))
+                .collect(),
+        ),
+        Formula::Or(children) => Formula::Or(
+            children
+                .into_iter()
+                .map(|c| rename_opaque(c, canonical))
+                .collect(),
+        ),
+    }
+}
+
+/// Name-independent form of the evaluations of one rule.
+///
+/// Two evaluations have the same key when they compute the same value: names
+/// and weights are ignored, `@` references are replaced by the key of the
+/// referenced evaluation and logical operands are sorted.
+struct Canonicalizer<'a> {
+    source: &'a str,
+    evaluations: HashMap<&'a str, &'a Evaluation>,
+}
+
+impl<'a> Canonicalizer<'a> {
+    fn new(rule: &'a LibraryRule) -> Self {
+        let mut evaluations = HashMap::new();
+        for evaluation in &rule.model.evaluations {
+            evaluations
+                .entry(evaluation.name.as_str())
+                .or_insert(evaluation);
+        }
+        Self {
+            source: &rule.source,
+            evaluations,
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        self.key_inner(name, &mut Vec::new())
+    }
+
+    fn key_inner(&self, name: &str, visiting: &mut Vec<String>) -> String {
+        let Some(evaluation) = self.evaluations.get(name) else {
+            // Unresolved names never match anything in another rule
+            return format!("unresolved({}:{})", self.source, name);
+        };
+        if visiting.iter().any(|v| v == name) {
+            return format!("cycle({}:{})", self.source, name);
+        }
+        visiting.push(name.to_string());
+
+        let key = match evaluation.evaluation_type {
+            EvaluationType::Logical => {
+                let mut operands: Vec<String> = evaluation
+                    .operands
+                    .iter()
+                    .flatten()
+                    .map(|operand| self.key_inner(operand, visiting))
+                    .collect();
+                operands.sort();
+                format!(
+                    "{}({})",
+                    evaluation.operator.as_deref().unwrap_or_default(),
+                    operands.join(", ")
+                )
+            }
+            EvaluationType::Aggregation => {
+                let mut conditions: Vec<String> = evaluation
+                    .conditions
+                    .iter()
+                    .flatten()
+                    .map(|c| {
+                        self.comparison(
+                            c.left.as_deref(),
+                            c.operator.as_deref(),
+                            c.right.as_ref(),
+                            visiting,
+                        )
+                    })
+                    .collect();
+                conditions.sort();
+                format!(
+                    "{}({}) where [{}]",
+                    evaluation
+                        .aggregation
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    evaluation.field.as_deref().unwrap_or_default(),
+                    conditions.join(", ")
+                )
+            }
+            ref other => {
+                let comparison = self.comparison(
+                    evaluation.left.as_deref(),
+                    evaluation.operator.as_deref(),
+                    evaluation.right.as_ref(),
+                    visiting,
+                );
+                match other {
+                    EvaluationType::Comparison => comparison,
+                    other => format!("{}({})", other, comparison),
+                }
+            }
+        };
+
+        visiting.pop();
+        key
+    }
+
+    fn comparison(
+        &self,
+        left: Option<&str>,
+        operator: Option<&str>,
+        right: Option<&Value>,
+        visiting: &mut Vec<String>,
+    ) -> String {
+        let left = left
+            .map(|l| self.expression(l, visiting))
+            .unwrap_or_default();
+        let operator = operator.map(str::trim).unwrap_or_default();
+        let right = match right {
+            Some(Value::String(s)) => self.expression(s, visiting),
+            Some(Value::Array(items)) if operator.ends_with("IN") => {
+                let mut items: Vec<String> = items.iter().map(literal).collect();
+                items.sort();
+                format!("[{}]", items.join(","))
+            }
+            Some(value) => literal(value),
+            None => String::new(),
+        };
+        format!("{} {} {}", left, operator, right)
+    }
+
+    // Replace `@Name` references with the key of the referenced evaluation
+    fn expression(&self, expr: &str, visiting: &mut Vec<String>) -> String {
+        let mut references = RuleAnalyzer::extract_references(expr);
+        if references.is_empty() {
+            return expr.trim().to_string();
+        }
+        // Longest names first so `@Count` does not clobber `@Count_Check`
+        references.sort_by_key(|r| std::cmp::Reverse(r.len()));
+        let mut result = expr.trim().to_string();
+        for reference in references {
+            let key = self.key_inner(&reference, visiting);
+            result = result.replace(&format!("@{}", reference), &format!("@{{{}}}", key));
+        }
+        result
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+        value => lrol_to_json(value).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, evaluation, logical};
+    use lrol_parser::{types::Aggregation, Action};
+
+    fn count(name: &str) -> Evaluation {
+        Evaluation {
+            aggregation: Some(Aggregation::COUNT),
+            field: Some("transaction_id".to_string()),
+            ..evaluation(name, EvaluationType::Aggregation)
+        }
+    }
+
+    fn rule(
+        source: &str,
+        model_id: &str,
+        action: &str,
+        evaluations: Vec<Evaluation>,
+    ) -> LibraryRule {
+        LibraryRule {
+            source: source.to_string(),
+            model: LrolModel {
+                model_id: model_id.to_string(),
+                threshold: 0.8,
+                evaluations,
+                actions: vec![Action {
+                    action_type: action.to_string(),
+                    reason: "test".to_string(),
+                }],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn library() -> Vec<LibraryRule> {
+        vec![
+            // Loaded out of order on purpose
+            rule(
+                "rules/b.json",
+                "R002",
+                "block_transaction",
+                vec![
+                    comparison("Big", "amount", ">", Value::Number(10000.0)),
+                    count("Count"),
+                    comparison("Busy", "@Count", ">", Value::Number(5.0)),
+                    logical("Logic", "AND", &["Big", "Busy"]),
+                ],
+            ),
+            rule(
+                "rules/a.json",
+                "R001",
+                "flag_transaction",
+                vec![
+                    comparison("Large", "amount", ">", Value::Number(5000.0)),
+                    count("Recent_Count"),
+                    comparison("Frequent", "@Recent_Count", ">", Value::Number(5.0)),
+                    logical("Flag", "AND", &["Frequent", "Large"]),
+                ],
+            ),
+            rule(
+                "rules/c.json",
+                "R001",
+                "flag_transaction",
+                vec![comparison("Small", "amount", "<", Value::Number(100.0))],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_duplicate_ids_and_equivalent_evaluations() {
+        let report = analyze_library(&library());
+
+        assert_eq!(report.rule_count, 3);
+        assert_eq!(
+            report.duplicate_model_ids,
+            vec![DuplicateModelId {
+                model_id: "R001".to_string(),
+                sources: vec!["rules/a.json".to_string(), "rules/c.json".to_string()],
+            }]
+        );
+
+        let names: Vec<Vec<&str>> = report
+            .equivalent_evaluations
+            .iter()
+            .map(|e| {
+                e.occurrences
+                    .iter()
+                    .map(|o| o.evaluation_name.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![vec!["Frequent", "Busy"], vec!["Recent_Count", "Count"]]
+        );
+        assert_eq!(
+            report.equivalent_evaluations[0].signature,
+            "@{COUNT(transaction_id) where []} > 5"
+        );
+    }
+
+    #[test]
+    fn test_subsumed_rules_and_conflicting_actions() {
+        let report = analyze_library(&library());
+
+        // amount > 10000 with the same count check implies amount > 5000
+        assert_eq!(report.subsumed_rules.len(), 1);
+        assert_eq!(report.subsumed_rules[0].rule.source, "rules/b.json");
+        assert_eq!(report.subsumed_rules[0].subsumed_by.source, "rules/a.json");
+        assert!(!report.subsumed_rules[0].equivalent);
+
+        // b blocks what a flags; c cannot fire together with b
+        assert_eq!(report.conflicting_actions.len(), 1);
+        let conflict = &report.conflicting_actions[0];
+        assert_eq!(conflict.rules[0].source, "rules/a.json");
+        assert_eq!(conflict.rules[1].source, "rules/b.json");
+        assert_eq!(
+            conflict.actions,
+            [
+                vec!["flag_transaction".to_string()],
+                vec!["block_transaction".to_string()]
+            ]
+        );
+        assert!(conflict
+            .shared_conditions
+            .contains(&"field amount".to_string()));
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use lrol_parser::parser::LrolModel;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::DependencyGraph;
+use crate::lints;
+
+/// How a lint's findings are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// Findings are discarded
+    Allow,
+    /// Findings are reported without failing the run
+    Warn,
+    /// Findings are reported and fail the run
+    Deny,
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "deny" => Ok(LintLevel::Deny),
+            other => Err(format!(
+                "Unknown lint level '{}', expected allow, warn or deny",
+                other
+            )),
+        }
+    }
+}
+
+/// A check run against a single model.
+///
+/// Implement this to add project-specific lints and register them with
+/// [`LintRegistry::register`].
+pub trait Lint: Send + Sync {
+    /// Stable identifier used in configuration and `allow_lints`, e.g. `magic-number`
+    fn id(&self) -> &'static str;
+
+    /// One-line description of what the lint looks for
+    fn description(&self) -> &'static str;
+
+    /// Level used when the configuration does not mention the lint
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>);
+}
+
+/// Model being linted, along with data shared between lints
+pub struct LintContext<'a> {
+    pub model: &'a LrolModel,
+    pub graph: &'a DependencyGraph,
+    /// Path of the rule file, if it was loaded from disk
+    pub path: Option<&'a str>,
+}
+
+/// Issue reported by a lint, before levels and suppressions are applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    /// Evaluation the finding is about, if it concerns a single one
+    pub evaluation_name: Option<String>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl LintFinding {
+    pub fn new(message: impl Into<String>) -> Self {
+        LintFinding {
+            evaluation_name: None,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    pub fn with_evaluation(mut self, name: impl Into<String>) -> Self {
+        self.evaluation_name = Some(name.into());
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+/// Finding that survived configuration and suppressions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintDiagnostic {
+    pub lint_id: String,
+    pub level: LintLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation_name: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Per-project lint levels, usually loaded from `lrol-lints.json`:
+///
+/// ```json
+/// {
+///   "lints": { "magic-number": "warn", "naming-convention": "deny" },
+///   "overrides": [
+///     { "paths": ["legacy/**"], "lints": { "naming-convention": "allow" } }
+///   ]
+/// }
+/// ```
+///
+/// Later overrides take precedence over earlier ones and over `lints`.
+/// Override paths are relative to the directory of the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub lints: BTreeMap<String, LintLevel>,
+    #[serde(default)]
+    pub overrides: Vec<LintOverride>,
+    /// Directory rule paths are made relative to before matching overrides
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+}
+
+/// Lint levels applied to rule files matching one of `paths`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintOverride {
+    /// Glob patterns; `*` and `?` stay within a path segment, `**` spans segments
+    pub paths: Vec<String>,
+    pub lints: BTreeMap<String, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content).map_err(|e| format!("Invalid lint configuration: {}", e))
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read lint configuration {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        let mut config = Self::from_json(&content)?;
+        config.root = fs::canonicalize(&path)
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf));
+        Ok(config)
+    }
+
+    /// Level of `lint` for the rule at `path`
+    pub fn level_for(&self, lint: &dyn Lint, path: Option<&str>) -> LintLevel {
+        let mut level = self
+            .lints
+            .get(lint.id())
+            .copied()
+            .unwrap_or_else(|| lint.default_level());
+
+        if let Some(path) = path {
+            let path = self.relative_path(path);
+            for entry in &self.overrides {
+                if entry.paths.iter().any(|pattern| glob_match(pattern, &path)) {
+                    if let Some(&overridden) = entry.lints.get(lint.id()) {
+                        level = overridden;
+                    }
+                }
+            }
+        }
+        level
+    }
+
+    // `path` relative to `root` when it lies inside it, with `/` separators
+    fn relative_path(&self, path: &str) -> String {
+        let relative = self.root.as_ref().and_then(|root| {
+            let absolute = fs::canonicalize(path)
+                .or_else(|_| std::path::absolute(path))
+                .ok()?;
+            let relative = absolute.strip_prefix(root).ok()?;
+            Some(relative.to_string_lossy().into_owned())
+        });
+        relative
+            .unwrap_or_else(|| path.to_string())
+            .replace('\\', "/")
+    }
+
+    /// Lint IDs mentioned in the configuration
+    pub fn lint_ids(&self) -> impl Iterator<Item = &str> {
+        self.lints
+            .keys()
+            .chain(self.overrides.iter().flat_map(|o| o.lints.keys()))
+            .map(String::as_str)
+    }
+}
+
+/// Set of lints to run, in registration order
+pub struct LintRegistry {
+    lints: Vec<Box<dyn Lint>>,
+}
+
+impl Default for LintRegistry {
+    fn default() -> Self {
+        Self::with_builtin_lints()
+    }
+}
+
+impl LintRegistry {
+    /// Registry without any lints
+    pub fn new() -> Self {
+        LintRegistry { lints: Vec::new() }
+    }
+
+    /// Registry with every lint shipped with LROL
+    pub fn with_builtin_lints() -> Self {
+        let mut registry = Self::new();
+        for lint in lints::builtin_lints() {
+            registry.register(lint);
+        }
+        registry
+    }
+
+    /// Adds a lint, replacing any registered lint with the same ID
+    pub fn register(&mut self, lint: Box<dyn Lint>) {
+        match self.lints.iter().position(|l| l.id() == lint.id()) {
+            Some(index) => self.lints[index] = lint,
+            None => self.lints.push(lint),
+        }
+    }
+
+    pub fn lints(&self) -> impl Iterator<Item = &dyn Lint> {
+        self.lints.iter().map(|lint| lint.as_ref())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Lint> {
+        self.lints().find(|lint| lint.id() == id)
+    }
+
+    /// Lint IDs in `config` that no registered lint answers to
+    pub fn unknown_lints<'a>(&self, config: &'a LintConfig) -> Vec<&'a str> {
+        let mut unknown: Vec<&str> = config
+            .lint_ids()
+            .filter(|id| self.get(id).is_none())
+            .collect();
+        unknown.sort_unstable();
+        unknown.dedup();
+        unknown
+    }
+
+    /// Runs every lint that is not allowed for `path` against the model.
+    ///
+    /// Findings suppressed through the model's `metadata.allow_lints` are
+    /// dropped; an entry is either a lint ID, suppressing it for the whole
+    /// rule, or `lint-id:Evaluation_Name`.
+    pub fn run(
+        &self,
+        model: &LrolModel,
+        path: Option<&str>,
+        config: &LintConfig,
+    ) -> Vec<LintDiagnostic> {
+        let graph = DependencyGraph::from_model(model);
+        let context = LintContext {
+            model,
+            graph: &graph,
+            path,
+        };
+        let allowed = model
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.allow_lints.as_deref())
+            .unwrap_or_default();
+
+        let mut diagnostics = Vec::new();
+        for lint in self.lints() {
+            let level = config.level_for(lint, path);
+            if level == LintLevel::Allow {
+                continue;
+            }
+
+            let mut findings = Vec::new();
+            lint.check(&context, &mut findings);
+            for finding in findings {
+                if is_suppressed(allowed, lint.id(), finding.evaluation_name.as_deref()) {
+                    continue;
+                }
+                diagnostics.push(LintDiagnostic {
+                    lint_id: lint.id().to_string(),
+                    level,
+                    evaluation_name: finding.evaluation_name,
+                    message: finding.message,
+                    suggestion: finding.suggestion,
+                });
+            }
+        }
+        diagnostics
+    }
+}
+
+fn is_suppressed(allowed: &[String], lint_id: &str, evaluation_name: Option<&str>) -> bool {
+    allowed.iter().any(|entry| match entry.split_once(':') {
+        Some((id, name)) => id.trim() == lint_id && evaluation_name == Some(name.trim()),
+        None => entry.trim() == lint_id,
+    })
+}
+
+/// Matches `path` against a glob pattern with `*`, `?` and `**`; `.` segments
+/// such as the one in `./legacy` are ignored
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = path_segments(pattern);
+    let path = path_segments(path);
+    match_segments(&pattern, &path)
+}
+
+fn path_segments(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect()
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, remaining)) => {
+                let segment: Vec<char> = segment.chars().collect();
+                let name: Vec<char> = name.chars().collect();
+                match_segment(&segment, &name) && match_segments(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lrol_parser::types::Metadata;
+
+    struct NoActions;
+
+    impl Lint for NoActions {
+        fn id(&self) -> &'static str {
+            "no-actions"
+        }
+
+        fn description(&self) -> &'static str {
+            "Rule declares no actions"
+        }
+
+        fn default_level(&self) -> LintLevel {
+            LintLevel::Deny
+        }
+
+        fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+            if context.model.actions.is_empty() {
+                findings.push(LintFinding::new("Rule has no actions"));
+            }
+        }
+    }
+
+    fn model(allow_lints: &[&str]) -> LrolModel {
+        LrolModel {
+            model_id: "R100".to_string(),
+            metadata: Some(Metadata {
+                allow_lints: Some(allow_lints.iter().map(|s| s.to_string()).collect()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("Library/*.json", "Library/R001.json"));
+        assert!(!glob_match("Library/*.json", "Library/legacy/R001.json"));
+        assert!(glob_match("**/legacy/**", "rules/legacy/old/R001.json"));
+        assert!(glob_match("**/R00?.json", "R003.json"));
+        assert!(!glob_match("**/R00?.json", "R0031.json"));
+        assert!(glob_match("legacy/**", "./legacy/R001.json"));
+        assert!(glob_match("./legacy/*.json", "legacy/R001.json"));
+    }
+
+    #[test]
+    fn test_override_paths_are_relative_to_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("lrol-lints.json");
+        fs::write(
+            &config_path,
+            r#"{"overrides": [{"paths": ["legacy/**"], "lints": {"no-actions": "allow"}}]}"#,
+        )
+        .unwrap();
+        let config = LintConfig::from_file(&config_path).unwrap();
+
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let inside = root.join("legacy").join("R001.json");
+        let outside = root.join("rules").join("R001.json");
+        assert_eq!(
+            config.level_for(&NoActions, inside.to_str()),
+            LintLevel::Allow
+        );
+        assert_eq!(
+            config.level_for(&NoActions, outside.to_str()),
+            LintLevel::Deny
+        );
+        assert_eq!(
+            config.level_for(&NoActions, Some("./legacy/R001.json")),
+            LintLevel::Allow
+        );
+    }
+
+    #[test]
+    fn test_config_levels_and_overrides() {
+        let config = LintConfig::from_json(
+            r#"{
+                "lints": {"no-actions": "warn"},
+                "overrides": [
+                    {"paths": ["legacy/**"], "lints": {"no-actions": "allow"}},
+                    {"paths": ["legacy/strict/*.json"], "lints": {"no-actions": "deny"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.level_for(&NoActions, None), LintLevel::Warn);
+        assert_eq!(
+            config.level_for(&NoActions, Some("legacy/R001.json")),
+            LintLevel::Allow
+        );
+        assert_eq!(
+            config.level_for(&NoActions, Some("legacy\\strict\\R001.json")),
+            LintLevel::Deny
+        );
+        assert_eq!(
+            LintConfig::default().level_for(&NoActions, None),
+            LintLevel::Deny
+        );
+        assert!(LintConfig::from_json(r#"{"lints": {"no-actions": "error"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_custom_lint_and_suppression() {
+        let mut registry = LintRegistry::new();
+        registry.register(Box::new(NoActions));
+        let config = LintConfig::default();
+
+        let diagnostics = registry.run(&model(&[]), None, &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].lint_id, "no-actions");
+        assert_eq!(diagnostics[0].level, LintLevel::Deny);
+
+        assert!(registry
+            .run(&model(&["no-actions"]), None, &config)
+            .is_empty());
+        // Evaluation-scoped suppressions do not cover rule-level findings
+        assert_eq!(
+            registry
+                .run(&model(&["no-actions:Some_Check"]), None, &config)
+                .len(),
+            1
+        );
+
+        let config =
+            LintConfig::from_json(r#"{"lints": {"no-actions": "allow", "typo": "warn"}}"#).unwrap();
+        assert!(registry.run(&model(&[]), None, &config).is_empty());
+        assert_eq!(registry.unknown_lints(&config), vec!["typo"]);
+    }
+}
@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use lrol_parser::{EvaluationType, Value};
+
+use crate::dead_code::{find_dead_code, DeadCodeKind};
+use crate::lint::{Lint, LintContext, LintFinding, LintLevel};
+
+/// Lints registered by [`crate::lint::LintRegistry::with_builtin_lints`]
+pub fn builtin_lints() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(NamingConvention),
+        Box::new(MissingDescription),
+        Box::new(MagicNumber),
+        Box::new(DuplicatedList),
+        Box::new(UnusedEvaluation),
+        Box::new(WeightImbalance),
+    ]
+}
+
+/// Evaluation names are `Capitalized_Words_With_Underscores`
+pub struct NamingConvention;
+
+impl Lint for NamingConvention {
+    fn id(&self) -> &'static str {
+        "naming-convention"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluation names should be capitalized words joined by underscores, e.g. High_Value_Check"
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        for evaluation in &context.model.evaluations {
+            if !is_conventional_name(&evaluation.name) {
+                findings.push(
+                    LintFinding::new(format!(
+                        "Evaluation name '{}' does not follow the Capitalized_Words convention",
+                        evaluation.name
+                    ))
+                    .with_evaluation(&evaluation.name)
+                    .with_suggestion(format!(
+                        "Rename it to {}",
+                        conventional_name(&evaluation.name)
+                    )),
+                );
+            }
+        }
+    }
+}
+
+fn is_conventional_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('_').all(|word| {
+            word.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && word.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+fn conventional_name(name: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        let boundary = !c.is_ascii_alphanumeric() || (c.is_ascii_uppercase() && previous_lower);
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        if c.is_ascii_alphanumeric() {
+            current.push(c);
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Rules and actions explain themselves
+pub struct MissingDescription;
+
+impl Lint for MissingDescription {
+    fn id(&self) -> &'static str {
+        "missing-description"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rules should have a description and every action a reason"
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        let model = context.model;
+        if model
+            .description
+            .as_deref()
+            .is_none_or(|d| d.trim().is_empty())
+        {
+            findings.push(
+                LintFinding::new(format!("Rule {} has no description", model.model_id))
+                    .with_suggestion(
+                        "Add a \"description\" explaining what the rule detects and why",
+                    ),
+            );
+        }
+        for action in &model.actions {
+            if action.reason.trim().is_empty() {
+                findings.push(
+                    LintFinding::new(format!(
+                        "Action '{}' has an empty reason",
+                        action.action_type
+                    ))
+                    .with_suggestion("Describe why the action is taken, for reviewers and alerts"),
+                );
+            }
+        }
+    }
+}
+
+/// Numeric thresholds written inline instead of read from the event context
+pub struct MagicNumber;
+
+impl Lint for MagicNumber {
+    fn id(&self) -> &'static str {
+        "magic-number"
+    }
+
+    fn description(&self) -> &'static str {
+        "Numeric thresholds other than 0 and 1 should come from a profile or configuration field"
+    }
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Allow
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        for evaluation in &context.model.evaluations {
+            let conditions = evaluation.conditions.iter().flatten();
+            let compared = std::iter::once((evaluation.left.as_deref(), evaluation.right.as_ref()))
+                .chain(conditions.map(|c| (c.left.as_deref(), c.right.as_ref())));
+
+            for (left, right) in compared {
+                let Some(Value::Number(number)) = right else {
+                    continue;
+                };
+                if *number == 0.0 || *number == 1.0 {
+                    continue;
+                }
+                let left = left.unwrap_or("the value");
+                findings.push(
+                    LintFinding::new(format!(
+                        "{} compares {} with the literal {}",
+                        evaluation.name, left, number
+                    ))
+                    .with_evaluation(&evaluation.name)
+                    .with_suggestion(format!(
+                        "Compare {} with a field such as profile.{}_limit so the threshold can change without editing the rule",
+                        left,
+                        left.trim_start_matches('@').replace('.', "_").to_lowercase()
+                    )),
+                );
+            }
+        }
+    }
+}
+
+/// List literals repeated across evaluations, or containing repeated items
+pub struct DuplicatedList;
+
+impl Lint for DuplicatedList {
+    fn id(&self) -> &'static str {
+        "duplicated-list"
+    }
+
+    fn description(&self) -> &'static str {
+        "List literals should not repeat items or be copied between evaluations"
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        let mut users: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+
+        for evaluation in &context.model.evaluations {
+            let lists = evaluation
+                .right
+                .iter()
+                .chain(
+                    evaluation
+                        .conditions
+                        .iter()
+                        .flatten()
+                        .filter_map(|c| c.right.as_ref()),
+                )
+                .filter_map(|value| match value {
+                    Value::Array(items) => Some(items),
+                    _ => None,
+                });
+
+            for items in lists {
+                let rendered: Vec<String> = items.iter().map(render).collect();
+                let mut repeated: Vec<&String> = Vec::new();
+                for (i, item) in rendered.iter().enumerate() {
+                    if rendered[..i].contains(item) && !repeated.contains(&item) {
+                        repeated.push(item);
+                    }
+                }
+                if !repeated.is_empty() {
+                    findings.push(
+                        LintFinding::new(format!(
+                            "{} lists {} more than once",
+                            evaluation.name,
+                            repeated
+                                .iter()
+                                .map(|s| s.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ))
+                        .with_evaluation(&evaluation.name)
+                        .with_suggestion("Remove the repeated items"),
+                    );
+                }
+
+                let key: BTreeSet<String> = rendered.into_iter().collect();
+                let key = key.into_iter().collect::<Vec<_>>().join(", ");
+                let users = users.entry(format!("[{}]", key)).or_default();
+                if !users.contains(&evaluation.name.as_str()) {
+                    users.push(&evaluation.name);
+                }
+            }
+        }
+
+        for (list, users) in users {
+            if users.len() > 1 {
+                findings.push(
+                    LintFinding::new(format!(
+                        "The list {} is repeated in {}",
+                        list,
+                        users.join(", ")
+                    ))
+                    .with_suggestion(format!(
+                        "Compare against a single field holding {} or a shared evaluation so the copies cannot drift apart",
+                        list
+                    )),
+                );
+            }
+        }
+    }
+}
+
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(render).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(entries) => format!(
+            "{{{}}}",
+            entries
+                .iter()
+                .map(|(k, v)| format!("{:?}: {}", k, render(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Evaluations that never reach the rule's logical outcome
+pub struct UnusedEvaluation;
+
+impl Lint for UnusedEvaluation {
+    fn id(&self) -> &'static str {
+        "unused-evaluation"
+    }
+
+    fn description(&self) -> &'static str {
+        "Evaluations should be referenced by the rule's logic or at least carry a weight"
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        for dead in find_dead_code(context.model, context.graph) {
+            if !matches!(
+                dead.kind,
+                DeadCodeKind::UnreferencedEvaluation | DeadCodeKind::UnweightedEvaluation
+            ) {
+                continue;
+            }
+            for name in dead.evaluations {
+                findings.push(
+                    LintFinding::new(dead.message.clone())
+                        .with_evaluation(name)
+                        .with_suggestion(dead.suggestion.clone()),
+                );
+            }
+        }
+    }
+}
+
+/// A single evaluation outweighing all the others together
+pub struct WeightImbalance;
+
+impl Lint for WeightImbalance {
+    fn id(&self) -> &'static str {
+        "weight-imbalance"
+    }
+
+    fn description(&self) -> &'static str {
+        "No evaluation should carry more weight than all other evaluations combined"
+    }
+
+    fn check(&self, context: &LintContext, findings: &mut Vec<LintFinding>) {
+        let weighted: Vec<(&str, i32)> = context
+            .model
+            .evaluations
+            .iter()
+            .filter(|e| e.evaluation_type != EvaluationType::Logical)
+            .filter_map(|e| e.weight.map(|w| (e.name.as_str(), w)))
+            .collect();
+        if weighted.len() < 3 {
+            return;
+        }
+
+        let total: i32 = weighted.iter().map(|(_, w)| w).sum();
+        for &(name, weight) in &weighted {
+            let others = total - weight;
+            if weight > others {
+                findings.push(
+                    LintFinding::new(format!(
+                        "{} has weight {} while the other {} evaluations weigh {} together",
+                        name,
+                        weight,
+                        weighted.len() - 1,
+                        others
+                    ))
+                    .with_evaluation(name)
+                    .with_suggestion(format!(
+                        "Lower the weight of {} or raise the others; if it should decide the outcome alone, make it a separate rule",
+                        name
+                    )),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{LintConfig, LintDiagnostic, LintRegistry};
+    use crate::test_support::{comparison, logical, weighted};
+    use lrol_parser::{parser::LrolModel, Condition};
+
+    fn strings(items: &[&str]) -> Value {
+        Value::Array(items.iter().map(|s| Value::String(s.to_string())).collect())
+    }
+
+    fn run(model: &LrolModel, config: &str) -> Vec<LintDiagnostic> {
+        LintRegistry::with_builtin_lints().run(model, None, &LintConfig::from_json(config).unwrap())
+    }
+
+    fn ids(diagnostics: &[LintDiagnostic]) -> Vec<(&str, Option<&str>)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.lint_id.as_str(), d.evaluation_name.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_naming_convention() {
+        assert!(is_conventional_name("PEP_Status_Check"));
+        assert!(is_conventional_name("Check_24H"));
+        assert!(!is_conventional_name("amount_check"));
+        assert!(!is_conventional_name("Amount__Check"));
+        assert_eq!(conventional_name("amount check"), "Amount_Check");
+        assert_eq!(conventional_name("highRiskCountry"), "High_Risk_Country");
+        assert_eq!(conventional_name("PEP-status"), "PEP_Status");
+    }
+
+    #[test]
+    fn test_builtin_lints() {
+        let industries = ["Gambling", "Crypto"];
+        let mut aggregation =
+            weighted(comparison("Industry_Count", "", "IN", Value::Bool(true)), 2);
+        aggregation.evaluation_type = EvaluationType::Aggregation;
+        aggregation.conditions = Some(vec![Condition {
+            left: Some("industry".to_string()),
+            operator: Some("IN".to_string()),
+            right: Some(strings(&["Crypto", "Gambling"])),
+        }]);
+
+        let model = LrolModel {
+            model_id: "R100".to_string(),
+            evaluations: vec![
+                weighted(
+                    comparison("industry_check", "industry", "IN", strings(&industries)),
+                    1,
+                ),
+                weighted(
+                    comparison("Amount_Check", "amount", "IN", Value::Number(10000.0)),
+                    1,
+                ),
+                weighted(
+                    comparison(
+                        "Country_Check",
+                        "country",
+                        "IN",
+                        strings(&["NG", "GH", "NG", "GH", "NG"]),
+                    ),
+                    9,
+                ),
+                aggregation,
+                weighted(
+                    comparison("Count_Check", "@Industry_Count", "IN", Value::Number(5.0)),
+                    1,
+                ),
+                weighted(
+                    logical(
+                        "Logic",
+                        "OR",
+                        &[
+                            "industry_check",
+                            "Amount_Check",
+                            "Country_Check",
+                            "Count_Check",
+                        ],
+                    ),
+                    5,
+                ),
+                weighted(
+                    comparison("Orphan_Check", "device", "IN", Value::Bool(true)),
+                    1,
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let diagnostics = run(&model, "{}");
+        assert_eq!(
+            ids(&diagnostics),
+            vec![
+                ("naming-convention", Some("industry_check")),
+                ("missing-description", None),
+                ("duplicated-list", Some("Country_Check")),
+                ("duplicated-list", None),
+                ("unused-evaluation", Some("Orphan_Check")),
+                ("weight-imbalance", Some("Country_Check")),
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "Country_Check lists \"NG\", \"GH\" more than once"
+        );
+        assert_eq!(
+            diagnostics[3].message,
+            "The list [\"Crypto\", \"Gambling\"] is repeated in industry_check, Industry_Count"
+        );
+        assert!(diagnostics.iter().all(|d| d.level == LintLevel::Warn));
+
+        let diagnostics = run(&model, r#"{"lints": {"magic-number": "deny"}}"#);
+        let magic: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.lint_id == "magic-number")
+            .collect();
+        assert_eq!(magic.len(), 2);
+        assert_eq!(magic[0].level, LintLevel::Deny);
+        assert_eq!(
+            magic[1].suggestion.as_deref(),
+            Some("Compare @Industry_Count with a field such as profile.industry_count_limit so the threshold can change without editing the rule")
+        );
+    }
+}
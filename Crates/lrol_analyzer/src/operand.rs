@@ -0,0 +1,394 @@
+use lrol_parser::{parser::LrolModel, Value};
+
+/// Structure of a `left`/`right` operand of a comparison.
+///
+/// Operands are strings in LROL, so their meaning is recovered here: `@Name`
+/// references another evaluation, `name(args)` calls a function, dotted
+/// identifiers name event fields and anything else is a literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// Event field such as `amount` or `profile.available_balance`
+    Field(String),
+    /// Result of another evaluation, written `@Name`
+    Reference(String),
+    /// Function call such as `datetime(now, '-2 hours')`
+    Call {
+        name: String,
+        args: Vec<Operand>,
+    },
+    /// The `now` keyword
+    Now,
+    Literal(Value),
+    /// Text that could not be recognised as any of the above
+    Expression(String),
+}
+
+impl Operand {
+    /// Parses an expression written in a string operand
+    pub fn parse(expr: &str) -> Operand {
+        let expr = expr.trim();
+
+        if let Some(reference) = expr.strip_prefix('@') {
+            // `@Name * 0.8` is arithmetic on the referenced value, not the value itself
+            if reference.contains(['+', '-', '*', '/']) {
+                return Operand::Expression(expr.to_string());
+            }
+            let name = reference.split_whitespace().next().unwrap_or_default();
+            return Operand::Reference(name.to_string());
+        }
+
+        if expr == "now" {
+            return Operand::Now;
+        }
+
+        if let Some(literal) = Self::quoted(expr) {
+            return Operand::Literal(Value::String(literal.to_string()));
+        }
+
+        if let Ok(number) = expr.parse::<f64>() {
+            return Operand::Literal(Value::Number(number));
+        }
+
+        if let Some((name, args)) = Self::call(expr) {
+            return Operand::Call {
+                name: name.to_string(),
+                args: args.into_iter().map(Operand::parse).collect(),
+            };
+        }
+
+        if is_field_path(expr) {
+            return Operand::Field(expr.to_string());
+        }
+
+        Operand::Expression(expr.to_string())
+    }
+
+    /// Interprets the `right` value of a comparison.
+    ///
+    /// Plain strings are ambiguous between a field name and a string literal. A
+    /// string is read as a field when `is_field` accepts it or when it is a
+    /// dotted path such as `profile.common_ips`; otherwise it is a literal.
+    pub fn from_value<F: Fn(&str) -> bool>(value: &Value, is_field: F) -> Operand {
+        match value {
+            Value::String(s) => {
+                let trimmed = s.trim();
+                if trimmed.starts_with('@') || Self::call(trimmed).is_some() || trimmed == "now" {
+                    Self::parse(trimmed)
+                } else if is_field_path(trimmed) && (is_field(trimmed) || trimmed.contains('.')) {
+                    Operand::Field(trimmed.to_string())
+                } else {
+                    Operand::Literal(value.clone())
+                }
+            }
+            _ => Operand::Literal(value.clone()),
+        }
+    }
+
+    /// Event fields read by this operand, including those inside function arguments
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            Operand::Field(path) => vec![path.as_str()],
+            Operand::Call { args, .. } => args.iter().flat_map(Operand::fields).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn quoted(expr: &str) -> Option<&str> {
+        let bytes = expr.as_bytes();
+        if bytes.len() >= 2
+            && (bytes[0] == b'\'' || bytes[0] == b'"')
+            && bytes[bytes.len() - 1] == bytes[0]
+        {
+            Some(&expr[1..expr.len() - 1])
+        } else {
+            None
+        }
+    }
+
+    // Split `name(a, b)` into the function name and its top-level arguments
+    fn call(expr: &str) -> Option<(&str, Vec<&str>)> {
+        let open = expr.find('(')?;
+        let name = &expr[..open];
+        if name.is_empty()
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            || !expr.ends_with(')')
+        {
+            return None;
+        }
+        Some((name, split_arguments(&expr[open + 1..expr.len() - 1])))
+    }
+}
+
+/// Event field read by an evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldReference {
+    pub evaluation_name: String,
+    /// Evaluation field the reference appears in, e.g. `left` or `conditions[0].right`
+    pub field_name: String,
+    pub field: String,
+}
+
+/// Every event field a model reads, in declaration order.
+///
+/// `is_field` disambiguates plain strings on the right of a comparison, see
+/// [`Operand::from_value`].
+pub fn referenced_fields<F: Fn(&str) -> bool>(
+    model: &LrolModel,
+    is_field: F,
+) -> Vec<FieldReference> {
+    let mut references = Vec::new();
+    let mut push = |evaluation_name: &str, field_name: String, operand: &Operand| {
+        for field in operand.fields() {
+            references.push(FieldReference {
+                evaluation_name: evaluation_name.to_string(),
+                field_name: field_name.clone(),
+                field: field.to_string(),
+            });
+        }
+    };
+
+    for evaluation in &model.evaluations {
+        let name = &evaluation.name;
+        if let Some(ref left) = evaluation.left {
+            push(name, "left".to_string(), &Operand::parse(left));
+        }
+        if let Some(ref right) = evaluation.right {
+            push(
+                name,
+                "right".to_string(),
+                &Operand::from_value(right, &is_field),
+            );
+        }
+        if let Some(ref field) = evaluation.field {
+            push(name, "field".to_string(), &Operand::parse(field));
+        }
+        for (i, condition) in evaluation.conditions.iter().flatten().enumerate() {
+            if let Some(ref left) = condition.left {
+                push(
+                    name,
+                    format!("conditions[{}].left", i),
+                    &Operand::parse(left),
+                );
+            }
+            if let Some(ref right) = condition.right {
+                push(
+                    name,
+                    format!("conditions[{}].right", i),
+                    &Operand::from_value(right, &is_field),
+                );
+            }
+        }
+    }
+
+    references
+}
+
+/// Whether the field `path` is named by `pattern`, a run of its segments:
+/// `card_number` names `payment.card_number`, and `customer.address` names
+/// `customer.address.city`
+pub fn path_matches(path: &str, pattern: &str) -> bool {
+    let path: Vec<&str> = path.split('.').collect();
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    path.windows(pattern.len())
+        .any(|segments| segments == pattern.as_slice())
+}
+
+/// Whether `expr` is a dotted identifier path like `transaction.amount`
+pub fn is_field_path(expr: &str) -> bool {
+    !expr.is_empty()
+        && expr.split('.').all(|part| {
+            let mut chars = part.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// Splits arithmetic such as `@Total * 0.8` into its operands and operator.
+///
+/// The rightmost operator of the lowest precedence outside quotes and
+/// parentheses splits the expression. A `+` or `-` that starts the expression
+/// or follows another operator is a sign, as in `@Total * -2`.
+pub fn split_arithmetic(expr: &str) -> Option<(&str, char, &str)> {
+    ["+-", "*/"].iter().find_map(|operators| {
+        let mut depth = 0;
+        let mut quote = None;
+        let mut previous = None;
+        let mut found = None;
+        for (i, c) in expr.char_indices() {
+            match c {
+                '\'' | '"' if quote == Some(c) => quote = None,
+                '\'' | '"' if quote.is_none() => quote = Some(c),
+                '(' if quote.is_none() => depth += 1,
+                ')' if quote.is_none() => depth -= 1,
+                _ if depth == 0
+                    && quote.is_none()
+                    && operators.contains(c)
+                    && previous.is_some_and(|p: char| !"+-*/".contains(p)) =>
+                {
+                    found = Some((i, c))
+                }
+                _ => {}
+            }
+            if !c.is_whitespace() {
+                previous = Some(c);
+            }
+        }
+        found.map(|(at, operator)| (&expr[..at], operator, &expr[at + 1..]))
+    })
+}
+
+/// Splits function arguments on commas that are not nested in quotes or parentheses
+pub fn split_arguments(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in args.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('(', None) => depth += 1,
+            (')', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = args[start..].trim();
+    if !last.is_empty() || !parts.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_operands() {
+        assert_eq!(
+            Operand::parse("profile.available_balance"),
+            Operand::Field("profile.available_balance".to_string())
+        );
+        assert_eq!(
+            Operand::parse("@Base_Check result"),
+            Operand::Reference("Base_Check".to_string())
+        );
+        assert_eq!(
+            Operand::parse("strftime('%H', transaction_date)"),
+            Operand::Call {
+                name: "strftime".to_string(),
+                args: vec![
+                    Operand::Literal(Value::String("%H".to_string())),
+                    Operand::Field("transaction_date".to_string()),
+                ],
+            }
+        );
+        assert_eq!(
+            Operand::parse("datetime(now, '-1 day, 2 hours')"),
+            Operand::Call {
+                name: "datetime".to_string(),
+                args: vec![
+                    Operand::Now,
+                    Operand::Literal(Value::String("-1 day, 2 hours".to_string())),
+                ],
+            }
+        );
+        assert!(matches!(Operand::parse("a + b"), Operand::Expression(_)));
+        assert!(matches!(
+            Operand::parse("@Large_Deposit_Check * 0.8"),
+            Operand::Expression(_)
+        ));
+    }
+
+    #[test]
+    fn test_split_arithmetic() {
+        assert_eq!(
+            split_arithmetic("@Total * 0.8 + 1"),
+            Some(("@Total * 0.8 ", '+', " 1"))
+        );
+        assert_eq!(split_arithmetic("a - b - c"), Some(("a - b ", '-', " c")));
+        assert_eq!(split_arithmetic("@A * -2"), Some(("@A ", '*', " -2")));
+        assert_eq!(
+            split_arithmetic("amount - -5"),
+            Some(("amount ", '-', " -5"))
+        );
+        assert_eq!(split_arithmetic("-5"), None);
+        assert_eq!(split_arithmetic("datetime(now, '-2 hours')"), None);
+        assert_eq!(split_arithmetic("'2024-01-01'"), None);
+    }
+
+    #[test]
+    fn test_referenced_fields() {
+        let model = LrolModel {
+            evaluations: vec![lrol_parser::Evaluation {
+                name: "Recent_Count".to_string(),
+                evaluation_type: lrol_parser::EvaluationType::Aggregation,
+                left: None,
+                operator: None,
+                right: None,
+                operands: None,
+                weight: Some(3),
+                aggregation: None,
+                field: Some("transaction_id".to_string()),
+                conditions: Some(vec![lrol_parser::Condition {
+                    left: Some("transaction_date".to_string()),
+                    operator: Some(">=".to_string()),
+                    right: Some(Value::String("datetime(now, '-1 hour')".to_string())),
+                }]),
+            }],
+            ..Default::default()
+        };
+
+        let fields: Vec<_> = referenced_fields(&model, |_| false)
+            .into_iter()
+            .map(|r| (r.field_name, r.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("field".to_string(), "transaction_id".to_string()),
+                (
+                    "conditions[0].left".to_string(),
+                    "transaction_date".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_right_operand_interpretation() {
+        let is_field = |f: &str| f == "amount";
+
+        assert_eq!(
+            Operand::from_value(&Value::String("amount".to_string()), is_field),
+            Operand::Field("amount".to_string())
+        );
+        assert_eq!(
+            Operand::from_value(&Value::String("high_risk".to_string()), is_field),
+            Operand::Literal(Value::String("high_risk".to_string()))
+        );
+        assert_eq!(
+            Operand::from_value(&Value::String("profile.common_ips".to_string()), is_field),
+            Operand::Field("profile.common_ips".to_string())
+        );
+        assert_eq!(
+            Operand::from_value(&Value::Number(5.0), is_field),
+            Operand::Literal(Value::Number(5.0))
+        );
+    }
+
+    #[test]
+    fn test_path_matches() {
+        assert!(path_matches("payment.card_number", "card_number"));
+        assert!(path_matches("customer.address.city", "customer.address"));
+        assert!(path_matches("amount", "amount"));
+        assert!(!path_matches("card_number_hash", "card_number"));
+        assert!(!path_matches("customer.city", "customer.address"));
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+
+use lrol_parser::{parser::LrolModel, EvaluationType};
+use serde::Serialize;
+
+use crate::error::AnalyzerError;
+use crate::graph::{DependencyGraph, DependencyKind};
+
+/// Evaluation as scheduled by an [`ExecutionPlan`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedEvaluation {
+    pub name: String,
+    pub evaluation_type: EvaluationType,
+    /// Evaluations read through operands or `@` references, all in earlier stages
+    pub dependencies: Vec<String>,
+    /// Aggregations read the entity's past events, not just the current one
+    pub requires_history: bool,
+    /// Set on AND and OR evaluations, which can stop at the first deciding operand
+    pub short_circuit: Option<ShortCircuit>,
+    /// Unweighted evaluation read only as an operand of short-circuiting
+    /// evaluations, which need not run when those are decided without it
+    pub lazy: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShortCircuit {
+    /// Operand value that decides the result: `false` for AND, `true` for OR
+    pub decided_by: bool,
+    /// Operands in the order they are best evaluated: those needing no
+    /// historical state, directly or through their dependencies, come first
+    pub operands: Vec<String>,
+}
+
+/// Evaluations that depend only on earlier stages and not on each other, so
+/// they can run in parallel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stage {
+    pub evaluations: Vec<PlannedEvaluation>,
+}
+
+/// Order in which an engine can run the evaluations of a model.
+///
+/// Each evaluation runs in the stage after the last of its dependencies, and
+/// evaluations keep declaration order within a stage, so the same model always
+/// yields the same plan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExecutionPlan {
+    pub stages: Vec<Stage>,
+}
+
+impl ExecutionPlan {
+    /// Plans a model, failing on the errors that leave no valid order:
+    /// duplicate names, references to undeclared evaluations and cycles
+    pub fn from_model(model: &LrolModel) -> Result<Self, Vec<AnalyzerError>> {
+        let graph = DependencyGraph::from_model(model);
+        let mut errors = Vec::new();
+
+        let mut declared = HashSet::new();
+        for evaluation in &model.evaluations {
+            if !declared.insert(evaluation.name.as_str()) {
+                errors.push(AnalyzerError::DuplicateEvaluationName(
+                    evaluation.name.clone(),
+                ));
+            }
+        }
+        for edge in graph.edges() {
+            if declared.contains(edge.to.as_str()) {
+                continue;
+            }
+            errors.push(match edge.kind {
+                DependencyKind::Operand => AnalyzerError::MissingOperandReference {
+                    evaluation_name: edge.from.clone(),
+                    missing_operand: edge.to.clone(),
+                },
+                _ => AnalyzerError::InvalidStringReference {
+                    evaluation_name: edge.from.clone(),
+                    field_name: edge.kind.field_name().to_string(),
+                    reference: edge.to.clone(),
+                },
+            });
+        }
+        for cycle in graph.cycles() {
+            errors.push(AnalyzerError::CircularDependency {
+                evaluation_name: cycle.members[0].clone(),
+                dependency_chain: cycle.path,
+                edges: cycle.edges,
+            });
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Stage of every evaluation, filled in one stage at a time
+        let mut stage_of: HashMap<&str, usize> = HashMap::new();
+        let mut levels: Vec<Vec<usize>> = Vec::new();
+        while stage_of.len() < model.evaluations.len() {
+            let stage = levels.len();
+            let ready: Vec<usize> = model
+                .evaluations
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| !stage_of.contains_key(e.name.as_str()))
+                .filter(|(_, e)| {
+                    graph
+                        .dependencies(&e.name)
+                        .iter()
+                        .all(|dep| stage_of.get(dep).is_some_and(|&s| s < stage))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            for &i in &ready {
+                stage_of.insert(&model.evaluations[i].name, stage);
+            }
+            levels.push(ready);
+        }
+
+        // Whether an evaluation needs history itself or through a dependency
+        let mut needs_history: HashMap<&str, bool> = HashMap::new();
+        for &i in levels.iter().flatten() {
+            let evaluation = &model.evaluations[i];
+            let needs = evaluation.evaluation_type == EvaluationType::Aggregation
+                || graph
+                    .dependencies(&evaluation.name)
+                    .iter()
+                    .any(|dep| needs_history[dep]);
+            needs_history.insert(&evaluation.name, needs);
+        }
+
+        let short_circuit = |i: usize| {
+            let evaluation = &model.evaluations[i];
+            if evaluation.evaluation_type != EvaluationType::Logical {
+                return None;
+            }
+            let decided_by = match evaluation.operator.as_deref().map(str::trim) {
+                Some("AND") => false,
+                Some("OR") => true,
+                _ => return None,
+            };
+            let mut operands: Vec<String> = Vec::new();
+            for operand in evaluation.operands.iter().flatten() {
+                if !operands.contains(operand) {
+                    operands.push(operand.clone());
+                }
+            }
+            operands.sort_by_key(|operand| needs_history[operand.as_str()]);
+            Some(ShortCircuit {
+                decided_by,
+                operands,
+            })
+        };
+        let short_circuits: HashMap<&str, Option<ShortCircuit>> = (0..model.evaluations.len())
+            .map(|i| (model.evaluations[i].name.as_str(), short_circuit(i)))
+            .collect();
+
+        let stages = levels
+            .into_iter()
+            .map(|level| Stage {
+                evaluations: level
+                    .into_iter()
+                    .map(|i| {
+                        let evaluation = &model.evaluations[i];
+                        let name = evaluation.name.as_str();
+                        let mut dependents = graph.incoming(name).peekable();
+                        let lazy = evaluation.weight.is_none()
+                            && dependents.peek().is_some()
+                            && dependents.all(|edge| {
+                                edge.kind == DependencyKind::Operand
+                                    && short_circuits[edge.from.as_str()].is_some()
+                            });
+                        PlannedEvaluation {
+                            name: name.to_string(),
+                            evaluation_type: evaluation.evaluation_type.clone(),
+                            dependencies: graph
+                                .dependencies(name)
+                                .into_iter()
+                                .map(str::to_string)
+                                .collect(),
+                            requires_history: evaluation.evaluation_type
+                                == EvaluationType::Aggregation,
+                            short_circuit: short_circuits[name].clone(),
+                            lazy,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(ExecutionPlan { stages })
+    }
+
+    /// Every evaluation in execution order
+    pub fn order(&self) -> impl Iterator<Item = &PlannedEvaluation> {
+        self.stages.iter().flat_map(|stage| &stage.evaluations)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PlannedEvaluation> {
+        self.order().find(|evaluation| evaluation.name == name)
+    }
+
+    /// Index of the stage `name` runs in
+    pub fn stage_of(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|stage| {
+            stage
+                .evaluations
+                .iter()
+                .any(|evaluation| evaluation.name == name)
+        })
+    }
+
+    /// Largest number of evaluations that can run at the same time
+    pub fn max_parallelism(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|stage| stage.evaluations.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(evaluations: &str) -> Result<ExecutionPlan, Vec<AnalyzerError>> {
+        let model = lrol_parser::parse_str(&format!(
+            r#"{{
+  "model_id": "P001",
+  "name": "Plan",
+  "threshold": 0.5,
+  "evaluations": [{}],
+  "actions": [{{"type": "flag_transaction", "reason": "Test"}}]
+}}"#,
+            evaluations
+        ))
+        .unwrap();
+        ExecutionPlan::from_model(&model)
+    }
+
+    fn names(stage: &Stage) -> Vec<&str> {
+        stage.evaluations.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_stages() {
+        let plan = plan(
+            r#"
+    {"name": "Logic", "type": "logical", "operator": "AND", "operands": ["Sum_Check", "Amount_Check"], "weight": 4},
+    {"name": "Sum_Check", "type": "comparison", "left": "@Amount_Sum", "operator": ">", "right": 50000},
+    {"name": "Amount_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 2},
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 10000},
+    {"name": "Country_Check", "type": "comparison", "left": "country", "operator": "==", "right": "XX", "weight": 1}"#,
+        )
+        .unwrap();
+
+        assert_eq!(plan.stages.len(), 3);
+        assert_eq!(
+            names(&plan.stages[0]),
+            vec!["Amount_Sum", "Amount_Check", "Country_Check"]
+        );
+        assert_eq!(names(&plan.stages[1]), vec!["Sum_Check"]);
+        assert_eq!(names(&plan.stages[2]), vec!["Logic"]);
+        assert_eq!(plan.max_parallelism(), 3);
+        assert_eq!(plan.stage_of("Logic"), Some(2));
+
+        let sum = plan.get("Amount_Sum").unwrap();
+        assert!(sum.requires_history);
+        assert!(!sum.lazy);
+        assert!(!plan.get("Sum_Check").unwrap().requires_history);
+        assert!(plan.get("Sum_Check").unwrap().lazy);
+        assert!(plan.get("Amount_Check").unwrap().lazy);
+        assert!(!plan.get("Country_Check").unwrap().lazy);
+
+        let logic = plan.get("Logic").unwrap();
+        assert_eq!(logic.dependencies, vec!["Sum_Check", "Amount_Check"]);
+        assert_eq!(
+            logic.short_circuit,
+            Some(ShortCircuit {
+                decided_by: false,
+                operands: vec!["Amount_Check".to_string(), "Sum_Check".to_string()],
+            })
+        );
+        assert_eq!(
+            plan.order().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            vec![
+                "Amount_Sum",
+                "Amount_Check",
+                "Country_Check",
+                "Sum_Check",
+                "Logic"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unplannable_models() {
+        let errors = plan(
+            r#"
+    {"name": "A", "type": "logical", "operator": "OR", "operands": ["B", "Missing"], "weight": 1},
+    {"name": "B", "type": "comparison", "left": "@A", "operator": "==", "right": true, "weight": 1}"#,
+        )
+        .unwrap_err();
+
+        assert!(matches!(&errors[..], [
+            AnalyzerError::MissingOperandReference { missing_operand, .. },
+            AnalyzerError::CircularDependency { dependency_chain, .. },
+        ] if missing_operand == "Missing" && dependency_chain == &["A", "B"]));
+    }
+}
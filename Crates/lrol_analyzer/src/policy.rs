@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use lrol_parser::{parser::LrolModel, EvaluationType};
+use serde::{Deserialize, Serialize};
+
+use crate::datetime::Duration;
+use crate::error::AnalyzerError;
+use crate::operand::{path_matches, referenced_fields};
+use crate::state_cost::{aggregation_window, duration_seconds, format_window};
+
+/// Metadata fields a policy can require
+pub const METADATA_FIELDS: [&str; 4] = ["created_by", "created_at", "last_updated", "notes"];
+
+/// Organization policies every rule is checked against, usually loaded from a
+/// JSON file:
+///
+/// ```json
+/// {
+///   "policies": [
+///     {
+///       "id": "POL-001",
+///       "description": "Blocking rules need a high threshold and an owner",
+///       "applies_to": { "actions": ["block_transaction"] },
+///       "require": { "min_threshold": 0.9, "metadata": ["created_by"] }
+///     },
+///     { "id": "POL-002", "require": { "forbidden_fields": ["card_number"] } },
+///     { "id": "POL-003", "require": { "max_window": "90 days" } }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicySet {
+    pub policies: Vec<Policy>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Rules the policy applies to, all of them when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applies_to: Option<PolicyScope>,
+    pub require: PolicyRequirements,
+}
+
+/// Rules a policy applies to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyScope {
+    /// Rules taking any of these actions
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+/// Constraints a rule must meet; every one that is set is checked
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRequirements {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_threshold: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_threshold: Option<f64>,
+    /// Metadata fields that must be set, such as `created_by`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<String>,
+    /// Event fields no evaluation may read. `card_number` also forbids
+    /// nested paths such as `payment.card_number`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_actions: Vec<String>,
+    /// Longest time window an aggregation may cover, e.g. `90 days`.
+    /// Aggregations without a window break it too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_window: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_evaluations: Option<usize>,
+}
+
+impl PolicySet {
+    /// Parses a policy file, rejecting duplicate ids, unknown metadata fields
+    /// and windows that are not durations
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let policies: PolicySet =
+            serde_json::from_str(content).map_err(|e| format!("Invalid policy file: {}", e))?;
+
+        let mut ids = HashSet::new();
+        for policy in &policies.policies {
+            if !ids.insert(policy.id.as_str()) {
+                return Err(format!("Duplicate policy id '{}'", policy.id));
+            }
+            let require = &policy.require;
+            if let Some(field) = require
+                .metadata
+                .iter()
+                .find(|field| !METADATA_FIELDS.contains(&field.as_str()))
+            {
+                return Err(format!(
+                    "Policy '{}' requires unknown metadata field '{}', expected one of {}",
+                    policy.id,
+                    field,
+                    METADATA_FIELDS.join(", ")
+                ));
+            }
+            if let Some(ref window) = require.max_window {
+                Duration::parse(window).map_err(|e| {
+                    format!("Policy '{}' has an invalid max_window: {}", policy.id, e)
+                })?;
+            }
+        }
+        Ok(policies)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read policy file {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Self::from_json(&content)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Policy> {
+        self.policies.iter().find(|policy| policy.id == id)
+    }
+
+    /// Reports every requirement `model` breaks, policy by policy
+    pub fn check_model(&self, model: &LrolModel, errors: &mut Vec<AnalyzerError>) {
+        for policy in &self.policies {
+            if policy.applies_to(model) {
+                policy.check_model(model, errors);
+            }
+        }
+    }
+}
+
+impl Policy {
+    pub fn applies_to(&self, model: &LrolModel) -> bool {
+        match self.applies_to {
+            Some(ref scope) if !scope.actions.is_empty() => model
+                .actions
+                .iter()
+                .any(|action| scope.actions.contains(&action.action_type)),
+            _ => true,
+        }
+    }
+
+    pub fn check_model(&self, model: &LrolModel, errors: &mut Vec<AnalyzerError>) {
+        let violation = |evaluation_name: Option<&str>, field_name: &str, message: String| {
+            AnalyzerError::PolicyViolation {
+                policy_id: self.id.clone(),
+                description: self.description.clone(),
+                evaluation_name: evaluation_name.map(str::to_string),
+                field_name: field_name.to_string(),
+                message,
+            }
+        };
+        let require = &self.require;
+
+        if let Some(min) = require.min_threshold {
+            if model.threshold < min {
+                errors.push(violation(
+                    None,
+                    "threshold",
+                    format!("threshold {} is below {}", model.threshold, min),
+                ));
+            }
+        }
+        if let Some(max) = require.max_threshold {
+            if model.threshold > max {
+                errors.push(violation(
+                    None,
+                    "threshold",
+                    format!("threshold {} is above {}", model.threshold, max),
+                ));
+            }
+        }
+
+        let metadata = model
+            .metadata
+            .as_ref()
+            .and_then(|metadata| serde_json::to_value(metadata).ok());
+        for field in &require.metadata {
+            let set = metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get(field))
+                .and_then(|value| value.as_str())
+                .is_some_and(|value| !value.trim().is_empty());
+            if !set {
+                errors.push(violation(
+                    None,
+                    "metadata",
+                    format!("metadata.{} must be set", field),
+                ));
+            }
+        }
+
+        if !require.forbidden_fields.is_empty() {
+            let forbidden = |path: &str| {
+                require
+                    .forbidden_fields
+                    .iter()
+                    .any(|field| path_matches(path, field))
+            };
+            for reference in referenced_fields(model, forbidden) {
+                if forbidden(&reference.field) {
+                    errors.push(violation(
+                        Some(&reference.evaluation_name),
+                        &reference.field_name,
+                        format!("reads forbidden field '{}'", reference.field),
+                    ));
+                }
+            }
+        }
+
+        for action in &model.actions {
+            if require.forbidden_actions.contains(&action.action_type) {
+                errors.push(violation(
+                    None,
+                    "actions",
+                    format!("action {} is not allowed", action.action_type),
+                ));
+            }
+        }
+
+        if let Some(max) = require.max_window.as_deref() {
+            let Ok(max_window) = Duration::parse(max) else {
+                return;
+            };
+            let max_seconds = duration_seconds(&max_window);
+            for evaluation in &model.evaluations {
+                if evaluation.evaluation_type != EvaluationType::Aggregation {
+                    continue;
+                }
+                let message = match aggregation_window(evaluation) {
+                    None => "aggregation has no time window".to_string(),
+                    Some(window) if window > max_seconds => format!(
+                        "aggregation window of {} is longer than {}",
+                        format_window(window),
+                        max.trim()
+                    ),
+                    Some(_) => continue,
+                };
+                errors.push(violation(Some(&evaluation.name), "conditions", message));
+            }
+        }
+
+        if let Some(max) = require.max_evaluations {
+            if model.evaluations.len() > max {
+                errors.push(violation(
+                    None,
+                    "evaluations",
+                    format!(
+                        "{} evaluations are more than {}",
+                        model.evaluations.len(),
+                        max
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICIES: &str = r#"{
+  "policies": [
+    {
+      "id": "POL-001",
+      "description": "Blocking rules need a high threshold and an owner",
+      "applies_to": { "actions": ["block_transaction"] },
+      "require": { "min_threshold": 0.9, "metadata": ["created_by"] }
+    },
+    { "id": "POL-002", "require": { "forbidden_fields": ["card_number"] } },
+    { "id": "POL-003", "require": { "max_window": "90 days" } }
+  ]
+}"#;
+
+    fn model(action: &str, evaluations: &str) -> LrolModel {
+        lrol_parser::parse_str(&format!(
+            r#"{{
+  "model_id": "P001",
+  "name": "Policy",
+  "threshold": 0.8,
+  "evaluations": [{}],
+  "actions": [{{"type": "{}", "reason": "Test"}}]
+}}"#,
+            evaluations, action
+        ))
+        .unwrap()
+    }
+
+    fn violations(policies: &PolicySet, model: &LrolModel) -> Vec<(String, Option<String>)> {
+        let mut errors = Vec::new();
+        policies.check_model(model, &mut errors);
+        errors
+            .into_iter()
+            .map(|error| match error {
+                AnalyzerError::PolicyViolation {
+                    policy_id,
+                    evaluation_name,
+                    ..
+                } => (policy_id, evaluation_name),
+                other => panic!("unexpected error {}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_policies() {
+        let policies = PolicySet::from_json(POLICIES).unwrap();
+        let evaluations = r#"
+    {"name": "Card_Check", "type": "comparison", "left": "payment.card_number", "operator": "==", "right": "4111", "weight": 2},
+    {"name": "Yearly_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 2,
+     "conditions": [{"left": "transaction_date", "operator": ">=", "right": "datetime(now, '-1 year')"}]},
+    {"name": "Monthly_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 2,
+     "conditions": [{"left": "transaction_date", "operator": ">=", "right": "datetime(now, '-30 days')"}]}"#;
+
+        assert_eq!(
+            violations(&policies, &model("block_transaction", evaluations)),
+            vec![
+                ("POL-001".to_string(), None),
+                ("POL-001".to_string(), None),
+                ("POL-002".to_string(), Some("Card_Check".to_string())),
+                ("POL-003".to_string(), Some("Yearly_Sum".to_string())),
+            ]
+        );
+        // Only blocking rules fall under POL-001
+        assert_eq!(
+            violations(&policies, &model("flag_transaction", evaluations)).len(),
+            2
+        );
+
+        let mut errors = Vec::new();
+        policies.check_model(&model("flag_transaction", evaluations), &mut errors);
+        assert_eq!(
+            errors[1].to_string(),
+            "Policy POL-003 violated by Yearly_Sum: aggregation window of 365.2d is longer than 90 days"
+        );
+        assert_eq!(errors[1].code(), "LROL0048");
+    }
+
+    #[test]
+    fn test_invalid_policy_files() {
+        assert!(PolicySet::from_json(
+            r#"{"policies": [{"id": "A", "require": {"max_window": "soon"}}]}"#
+        )
+        .unwrap_err()
+        .contains("invalid max_window"));
+        assert!(PolicySet::from_json(
+            r#"{"policies": [{"id": "A", "require": {"metadata": ["owner"]}}]}"#
+        )
+        .unwrap_err()
+        .contains("unknown metadata field 'owner'"));
+        assert!(PolicySet::from_json(
+            r#"{"policies": [{"id": "A", "require": {}}, {"id": "A", "require": {}}]}"#
+        )
+        .unwrap_err()
+        .contains("Duplicate policy id"));
+        assert!(
+            PolicySet::from_json(r#"{"policies": [{"id": "A", "require": {"max_age": 3}}]}"#)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, NaiveDate};
+use lrol_parser::parser::LrolModel;
+use serde_json::Value as JsonValue;
+
+use crate::event_schema::{EventSchema, FieldSpec, FieldType};
+use crate::operand::{referenced_fields, FieldReference};
+
+/// String fields with at most this many distinct values are reported as enums
+pub const MAX_ENUM_VALUES: usize = 10;
+
+/// Infers an [`EventSchema`] from sample events.
+///
+/// Nested objects are flattened to dotted paths, and the fields of objects
+/// inside arrays are listed under the array's path, e.g. `items.sku`. A field
+/// is optional when some sample lacks it or sets it to `null`, or for a field
+/// of array items, when some item does. A string field is given a closed value
+/// set when it takes few distinct values that repeat across samples.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaInference {
+    samples: usize,
+    fields: BTreeMap<String, FieldStats>,
+    /// Objects seen as items of the array at each path
+    items: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FieldStats {
+    field_type: Option<FieldType>,
+    /// Samples in which the field was present and not null
+    present: usize,
+    nullable: bool,
+    values: BTreeSet<String>,
+    /// More distinct values were seen than `MAX_ENUM_VALUES`
+    open_values: bool,
+    /// Path of the array whose items hold the field, if any
+    array: Option<String>,
+}
+
+impl SchemaInference {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of events ingested so far
+    pub fn sample_count(&self) -> usize {
+        self.samples
+    }
+
+    /// Ingests a single event, which must be a JSON object
+    pub fn add_sample(&mut self, event: &JsonValue) -> Result<(), String> {
+        match event {
+            JsonValue::Object(_) => {
+                self.samples += 1;
+                self.observe_object(event, "", None);
+                Ok(())
+            }
+            other => Err(format!(
+                "Sample events must be JSON objects, found {}",
+                json_kind(other)
+            )),
+        }
+    }
+
+    /// Ingests events from a JSON document or from newline-delimited JSON.
+    ///
+    /// A document may hold a single event or an array of events. Returns the
+    /// number of events read.
+    pub fn add_samples_from_str(&mut self, input: &str) -> Result<usize, String> {
+        let events = match serde_json::from_str::<JsonValue>(input) {
+            Ok(JsonValue::Array(events)) => events,
+            Ok(event) => vec![event],
+            Err(_) => input
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    serde_json::from_str(line)
+                        .map_err(|e| format!("Invalid JSON on line {}: {}", i + 1, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        for event in &events {
+            self.add_sample(event)?;
+        }
+        Ok(events.len())
+    }
+
+    /// Schema describing every sample ingested so far
+    pub fn schema(&self) -> EventSchema {
+        let mut schema = EventSchema::new();
+
+        for (path, stats) in &self.fields {
+            let field_type = stats.field_type.clone().unwrap_or(FieldType::Any);
+            let enum_like = field_type == FieldType::String
+                && !stats.open_values
+                && !stats.values.is_empty()
+                && stats.values.len() < stats.present;
+
+            let holders = match &stats.array {
+                Some(array) => self.items.get(array).copied().unwrap_or_default(),
+                None => self.samples,
+            };
+            schema.insert(
+                path.clone(),
+                FieldSpec {
+                    optional: stats.nullable || stats.present < holders,
+                    values: enum_like.then(|| stats.values.iter().cloned().collect()),
+                    field_type,
+                },
+            );
+        }
+
+        schema
+    }
+
+    // `array` is the path of the innermost array the object is an item of
+    fn observe_object(&mut self, object: &JsonValue, prefix: &str, array: Option<&str>) {
+        let JsonValue::Object(fields) = object else {
+            return;
+        };
+
+        for (name, value) in fields {
+            let path = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            self.observe(&path, value, array);
+            self.observe_nested(value, &path, array);
+        }
+    }
+
+    // Objects in nested arrays are items of the outer array, so their fields
+    // share its path
+    fn observe_nested(&mut self, value: &JsonValue, path: &str, array: Option<&str>) {
+        match value {
+            JsonValue::Object(_) => self.observe_object(value, path, array),
+            JsonValue::Array(items) => {
+                for item in items {
+                    if item.is_object() {
+                        *self.items.entry(path.to_string()).or_default() += 1;
+                        self.observe_object(item, path, Some(path));
+                    } else {
+                        self.observe_nested(item, path, array);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn observe(&mut self, path: &str, value: &JsonValue, array: Option<&str>) {
+        let stats = self.fields.entry(path.to_string()).or_default();
+        stats.array = array.map(str::to_string);
+
+        if value.is_null() {
+            stats.nullable = true;
+            return;
+        }
+
+        stats.present += 1;
+        let found = value_type(value);
+        stats.field_type = Some(match stats.field_type.take() {
+            Some(known) => merge_types(known, found),
+            None => found,
+        });
+
+        if let JsonValue::String(s) = value {
+            if !stats.open_values {
+                stats.values.insert(s.clone());
+                if stats.values.len() > MAX_ENUM_VALUES {
+                    stats.open_values = true;
+                    stats.values.clear();
+                }
+            }
+        }
+    }
+}
+
+/// Fields the model reads that are not described by `schema`.
+///
+/// Run against an inferred schema this lists the fields that never appeared in
+/// the samples.
+pub fn unobserved_fields(model: &LrolModel, schema: &EventSchema) -> Vec<FieldReference> {
+    referenced_fields(model, |path| schema.resolve(path).is_some())
+        .into_iter()
+        .filter(|reference| schema.resolve(&reference.field).is_none())
+        .collect()
+}
+
+fn value_type(value: &JsonValue) -> FieldType {
+    match value {
+        JsonValue::Null => FieldType::Any,
+        JsonValue::Bool(_) => FieldType::Boolean,
+        JsonValue::Number(_) => FieldType::Number,
+        JsonValue::String(s) if is_timestamp(s) => FieldType::DateTime,
+        JsonValue::String(_) => FieldType::String,
+        JsonValue::Object(_) => FieldType::Object,
+        JsonValue::Array(items) => {
+            let item = items
+                .iter()
+                .filter(|item| !item.is_null())
+                .map(value_type)
+                .reduce(merge_types);
+            FieldType::Array(Box::new(item.unwrap_or(FieldType::Any)))
+        }
+    }
+}
+
+// Combine the types of two observations of the same field
+fn merge_types(a: FieldType, b: FieldType) -> FieldType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (FieldType::Array(a), FieldType::Array(b)) => match (*a, *b) {
+            // An empty array tells nothing about the item type
+            (FieldType::Any, item) | (item, FieldType::Any) => FieldType::Array(Box::new(item)),
+            (a, b) => FieldType::Array(Box::new(merge_types(a, b))),
+        },
+        (FieldType::DateTime, FieldType::String) | (FieldType::String, FieldType::DateTime) => {
+            FieldType::String
+        }
+        _ => FieldType::Any,
+    }
+}
+
+fn is_timestamp(s: &str) -> bool {
+    DateTime::parse_from_rfc3339(s).is_ok() || NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+fn json_kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::comparison;
+    use lrol_parser::Value;
+    use serde_json::json;
+
+    const SAMPLES: &str = r#"
+{"status": "AUTHORIZATION", "amount": 48, "created": "2024-10-14T15:10:07Z", "avs_address": null, "events": [], "merchant": {"state": "NY"}}
+{"status": "AUTHORIZATION", "amount": 12.5, "created": "2024-10-14T16:00:00Z", "avs_address": "1 Main St", "events": [{"id": 1}], "merchant": {"state": "CT"}, "tags": [["a"], ["b", "c"]]}
+{"status": "FINANCIAL_AUTHORIZATION", "amount": 300, "created": "2024-10-15T09:30:00Z", "events": [], "merchant": {"state": "NY"}}
+"#;
+
+    fn inferred() -> EventSchema {
+        let mut inference = SchemaInference::new();
+        assert_eq!(inference.add_samples_from_str(SAMPLES).unwrap(), 3);
+        inference.schema()
+    }
+
+    #[test]
+    fn test_infer_types_and_optionality() {
+        let schema = inferred();
+
+        assert_eq!(schema.resolve("amount"), Some(FieldType::Number));
+        assert_eq!(schema.resolve("created"), Some(FieldType::DateTime));
+        assert_eq!(schema.resolve("merchant"), Some(FieldType::Object));
+        assert_eq!(schema.resolve("merchant.state"), Some(FieldType::String));
+        assert_eq!(
+            schema.resolve("events"),
+            Some(FieldType::Array(Box::new(FieldType::Object)))
+        );
+        assert_eq!(
+            schema.resolve("tags"),
+            Some(FieldType::Array(Box::new(FieldType::Array(Box::new(
+                FieldType::String
+            )))))
+        );
+
+        assert!(!schema.get("amount").unwrap().optional);
+        assert!(schema.get("avs_address").unwrap().optional);
+        assert!(schema.get("tags").unwrap().optional);
+    }
+
+    #[test]
+    fn test_infer_array_items() {
+        let mut inference = SchemaInference::new();
+        inference
+            .add_samples_from_str(
+                r#"
+{"items": [{"sku": "A1", "price": 10, "discount": {"code": "X"}}, {"sku": "B2", "price": 2.5}]}
+{"items": [{"sku": "C3", "price": 7}], "batches": [[{"id": 1}], [{"id": 2}, {"id": 3, "note": "late"}]]}
+{"items": []}
+"#,
+            )
+            .unwrap();
+        let schema = inference.schema();
+
+        assert_eq!(schema.resolve("items.sku"), Some(FieldType::String));
+        assert_eq!(schema.resolve("items.price"), Some(FieldType::Number));
+        assert_eq!(
+            schema.resolve("items.discount.code"),
+            Some(FieldType::String)
+        );
+        assert_eq!(schema.resolve("batches.id"), Some(FieldType::Number));
+
+        // Optional when some item lacks the field, whatever the sample count
+        assert!(!schema.get("items.sku").unwrap().optional);
+        assert!(schema.get("items.discount").unwrap().optional);
+        assert!(!schema.get("batches.id").unwrap().optional);
+        assert!(schema.get("batches.note").unwrap().optional);
+    }
+
+    #[test]
+    fn test_infer_enum_values() {
+        let schema = inferred();
+
+        assert_eq!(
+            schema.get("status").unwrap().values,
+            Some(vec![
+                "AUTHORIZATION".to_string(),
+                "FINANCIAL_AUTHORIZATION".to_string()
+            ])
+        );
+        // Every value seen once: nothing suggests a closed set
+        assert_eq!(schema.get("avs_address").unwrap().values, None);
+    }
+
+    #[test]
+    fn test_samples_as_json_array() {
+        let mut inference = SchemaInference::new();
+        let count = inference
+            .add_samples_from_str(r#"[{"amount": 1}, {"amount": "1"}]"#)
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(inference.schema().resolve("amount"), Some(FieldType::Any));
+        assert!(inference.add_sample(&json!([1, 2])).is_err());
+        assert!(inference
+            .add_samples_from_str("{\"a\": 1}\nnot json")
+            .is_err());
+    }
+
+    #[test]
+    fn test_unobserved_fields() {
+        let model = LrolModel {
+            evaluations: vec![
+                comparison(
+                    "Balance_Check",
+                    "amount",
+                    "<",
+                    Value::String("profile.available_balance".to_string()),
+                ),
+                comparison(
+                    "State_Check",
+                    "merchant.state",
+                    "==",
+                    Value::String("CT".to_string()),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let missing = unobserved_fields(&model, &inferred());
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].evaluation_name, "Balance_Check");
+        assert_eq!(missing[0].field, "profile.available_balance");
+    }
+}
@@ -0,0 +1,753 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use lrol_parser::{parser::LrolModel, Evaluation, EvaluationType};
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::{conflict, Formula, Literal};
+use crate::error::AnalyzerError;
+use crate::graph::DependencyGraph;
+
+/// Models whose weights depend on more independent evaluations than this are not analyzed
+pub const MAX_SCORING_INPUTS: usize = 16;
+
+/// Highest weight an evaluation can declare
+pub const MAX_WEIGHT: i32 = 5;
+
+// Scores within this distance of the threshold are treated as reaching it
+const SCORE_EPSILON: f64 = 1e-9;
+
+/// How the weights of firing evaluations combine into a score in `[0, 1]`
+pub trait ScoringStrategy {
+    /// Evaluations whose weights count towards the score, in declaration order.
+    /// By default every evaluation with a positive weight.
+    fn inputs<'a>(&self, model: &'a LrolModel) -> Vec<(&'a str, i32)> {
+        weighted_evaluations(model)
+    }
+
+    /// Score given each input's weight and whether it fired
+    fn score(&self, weights: &[(i32, bool)]) -> f64;
+}
+
+/// Sum of the weights of firing evaluations over the sum of all weights
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedRatio;
+
+impl ScoringStrategy for WeightedRatio {
+    fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        let total: i32 = weights.iter().map(|(w, _)| w).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        fired(weights).sum::<i32>() as f64 / total as f64
+    }
+}
+
+/// [`WeightedRatio`] over root evaluations only, those no other evaluation
+/// depends on, so that a logical evaluation's operands do not count twice
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RootWeightedRatio;
+
+impl ScoringStrategy for RootWeightedRatio {
+    fn inputs<'a>(&self, model: &'a LrolModel) -> Vec<(&'a str, i32)> {
+        let graph = DependencyGraph::from_model(model);
+        weighted_evaluations(model)
+            .into_iter()
+            .filter(|(name, _)| graph.dependents(name).is_empty())
+            .collect()
+    }
+
+    fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        WeightedRatio.score(weights)
+    }
+}
+
+/// Highest weight among firing evaluations over [`MAX_WEIGHT`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxWeight;
+
+impl ScoringStrategy for MaxWeight {
+    fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        fired(weights)
+            .max()
+            .map_or(0.0, |w| w as f64 / MAX_WEIGHT as f64)
+    }
+}
+
+/// Each firing evaluation independently raises the score by `weight / 5` of
+/// what is left: `1 - Π(1 - weight / 5)`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoisyOr;
+
+impl ScoringStrategy for NoisyOr {
+    fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        1.0 - fired(weights)
+            .map(|w| 1.0 - (w as f64 / MAX_WEIGHT as f64).min(1.0))
+            .product::<f64>()
+    }
+}
+
+/// Logistic curve of the firing weights, centred on half the total weight:
+/// `1 / (1 + e^-(fired - total / 2))`. The score is 0.5 when half the weight
+/// fires and approaches 0 and 1 without reaching them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Logistic;
+
+impl ScoringStrategy for Logistic {
+    fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        let total: i32 = weights.iter().map(|(w, _)| w).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let excess = fired(weights).sum::<i32>() as f64 - total as f64 / 2.0;
+        1.0 / (1.0 + (-excess).exp())
+    }
+}
+
+fn fired(weights: &[(i32, bool)]) -> impl Iterator<Item = i32> + '_ {
+    weights.iter().filter(|(_, fired)| *fired).map(|(w, _)| *w)
+}
+
+/// Evaluations that can fire and have a positive weight, the first of each name
+pub fn weighted_evaluations(model: &LrolModel) -> Vec<(&str, i32)> {
+    let mut seen = BTreeSet::new();
+    model
+        .evaluations
+        .iter()
+        .filter(|e| seen.insert(e.name.as_str()))
+        .filter(|e| can_fire(e))
+        .filter_map(|e| Some((e.name.as_str(), e.weight.filter(|w| *w > 0)?)))
+        .collect()
+}
+
+/// Whether an evaluation yields true or false. Aggregations yield a number
+/// instead, so they never fire and their weight is not scored.
+pub fn can_fire(evaluation: &Evaluation) -> bool {
+    evaluation.evaluation_type != EvaluationType::Aggregation
+}
+
+/// Scoring strategy a model can name in its `scoring` field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringFormula {
+    /// See [`WeightedRatio`]
+    #[default]
+    WeightedRatio,
+    /// See [`RootWeightedRatio`]
+    RootWeightedRatio,
+    /// See [`MaxWeight`]
+    MaxWeight,
+    /// See [`NoisyOr`]
+    NoisyOr,
+    /// See [`Logistic`]
+    Logistic,
+}
+
+impl ScoringFormula {
+    pub const ALL: [ScoringFormula; 5] = [
+        ScoringFormula::WeightedRatio,
+        ScoringFormula::RootWeightedRatio,
+        ScoringFormula::MaxWeight,
+        ScoringFormula::NoisyOr,
+        ScoringFormula::Logistic,
+    ];
+
+    pub fn strategy(&self) -> &'static dyn ScoringStrategy {
+        match self {
+            ScoringFormula::WeightedRatio => &WeightedRatio,
+            ScoringFormula::RootWeightedRatio => &RootWeightedRatio,
+            ScoringFormula::MaxWeight => &MaxWeight,
+            ScoringFormula::NoisyOr => &NoisyOr,
+            ScoringFormula::Logistic => &Logistic,
+        }
+    }
+
+    /// Formula named by the model's `scoring` field, or `fallback` when the
+    /// model names none or names an unknown one
+    pub fn for_model(model: &LrolModel, fallback: ScoringFormula) -> ScoringFormula {
+        model
+            .scoring
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(fallback)
+    }
+
+    /// Score of a rule given each weighted evaluation and whether it fired
+    pub fn score(&self, weights: &[(i32, bool)]) -> f64 {
+        self.strategy().score(weights)
+    }
+
+    /// Score of a model given whether each evaluation fired
+    pub fn score_model(&self, model: &LrolModel, fired: impl Fn(&str) -> bool) -> f64 {
+        let weights: Vec<(i32, bool)> = self
+            .strategy()
+            .inputs(model)
+            .into_iter()
+            .map(|(name, weight)| (weight, fired(name)))
+            .collect();
+        self.score(&weights)
+    }
+}
+
+impl fmt::Display for ScoringFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoringFormula::WeightedRatio => write!(f, "weighted_ratio"),
+            ScoringFormula::RootWeightedRatio => write!(f, "root_weighted_ratio"),
+            ScoringFormula::MaxWeight => write!(f, "max_weight"),
+            ScoringFormula::NoisyOr => write!(f, "noisy_or"),
+            ScoringFormula::Logistic => write!(f, "logistic"),
+        }
+    }
+}
+
+impl FromStr for ScoringFormula {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase().replace('-', "_");
+        ScoringFormula::ALL
+            .into_iter()
+            .find(|formula| formula.to_string() == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown scoring formula '{}', expected weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic",
+                    name
+                )
+            })
+    }
+}
+
+/// Scores a rule can reach under a scoring formula
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoringAnalysis {
+    pub formula: ScoringFormula,
+    pub threshold: f64,
+    pub min_score: f64,
+    pub max_score: f64,
+    /// Smallest sets of weighted evaluations whose firing triggers the rule
+    pub minimal_trigger_sets: Vec<Vec<String>>,
+    /// Weighted evaluations whose weight never decides whether the rule triggers
+    pub ineffective_weights: Vec<String>,
+}
+
+impl ScoringAnalysis {
+    pub fn is_reachable(&self) -> bool {
+        reaches(self.max_score, self.threshold)
+    }
+
+    pub fn is_always_reached(&self) -> bool {
+        reaches(self.min_score, self.threshold)
+    }
+}
+
+/// Enumerates which weighted evaluations can fire together and scores each combination.
+///
+/// Logical evaluations fire exactly when their operands do, aggregations never
+/// fire, as in the engine, and comparisons on the same field that contradict
+/// each other never fire together; every other evaluation is treated as
+/// independent. Returns `None` when the model has no
+/// weighted evaluation or depends on more than [`MAX_SCORING_INPUTS`]
+/// independent evaluations.
+pub fn analyze_scoring(model: &LrolModel, formula: ScoringFormula) -> Option<ScoringAnalysis> {
+    let mut evaluations: HashMap<&str, &Evaluation> = HashMap::new();
+    for evaluation in &model.evaluations {
+        evaluations.entry(&evaluation.name).or_insert(evaluation);
+    }
+
+    let weighted = formula.strategy().inputs(model);
+    if weighted.is_empty() {
+        return None;
+    }
+
+    let mut inputs = Vec::new();
+    for (name, _) in &weighted {
+        collect_inputs(name, &evaluations, &mut Vec::new(), &mut inputs);
+    }
+    if inputs.len() > MAX_SCORING_INPUTS {
+        return None;
+    }
+
+    // Field comparisons among the inputs, to rule out impossible combinations
+    let atoms: Vec<Option<Literal>> = inputs
+        .iter()
+        .map(|name| match Formula::from_model(model, name) {
+            Some(Formula::Literal(literal @ Literal::Atom(_))) => Some(literal),
+            _ => None,
+        })
+        .collect();
+
+    let threshold = model.threshold;
+    let mut min_score = f64::INFINITY;
+    let mut max_score = f64::NEG_INFINITY;
+    let mut trigger_sets: BTreeSet<Vec<String>> = BTreeSet::new();
+    let mut pivotal = vec![false; weighted.len()];
+
+    for assignment in 0u32..(1 << inputs.len()) {
+        let fires = |i: usize| assignment & (1 << i) != 0;
+
+        let term: Vec<Literal> = atoms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, atom)| {
+                let atom = atom.as_ref()?;
+                Some(if fires(i) {
+                    atom.clone()
+                } else {
+                    atom.negate()
+                })
+            })
+            .collect();
+        if conflict(&term).is_some() {
+            continue;
+        }
+
+        let values: HashMap<&str, bool> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, fires(i)))
+            .collect();
+        let weights: Vec<(i32, bool)> = weighted
+            .iter()
+            .map(|(name, weight)| (*weight, value(name, &evaluations, &values, &mut Vec::new())))
+            .collect();
+
+        let score = formula.score(&weights);
+        let triggered = reaches(score, threshold);
+        min_score = min_score.min(score);
+        max_score = max_score.max(score);
+
+        if triggered {
+            trigger_sets.insert(
+                weighted
+                    .iter()
+                    .zip(&weights)
+                    .filter(|(_, (_, fired))| *fired)
+                    .map(|((name, _), _)| name.to_string())
+                    .collect(),
+            );
+        }
+
+        for (i, (weight, fired)) in weights.iter().enumerate() {
+            if *fired && !pivotal[i] {
+                let mut without = weights.clone();
+                without[i] = (*weight, false);
+                pivotal[i] = triggered != reaches(formula.score(&without), threshold);
+            }
+        }
+    }
+
+    if min_score > max_score {
+        return None;
+    }
+
+    let mut minimal_trigger_sets: Vec<Vec<String>> = trigger_sets
+        .iter()
+        .filter(|set| {
+            !trigger_sets
+                .iter()
+                .any(|other| other.len() < set.len() && other.iter().all(|n| set.contains(n)))
+        })
+        .cloned()
+        .collect();
+    minimal_trigger_sets.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    Some(ScoringAnalysis {
+        formula,
+        threshold,
+        min_score,
+        max_score,
+        minimal_trigger_sets,
+        ineffective_weights: weighted
+            .iter()
+            .zip(&pivotal)
+            .filter(|(_, pivotal)| !**pivotal)
+            .map(|((name, _), _)| name.to_string())
+            .collect(),
+    })
+}
+
+/// Reports thresholds that can never or always be reached, weights that never
+/// matter and single low-weight evaluations that trigger the rule on their own
+pub fn check_scoring(
+    model: &LrolModel,
+    formula: ScoringFormula,
+    warnings: &mut Vec<AnalyzerError>,
+) {
+    for evaluation in &model.evaluations {
+        if !can_fire(evaluation) && evaluation.weight.is_some_and(|w| w > 0) {
+            warnings.push(AnalyzerError::IneffectiveWeight {
+                evaluation_name: evaluation.name.clone(),
+                weight: evaluation.weight.unwrap_or_default(),
+                reason:
+                    "an aggregation yields a number rather than firing, so its weight is not scored"
+                        .to_string(),
+            });
+        }
+    }
+
+    let Some(analysis) = analyze_scoring(model, formula) else {
+        return;
+    };
+
+    if !analysis.is_reachable() {
+        warnings.push(AnalyzerError::UnreachableThreshold {
+            threshold: analysis.threshold,
+            max_score: analysis.max_score,
+            formula: formula.to_string(),
+        });
+        return;
+    }
+    if analysis.is_always_reached() {
+        warnings.push(AnalyzerError::ThresholdAlwaysReached {
+            threshold: analysis.threshold,
+            min_score: analysis.min_score,
+            formula: formula.to_string(),
+        });
+        return;
+    }
+
+    let weight_of = |name: &str| {
+        model
+            .evaluations
+            .iter()
+            .find(|e| e.name == name)
+            .and_then(|e| e.weight)
+            .unwrap_or_default()
+    };
+    let heaviest = model
+        .evaluations
+        .iter()
+        .filter(|e| can_fire(e))
+        .filter_map(|e| e.weight)
+        .max()
+        .unwrap_or_default();
+
+    for set in &analysis.minimal_trigger_sets {
+        if let [name] = set.as_slice() {
+            let weight = weight_of(name);
+            if weight < heaviest {
+                warnings.push(AnalyzerError::LowWeightTrigger {
+                    evaluation_name: name.clone(),
+                    weight,
+                    threshold: analysis.threshold,
+                });
+            }
+        }
+    }
+
+    for name in &analysis.ineffective_weights {
+        warnings.push(AnalyzerError::IneffectiveWeight {
+            evaluation_name: name.clone(),
+            weight: weight_of(name),
+            reason: format!(
+                "the rule triggers the same way with or without it under {} scoring",
+                formula
+            ),
+        });
+    }
+}
+
+/// Whether a score reaches the threshold, allowing for rounding in the formulas
+pub fn reaches(score: f64, threshold: f64) -> bool {
+    score + SCORE_EPSILON >= threshold
+}
+
+// Logical evaluations combine their operands; anything else is an input
+fn is_combinator(evaluation: &Evaluation) -> bool {
+    evaluation.evaluation_type == EvaluationType::Logical
+        && evaluation.operands.as_ref().is_some_and(|o| !o.is_empty())
+        && matches!(evaluation.operator.as_deref(), Some("AND") | Some("OR"))
+}
+
+fn collect_inputs<'a>(
+    name: &'a str,
+    evaluations: &HashMap<&str, &'a Evaluation>,
+    visiting: &mut Vec<&'a str>,
+    inputs: &mut Vec<&'a str>,
+) {
+    match evaluations.get(name) {
+        Some(evaluation) if is_combinator(evaluation) && !visiting.contains(&name) => {
+            visiting.push(name);
+            for operand in evaluation.operands.iter().flatten() {
+                collect_inputs(operand, evaluations, visiting, inputs);
+            }
+            visiting.pop();
+        }
+        // Aggregations never fire, so they are not a free input
+        Some(evaluation) if !can_fire(evaluation) => {}
+        _ => {
+            if !inputs.contains(&name) {
+                inputs.push(name);
+            }
+        }
+    }
+}
+
+fn value<'a>(
+    name: &'a str,
+    evaluations: &HashMap<&str, &'a Evaluation>,
+    inputs: &HashMap<&str, bool>,
+    visiting: &mut Vec<&'a str>,
+) -> bool {
+    if let Some(value) = inputs.get(name) {
+        return *value;
+    }
+    let Some(evaluation) = evaluations.get(name) else {
+        return false;
+    };
+    if !can_fire(evaluation) || visiting.contains(&name) {
+        return false;
+    }
+
+    visiting.push(name);
+    let mut operands = Vec::new();
+    for operand in evaluation.operands.iter().flatten() {
+        operands.push(value(operand, evaluations, inputs, visiting));
+    }
+    visiting.pop();
+
+    match evaluation.operator.as_deref() {
+        Some("AND") => operands.iter().all(|v| *v),
+        _ => operands.iter().any(|v| *v),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, evaluation, logical, weighted};
+    use lrol_parser::Value;
+
+    fn model(threshold: f64, evaluations: Vec<Evaluation>) -> LrolModel {
+        LrolModel {
+            threshold,
+            evaluations,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_formulas() {
+        let weights = [(5, true), (3, false), (2, true)];
+        assert_eq!(ScoringFormula::WeightedRatio.score(&weights), 0.7);
+        assert_eq!(ScoringFormula::MaxWeight.score(&weights), 1.0);
+        assert!((ScoringFormula::NoisyOr.score(&[(2, true), (1, true)]) - 0.52).abs() < 1e-9);
+        assert_eq!(ScoringFormula::NoisyOr.score(&[(2, false)]), 0.0);
+        assert_eq!("max-weight".parse(), Ok(ScoringFormula::MaxWeight));
+        assert!("average".parse::<ScoringFormula>().is_err());
+    }
+
+    #[test]
+    fn test_strategies_and_model_scoring() {
+        // Big and Young feed Both, so only Both and Foreign are roots
+        let mut model = model(
+            0.5,
+            vec![
+                weighted(comparison("Big", "amount", ">", Value::Number(1000.0)), 3),
+                weighted(comparison("Young", "age_days", "<", Value::Number(30.0)), 1),
+                weighted(logical("Both", "AND", &["Big", "Young"]), 4),
+                weighted(
+                    comparison("Foreign", "country_risk", ">", Value::Number(0.5)),
+                    2,
+                ),
+            ],
+        );
+        let roots = RootWeightedRatio.inputs(&model);
+        assert_eq!(roots, vec![("Both", 4), ("Foreign", 2)]);
+
+        let fired = |name: &str| name == "Big" || name == "Foreign";
+        assert_eq!(
+            ScoringFormula::WeightedRatio.score_model(&model, fired),
+            0.5
+        );
+        assert!(
+            (ScoringFormula::RootWeightedRatio.score_model(&model, fired) - 1.0 / 3.0).abs() < 1e-9
+        );
+        assert_eq!(ScoringFormula::Logistic.score_model(&model, fired), 0.5);
+        assert_eq!(ScoringFormula::Logistic.score(&[]), 0.0);
+
+        assert_eq!(
+            "root-weighted-ratio".parse(),
+            Ok(ScoringFormula::RootWeightedRatio)
+        );
+        assert_eq!(
+            ScoringFormula::for_model(&model, ScoringFormula::MaxWeight),
+            ScoringFormula::MaxWeight
+        );
+        model.scoring = Some("logistic".to_string());
+        assert_eq!(
+            ScoringFormula::for_model(&model, ScoringFormula::MaxWeight),
+            ScoringFormula::Logistic
+        );
+        model.scoring = Some("average".to_string());
+        assert_eq!(
+            ScoringFormula::for_model(&model, ScoringFormula::MaxWeight),
+            ScoringFormula::MaxWeight
+        );
+    }
+
+    #[test]
+    fn test_dependent_evaluations_fire_together() {
+        // The logical fires only with both comparisons, so the threshold is
+        // reached only when everything fires
+        let mut model = model(
+            0.9,
+            vec![
+                weighted(
+                    comparison("Large", "amount", ">", Value::Number(10000.0)),
+                    4,
+                ),
+                weighted(
+                    comparison("Young", "account_age_days", "<=", Value::Number(30.0)),
+                    3,
+                ),
+                weighted(logical("Both", "AND", &["Large", "Young"]), 5),
+            ],
+        );
+        let analysis = analyze_scoring(&model, ScoringFormula::WeightedRatio).unwrap();
+
+        assert_eq!(analysis.min_score, 0.0);
+        assert_eq!(analysis.max_score, 1.0);
+        assert_eq!(
+            analysis.minimal_trigger_sets,
+            vec![vec!["Large", "Young", "Both"]]
+        );
+        assert!(analysis.ineffective_weights.is_empty());
+
+        // With a lower threshold the logical alone decides the outcome
+        model.threshold = 0.6;
+        let analysis = analyze_scoring(&model, ScoringFormula::WeightedRatio).unwrap();
+        assert_eq!(analysis.ineffective_weights, vec!["Large", "Young"]);
+    }
+
+    #[test]
+    fn test_contradictory_comparisons_limit_score() {
+        let mut model = model(
+            0.9,
+            vec![
+                weighted(
+                    comparison("Large", "amount", ">", Value::Number(10000.0)),
+                    5,
+                ),
+                weighted(comparison("Small", "amount", "<", Value::Number(100.0)), 5),
+            ],
+        );
+        let analysis = analyze_scoring(&model, ScoringFormula::WeightedRatio).unwrap();
+        assert_eq!(analysis.max_score, 0.5);
+
+        let mut warnings = Vec::new();
+        check_scoring(&model, ScoringFormula::WeightedRatio, &mut warnings);
+        assert!(matches!(
+            warnings.as_slice(),
+            [AnalyzerError::UnreachableThreshold { max_score, .. }] if *max_score == 0.5
+        ));
+
+        model.threshold = 0.0;
+        warnings.clear();
+        check_scoring(&model, ScoringFormula::WeightedRatio, &mut warnings);
+        assert!(matches!(
+            warnings.as_slice(),
+            [AnalyzerError::ThresholdAlwaysReached { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_low_weight_trigger_and_ineffective_weight() {
+        let model = model(
+            0.35,
+            vec![
+                weighted(
+                    comparison("Large", "amount", ">", Value::Number(10000.0)),
+                    5,
+                ),
+                weighted(
+                    comparison("Foreign", "country_risk", ">", Value::Number(7.0)),
+                    2,
+                ),
+                weighted(comparison("Night", "hour", "<", Value::Number(6.0)), 1),
+            ],
+        );
+
+        let analysis = analyze_scoring(&model, ScoringFormula::MaxWeight).unwrap();
+        assert_eq!(
+            analysis.minimal_trigger_sets,
+            vec![vec!["Foreign".to_string()], vec!["Large".to_string()]]
+        );
+        assert_eq!(analysis.ineffective_weights, vec!["Night"]);
+
+        let mut warnings = Vec::new();
+        check_scoring(&model, ScoringFormula::MaxWeight, &mut warnings);
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                AnalyzerError::LowWeightTrigger { evaluation_name, weight: 2, .. },
+                AnalyzerError::IneffectiveWeight { evaluation_name: ineffective, .. },
+            ] if evaluation_name == "Foreign" && ineffective == "Night"
+        ));
+    }
+
+    #[test]
+    fn test_weighted_aggregation_is_not_scored() {
+        // Shape of R005: the aggregation's weight used to cap the score at 14/18
+        let model = model(
+            0.85,
+            vec![
+                weighted(comparison("Above", "amount", ">", Value::Number(100.0)), 5),
+                weighted(evaluation("Volume", EvaluationType::Aggregation), 4),
+                weighted(
+                    comparison("Exceeded", "@Volume", ">", Value::Number(1000.0)),
+                    4,
+                ),
+                weighted(logical("Logic", "OR", &["Above", "Exceeded"]), 5),
+                weighted(logical("Volume_Only", "OR", &["Volume"]), 1),
+            ],
+        );
+        assert_eq!(
+            weighted_evaluations(&model),
+            vec![
+                ("Above", 5),
+                ("Exceeded", 4),
+                ("Logic", 5),
+                ("Volume_Only", 1)
+            ]
+        );
+
+        let analysis = analyze_scoring(&model, ScoringFormula::WeightedRatio).unwrap();
+        // Volume_Only only has the aggregation to go on, so it never fires
+        assert!((analysis.max_score - 14.0 / 15.0).abs() < 1e-9);
+        assert!(analysis.is_reachable());
+        assert_eq!(
+            analysis.minimal_trigger_sets,
+            vec![vec!["Above", "Exceeded", "Logic"]]
+        );
+
+        let mut warnings = Vec::new();
+        check_scoring(&model, ScoringFormula::WeightedRatio, &mut warnings);
+        assert!(warnings.iter().any(|w| matches!(w,
+            AnalyzerError::IneffectiveWeight { evaluation_name, .. } if evaluation_name == "Volume"
+        )));
+        assert!(!warnings
+            .iter()
+            .any(|w| matches!(w, AnalyzerError::UnreachableThreshold { .. })));
+    }
+
+    #[test]
+    fn test_too_many_inputs() {
+        let evaluations = (0..=MAX_SCORING_INPUTS)
+            .map(|i| {
+                weighted(
+                    comparison(
+                        &format!("Check_{}", i),
+                        &format!("f{}", i),
+                        ">",
+                        Value::Number(0.0),
+                    ),
+                    3,
+                )
+            })
+            .collect();
+        assert!(analyze_scoring(&model(0.5, evaluations), ScoringFormula::WeightedRatio).is_none());
+    }
+}
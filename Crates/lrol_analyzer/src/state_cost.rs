@@ -0,0 +1,398 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use lrol_parser::{
+    parser::LrolModel, types::Aggregation, Condition, Evaluation, EvaluationType, Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::datetime::{DateTimeExpr, Duration, DurationUnit};
+use crate::error::AnalyzerError;
+
+/// Bytes kept per event in a window: its timestamp, the aggregated value and
+/// the engine's bookkeeping
+pub const DEFAULT_BYTES_PER_EVENT: u64 = 32;
+
+/// Bytes kept per entity and aggregation: the key and the running result
+pub const DEFAULT_BYTES_PER_ENTITY: u64 = 64;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const DAYS_PER_MONTH: f64 = 365.2425 / 12.0;
+
+/// Traffic a rule is deployed against, usually loaded from a JSON file:
+///
+/// ```json
+/// {
+///   "events_per_second": 2000,
+///   "entities": 5000000,
+///   "budget_bytes": 1073741824,
+///   "evaluations": { "Gambling_Sum": { "events_per_second": 20 } }
+/// }
+/// ```
+///
+/// `entities` is the number of distinct keys, such as cards, that state is
+/// kept for. `evaluations` overrides the rates for aggregations whose
+/// conditions only cover part of the traffic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateCostConfig {
+    pub events_per_second: f64,
+    pub entities: u64,
+    #[serde(default = "default_bytes_per_event")]
+    pub bytes_per_event: u64,
+    #[serde(default = "default_bytes_per_entity")]
+    pub bytes_per_entity: u64,
+    /// Memory the whole rule may use, warned about when exceeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub evaluations: BTreeMap<String, TrafficOverride>,
+}
+
+/// Rates of a single aggregation, replacing those of the whole rule
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrafficOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events_per_second: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<u64>,
+}
+
+fn default_bytes_per_event() -> u64 {
+    DEFAULT_BYTES_PER_EVENT
+}
+
+fn default_bytes_per_entity() -> u64 {
+    DEFAULT_BYTES_PER_ENTITY
+}
+
+impl StateCostConfig {
+    pub fn new(events_per_second: f64, entities: u64) -> Self {
+        StateCostConfig {
+            events_per_second,
+            entities,
+            bytes_per_event: DEFAULT_BYTES_PER_EVENT,
+            bytes_per_entity: DEFAULT_BYTES_PER_ENTITY,
+            budget_bytes: None,
+            evaluations: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_budget(mut self, budget_bytes: u64) -> Self {
+        self.budget_bytes = Some(budget_bytes);
+        self
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content)
+            .map_err(|e| format!("Invalid state cost configuration: {}", e))
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Failed to read state cost configuration {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Self::from_json(&content)
+    }
+}
+
+/// Estimated state of one aggregation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AggregationCost {
+    pub evaluation_name: String,
+    pub aggregation: Option<Aggregation>,
+    /// Time covered by the aggregation, `None` when no condition bounds it
+    pub window_seconds: Option<f64>,
+    pub events_per_second: f64,
+    pub entities: u64,
+    /// Events held in the window plus one running result per active entity
+    pub state_entries: Option<u64>,
+    pub memory_bytes: Option<u64>,
+}
+
+/// Estimated state of every aggregation of a rule
+#[derive(Debug, Clone, Serialize)]
+pub struct StateCostReport {
+    pub aggregations: Vec<AggregationCost>,
+    /// Sum over all aggregations, `None` when one of them is unbounded
+    pub total_state_entries: Option<u64>,
+    pub total_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_bytes: Option<u64>,
+    /// Unbounded windows and an exceeded budget
+    #[serde(skip)]
+    pub warnings: Vec<AnalyzerError>,
+}
+
+/// Estimates the state a streaming engine keeps for the aggregations of a model.
+///
+/// Sliding windows are assumed to be exact, so every aggregation holds each
+/// event of its window to expire it later, whatever its function. Conditions
+/// other than the time window are assumed to match every event unless the
+/// configuration overrides the rates, which makes the estimate an upper bound.
+pub fn estimate_state_cost(model: &LrolModel, config: &StateCostConfig) -> StateCostReport {
+    let mut warnings = Vec::new();
+    let aggregations: Vec<AggregationCost> = model
+        .evaluations
+        .iter()
+        .filter(|e| e.evaluation_type == EvaluationType::Aggregation)
+        .map(|evaluation| {
+            let traffic = config.evaluations.get(&evaluation.name);
+            let events_per_second = traffic
+                .and_then(|t| t.events_per_second)
+                .unwrap_or(config.events_per_second);
+            let entities = traffic.and_then(|t| t.entities).unwrap_or(config.entities);
+
+            let window_seconds = aggregation_window(evaluation);
+            if window_seconds.is_none() {
+                warnings.push(AnalyzerError::UnboundedAggregationWindow {
+                    evaluation_name: evaluation.name.clone(),
+                });
+            }
+
+            let (state_entries, memory_bytes) = window_seconds
+                .map(|window| {
+                    let events = (events_per_second * window).ceil() as u64;
+                    let active_entities = events.min(entities);
+                    (
+                        events + active_entities,
+                        events * config.bytes_per_event + active_entities * config.bytes_per_entity,
+                    )
+                })
+                .unzip();
+
+            AggregationCost {
+                evaluation_name: evaluation.name.clone(),
+                aggregation: evaluation.aggregation.clone(),
+                window_seconds,
+                events_per_second,
+                entities,
+                state_entries,
+                memory_bytes,
+            }
+        })
+        .collect();
+
+    let total_state_entries = aggregations.iter().map(|a| a.state_entries).sum();
+    let total_memory_bytes: Option<u64> = aggregations.iter().map(|a| a.memory_bytes).sum();
+
+    if let (Some(memory_bytes), Some(budget_bytes)) = (total_memory_bytes, config.budget_bytes) {
+        if memory_bytes > budget_bytes {
+            let largest = aggregations
+                .iter()
+                .max_by_key(|a| a.memory_bytes)
+                .map(|a| a.evaluation_name.clone())
+                .unwrap_or_default();
+            warnings.push(AnalyzerError::StateBudgetExceeded {
+                memory_bytes,
+                budget_bytes,
+                largest_evaluation: largest,
+            });
+        }
+    }
+
+    StateCostReport {
+        aggregations,
+        total_state_entries,
+        total_memory_bytes,
+        budget_bytes: config.budget_bytes,
+        warnings,
+    }
+}
+
+/// Length in seconds of the time window an aggregation's conditions limit it
+/// to, `None` when none of them does
+pub fn aggregation_window(evaluation: &Evaluation) -> Option<f64> {
+    evaluation
+        .conditions
+        .iter()
+        .flatten()
+        .filter_map(lookback)
+        .reduce(f64::min)
+}
+
+/// How far back a condition such as `transaction_date >= datetime(now, '-2 hours')`
+/// reaches, in seconds
+fn lookback(condition: &Condition) -> Option<f64> {
+    let Some(Value::String(right)) = &condition.right else {
+        return None;
+    };
+    let left = condition.left.as_deref()?;
+    let bound = match condition.operator.as_deref()?.trim() {
+        ">" | ">=" if DateTimeExpr::is_call(right) => right.as_str(),
+        "<" | "<=" if DateTimeExpr::is_call(left) => left,
+        _ => return None,
+    };
+    let seconds = expr_lookback(&DateTimeExpr::parse(bound).ok()?)?;
+    Some(seconds.max(0.0))
+}
+
+fn expr_lookback(expr: &DateTimeExpr) -> Option<f64> {
+    match expr {
+        DateTimeExpr::Now => Some(0.0),
+        DateTimeExpr::Shifted { base, duration } => {
+            Some(expr_lookback(base)? - duration_seconds(duration))
+        }
+        // The start of the current day is at most a day back, and so on
+        DateTimeExpr::StartOf { base, unit } => {
+            let longest = match unit {
+                DurationUnit::Weeks => 7.0 * SECONDS_PER_DAY,
+                DurationUnit::Months => 31.0 * SECONDS_PER_DAY,
+                DurationUnit::Years => 366.0 * SECONDS_PER_DAY,
+                _ => SECONDS_PER_DAY,
+            };
+            Some(expr_lookback(base)? + longest)
+        }
+        _ => None,
+    }
+}
+
+/// Length of a duration in seconds, counting months at their average length
+pub fn duration_seconds(duration: &Duration) -> f64 {
+    (duration.months as f64 * DAYS_PER_MONTH + duration.days as f64) * SECONDS_PER_DAY
+        + duration.milliseconds as f64 / 1000.0
+}
+
+/// Formats a window length with its largest whole unit, e.g. `30d` or `90m`,
+/// or rounded in the largest unit it spans, e.g. `365.2d`
+pub fn format_window(seconds: f64) -> String {
+    let units = [(SECONDS_PER_DAY, "d"), (3_600.0, "h"), (60.0, "m")];
+    for (size, suffix) in units {
+        if seconds >= size && (seconds / size).fract() == 0.0 {
+            return format!("{}{}", seconds / size, suffix);
+        }
+    }
+    match units.iter().find(|(size, _)| seconds >= *size) {
+        Some((size, suffix)) => format!("{:.1}{}", seconds / size, suffix),
+        None => format!("{}s", seconds),
+    }
+}
+
+/// Formats a byte count in binary units, e.g. `1.5 GiB`
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(evaluations: &str) -> LrolModel {
+        lrol_parser::parse_str(&format!(
+            r#"{{
+  "model_id": "S001",
+  "name": "State",
+  "threshold": 0.5,
+  "evaluations": [{}],
+  "actions": [{{"type": "flag_transaction", "reason": "Test"}}]
+}}"#,
+            evaluations
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_estimate_windows() {
+        let model = model(
+            r#"
+    {"name": "Recent_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 2,
+     "conditions": [{"left": "transaction_date", "operator": ">=", "right": "datetime(now, '-10 minutes')"}]},
+    {"name": "Monthly_Max", "type": "aggregation", "aggregation": "MAX", "field": "amount", "weight": 2,
+     "conditions": [
+       {"left": "transaction_date", "operator": ">", "right": "datetime(now, '-30 days')"},
+       {"left": "merchant_category", "operator": "==", "right": "gambling"}]},
+    {"name": "Today_Count", "type": "aggregation", "aggregation": "COUNT", "field": "amount", "weight": 1,
+     "conditions": [{"left": "datetime(now, 'start of day')", "operator": "<=", "right": "transaction_date"}]},
+    {"name": "Amount_Check", "type": "comparison", "left": "amount", "operator": ">", "right": 100, "weight": 1}"#,
+        );
+        let mut config = StateCostConfig::new(100.0, 1_000);
+        config.evaluations.insert(
+            "Monthly_Max".to_string(),
+            TrafficOverride {
+                events_per_second: Some(0.01),
+                entities: None,
+            },
+        );
+        let report = estimate_state_cost(&model, &config);
+
+        assert_eq!(report.aggregations.len(), 3);
+        let recent = &report.aggregations[0];
+        assert_eq!(recent.window_seconds, Some(600.0));
+        assert_eq!(recent.state_entries, Some(60_000 + 1_000));
+        assert_eq!(
+            recent.memory_bytes,
+            Some(60_000 * DEFAULT_BYTES_PER_EVENT + 1_000 * DEFAULT_BYTES_PER_ENTITY)
+        );
+
+        let monthly = &report.aggregations[1];
+        assert_eq!(monthly.window_seconds, Some(30.0 * SECONDS_PER_DAY));
+        assert_eq!(monthly.events_per_second, 0.01);
+        assert_eq!(monthly.state_entries, Some(25_920 + 1_000));
+
+        assert_eq!(report.aggregations[2].window_seconds, Some(SECONDS_PER_DAY));
+        assert!(report.warnings.is_empty());
+        assert_eq!(
+            report.total_state_entries,
+            Some(61_000 + 26_920 + 8_640_000 + 1_000)
+        );
+    }
+
+    #[test]
+    fn test_unbounded_and_over_budget() {
+        let model = model(
+            r#"
+    {"name": "Lifetime_Sum", "type": "aggregation", "aggregation": "SUM", "field": "amount", "weight": 2},
+    {"name": "Hourly_Avg", "type": "aggregation", "aggregation": "AVG", "field": "amount", "weight": 2,
+     "conditions": [{"left": "transaction_date", "operator": ">=", "right": "datetime(now, 'PT1H')"}]}"#,
+        );
+        let report = estimate_state_cost(&model, &StateCostConfig::new(10.0, 5).with_budget(1));
+        assert!(matches!(&report.warnings[..], [
+            AnalyzerError::UnboundedAggregationWindow { evaluation_name }
+        ] if evaluation_name == "Lifetime_Sum"));
+        assert_eq!(report.total_memory_bytes, None);
+        // A window ending in the future holds nothing yet
+        assert_eq!(report.aggregations[1].state_entries, Some(0));
+
+        let model = model_with_window("-1 hour");
+        let report = estimate_state_cost(&model, &StateCostConfig::new(10.0, 5).with_budget(1024));
+        assert!(matches!(&report.warnings[..], [
+            AnalyzerError::StateBudgetExceeded { memory_bytes, budget_bytes: 1024, largest_evaluation }
+        ] if *memory_bytes == 36_000 * 32 + 5 * 64 && largest_evaluation == "Windowed"));
+    }
+
+    fn model_with_window(duration: &str) -> LrolModel {
+        model(&format!(
+            r#"{{"name": "Windowed", "type": "aggregation", "aggregation": "STDDEV", "field": "amount", "weight": 1,
+     "conditions": [{{"left": "transaction_date", "operator": ">=", "right": "datetime(now, '{}')"}}]}}"#,
+            duration
+        ))
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_window(600.0), "10m");
+        assert_eq!(format_window(30.0 * SECONDS_PER_DAY), "30d");
+        assert_eq!(format_window(5400.0), "90m");
+        assert_eq!(format_window(31_556_952.0), "365.2d");
+        assert_eq!(format_window(45.0), "45s");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+}
@@ -0,0 +1,41 @@
+use lrol_parser::{types::EvaluationType, Evaluation, Value};
+
+/// Evaluation of the given type with weight 3 and no other fields set
+pub fn evaluation(name: &str, evaluation_type: EvaluationType) -> Evaluation {
+    Evaluation {
+        name: name.to_string(),
+        evaluation_type,
+        left: None,
+        operator: None,
+        right: None,
+        operands: None,
+        weight: Some(3),
+        aggregation: None,
+        field: None,
+        conditions: None,
+    }
+}
+
+pub fn comparison(name: &str, left: &str, operator: &str, right: Value) -> Evaluation {
+    Evaluation {
+        left: Some(left.to_string()),
+        operator: Some(operator.to_string()),
+        right: Some(right),
+        ..evaluation(name, EvaluationType::Comparison)
+    }
+}
+
+pub fn logical(name: &str, operator: &str, operands: &[&str]) -> Evaluation {
+    Evaluation {
+        operator: Some(operator.to_string()),
+        operands: Some(operands.iter().map(|o| o.to_string()).collect()),
+        ..evaluation(name, EvaluationType::Logical)
+    }
+}
+
+pub fn weighted(evaluation: Evaluation, weight: i32) -> Evaluation {
+    Evaluation {
+        weight: Some(weight),
+        ..evaluation
+    }
+}
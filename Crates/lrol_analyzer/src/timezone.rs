@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use chrono_tz::Tz;
+use lrol_parser::{parser::LrolModel, TimezoneSpec};
+
+use crate::error::AnalyzerError;
+
+/// Parses an IANA zone name such as `America/New_York` or `UTC`
+pub fn parse_zone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("Unknown timezone '{}'", name))
+}
+
+/// Timezone of a [`TimezoneSpec`] with its zone names resolved
+#[derive(Debug, Clone, PartialEq)]
+pub enum Timezone {
+    Zone(Tz),
+    /// Zone read per event from `field`, mapped through `zones` when it is not empty
+    Field {
+        field: String,
+        zones: BTreeMap<String, Tz>,
+        default: Option<Tz>,
+    },
+}
+
+impl Timezone {
+    pub fn from_spec(spec: &TimezoneSpec) -> Result<Self, String> {
+        match spec {
+            TimezoneSpec::Zone(zone) => parse_zone(zone).map(Timezone::Zone),
+            TimezoneSpec::Field {
+                field,
+                zones,
+                default,
+            } => Ok(Timezone::Field {
+                field: field.clone(),
+                zones: zones
+                    .iter()
+                    .map(|(key, zone)| Ok((key.clone(), parse_zone(zone)?)))
+                    .collect::<Result<_, String>>()?,
+                default: default.as_deref().map(parse_zone).transpose()?,
+            }),
+        }
+    }
+
+    /// Zone for an event whose fields are read as text with `field`
+    pub fn resolve(&self, field: &dyn Fn(&str) -> Option<String>) -> Option<Tz> {
+        match self {
+            Timezone::Zone(zone) => Some(*zone),
+            Timezone::Field {
+                field: name,
+                zones,
+                default,
+            } => field(name)
+                .and_then(|value| {
+                    if zones.is_empty() {
+                        parse_zone(&value).ok()
+                    } else {
+                        zones.get(value.trim()).copied()
+                    }
+                })
+                .or(*default),
+        }
+    }
+
+    /// Event field the zone is read from, if any
+    pub fn source_field(&self) -> Option<&str> {
+        match self {
+            Timezone::Zone(_) => None,
+            Timezone::Field { field, .. } => Some(field),
+        }
+    }
+}
+
+/// Timezones declared by a model: a rule-level default and per-field zones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timezones {
+    default: Option<Timezone>,
+    fields: BTreeMap<String, Timezone>,
+}
+
+impl Timezones {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the timezones of a model, reporting unknown zone names
+    pub fn from_model(model: &LrolModel) -> (Self, Vec<AnalyzerError>) {
+        let mut timezones = Timezones::new();
+        let mut errors = Vec::new();
+        let mut resolve = |field: Option<&String>, spec: &TimezoneSpec| {
+            Timezone::from_spec(spec)
+                .map_err(|reason| {
+                    errors.push(AnalyzerError::InvalidTimezone {
+                        field: field.cloned(),
+                        reason,
+                    })
+                })
+                .ok()
+        };
+
+        if let Some(ref spec) = model.timezone {
+            timezones.default = resolve(None, spec);
+        }
+        for (field, spec) in model.field_timezones.iter().flatten() {
+            if let Some(timezone) = resolve(Some(field), spec) {
+                timezones.fields.insert(field.clone(), timezone);
+            }
+        }
+        (timezones, errors)
+    }
+
+    /// Reports the unknown zone names of a model, see [`Timezones::from_model`]
+    pub fn check_model(model: &LrolModel, errors: &mut Vec<AnalyzerError>) {
+        let (_, invalid) = Self::from_model(model);
+        errors.extend(invalid);
+    }
+
+    pub fn set_default(&mut self, timezone: Timezone) {
+        self.default = Some(timezone);
+    }
+
+    pub fn insert(&mut self, field: impl Into<String>, timezone: Timezone) {
+        self.fields.insert(field.into(), timezone);
+    }
+
+    /// Rule-level timezone, used for `now`, timestamps and fields without their own
+    pub fn default_timezone(&self) -> Option<&Timezone> {
+        self.default.as_ref()
+    }
+
+    /// Timezone of a datetime field, falling back to the rule-level default
+    pub fn for_field(&self, field: &str) -> Option<&Timezone> {
+        self.fields.get(field).or(self.default.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_timezones() {
+        let model = LrolModel {
+            timezone: Some(TimezoneSpec::Zone("Europe/London".to_string())),
+            field_timezones: Some(BTreeMap::from([
+                (
+                    "merchant_time".to_string(),
+                    TimezoneSpec::Field {
+                        field: "merchant_country".to_string(),
+                        zones: BTreeMap::from([("US".to_string(), "America/New_York".to_string())]),
+                        default: Some("UTC".to_string()),
+                    },
+                ),
+                (
+                    "local_time".to_string(),
+                    TimezoneSpec::Field {
+                        field: "merchant_timezone".to_string(),
+                        zones: BTreeMap::new(),
+                        default: None,
+                    },
+                ),
+            ])),
+            ..Default::default()
+        };
+        let (timezones, errors) = Timezones::from_model(&model);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let event = |field: &str| match field {
+            "merchant_country" => Some("US".to_string()),
+            "merchant_timezone" => Some("Asia/Tokyo".to_string()),
+            _ => None,
+        };
+        let zone = |field: &str| timezones.for_field(field).unwrap().resolve(&event);
+        assert_eq!(zone("merchant_time"), Some(Tz::America__New_York));
+        assert_eq!(zone("local_time"), Some(Tz::Asia__Tokyo));
+        assert_eq!(zone("created"), Some(Tz::Europe__London));
+
+        let unknown = |_: &str| Some("CA".to_string());
+        assert_eq!(
+            timezones
+                .for_field("merchant_time")
+                .unwrap()
+                .resolve(&unknown),
+            Some(Tz::UTC)
+        );
+    }
+
+    #[test]
+    fn test_unknown_zones() {
+        let model = LrolModel {
+            timezone: Some(TimezoneSpec::Zone("Mars/Olympus_Mons".to_string())),
+            field_timezones: Some(BTreeMap::from([(
+                "created".to_string(),
+                TimezoneSpec::Field {
+                    field: "country".to_string(),
+                    zones: BTreeMap::from([("FR".to_string(), "Europe/Pariss".to_string())]),
+                    default: None,
+                },
+            )])),
+            ..Default::default()
+        };
+        let (timezones, errors) = Timezones::from_model(&model);
+        assert!(timezones.default_timezone().is_none());
+        assert!(matches!(&errors[..], [
+            AnalyzerError::InvalidTimezone { field: None, .. },
+            AnalyzerError::InvalidTimezone { field: Some(field), reason },
+        ] if field == "created" && reason.contains("Europe/Pariss")));
+    }
+}
@@ -0,0 +1,692 @@
+use lrol_parser::{parser::LrolModel, types::Aggregation, Evaluation, EvaluationType, Value};
+
+use crate::analyzer::SymbolTable;
+use crate::datetime::Duration;
+use crate::error::AnalyzerError;
+use crate::event_schema::{EventSchema, FieldType};
+use crate::operand::{split_arithmetic, Operand};
+
+const ORDERING_OPERATORS: [&str; 4] = [">", "<", ">=", "<="];
+const EQUALITY_OPERATORS: [&str; 2] = ["==", "!="];
+const MEMBERSHIP_OPERATORS: [&str; 2] = ["IN", "NOT IN"];
+const PATTERN_OPERATORS: [&str; 2] = ["LIKE", "NOT LIKE"];
+
+/// Checks the operands of every comparison in a model against an event schema.
+///
+/// Types are inferred for event fields from the schema, for `@` references from
+/// the evaluation they name and for function calls from the function. Operands
+/// whose type cannot be inferred are treated as [`FieldType::Any`] and never
+/// reported.
+pub struct TypeChecker<'a> {
+    schema: Option<&'a EventSchema>,
+    symbols: &'a SymbolTable,
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(schema: &'a EventSchema, symbols: &'a SymbolTable) -> Self {
+        Self {
+            schema: Some(schema),
+            symbols,
+        }
+    }
+
+    /// Checker for models whose events are not described, where every event
+    /// field is [`FieldType::Any`] and only `@` references, literals and
+    /// function calls are checked
+    pub fn without_schema(symbols: &'a SymbolTable) -> Self {
+        Self {
+            schema: None,
+            symbols,
+        }
+    }
+
+    pub fn check_model(&self, model: &LrolModel, errors: &mut Vec<AnalyzerError>) {
+        for evaluation in &model.evaluations {
+            self.check_evaluation(evaluation, errors);
+        }
+    }
+
+    pub(crate) fn check_evaluation(
+        &self,
+        evaluation: &Evaluation,
+        errors: &mut Vec<AnalyzerError>,
+    ) {
+        match evaluation.evaluation_type {
+            EvaluationType::Comparison | EvaluationType::TimeBased => {
+                if let (Some(left), Some(operator), Some(right)) =
+                    (&evaluation.left, &evaluation.operator, &evaluation.right)
+                {
+                    self.check_comparison(&evaluation.name, "", left, operator, right, errors);
+                }
+            }
+            EvaluationType::Aggregation => self.check_aggregation(evaluation, errors),
+            EvaluationType::Logical => {
+                for (i, operand) in evaluation.operands.iter().flatten().enumerate() {
+                    let field_name = format!("operands[{}]", i);
+                    self.check_reference(
+                        &evaluation.name,
+                        &field_name,
+                        operand,
+                        &FieldType::Boolean,
+                        errors,
+                    );
+                }
+            }
+            EvaluationType::Conditional => {}
+        }
+    }
+
+    // Reports a referenced evaluation whose output is not of the `expected` type
+    fn check_reference(
+        &self,
+        evaluation_name: &str,
+        field_name: &str,
+        reference: &str,
+        expected: &FieldType,
+        errors: &mut Vec<AnalyzerError>,
+    ) {
+        let Some(symbol) = self.symbols.get(reference) else {
+            return;
+        };
+        let found = Self::output_type(&symbol.evaluation_type);
+        if !found.is_compatible_with(expected) {
+            errors.push(AnalyzerError::IncompatibleReference {
+                evaluation_name: evaluation_name.to_string(),
+                field_name: field_name.to_string(),
+                reference: reference.to_string(),
+                found: found.to_string(),
+                expected: expected.to_string(),
+            });
+        }
+    }
+
+    fn check_aggregation(&self, evaluation: &Evaluation, errors: &mut Vec<AnalyzerError>) {
+        if let Some(ref field) = evaluation.field {
+            let operand = Operand::parse(field);
+            let found = self.infer(&evaluation.name, "field", &operand, errors);
+
+            let numeric = matches!(
+                evaluation.aggregation,
+                Some(Aggregation::SUM) | Some(Aggregation::AVG) | Some(Aggregation::STDDEV)
+            );
+            let ordered = matches!(
+                evaluation.aggregation,
+                Some(Aggregation::MIN) | Some(Aggregation::MAX)
+            );
+
+            if numeric && !found.is_compatible_with(&FieldType::Number) {
+                errors.push(AnalyzerError::TypeMismatch {
+                    evaluation_name: evaluation.name.clone(),
+                    field_name: "field".to_string(),
+                    expected: FieldType::Number.to_string(),
+                    found: found.to_string(),
+                });
+            } else if ordered && !found.is_ordered() {
+                errors.push(AnalyzerError::IncompatibleOperator {
+                    evaluation_name: evaluation.name.clone(),
+                    field_name: "field".to_string(),
+                    operator: evaluation
+                        .aggregation
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    operand_type: found.to_string(),
+                    reason: "values of this type have no ordering".to_string(),
+                });
+            }
+        }
+
+        for (i, condition) in evaluation.conditions.iter().flatten().enumerate() {
+            if let (Some(left), Some(operator), Some(right)) =
+                (&condition.left, &condition.operator, &condition.right)
+            {
+                let prefix = format!("conditions[{}].", i);
+                self.check_comparison(&evaluation.name, &prefix, left, operator, right, errors);
+            }
+        }
+    }
+
+    // `prefix` locates the comparison inside the evaluation, e.g. `conditions[0].`
+    fn check_comparison(
+        &self,
+        evaluation_name: &str,
+        prefix: &str,
+        left: &str,
+        operator: &str,
+        right: &Value,
+        errors: &mut Vec<AnalyzerError>,
+    ) {
+        let left_field = format!("{}left", prefix);
+        let right_field = format!("{}right", prefix);
+
+        let left_operand = Operand::parse(left);
+        // Without a schema any plain string may name a field
+        let right_operand = Operand::from_value(right, |path| {
+            self.schema
+                .is_none_or(|schema| schema.resolve(path).is_some())
+        });
+
+        let left_type = self.infer(evaluation_name, &left_field, &left_operand, errors);
+        let right_type = self.infer(evaluation_name, &right_field, &right_operand, errors);
+
+        let operator = operator.trim();
+        let mismatch = |expected: &FieldType, found: &FieldType| AnalyzerError::TypeMismatch {
+            evaluation_name: evaluation_name.to_string(),
+            field_name: right_field.clone(),
+            expected: expected.to_string(),
+            found: found.to_string(),
+        };
+        let incompatible = |field_name: &str, operand_type: &FieldType, reason: &str| {
+            AnalyzerError::IncompatibleOperator {
+                evaluation_name: evaluation_name.to_string(),
+                field_name: field_name.to_string(),
+                operator: operator.to_string(),
+                operand_type: operand_type.to_string(),
+                reason: reason.to_string(),
+            }
+        };
+
+        if ORDERING_OPERATORS.contains(&operator) {
+            if !left_type.is_ordered() {
+                errors.push(incompatible(
+                    &left_field,
+                    &left_type,
+                    "values of this type have no ordering",
+                ));
+            } else if !right_type.is_ordered() {
+                errors.push(incompatible(
+                    &right_field,
+                    &right_type,
+                    "values of this type have no ordering",
+                ));
+            } else if !Self::comparable(&left_type, &right_type, &right_operand) {
+                errors.push(mismatch(&left_type, &right_type));
+            }
+        } else if EQUALITY_OPERATORS.contains(&operator) {
+            if !Self::comparable(&left_type, &right_type, &right_operand) {
+                errors.push(mismatch(&left_type, &right_type));
+            }
+        } else if MEMBERSHIP_OPERATORS.contains(&operator) {
+            if !left_type.is_scalar() {
+                errors.push(incompatible(
+                    &left_field,
+                    &left_type,
+                    "the left operand of a membership test must be a single value",
+                ));
+            }
+            match right_type {
+                FieldType::Array(ref item) => {
+                    if !Self::comparable(&left_type, item, &Operand::Expression(String::new())) {
+                        errors.push(mismatch(
+                            &FieldType::Array(Box::new(left_type.clone())),
+                            &right_type,
+                        ));
+                    }
+                }
+                FieldType::Any => {}
+                _ => errors.push(incompatible(
+                    &right_field,
+                    &right_type,
+                    "the right operand of a membership test must be an array",
+                )),
+            }
+        } else if PATTERN_OPERATORS.contains(&operator) {
+            if !left_type.is_compatible_with(&FieldType::String) {
+                errors.push(incompatible(
+                    &left_field,
+                    &left_type,
+                    "pattern matching requires a string",
+                ));
+            }
+            if !right_type.is_compatible_with(&FieldType::String) {
+                errors.push(incompatible(
+                    &right_field,
+                    &right_type,
+                    "pattern matching requires a string",
+                ));
+            }
+        }
+    }
+
+    // String literals stand in for timestamps and durations, e.g. `"2024-01-01"`
+    fn comparable(left: &FieldType, right: &FieldType, right_operand: &Operand) -> bool {
+        if left.is_compatible_with(right) {
+            return true;
+        }
+        matches!(right_operand, Operand::Literal(Value::String(_)))
+            && *right == FieldType::String
+            && matches!(left, FieldType::DateTime | FieldType::Duration)
+    }
+
+    /// Infers the type of an operand, reporting event fields missing from the schema
+    pub fn infer(
+        &self,
+        evaluation_name: &str,
+        field_name: &str,
+        operand: &Operand,
+        errors: &mut Vec<AnalyzerError>,
+    ) -> FieldType {
+        match operand {
+            Operand::Field(path) => match self.schema.map(|schema| schema.resolve(path)) {
+                Some(Some(field_type)) => field_type,
+                None => FieldType::Any,
+                Some(None) => {
+                    errors.push(AnalyzerError::UnknownField {
+                        evaluation_name: evaluation_name.to_string(),
+                        field_name: field_name.to_string(),
+                        field: path.clone(),
+                    });
+                    FieldType::Any
+                }
+            },
+            Operand::Reference(name) => self
+                .symbols
+                .get(name)
+                .map(|symbol| Self::output_type(&symbol.evaluation_type))
+                .unwrap_or(FieldType::Any),
+            Operand::Call { name, args } => {
+                // The argument of a datetime function that must hold a datetime
+                let datetime_arg = match name.as_str() {
+                    "datetime" | "hour_of_day" | "day_of_week" | "is_weekend" | "is_holiday"
+                    | "is_business_day" | "is_business_hours" => Some(0),
+                    "strftime" => Some(1),
+                    _ => None,
+                };
+                for (i, arg) in args.iter().enumerate() {
+                    let found = self.infer(evaluation_name, field_name, arg, errors);
+                    if datetime_arg == Some(i)
+                        && !Self::comparable(&FieldType::DateTime, &found, arg)
+                    {
+                        errors.push(AnalyzerError::TypeMismatch {
+                            evaluation_name: evaluation_name.to_string(),
+                            field_name: field_name.to_string(),
+                            expected: FieldType::DateTime.to_string(),
+                            found: found.to_string(),
+                        });
+                    }
+                }
+                Self::call_type(name)
+            }
+            Operand::Now => FieldType::DateTime,
+            Operand::Literal(value) => Self::literal_type(value),
+            Operand::Expression(expr) => {
+                // Operands of arithmetic such as `@Total * 0.8` must be numbers
+                if let Some((left, _, right)) = split_arithmetic(expr) {
+                    for side in [left, right] {
+                        match Operand::parse(side) {
+                            Operand::Reference(ref name) => self.check_reference(
+                                evaluation_name,
+                                field_name,
+                                name,
+                                &FieldType::Number,
+                                errors,
+                            ),
+                            nested @ Operand::Expression(_) => {
+                                self.infer(evaluation_name, field_name, &nested, errors);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                FieldType::Any
+            }
+        }
+    }
+
+    /// Type of the value an evaluation produces when referenced with `@`: true or
+    /// false, the number an aggregation computes or the category a conditional picks
+    pub fn output_type(evaluation_type: &EvaluationType) -> FieldType {
+        match evaluation_type {
+            EvaluationType::Comparison | EvaluationType::Logical | EvaluationType::TimeBased => {
+                FieldType::Boolean
+            }
+            EvaluationType::Aggregation => FieldType::Number,
+            EvaluationType::Conditional => FieldType::String,
+        }
+    }
+
+    fn call_type(name: &str) -> FieldType {
+        match name {
+            "datetime" => FieldType::DateTime,
+            "timediff" => FieldType::Duration,
+            "strftime" => FieldType::String,
+            "hour_of_day" | "day_of_week" => FieldType::Number,
+            "is_weekend" | "is_holiday" | "is_business_day" | "is_business_hours" => {
+                FieldType::Boolean
+            }
+            _ => FieldType::Any,
+        }
+    }
+
+    fn literal_type(value: &Value) -> FieldType {
+        match value {
+            Value::String(s) if Self::is_duration_literal(s) => FieldType::Duration,
+            Value::String(_) => FieldType::String,
+            Value::Number(_) => FieldType::Number,
+            Value::Bool(_) => FieldType::Boolean,
+            Value::Array(items) => {
+                let mut item_type: Option<FieldType> = None;
+                for item in items {
+                    let found = Self::literal_type(item);
+                    item_type = match item_type {
+                        None => Some(found),
+                        Some(t) if t == found => Some(t),
+                        Some(_) => Some(FieldType::Any),
+                    };
+                }
+                FieldType::Array(Box::new(item_type.unwrap_or(FieldType::Any)))
+            }
+            Value::Object(_) => FieldType::Object,
+        }
+    }
+
+    // Durations are written as a number and a unit, e.g. `"30 days"`, or in ISO-8601
+    fn is_duration_literal(s: &str) -> bool {
+        s.chars().any(|c| c.is_ascii_digit()) && Duration::parse(s).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::RuleAnalyzer;
+    use crate::event_schema::FieldSpec;
+    use crate::test_support::{comparison, evaluation};
+    use lrol_parser::{Action, Condition};
+
+    fn schema() -> EventSchema {
+        let mut schema = EventSchema::new();
+        schema.insert("amount", FieldSpec::new(FieldType::Number));
+        schema.insert("merchant_state", FieldSpec::new(FieldType::String));
+        schema.insert("merchant_mcc", FieldSpec::new(FieldType::String));
+        schema.insert("created", FieldSpec::new(FieldType::DateTime));
+        schema.insert(
+            "recent_states",
+            FieldSpec::new(FieldType::Array(Box::new(FieldType::String))),
+        );
+        schema
+    }
+
+    fn model(evaluations: Vec<Evaluation>) -> LrolModel {
+        LrolModel {
+            model_id: "TEST001".to_string(),
+            name: "Typed Model".to_string(),
+            threshold: 0.8,
+            evaluations,
+            actions: vec![Action {
+                action_type: "flag_transaction".to_string(),
+                reason: "Test".to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn analyze(evaluations: Vec<Evaluation>) -> Vec<AnalyzerError> {
+        RuleAnalyzer::with_event_schema(schema())
+            .analyze(&model(evaluations))
+            .errors
+    }
+
+    #[test]
+    fn test_well_typed_comparisons() {
+        let errors = analyze(vec![
+            comparison("a", "amount", ">", Value::Number(100.0)),
+            comparison("b", "merchant_state", "==", Value::String("CT".to_string())),
+            comparison(
+                "c",
+                "merchant_mcc",
+                "IN",
+                Value::Array(vec![Value::String("5933".to_string())]),
+            ),
+            comparison(
+                "d",
+                "created",
+                ">=",
+                Value::String("datetime(now, '-2 hours')".to_string()),
+            ),
+            comparison(
+                "e",
+                "merchant_state",
+                "IN",
+                Value::String("recent_states".to_string()),
+            ),
+            comparison("f", "@a", "==", Value::Bool(true)),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_ordering_on_string_against_number() {
+        let errors = analyze(vec![comparison(
+            "State_Check",
+            "merchant_state",
+            ">",
+            Value::Number(5.0),
+        )]);
+        assert!(errors.iter().any(|e| matches!(e,
+            AnalyzerError::TypeMismatch { field_name, expected, found, .. }
+                if field_name == "right" && expected == "string" && found == "number"
+        )));
+    }
+
+    #[test]
+    fn test_membership_against_scalar() {
+        let errors = analyze(vec![comparison(
+            "Mcc_Check",
+            "merchant_mcc",
+            "IN",
+            Value::Number(5933.0),
+        )]);
+        assert!(errors.iter().any(|e| matches!(e,
+            AnalyzerError::IncompatibleOperator { operator, field_name, .. }
+                if operator == "IN" && field_name == "right"
+        )));
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let errors = analyze(vec![comparison(
+            "Balance_Check",
+            "amount",
+            "<",
+            Value::String("profile.available_balance".to_string()),
+        )]);
+        assert!(errors.iter().any(|e| matches!(e,
+            AnalyzerError::UnknownField { field, .. } if field == "profile.available_balance"
+        )));
+    }
+
+    #[test]
+    fn test_aggregation_types() {
+        let errors = analyze(vec![Evaluation {
+            aggregation: Some(Aggregation::SUM),
+            field: Some("merchant_state".to_string()),
+            conditions: Some(vec![Condition {
+                left: Some("created".to_string()),
+                operator: Some("LIKE".to_string()),
+                right: Some(Value::String("2024%".to_string())),
+            }]),
+            ..evaluation("State_Sum", EvaluationType::Aggregation)
+        }]);
+
+        assert!(errors.iter().any(|e| matches!(e,
+            AnalyzerError::TypeMismatch { field_name, .. } if field_name == "field"
+        )));
+        assert!(errors.iter().any(|e| matches!(e,
+            AnalyzerError::IncompatibleOperator { field_name, .. }
+                if field_name == "conditions[0].left"
+        )));
+    }
+
+    #[test]
+    fn test_reference_output_types() {
+        let mut count = comparison("Count", "amount", ">", Value::Number(0.0));
+        count.evaluation_type = EvaluationType::Aggregation;
+        count.aggregation = Some(Aggregation::COUNT);
+        count.field = Some("amount".to_string());
+
+        let errors = analyze(vec![
+            count,
+            comparison("Count_Check", "@Count", ">", Value::Number(10.0)),
+            comparison("Flag", "amount", ">", Value::Number(10.0)),
+            comparison("Flag_Check", "@Flag", ">", Value::Number(1.0)),
+        ]);
+
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(matches!(&errors[0],
+            AnalyzerError::IncompatibleOperator { evaluation_name, operand_type, .. }
+                if evaluation_name == "Flag_Check" && operand_type == "boolean"
+        ));
+    }
+
+    #[test]
+    fn test_reference_kinds_without_schema() {
+        let mut total = comparison("Total", "amount", ">", Value::Number(0.0));
+        total.evaluation_type = EvaluationType::Aggregation;
+        total.aggregation = Some(Aggregation::SUM);
+        total.field = Some("amount".to_string());
+        let mut logic = comparison("Logic", "", "AND", Value::Bool(true));
+        logic.evaluation_type = EvaluationType::Logical;
+        logic.left = None;
+        logic.right = None;
+        logic.operands = Some(vec!["Flag".to_string(), "Total".to_string()]);
+
+        let result = RuleAnalyzer::new().analyze(&model(vec![
+            total,
+            comparison("Flag", "amount", ">", Value::Number(10.0)),
+            comparison("Flag_Check", "@Flag", ">", Value::Number(1.0)),
+            comparison("Flag_Set", "@Flag", "==", Value::Bool(true)),
+            comparison("Scaled", "@Total * 0.8", ">", Value::Number(100.0)),
+            comparison("Negated", "@Total * -1", "<", Value::Number(0.0)),
+            comparison("Doubled", "@Flag * 2", ">", Value::Number(1.0)),
+            logic,
+        ]));
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let warnings = type_warnings(result.warnings);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(matches!(&warnings[0],
+            AnalyzerError::IncompatibleOperator { evaluation_name, operand_type, .. }
+                if evaluation_name == "Flag_Check" && operand_type == "boolean"
+        ));
+        assert!(matches!(&warnings[1],
+            AnalyzerError::IncompatibleReference { evaluation_name, reference, expected, .. }
+                if evaluation_name == "Doubled" && reference == "Flag" && expected == "number"
+        ));
+        assert!(matches!(&warnings[2],
+            AnalyzerError::IncompatibleReference { field_name, reference, found, .. }
+                if field_name == "operands[1]" && reference == "Total" && found == "number"
+        ));
+    }
+
+    #[test]
+    fn test_numeric_string_reported_once_without_schema() {
+        let mut total = comparison("Total", "amount", ">", Value::Number(0.0));
+        total.evaluation_type = EvaluationType::Aggregation;
+        total.aggregation = Some(Aggregation::SUM);
+        total.field = Some("amount".to_string());
+
+        let result = RuleAnalyzer::new().analyze(&model(vec![
+            total,
+            comparison(
+                "Total_Check",
+                "@Total",
+                ">",
+                Value::String("20000".to_string()),
+            ),
+            comparison(
+                "Total_Named",
+                "@Total",
+                "==",
+                Value::String("high risk".to_string()),
+            ),
+        ]));
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let located: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|w| w.location().is_some_and(|(name, _)| name != "Total"))
+            .map(|w| (w.code(), w.location()))
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                ("LROL0040", Some(("Total_Check", Some("right")))),
+                ("LROL0031", Some(("Total_Named", Some("right")))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hyphens_are_not_arithmetic() {
+        let result = RuleAnalyzer::new().analyze(&model(vec![
+            comparison("Flag", "amount", ">", Value::Number(10.0)),
+            comparison(
+                "Recent",
+                "@Flag",
+                "==",
+                Value::String("datetime('2024-01-01')".to_string()),
+            ),
+            comparison("Offset", "amount - -5", ">", Value::Number(0.0)),
+        ]));
+        let warnings = type_warnings(result.warnings);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(matches!(&warnings[0],
+            AnalyzerError::TypeMismatch { evaluation_name, .. } if evaluation_name == "Recent"
+        ));
+    }
+
+    // Warnings of the type checker, which reports them when there is no schema
+    fn type_warnings(warnings: Vec<AnalyzerError>) -> Vec<AnalyzerError> {
+        warnings
+            .into_iter()
+            .filter(|w| {
+                matches!(
+                    w,
+                    AnalyzerError::TypeMismatch { .. }
+                        | AnalyzerError::IncompatibleOperator { .. }
+                        | AnalyzerError::IncompatibleReference { .. }
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_datetime_functions() {
+        let errors = analyze(vec![
+            comparison("Night", "hour_of_day(created)", "<", Value::Number(6.0)),
+            comparison("Weekend", "day_of_week(created)", ">=", Value::Number(6.0)),
+            comparison(
+                "Hour",
+                "strftime('%H', created)",
+                "IN",
+                Value::Array(vec![Value::String("23".to_string())]),
+            ),
+            comparison(
+                "Recent",
+                "created",
+                ">",
+                Value::String("datetime(now, 'PT30M')".to_string()),
+            ),
+            comparison(
+                "OffHours",
+                "is_business_hours(created, 'us_banking')",
+                "==",
+                Value::Bool(false),
+            ),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let errors = analyze(vec![comparison(
+            "Night",
+            "hour_of_day(merchant_state)",
+            "<",
+            Value::Number(6.0),
+        )]);
+        assert!(matches!(&errors[..],
+            [AnalyzerError::TypeMismatch { field_name, expected, found, .. }]
+                if field_name == "left" && expected == "datetime" && found == "string"
+        ));
+    }
+}
@@ -1,14 +1,23 @@
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
 
 use lrol_parser::{
-    parser::LrolModel, types::Metadata, Action, Evaluation, LrolParser, ParserError,
+    parser::LrolModel, types::Metadata, Action, Evaluation, LrolParser, ParserError, SourceMap,
+    Span,
 };
 
 use crate::{
     analyzer::RuleAnalyzer,
+    diagnostic::{Diagnostic, Severity},
     error::{AnalyzerError, FileValidationError, ValidationError},
+    fix::{self, Fix},
+    scoring::ScoringFormula,
 };
 
+#[derive(Debug, Clone, Default)]
 pub struct RuleValidator {
     analyzer: RuleAnalyzer,
 }
@@ -20,8 +29,13 @@ impl RuleValidator {
         }
     }
 
+    /// Validator that analyzes models with a preconfigured analyzer
+    pub fn with_analyzer(analyzer: RuleAnalyzer) -> Self {
+        Self { analyzer }
+    }
+
     /// Validates an LROL rule string by both parsing and analyzing it
-    pub fn validate(&mut self, input: &str) -> Result<LrolModel, Vec<ValidationError>> {
+    pub fn validate(&self, input: &str) -> Result<LrolModel, Vec<ValidationError>> {
         let mut errors = Vec::new();
 
         // First try to parse the input
@@ -34,9 +48,8 @@ impl RuleValidator {
         };
 
         // If parsing succeeds, analyze the model
-        if let Err(analyzer_errors) = self.analyzer.analyze(&model) {
-            errors.extend(analyzer_errors.into_iter().map(ValidationError::Analyzer));
-        }
+        let analysis = self.analyzer.analyze(&model);
+        errors.extend(analysis.errors.into_iter().map(ValidationError::Analyzer));
 
         if errors.is_empty() {
             Ok(model)
@@ -46,29 +59,33 @@ impl RuleValidator {
     }
 
     /// Provides a detailed report of all validation issues
-    pub fn validate_with_report(&mut self, input: &str) -> ValidationReport {
+    pub fn validate_with_report(&self, input: &str) -> ValidationReport {
         let mut report = ValidationReport::new();
+        let source_map = SourceMap::from_source(input);
 
         match LrolParser::parse(input) {
             Ok(model) => {
-                report.model = Some(model.clone());
-
                 // Add analysis phase if parsing succeeds
-                if let Err(analyzer_errors) = self.analyzer.analyze(&model) {
-                    report.analyzer_errors = analyzer_errors;
-                }
+                let analysis = self.analyzer.analyze(&model);
+                report.analyzer_errors = analysis.errors;
+                report.analyzer_warnings = analysis.warnings;
+                check_aggregation_case(&model, input, &source_map, &mut report.analyzer_warnings);
+
+                report.model = Some(model);
             }
             Err(e) => {
                 report.parser_error = Some(e);
             }
         }
 
+        report.source_map = Some(source_map);
+        report.source = Some(input.to_string());
         report
     }
 
     /// Validates an LROL rule from a file path
     pub fn validate_with_report_from_file<P: AsRef<Path>>(
-        &mut self,
+        &self,
         path: P,
     ) -> Result<ValidationReport, FileValidationError> {
         let path_str = path.as_ref().to_string_lossy().into_owned();
@@ -86,18 +103,19 @@ impl RuleValidator {
             })?;
 
         // Validate content
-        let report = self.validate_with_report(&content);
+        let mut report = self.validate_with_report(&content);
+        report.file_path = Some(path_str);
 
         if report.is_valid() {
             Ok(report)
         } else {
-            Err(FileValidationError::ValidationErrors(report))
+            Err(FileValidationError::ValidationErrors(Box::new(report)))
         }
     }
 
     /// Validates multiple LROL rule files from a directory
     pub fn validate_directory<P: AsRef<Path>>(
-        &mut self,
+        &self,
         dir_path: P,
     ) -> Vec<(String, Result<ValidationReport, FileValidationError>)> {
         let mut results = Vec::new();
@@ -105,7 +123,7 @@ impl RuleValidator {
         if let Ok(entries) = fs::read_dir(dir_path) {
             for entry in entries.filter_map(Result::ok) {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "json") {
+                if path.extension().is_some_and(|ext| ext == "json") {
                     let file_name = path.to_string_lossy().into_owned();
                     let validation_result = self.validate_with_report_from_file(&path);
                     results.push((file_name, validation_result));
@@ -117,13 +135,40 @@ impl RuleValidator {
     }
 }
 
+// The parser accepts aggregations in any case, but only upper case is canonical
+fn check_aggregation_case(
+    model: &LrolModel,
+    source: &str,
+    source_map: &SourceMap,
+    warnings: &mut Vec<AnalyzerError>,
+) {
+    for (index, evaluation) in model.evaluations.iter().enumerate() {
+        let Some(span) = source_map.evaluation_field(index, "aggregation") else {
+            continue;
+        };
+        let written = source[span.start..span.end].trim_matches('"');
+        if written != written.to_ascii_uppercase() {
+            warnings.push(AnalyzerError::NonCanonicalAggregation {
+                evaluation_name: evaluation.name.clone(),
+                aggregation: written.to_string(),
+            });
+        }
+    }
+}
+
 /// Struct to hold detailed validation results
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ValidationReport {
     pub file_path: Option<String>,
     pub model: Option<LrolModel>,
     pub parser_error: Option<ParserError>,
     pub analyzer_errors: Vec<AnalyzerError>,
+    /// Findings that do not make the model invalid
+    pub analyzer_warnings: Vec<AnalyzerError>,
+    /// Positions of the values in the validated source, when it is available
+    pub source_map: Option<SourceMap>,
+    /// The validated source, used to suggest fixes
+    pub source: Option<String>,
 }
 
 impl ValidationReport {
@@ -133,7 +178,88 @@ impl ValidationReport {
             model: None,
             parser_error: None,
             analyzer_errors: Vec::new(),
+            analyzer_warnings: Vec::new(),
+            source_map: None,
+            source: None,
+        }
+    }
+
+    /// Location of an analyzer error in the validated source.
+    ///
+    /// Errors are located at the offending field when it is known, otherwise at
+    /// the evaluation they were found in.
+    pub fn span_of(&self, error: &AnalyzerError) -> Option<Span> {
+        let source_map = self.source_map.as_ref()?;
+        let (evaluation_name, field_name) = error.location()?;
+        let index = self
+            .model
+            .as_ref()?
+            .evaluations
+            .iter()
+            .position(|e| e.name == evaluation_name)?;
+
+        field_name
+            .and_then(|field| source_map.evaluation_field(index, field))
+            .or_else(|| source_map.evaluation(index))
+    }
+
+    /// Suggested fix for an analyzer error or warning of this report
+    pub fn fix_for(&self, error: &AnalyzerError) -> Option<Fix> {
+        fix::suggest_fix(
+            error,
+            self.model.as_ref()?,
+            self.source.as_deref()?,
+            self.source_map.as_ref()?,
+        )
+    }
+
+    /// Fixes for every error and warning, errors first, without duplicates
+    pub fn fixes(&self) -> Vec<Fix> {
+        let mut fixes: Vec<Fix> = Vec::new();
+        for error in self.analyzer_errors.iter().chain(&self.analyzer_warnings) {
+            if let Some(fix) = self.fix_for(error) {
+                if !fixes.contains(&fix) {
+                    fixes.push(fix);
+                }
+            }
         }
+        fixes
+    }
+
+    /// Diagnostics for the parser error and every analyzer error and warning
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .parser_error
+            .iter()
+            .map(|error| Diagnostic::from_parser_error(error, self.source_map.as_ref()))
+            .collect();
+
+        let mut occurrences: HashMap<(&'static str, Option<&str>), usize> = HashMap::new();
+        let findings = self
+            .analyzer_errors
+            .iter()
+            .map(|error| (error, Severity::Error))
+            .chain(
+                self.analyzer_warnings
+                    .iter()
+                    .map(|warning| (warning, Severity::Warning)),
+            );
+        for (error, severity) in findings {
+            let occurrence = occurrences
+                .entry((error.code(), error.location().map(|(name, _)| name)))
+                .or_default();
+            let mut diagnostic = Diagnostic::from_analyzer_error(
+                error,
+                severity,
+                self.model.as_ref(),
+                self.source_map.as_ref(),
+                *occurrence,
+            );
+            diagnostic.fix = self.fix_for(error);
+            diagnostics.push(diagnostic);
+            *occurrence += 1;
+        }
+        diagnostics
     }
 
     pub fn is_valid(&self) -> bool {
@@ -146,6 +272,9 @@ impl ValidationReport {
             model: None,
             parser_error: None,
             analyzer_errors: Vec::new(),
+            analyzer_warnings: Vec::new(),
+            source_map: None,
+            source: None,
         }
     }
 
@@ -162,9 +291,12 @@ impl ValidationReport {
 
         if !self.analyzer_errors.is_empty() {
             output.push_str("Analyzer Errors:\n");
-            for (i, error) in self.analyzer_errors.iter().enumerate() {
-                output.push_str(&format!("{}. {:?}\n", i + 1, error));
-            }
+            self.format_list(&self.analyzer_errors, &mut output);
+        }
+
+        if !self.analyzer_warnings.is_empty() {
+            output.push_str("Analyzer Warnings:\n");
+            self.format_list(&self.analyzer_warnings, &mut output);
         }
 
         if output.is_empty() || output.ends_with("File: {}\n") {
@@ -173,6 +305,22 @@ impl ValidationReport {
 
         output
     }
+
+    fn format_list(&self, errors: &[AnalyzerError], output: &mut String) {
+        for (i, error) in errors.iter().enumerate() {
+            match self.span_of(error) {
+                Some(span) => output.push_str(&format!(
+                    "{}. [{}] {} (line {}, column {})\n",
+                    i + 1,
+                    error.code(),
+                    error,
+                    span.line,
+                    span.column
+                )),
+                None => output.push_str(&format!("{}. [{}] {}\n", i + 1, error.code(), error)),
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -183,6 +331,12 @@ pub struct SchemaValidator {
     // valid_action_types: HashSet<String>,
 }
 
+impl Default for SchemaValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SchemaValidator {
     pub fn new() -> Self {
         let mut validator = Self {
@@ -271,6 +425,14 @@ impl RuleAnalyzer {
             });
         }
 
+        if let Some(ref scoring) = model.scoring {
+            if scoring.parse::<ScoringFormula>().is_err() {
+                errors.push(AnalyzerError::InvalidScoring {
+                    scoring: scoring.clone(),
+                });
+            }
+        }
+
         // Validate at least one evaluation exists
         if model.evaluations.is_empty() {
             errors.push(AnalyzerError::MissingRequiredSchemaField {
@@ -303,24 +465,23 @@ impl RuleAnalyzer {
 
         if let Some(ref operator) = evaluation.operator {
             match evaluation.evaluation_type {
-                lrol_parser::EvaluationType::Comparison => {
+                lrol_parser::EvaluationType::Comparison
                     if !schema_validator
                         .valid_comparison_operators
-                        .contains(operator)
-                    {
-                        errors.push(AnalyzerError::InvalidComparisonOperator {
-                            evaluation_name: evaluation.name.clone(),
-                            operator: operator.clone(),
-                        });
-                    }
+                        .contains(operator) =>
+                {
+                    errors.push(AnalyzerError::InvalidComparisonOperator {
+                        evaluation_name: evaluation.name.clone(),
+                        operator: operator.clone(),
+                    });
                 }
-                lrol_parser::EvaluationType::Logical => {
-                    if !schema_validator.valid_logical_operators.contains(operator) {
-                        errors.push(AnalyzerError::InvalidLogicalOperator {
-                            evaluation_name: evaluation.name.clone(),
-                            operator: operator.clone(),
-                        });
-                    }
+                lrol_parser::EvaluationType::Logical
+                    if !schema_validator.valid_logical_operators.contains(operator) =>
+                {
+                    errors.push(AnalyzerError::InvalidLogicalOperator {
+                        evaluation_name: evaluation.name.clone(),
+                        operator: operator.clone(),
+                    });
                 }
                 lrol_parser::EvaluationType::TimeBased => todo!(),
                 lrol_parser::EvaluationType::Conditional => todo!(),
@@ -330,7 +491,7 @@ impl RuleAnalyzer {
 
         // Validate weight range (1-5)
         if let Some(weight) = evaluation.weight {
-            if weight < 1 || weight > 5 {
+            if !(1..=5).contains(&weight) {
                 errors.push(AnalyzerError::InvalidWeightRange {
                     evaluation_name: evaluation.name.clone(),
                     weight,
@@ -350,14 +511,13 @@ impl RuleAnalyzer {
         //     .valid_action_types
         //     .contains(&action.action_type)
         // {
-           
+
         // }
         if action.action_type.trim().is_empty() {
             errors.push(AnalyzerError::InvalidActionType {
                 action_type: action.action_type.clone(),
             });
         }
-       
 
         // Validate reason is present and not empty
         if action.reason.trim().is_empty() {
@@ -421,7 +581,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let result = validator.validate(input);
         assert!(result.is_ok());
     }
@@ -436,7 +596,7 @@ mod tests {
             "actions": []
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(report.parser_error.is_some());
         assert!(!report.is_valid());
@@ -465,7 +625,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(report.parser_error.is_none());
         assert!(!report.analyzer_errors.is_empty());
@@ -503,7 +663,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(report.parser_error.is_none());
         assert!(report.analyzer_errors.len() > 1);
@@ -545,7 +705,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(report.parser_error.is_none());
         assert!(
@@ -578,7 +738,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         let formatted_report = report.format_errors();
 
@@ -618,7 +778,7 @@ mod tests {
             }
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(
             report.is_valid(),
@@ -651,7 +811,7 @@ mod tests {
             ]
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(!report.is_valid());
         assert!(report.analyzer_errors.iter().any(|e| matches!(e,
@@ -659,6 +819,95 @@ mod tests {
         )));
     }
 
+    #[test]
+    fn test_model_scoring() {
+        let rule = |scoring: &str| {
+            format!(
+                r#"{{
+                "model_id": "TEST001",
+                "name": "Scored Model",
+                "threshold": 0.9,
+                "scoring": "{}",
+                "evaluations": [
+                    {{
+                        "name": "test_check",
+                        "type": "comparison",
+                        "left": "value",
+                        "operator": ">",
+                        "right": 100,
+                        "weight": 3
+                    }}
+                ],
+                "actions": [
+                    {{
+                        "type": "flag_transaction",
+                        "reason": "Test"
+                    }}
+                ]
+            }}"#,
+                scoring
+            )
+        };
+
+        let validator = RuleValidator::new();
+        let report = validator.validate_with_report(&rule("average"));
+        assert!(!report.is_valid());
+        let error = report
+            .analyzer_errors
+            .iter()
+            .find(|e| matches!(e, AnalyzerError::InvalidScoring { .. }))
+            .unwrap();
+        assert_eq!(error.code(), "LROL0049");
+
+        // The model's strategy, not the default weighted_ratio, is checked
+        let report = validator.validate_with_report(&rule("max-weight"));
+        assert!(report.is_valid());
+        assert!(report.analyzer_warnings.iter().any(|e| matches!(e,
+            AnalyzerError::UnreachableThreshold { formula, .. } if formula == "max_weight"
+        )));
+    }
+
+    #[test]
+    fn test_type_errors_are_located() {
+        let input = r#"{
+            "model_id": "TEST001",
+            "name": "Typed Rule",
+            "threshold": 0.9,
+            "event_schema": {
+                "fields": { "merchant_state": "string", "merchant_mcc": "string" }
+            },
+            "evaluations": [
+                {
+                    "name": "state_check",
+                    "type": "comparison",
+                    "left": "merchant_state",
+                    "operator": ">",
+                    "right": 5,
+                    "weight": 3
+                }
+            ],
+            "actions": [
+                {
+                    "type": "flag_transaction",
+                    "reason": "Test"
+                }
+            ]
+        }"#;
+
+        let validator = RuleValidator::new();
+        let report = validator.validate_with_report(input);
+        let error = report
+            .analyzer_errors
+            .iter()
+            .find(|e| matches!(e, AnalyzerError::TypeMismatch { .. }))
+            .expect("type mismatch reported");
+
+        let span = report.span_of(error).unwrap();
+        assert_eq!(&input[span.start..span.end], "5");
+        assert_eq!(span.line, 14);
+        assert!(report.format_errors().contains("(line 14, column"));
+    }
+
     #[test]
     fn test_invalid_metadata_datetime() {
         let input = r#"{
@@ -687,7 +936,7 @@ mod tests {
             }
         }"#;
 
-        let mut validator = RuleValidator::new();
+        let validator = RuleValidator::new();
         let report = validator.validate_with_report(input);
         assert!(!report.is_valid());
         assert!(report.analyzer_errors.iter().any(|e| matches!(e,
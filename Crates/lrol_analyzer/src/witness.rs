@@ -0,0 +1,621 @@
+use std::collections::BTreeMap;
+
+use lrol_parser::{parser::LrolModel, EvaluationType};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::constraints::{conflict, Atom, CompareOp, Domain, Formula, Literal, Predicate, Scalar};
+use crate::graph::DependencyGraph;
+use crate::scoring::{analyze_scoring, reaches, ScoringFormula};
+
+// Values of event fields by dotted path
+type Values = BTreeMap<String, Scalar>;
+
+/// Satisfiability of one root evaluation of a model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootSatisfiability {
+    pub evaluation_name: String,
+    pub outcome: Satisfiability,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Satisfiability {
+    Satisfiable {
+        /// Event on which the evaluation fires and the rule reaches its threshold
+        firing: Witness,
+        /// Event differing from `firing` in a single field on which the rule
+        /// does not trigger
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        near_miss: Option<Witness>,
+    },
+    Unsatisfiable {
+        /// One line per way of satisfying the evaluation, each naming the
+        /// comparisons that contradict each other, or why the evaluation
+        /// cannot fire with enough weight to reach the threshold
+        explanation: Vec<String>,
+    },
+    /// The logical tree is too large to decide
+    Unknown { reason: String },
+}
+
+/// Concrete event produced by the solver
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Witness {
+    pub event: JsonValue,
+    /// Evaluations whose result cannot be derived from the event and are
+    /// assumed to be true, e.g. aggregations over past events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assumptions: Vec<String>,
+    /// Evaluation whose comparison was flipped to produce a near miss
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flipped: Option<String>,
+}
+
+/// Decides satisfiability of every root evaluation of the model.
+///
+/// Roots are the logical evaluations nothing else depends on; a model without
+/// one is solved for each of its roots instead. See [`solve`].
+pub fn solve_model(model: &LrolModel, scoring: ScoringFormula) -> Vec<RootSatisfiability> {
+    let graph = DependencyGraph::from_model(model);
+    let roots = graph.roots();
+    let is_logical = |name: &&str| {
+        model
+            .evaluations
+            .iter()
+            .any(|e| e.name == *name && e.evaluation_type == EvaluationType::Logical)
+    };
+
+    let logical_roots: Vec<&str> = roots.iter().copied().filter(is_logical).collect();
+    let selected = if logical_roots.is_empty() {
+        roots
+    } else {
+        logical_roots
+    };
+
+    selected
+        .into_iter()
+        .filter_map(|name| {
+            Some(RootSatisfiability {
+                evaluation_name: name.to_string(),
+                outcome: solve(model, name, scoring)?,
+            })
+        })
+        .collect()
+}
+
+/// Decides whether the evaluation called `name` can fire and trigger the rule,
+/// with witnesses when it can.
+///
+/// The rule triggers when the weights of the evaluations that fire reach the
+/// threshold under `scoring`, so the solver looks for an event firing `name`
+/// together with one of the [minimal trigger sets](crate::scoring::ScoringAnalysis).
+/// Models whose scores cannot be analyzed only need `name` to fire.
+pub fn solve(model: &LrolModel, name: &str, scoring: ScoringFormula) -> Option<Satisfiability> {
+    let formula = Formula::from_model(model, name)?;
+    let too_large = || Satisfiability::Unknown {
+        reason: format!("{} expands to more combinations than can be analyzed", name),
+    };
+    let Some(terms) = formula.dnf() else {
+        return Some(too_large());
+    };
+
+    if terms.iter().all(|term| conflict(term).is_some()) {
+        let explanation = terms
+            .iter()
+            .map(|term| {
+                let parts: Vec<String> = conflict(term)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|l| format!("{} ({})", l, l.evaluation()))
+                    .collect();
+                parts.join(" contradicts ")
+            })
+            .collect();
+        return Some(Satisfiability::Unsatisfiable { explanation });
+    }
+
+    let analysis = analyze_scoring(model, scoring);
+    let trigger_sets = match analysis {
+        Some(ref analysis) => analysis.minimal_trigger_sets.clone(),
+        None => vec![Vec::new()],
+    };
+    let scored = analysis.is_some().then_some(scoring);
+    let triggers = |values: &BTreeMap<String, Scalar>, assumed: &[String]| {
+        evaluate(&formula, values, assumed)
+            && scored.is_none_or(|scoring| {
+                let score = scoring.score_model(model, |name| {
+                    Formula::from_model(model, name).is_some_and(|f| evaluate(&f, values, assumed))
+                });
+                reaches(score, model.threshold)
+            })
+    };
+
+    for set in &trigger_sets {
+        let goal = Formula::And(
+            std::iter::once(formula.clone())
+                .chain(
+                    set.iter()
+                        .filter_map(|member| Formula::from_model(model, member)),
+                )
+                .collect(),
+        );
+        let Some(terms) = goal.dnf() else {
+            return Some(too_large());
+        };
+
+        // Prefer a combination that depends on the event alone
+        let mut satisfiable: Vec<&Vec<Literal>> = terms
+            .iter()
+            .filter(|term| conflict(term).is_none())
+            .collect();
+        satisfiable.sort_by_key(|term| !term.iter().all(|l| matches!(l, Literal::Atom(_))));
+
+        for term in satisfiable {
+            let firing = firing_witness(term);
+            if !triggers(&firing, &assumed_true(term)) {
+                continue;
+            }
+            let near_miss = near_miss(term, &firing, &triggers);
+            return Some(Satisfiability::Satisfiable {
+                firing: witness(&firing, term, None),
+                near_miss: near_miss.map(|(values, flipped)| witness(&values, term, Some(flipped))),
+            });
+        }
+    }
+
+    Some(Satisfiability::Unsatisfiable {
+        explanation: vec![format!(
+            "{} can fire, but never with evaluations weighing enough to reach the threshold {} under {} scoring",
+            name, model.threshold, scoring
+        )],
+    })
+}
+
+fn witness(
+    values: &BTreeMap<String, Scalar>,
+    term: &[Literal],
+    flipped: Option<String>,
+) -> Witness {
+    Witness {
+        event: to_event(values),
+        assumptions: assumed_true(term),
+        flipped,
+    }
+}
+
+fn assumed_true(term: &[Literal]) -> Vec<String> {
+    let mut assumed: Vec<String> = Vec::new();
+    for literal in term {
+        if let Literal::Opaque {
+            evaluation,
+            negated: false,
+        } = literal
+        {
+            if !assumed.contains(evaluation) {
+                assumed.push(evaluation.clone());
+            }
+        }
+    }
+    assumed
+}
+
+// Atoms of a term constraining `field`, grouped in first-seen order
+fn fields(term: &[Literal]) -> Vec<(&str, Vec<&Atom>)> {
+    let mut fields: Vec<(&str, Vec<&Atom>)> = Vec::new();
+    for literal in term {
+        if let Literal::Atom(atom) = literal {
+            match fields.iter_mut().find(|(f, _)| *f == atom.field) {
+                Some((_, atoms)) => atoms.push(atom),
+                None => fields.push((&atom.field, vec![atom])),
+            }
+        }
+    }
+    fields
+}
+
+fn firing_witness(term: &[Literal]) -> BTreeMap<String, Scalar> {
+    let mut values = BTreeMap::new();
+    for (field, atoms) in fields(term) {
+        if let Some(value) = sample(&atoms, None) {
+            values.insert(field.to_string(), value);
+        }
+    }
+    values
+}
+
+// Flip one comparison of the term at a time, keeping its field as close as
+// possible to the firing value, until the rule no longer triggers
+fn near_miss(
+    term: &[Literal],
+    firing: &Values,
+    triggers: &dyn Fn(&Values, &[String]) -> bool,
+) -> Option<(Values, String)> {
+    let opaque = assumed_true(term);
+
+    for literal in term {
+        let Literal::Atom(flipped) = literal else {
+            continue;
+        };
+        let negated = flipped.negate();
+        let mut atoms: Vec<&Atom> = term
+            .iter()
+            .filter_map(|l| match l {
+                Literal::Atom(a) if a.field == flipped.field && a != flipped => Some(a),
+                _ => None,
+            })
+            .collect();
+        atoms.push(&negated);
+
+        let Some(value) = sample(&atoms, firing.get(&flipped.field)) else {
+            continue;
+        };
+        let mut values = firing.clone();
+        values.insert(flipped.field.clone(), value);
+
+        if !triggers(&values, &opaque) {
+            return Some((values, flipped.evaluation.clone()));
+        }
+    }
+
+    None
+}
+
+/// Whether `formula` holds on an event; opaque evaluations listed in `assumed` are true
+pub fn evaluate(formula: &Formula, values: &BTreeMap<String, Scalar>, assumed: &[String]) -> bool {
+    match formula {
+        Formula::Literal(Literal::Atom(atom)) => values
+            .get(&atom.field)
+            .is_some_and(|value| matches(&atom.predicate, value)),
+        Formula::Literal(Literal::Opaque {
+            evaluation,
+            negated,
+        }) => assumed.contains(evaluation) != *negated,
+        Formula::And(children) => children.iter().all(|c| evaluate(c, values, assumed)),
+        Formula::Or(children) => children.iter().any(|c| evaluate(c, values, assumed)),
+    }
+}
+
+fn matches(predicate: &Predicate, value: &Scalar) -> bool {
+    match predicate {
+        Predicate::Compare(op, expected) => match (value, expected) {
+            (Scalar::Number(v), Scalar::Number(e)) => match op {
+                CompareOp::Eq => v == e,
+                CompareOp::Ne => v != e,
+                CompareOp::Lt => v < e,
+                CompareOp::Le => v <= e,
+                CompareOp::Gt => v > e,
+                CompareOp::Ge => v >= e,
+            },
+            (v, e) => match op {
+                CompareOp::Eq => v == e,
+                CompareOp::Ne => v != e,
+                _ => false,
+            },
+        },
+        Predicate::In(values) => values.contains(value),
+        Predicate::NotIn(values) => !values.contains(value),
+    }
+}
+
+// A value satisfying every atom, as close to `target` as the atoms allow
+fn sample(atoms: &[&Atom], target: Option<&Scalar>) -> Option<Scalar> {
+    let kind = atoms.first()?.predicate.kind()?;
+    match Domain::from_atoms(kind, atoms) {
+        Domain::Numeric {
+            lower,
+            upper,
+            allowed,
+            excluded,
+        } => {
+            let target = match target {
+                Some(Scalar::Number(n)) => Some(*n),
+                _ => None,
+            };
+            sample_number(lower, upper, allowed, &excluded, target).map(Scalar::Number)
+        }
+        Domain::Discrete { allowed, excluded } => {
+            let target = target.map(|t| match t {
+                Scalar::Text(s) => s.clone(),
+                Scalar::Bool(b) => b.to_string(),
+                Scalar::Number(n) => n.to_string(),
+            });
+            let value = match allowed {
+                Some(allowed) => target
+                    .filter(|t| allowed.contains(t) && !excluded.contains(t))
+                    .or_else(|| allowed.into_iter().find(|v| !excluded.contains(v)))?,
+                None => target.filter(|t| !excluded.contains(t)).unwrap_or_else(|| {
+                    (0..)
+                        .map(|i| format!("other_{}", i))
+                        .find(|v| !excluded.contains(v))
+                        .unwrap_or_default()
+                }),
+            };
+            Some(match kind {
+                crate::constraints::ScalarKind::Bool => Scalar::Bool(value == "true"),
+                _ => Scalar::Text(value),
+            })
+        }
+    }
+}
+
+fn sample_number(
+    lower: Option<(f64, bool)>,
+    upper: Option<(f64, bool)>,
+    allowed: Option<Vec<f64>>,
+    excluded: &[f64],
+    target: Option<f64>,
+) -> Option<f64> {
+    let within = |v: f64| {
+        lower.is_none_or(|(l, inclusive)| v > l || (inclusive && v == l))
+            && upper.is_none_or(|(u, inclusive)| v < u || (inclusive && v == u))
+            && !excluded.contains(&v)
+    };
+
+    if let Some(allowed) = allowed {
+        let target = target.unwrap_or(0.0);
+        return allowed
+            .into_iter()
+            .filter(|v| within(*v))
+            .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()));
+    }
+
+    // Whole steps keep witnesses readable; narrow ranges are split instead
+    let step = match (lower, upper) {
+        (Some((l, _)), Some((u, _))) if u - l <= 2.0 => (u - l) / 4.0,
+        _ => 1.0,
+    };
+    let start = match (target, lower, upper) {
+        (Some(t), _, _) if within(t) => return Some(t),
+        (Some(t), Some((l, inclusive)), _) if t <= l => {
+            if inclusive {
+                l
+            } else {
+                l + step
+            }
+        }
+        (Some(t), _, Some((u, inclusive))) if t >= u => {
+            if inclusive {
+                u
+            } else {
+                u - step
+            }
+        }
+        (Some(t), _, _) => t,
+        (None, Some((l, inclusive)), _) => {
+            if inclusive {
+                l
+            } else {
+                l + step
+            }
+        }
+        (None, None, Some((u, inclusive))) => {
+            if inclusive {
+                u
+            } else {
+                u - step
+            }
+        }
+        (None, None, None) => 0.0,
+    };
+
+    // Step away from excluded values, alternating directions
+    (0..64)
+        .flat_map(|i| [start + step * i as f64, start - step * i as f64])
+        .find(|v| within(*v))
+}
+
+// Builds a nested JSON event from dotted field paths
+fn to_event(values: &BTreeMap<String, Scalar>) -> JsonValue {
+    let mut event = Map::new();
+    for (path, value) in values {
+        let json = match value {
+            Scalar::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => JsonValue::from(*n as i64),
+            Scalar::Number(n) => JsonValue::from(*n),
+            Scalar::Text(s) => JsonValue::from(s.clone()),
+            Scalar::Bool(b) => JsonValue::from(*b),
+        };
+
+        let mut parts: Vec<&str> = path.split('.').collect();
+        let last = parts.pop().unwrap_or_default();
+        let mut object = &mut event;
+        for part in parts {
+            let entry = object
+                .entry(part.to_string())
+                .or_insert_with(|| JsonValue::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = JsonValue::Object(Map::new());
+            }
+            object = entry.as_object_mut().unwrap();
+        }
+        object.insert(last.to_string(), json);
+    }
+    JsonValue::Object(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{comparison, logical, weighted};
+    use lrol_parser::{Evaluation, Value};
+    use serde_json::json;
+
+    fn model(evaluations: Vec<Evaluation>) -> LrolModel {
+        LrolModel {
+            evaluations,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_witnesses_for_satisfiable_root() {
+        let model = model(vec![
+            comparison("Large", "transaction.amount", ">", Value::Number(10000.0)),
+            comparison(
+                "Risky",
+                "industry",
+                "IN",
+                Value::Array(vec![
+                    Value::String("Gambling".to_string()),
+                    Value::String("Crypto".to_string()),
+                ]),
+            ),
+            comparison("Cross_Border", "isCrossBorder", "==", Value::Bool(true)),
+            logical("Flag", "AND", &["Large", "Risky", "Cross_Border"]),
+        ]);
+
+        let results = solve_model(&model, ScoringFormula::WeightedRatio);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].evaluation_name, "Flag");
+
+        match &results[0].outcome {
+            Satisfiability::Satisfiable { firing, near_miss } => {
+                assert_eq!(
+                    firing.event,
+                    json!({
+                        "transaction": { "amount": 10001 },
+                        "industry": "Crypto",
+                        "isCrossBorder": true
+                    })
+                );
+                let near_miss = near_miss.as_ref().unwrap();
+                assert_eq!(near_miss.flipped.as_deref(), Some("Large"));
+                assert_eq!(near_miss.event["transaction"]["amount"], json!(10000));
+                assert_eq!(near_miss.event["industry"], json!("Crypto"));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_near_miss_skips_flips_caught_by_other_branch() {
+        let model = model(vec![
+            comparison("Large", "amount", ">=", Value::Number(500.0)),
+            comparison("Huge", "amount", ">", Value::Number(100.0)),
+            comparison("Foreign", "country", "!=", Value::String("US".to_string())),
+            logical("Either", "OR", &["Large", "Huge"]),
+            logical("Flag", "AND", &["Either", "Foreign"]),
+        ]);
+
+        match solve(&model, "Flag", ScoringFormula::WeightedRatio).unwrap() {
+            Satisfiability::Satisfiable { firing, near_miss } => {
+                assert_eq!(firing.event, json!({ "amount": 500, "country": "other_0" }));
+                // Lowering the amount to 499 still satisfies `Huge`
+                let near_miss = near_miss.unwrap();
+                assert_eq!(near_miss.flipped.as_deref(), Some("Foreign"));
+                assert_eq!(near_miss.event["country"], json!("US"));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsatisfiable_explanation() {
+        let model = model(vec![
+            comparison("Large", "amount", ">", Value::Number(10000.0)),
+            comparison("Small", "amount", "<", Value::Number(500.0)),
+            comparison("Tiny", "amount", "<", Value::Number(10.0)),
+            logical("Either_Small", "OR", &["Small", "Tiny"]),
+            logical("Never", "AND", &["Large", "Either_Small"]),
+        ]);
+
+        match solve(&model, "Never", ScoringFormula::WeightedRatio).unwrap() {
+            Satisfiability::Unsatisfiable { explanation } => {
+                assert_eq!(
+                    explanation,
+                    vec![
+                        "amount > 10000 (Large) contradicts amount < 500 (Small)",
+                        "amount > 10000 (Large) contradicts amount < 10 (Tiny)",
+                    ]
+                );
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_opaque_evaluations_become_assumptions() {
+        let mut count = comparison("Count", "amount", ">", Value::Number(0.0));
+        count.evaluation_type = EvaluationType::Aggregation;
+
+        let model = model(vec![
+            count,
+            comparison("Count_Check", "@Count", ">", Value::Number(5.0)),
+            comparison("Small", "amount", "<", Value::Number(200.0)),
+            logical("Velocity", "AND", &["Count_Check", "Small"]),
+        ]);
+
+        match solve(&model, "Velocity", ScoringFormula::WeightedRatio).unwrap() {
+            Satisfiability::Satisfiable { firing, near_miss } => {
+                assert_eq!(firing.assumptions, vec!["Count_Check"]);
+                assert_eq!(firing.event, json!({ "amount": 199 }));
+                assert_eq!(near_miss.unwrap().event, json!({ "amount": 200 }));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_firing_witness_reaches_threshold() {
+        let mut model = model(vec![
+            weighted(
+                comparison("Cross", "geo", "!=", Value::String("US".to_string())),
+                5,
+            ),
+            weighted(comparison("Pep", "pep", "==", Value::Bool(true)), 4),
+            comparison("Large", "amount", ">", Value::Number(1000.0)),
+            weighted(logical("Flag", "OR", &["Cross", "Pep", "Large"]), 5),
+        ]);
+        model.threshold = 0.9;
+
+        // Firing `Flag` alone scores 10/17, so every evaluation has to fire
+        match solve(&model, "Flag", ScoringFormula::WeightedRatio).unwrap() {
+            Satisfiability::Satisfiable { firing, near_miss } => {
+                assert_eq!(
+                    firing.event,
+                    json!({ "geo": "other_0", "pep": true, "amount": 1001 })
+                );
+                let near_miss = near_miss.unwrap();
+                assert_eq!(near_miss.flipped.as_deref(), Some("Cross"));
+                assert_eq!(near_miss.event["geo"], json!("US"));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unreachable_threshold() {
+        let mut flag = logical("Flag", "OR", &["Large", "Small"]);
+        flag.weight = None;
+        let mut model = model(vec![
+            comparison("Large", "amount", ">", Value::Number(1000.0)),
+            comparison("Small", "amount", "<", Value::Number(10.0)),
+            flag,
+        ]);
+        model.threshold = 0.9;
+
+        match solve(&model, "Flag", ScoringFormula::WeightedRatio).unwrap() {
+            Satisfiability::Unsatisfiable { explanation } => {
+                assert_eq!(
+                    explanation,
+                    vec![
+                        "Flag can fire, but never with evaluations weighing enough to reach the threshold 0.9 under weighted_ratio scoring"
+                    ]
+                );
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_report_serializes() {
+        let model = model(vec![
+            comparison("Large", "amount", ">", Value::Number(1.5)),
+            logical("Flag", "AND", &["Large"]),
+        ]);
+        let json =
+            serde_json::to_value(solve_model(&model, ScoringFormula::WeightedRatio)).unwrap();
+        assert_eq!(json[0]["outcome"]["status"], "satisfiable");
+        assert_eq!(json[0]["outcome"]["firing"]["event"]["amount"], json!(2.5));
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::field_audit::{FieldClassifications, LibraryAudit, UsageKind};
+use lrol_analyzer::library::LibraryRule;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn handle_audit(path: PathBuf, config: PathBuf, output: &str) -> Result<()> {
+    let classifications = FieldClassifications::from_file(&config).map_err(anyhow::Error::msg)?;
+
+    let mut rules = Vec::new();
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(&path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        for file in files {
+            let source = file.to_string_lossy().into_owned();
+            match lrol_parser::parse_file(&file) {
+                Ok(model) => rules.push(LibraryRule { source, model }),
+                Err(error) => eprintln!(
+                    "{} {}: {}",
+                    "Skipped unparseable rule:".yellow(),
+                    source,
+                    error
+                ),
+            }
+        }
+    } else {
+        let model = lrol_parser::parse_file(&path)
+            .with_context(|| format!("Failed to parse file: {}", path.display()))?;
+        rules.push(LibraryRule {
+            source: path.to_string_lossy().into_owned(),
+            model,
+        });
+    }
+    let audit = LibraryAudit::from_library(&rules, &classifications);
+
+    match output {
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&audit).context("Failed to serialize audit")?
+        ),
+        "csv" => print!("{}", audit.to_csv()),
+        _ => print_audit(&audit),
+    }
+
+    Ok(())
+}
+
+fn print_audit(audit: &LibraryAudit) {
+    for rule in &audit.rules {
+        let source = rule.source.as_deref().unwrap_or("-");
+        if rule.usages.is_empty() {
+            println!(
+                "{} {} ({})",
+                "✓".green().bold(),
+                rule.model_id.bold(),
+                source.dimmed()
+            );
+            continue;
+        }
+        println!(
+            "{} {} ({}): {}",
+            "⚠".yellow().bold(),
+            rule.model_id.bold(),
+            source.dimmed(),
+            rule.categories.join(", ").yellow()
+        );
+        for usage in &rule.usages {
+            let detail = match usage.usage {
+                UsageKind::Direct => format!("in {}", usage.location.as_deref().unwrap_or("?")),
+                UsageKind::Aggregated => {
+                    format!("aggregated in {}", usage.location.as_deref().unwrap_or("?"))
+                }
+                UsageKind::Referenced => format!("via {}", usage.via.join(" → ")),
+            };
+            println!(
+                "    {}: {} [{}] {}",
+                usage.evaluation_name.cyan(),
+                usage.field,
+                usage.categories.join(", "),
+                detail.dimmed()
+            );
+        }
+    }
+
+    if audit.rules.len() > 1 {
+        println!();
+        println!("{}", "Categories:".cyan().bold());
+        if audit.categories.is_empty() {
+            println!("  {}", "No rule uses a classified field".dimmed());
+        }
+        for (category, rules) in &audit.categories {
+            let ids: Vec<&str> = rules.iter().map(|rule| rule.model_id.as_str()).collect();
+            println!(
+                "  {} ({}): {}",
+                category.bold(),
+                rules.len(),
+                ids.join(", ")
+            );
+        }
+    }
+}
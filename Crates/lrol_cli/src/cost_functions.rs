@@ -0,0 +1,100 @@
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use lrol_analyzer::diagnostic::Severity;
+use lrol_analyzer::state_cost::{
+    estimate_state_cost, format_bytes, format_window, StateCostConfig,
+};
+use lrol_analyzer::validator::RuleValidator;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::print_diagnostics;
+
+pub fn handle_cost(file: PathBuf, config: PathBuf, output: &str) -> Result<()> {
+    let config = StateCostConfig::from_file(&config).map_err(anyhow::Error::msg)?;
+    let source = fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+
+    // Validation only provides the source map; the rule's own errors are for
+    // `lrol validate` to report
+    let mut report = RuleValidator::new().validate_with_report(&source);
+    report.file_path = Some(file.to_string_lossy().into_owned());
+    let Some(ref model) = report.model else {
+        match report.parser_error {
+            Some(ref error) => bail!("Failed to parse file {}: {}", file.display(), error),
+            None => bail!("Failed to parse file: {}", file.display()),
+        }
+    };
+    let cost = estimate_state_cost(model, &config);
+
+    if output == "json" {
+        let warnings: Vec<serde_json::Value> = cost
+            .warnings
+            .iter()
+            .map(|warning| {
+                serde_json::json!({ "code": warning.code(), "message": warning.to_string() })
+            })
+            .collect();
+        let mut json = serde_json::to_value(&cost).context("Failed to serialize state cost")?;
+        json["warnings"] = serde_json::Value::Array(warnings);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).context("Failed to serialize state cost")?
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Aggregation state".cyan().bold());
+    if cost.aggregations.is_empty() {
+        println!("  {}", "No aggregations".dimmed());
+    }
+    for aggregation in &cost.aggregations {
+        let function = aggregation
+            .aggregation
+            .as_ref()
+            .map_or("?".to_string(), ToString::to_string);
+        match (
+            aggregation.window_seconds,
+            aggregation.state_entries,
+            aggregation.memory_bytes,
+        ) {
+            (Some(window), Some(entries), Some(memory)) => println!(
+                "  {} ({}, {} window): {} entries, ~{}",
+                aggregation.evaluation_name.bold(),
+                function,
+                format_window(window),
+                entries,
+                format_bytes(memory)
+            ),
+            _ => println!(
+                "  {} ({}, {})",
+                aggregation.evaluation_name.bold(),
+                function,
+                "unbounded window".red()
+            ),
+        }
+    }
+
+    match (cost.total_state_entries, cost.total_memory_bytes) {
+        (Some(entries), Some(memory)) => {
+            let budget = cost
+                .budget_bytes
+                .map(|budget| format!(" of {} budget", format_bytes(budget)))
+                .unwrap_or_default();
+            println!(
+                "\n{} {} entries, ~{}{}",
+                "Total:".bold(),
+                entries,
+                format_bytes(memory),
+                budget
+            );
+        }
+        _ => println!("\n{} {}", "Total:".bold(), "unbounded".red()),
+    }
+
+    report.analyzer_errors.clear();
+    report.analyzer_warnings = cost.warnings;
+    print_diagnostics(&report, Some(Severity::Warning));
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::diff::{ChangeKind, FieldChange, RuleDiff};
+use lrol_analyzer::impact::{Impact, ImpactReport};
+use lrol_analyzer::scoring::ScoringFormula;
+use std::path::PathBuf;
+
+pub fn handle_diff(
+    old: PathBuf,
+    new: PathBuf,
+    scoring: ScoringFormula,
+    output: &str,
+) -> Result<()> {
+    let old_model = lrol_parser::parse_file(&old)
+        .with_context(|| format!("Failed to parse file: {}", old.display()))?;
+    let new_model = lrol_parser::parse_file(&new)
+        .with_context(|| format!("Failed to parse file: {}", new.display()))?;
+    let diff = RuleDiff::between(&old_model, &new_model);
+    let impact = ImpactReport::classify(&old_model, &new_model, &diff, scoring);
+
+    if output == "json" {
+        let mut json = serde_json::to_value(&diff).context("Failed to serialize diff")?;
+        json["impact"] = serde_json::to_value(&impact).context("Failed to serialize impact")?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).context("Failed to serialize diff")?
+        );
+        return Ok(());
+    }
+
+    if diff.is_empty() {
+        println!("{}", "✓ No semantic changes".green().bold());
+        if diff.evaluation_order_changed {
+            println!("  {}", "Evaluations were only reordered".dimmed());
+        }
+        return Ok(());
+    }
+
+    if !diff.rule.is_empty() {
+        println!("{}", "Rule:".cyan().bold());
+        for change in &diff.rule {
+            print_field_change(change, "  ");
+        }
+    }
+
+    if !diff.evaluations.is_empty() {
+        println!("{}", "Evaluations:".cyan().bold());
+        for evaluation in &diff.evaluations {
+            let kind = evaluation
+                .field("type")
+                .and_then(|change| change.new.as_ref().or(change.old.as_ref()))
+                .and_then(|value| value.as_str())
+                .unwrap_or("?");
+            match evaluation.change {
+                ChangeKind::Added => {
+                    println!(
+                        "  {} {} ({})",
+                        "+".green().bold(),
+                        evaluation.name.green(),
+                        kind
+                    )
+                }
+                ChangeKind::Removed => {
+                    println!(
+                        "  {} {} ({})",
+                        "-".red().bold(),
+                        evaluation.name.red(),
+                        kind
+                    )
+                }
+                ChangeKind::Modified => {
+                    println!("  {} {}", "~".yellow().bold(), evaluation.name.yellow());
+                    for change in &evaluation.fields {
+                        print_field_change(change, "      ");
+                    }
+                }
+            }
+        }
+        if diff.evaluation_order_changed {
+            println!("  {}", "Evaluations were also reordered".dimmed());
+        }
+    }
+
+    if !diff.actions.is_empty() {
+        println!("{}", "Actions:".cyan().bold());
+        for action in &diff.actions {
+            let reason = |reason: &Option<String>| {
+                reason
+                    .as_deref()
+                    .map(|r| format!("{:?}", r))
+                    .unwrap_or_default()
+            };
+            match action.change {
+                ChangeKind::Added => println!(
+                    "  {} {}: {}",
+                    "+".green().bold(),
+                    action.action_type,
+                    reason(&action.new_reason)
+                ),
+                ChangeKind::Removed => println!(
+                    "  {} {}: {}",
+                    "-".red().bold(),
+                    action.action_type,
+                    reason(&action.old_reason)
+                ),
+                ChangeKind::Modified => println!(
+                    "  {} {}: {} → {}",
+                    "~".yellow().bold(),
+                    action.action_type,
+                    reason(&action.old_reason),
+                    reason(&action.new_reason)
+                ),
+            }
+        }
+    }
+
+    let dependencies = &diff.dependencies;
+    if !dependencies.added.is_empty() || !dependencies.removed.is_empty() {
+        println!("{}", "Dependencies:".cyan().bold());
+        for edge in &dependencies.added {
+            println!("  {} {}", "+".green().bold(), edge);
+        }
+        for edge in &dependencies.removed {
+            println!("  {} {}", "-".red().bold(), edge);
+        }
+    }
+
+    print_impact(&impact);
+
+    Ok(())
+}
+
+fn print_impact(report: &ImpactReport) {
+    let paint = |impact: Impact| {
+        let text = impact.to_string();
+        match impact {
+            Impact::Broadening => text.red().bold(),
+            Impact::Narrowing => text.green().bold(),
+            Impact::Neutral => text.dimmed(),
+            Impact::Unknown => text.yellow().bold(),
+        }
+    };
+
+    println!("{} {}", "Impact:".cyan().bold(), paint(report.impact));
+    for change in &report.changes {
+        let blocking = if change.blocking {
+            format!(" {}", "blocking".red().bold())
+        } else {
+            String::new()
+        };
+        println!(
+            "  {} {}{}: {}",
+            paint(change.impact),
+            change.subject,
+            blocking,
+            change.reason.dimmed()
+        );
+    }
+
+    let approvals: Vec<&str> = report
+        .second_approvals()
+        .map(|change| change.subject.as_str())
+        .collect();
+    if approvals.is_empty() {
+        println!("{}", "✓ No second approval required".green().bold());
+    } else {
+        println!(
+            "{} {}",
+            "⚠ Second approval required for:".yellow().bold(),
+            approvals.join(", ")
+        );
+    }
+}
+
+fn print_field_change(change: &FieldChange, indent: &str) {
+    match (&change.old, &change.new) {
+        (Some(old), Some(new)) => println!("{}{}: {} → {}", indent, change.field, old, new),
+        (None, Some(new)) => println!("{}{}: {}", indent, change.field, new.to_string().green()),
+        (Some(old), None) => println!(
+            "{}{}: {} (removed)",
+            indent,
+            change.field,
+            old.to_string().red()
+        ),
+        (None, None) => {}
+    }
+}
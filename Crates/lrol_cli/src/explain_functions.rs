@@ -0,0 +1,30 @@
+use anyhow::{bail, Result};
+use colored::Colorize;
+use lrol_analyzer::error_codes::{explain, ERROR_CODES};
+
+pub fn handle_explain(code: Option<String>) -> Result<()> {
+    let Some(code) = code else {
+        println!("{}", "Diagnostic codes:".cyan().bold());
+        for error_code in ERROR_CODES {
+            println!("  {}  {}", error_code.code.bold(), error_code.title);
+        }
+        println!("\nRun `lrol explain <code>` for a detailed explanation.");
+        return Ok(());
+    };
+
+    match explain(&code) {
+        Some(error_code) => {
+            println!(
+                "{} {}\n",
+                error_code.code.cyan().bold(),
+                error_code.title.bold()
+            );
+            println!("{}", error_code.explanation);
+            Ok(())
+        }
+        None => bail!(
+            "Unknown diagnostic code: {} (run `lrol explain` to list all codes)",
+            code
+        ),
+    }
+}
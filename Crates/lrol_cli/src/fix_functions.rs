@@ -0,0 +1,52 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::fix::fix_source;
+use lrol_analyzer::validator::RuleValidator;
+use std::path::PathBuf;
+
+pub fn handle_fix(file: PathBuf, unsafe_fixes: bool, dry_run: bool) -> Result<()> {
+    let source = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+
+    let validator = RuleValidator::new();
+    let outcome = fix_source(&validator, &source, unsafe_fixes);
+
+    for fix in &outcome.applied {
+        eprintln!("{} {}", "Fixed:".green().bold(), fix.message);
+    }
+
+    if dry_run {
+        print!("{}", outcome.source);
+    } else if !outcome.applied.is_empty() {
+        std::fs::write(&file, &outcome.source)
+            .with_context(|| format!("Failed to write file: {}", file.display()))?;
+    }
+
+    let remaining = validator.validate_with_report(&outcome.source).fixes();
+    let skipped = remaining.iter().filter(|fix| !fix.is_machine_applicable());
+    for fix in skipped {
+        eprintln!(
+            "{} {} {}",
+            "Not applied:".yellow().bold(),
+            fix.message,
+            "(use --unsafe-fixes)".dimmed()
+        );
+    }
+
+    if outcome.applied.is_empty() {
+        eprintln!("{}", "No fixes to apply".cyan());
+    } else if !dry_run {
+        eprintln!(
+            "{}",
+            format!(
+                "✓ Applied {} fix(es) to {}",
+                outcome.applied.len(),
+                file.display()
+            )
+            .green()
+            .bold()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::graph_export::{EvaluationGraph, GraphFormat};
+use lrol_analyzer::library::LibraryRule;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn handle_graph(path: PathBuf, format: GraphFormat, output: Option<PathBuf>) -> Result<()> {
+    let graph = if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(&path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        let mut rules = Vec::new();
+        for file in files {
+            let source = file.to_string_lossy().into_owned();
+            match lrol_parser::parse_file(&file) {
+                Ok(model) => rules.push(LibraryRule { source, model }),
+                Err(error) => eprintln!(
+                    "{} {}: {}",
+                    "Skipped unparseable rule:".yellow(),
+                    source,
+                    error
+                ),
+            }
+        }
+        EvaluationGraph::from_library(&rules)
+    } else {
+        let model = lrol_parser::parse_file(&path)
+            .with_context(|| format!("Failed to parse file: {}", path.display()))?;
+        EvaluationGraph::from_model(&model, Some(&path.to_string_lossy()))
+    };
+
+    let rendered = graph.render(format);
+    match output {
+        Some(ref file) => {
+            fs::write(file, rendered)
+                .with_context(|| format!("Failed to write graph: {}", file.display()))?;
+            eprintln!(
+                "{}",
+                format!(
+                    "✓ Wrote {} evaluations of {} rules to {}",
+                    graph.nodes.len(),
+                    graph.rules.len(),
+                    file.display()
+                )
+                .green()
+                .bold()
+            );
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
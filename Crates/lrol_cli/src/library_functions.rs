@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::error::FileValidationError;
+use lrol_analyzer::library::{analyze_library, LibraryReport, LibraryRule};
+use lrol_analyzer::validator::RuleValidator;
+use std::path::PathBuf;
+
+pub fn handle_library(dir: PathBuf, output: &str) -> Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!("Not a directory: {}", dir.display());
+    }
+
+    // Rules with semantic errors still take part, as long as they parse
+    let mut rules = Vec::new();
+    let mut skipped = Vec::new();
+    for (source, result) in RuleValidator::new().validate_directory(&dir) {
+        let model = match result {
+            Ok(report) => report.model,
+            Err(FileValidationError::ValidationErrors(report)) => report.model,
+            Err(_) => None,
+        };
+        match model {
+            Some(model) => rules.push(LibraryRule { source, model }),
+            None => skipped.push(source),
+        }
+    }
+    skipped.sort();
+
+    let report = analyze_library(&rules);
+
+    if output == "json" {
+        let json = serde_json::to_string_pretty(&report)
+            .context("Failed to serialize library report to JSON")?;
+        println!("{}", json);
+    } else {
+        print_library_report(&report);
+    }
+
+    for source in skipped {
+        eprintln!("{} {}", "Skipped unparseable rule:".yellow(), source);
+    }
+
+    Ok(())
+}
+
+fn print_library_report(report: &LibraryReport) {
+    println!(
+        "{}",
+        format!("Analyzed {} rules", report.rule_count)
+            .cyan()
+            .bold()
+    );
+
+    if report.is_clean() {
+        println!("{}", "✓ No overlaps between rules found".green().bold());
+        return;
+    }
+
+    if !report.duplicate_model_ids.is_empty() {
+        println!("\n{}", "Duplicate model IDs:".red().bold());
+        for duplicate in &report.duplicate_model_ids {
+            println!(
+                "  {} in {}",
+                duplicate.model_id.red(),
+                duplicate.sources.join(", ")
+            );
+        }
+    }
+
+    if !report.conflicting_actions.is_empty() {
+        println!(
+            "\n{}",
+            "Overlapping rules with conflicting actions:".red().bold()
+        );
+        for conflict in &report.conflicting_actions {
+            let [a, b] = &conflict.rules;
+            println!(
+                "  {} ({}) and {} ({})",
+                a.model_id.cyan(),
+                conflict.actions[0].join(", ").yellow(),
+                b.model_id.cyan(),
+                conflict.actions[1].join(", ").yellow()
+            );
+            println!("    shared: {}", conflict.shared_conditions.join("; "));
+        }
+    }
+
+    if !report.subsumed_rules.is_empty() {
+        println!("\n{}", "Rules with subsumed conditions:".yellow().bold());
+        for subsumed in &report.subsumed_rules {
+            let relation = if subsumed.equivalent {
+                "are equivalent to"
+            } else {
+                "imply"
+            };
+            println!(
+                "  {}'s conditions {} {}'s conditions",
+                subsumed.rule.model_id.cyan(),
+                relation,
+                subsumed.subsumed_by.model_id.cyan()
+            );
+        }
+    }
+
+    if !report.equivalent_evaluations.is_empty() {
+        println!(
+            "\n{}",
+            "Equivalent evaluations across rules:".yellow().bold()
+        );
+        for equivalent in &report.equivalent_evaluations {
+            println!("  {}", equivalent.signature.dimmed());
+            for occurrence in &equivalent.occurrences {
+                println!(
+                    "    • {} in {}",
+                    occurrence.evaluation_name.cyan(),
+                    occurrence.model_id
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::lint::{LintConfig, LintDiagnostic, LintLevel, LintRegistry};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Configuration picked up from the working directory when `--config` is not given
+pub const DEFAULT_LINT_CONFIG: &str = "lrol-lints.json";
+
+#[derive(Serialize)]
+struct FileLints {
+    file: String,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+pub fn handle_lint(path: PathBuf, config: Option<PathBuf>, output: &str) -> Result<()> {
+    let config = match config {
+        Some(config) => LintConfig::from_file(config).map_err(anyhow::Error::msg)?,
+        None if Path::new(DEFAULT_LINT_CONFIG).is_file() => {
+            LintConfig::from_file(DEFAULT_LINT_CONFIG).map_err(anyhow::Error::msg)?
+        }
+        None => LintConfig::default(),
+    };
+    let registry = LintRegistry::with_builtin_lints();
+    for id in registry.unknown_lints(&config) {
+        eprintln!("{} {}", "Unknown lint in configuration:".yellow(), id);
+    }
+
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&path)
+            .with_context(|| format!("Failed to read directory: {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path]
+    };
+
+    let mut results = Vec::new();
+    for file in files {
+        let source = file.to_string_lossy().into_owned();
+        match lrol_parser::parse_file(&file) {
+            Ok(model) => results.push(FileLints {
+                diagnostics: registry.run(&model, Some(&source), &config),
+                file: source,
+            }),
+            Err(error) => eprintln!(
+                "{} {}: {}",
+                "Skipped unparseable rule:".yellow(),
+                source,
+                error
+            ),
+        }
+    }
+
+    if output == "json" {
+        let json =
+            serde_json::to_string_pretty(&results).context("Failed to serialize lints to JSON")?;
+        println!("{}", json);
+    } else {
+        print_lints(&results);
+    }
+
+    let denied = results
+        .iter()
+        .flat_map(|result| &result.diagnostics)
+        .any(|diagnostic| diagnostic.level == LintLevel::Deny);
+    if denied {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_lints(results: &[FileLints]) {
+    let mut warned = 0;
+    let mut denied = 0;
+
+    for result in results.iter().filter(|r| !r.diagnostics.is_empty()) {
+        println!("\n{}", result.file.cyan().bold());
+        for diagnostic in &result.diagnostics {
+            let level = match diagnostic.level {
+                LintLevel::Deny => {
+                    denied += 1;
+                    "error".red().bold()
+                }
+                _ => {
+                    warned += 1;
+                    "warning".yellow().bold()
+                }
+            };
+            let location = diagnostic
+                .evaluation_name
+                .as_ref()
+                .map(|name| format!(" in {}", name.cyan()))
+                .unwrap_or_default();
+            println!(
+                "  {}[{}]{}: {}",
+                level, diagnostic.lint_id, location, diagnostic.message
+            );
+            if let Some(ref suggestion) = diagnostic.suggestion {
+                println!("    {} {}", "Fix:".green(), suggestion);
+            }
+        }
+    }
+
+    if warned == 0 && denied == 0 {
+        println!(
+            "{}",
+            format!("✓ No lint findings in {} rule(s)", results.len())
+                .green()
+                .bold()
+        );
+    } else {
+        println!(
+            "\n{} error(s), {} warning(s) in {} rule(s)",
+            denied,
+            warned,
+            results.len()
+        );
+    }
+}
@@ -1,16 +1,43 @@
 use analyzer_functions::handle_analyze;
 use anyhow::{Context, Result};
+use audit_functions::handle_audit;
 use clap::{Parser, Subcommand};
 use colored::*;
+use cost_functions::handle_cost;
+use diff_functions::handle_diff;
+use explain_functions::handle_explain;
+use fix_functions::handle_fix;
+use graph_functions::handle_graph;
+use library_functions::handle_library;
+use lint_functions::handle_lint;
 use lrol_analyzer::{
+    analyzer::RuleAnalyzer,
+    calendar::CalendarRegistry,
+    diagnostic::{Diagnostic, Severity},
     error::*,
+    event_schema::EventSchema,
+    graph_export::GraphFormat,
+    policy::PolicySet,
+    scoring::ScoringFormula,
     validator::{RuleValidator, ValidationReport},
 };
-use lrol_parser::ParserError;
+use run_functions::handle_run;
+use schema_functions::handle_infer_schema;
 use std::path::PathBuf;
+use witness_functions::handle_witness;
 
-mod types;
 mod analyzer_functions;
+mod audit_functions;
+mod cost_functions;
+mod diff_functions;
+mod explain_functions;
+mod fix_functions;
+mod graph_functions;
+mod library_functions;
+mod lint_functions;
+mod run_functions;
+mod schema_functions;
+mod witness_functions;
 
 #[derive(Parser)]
 #[command(name = "lrol")]
@@ -46,6 +73,23 @@ enum Commands {
         /// Enable verbose output for detailed error messages
         #[arg(short, long)]
         verbose: bool,
+
+        /// Event schema to type-check comparisons against (LROL or JSON Schema format)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+
+        /// Scoring formula used to check thresholds of rules without a `scoring` field
+        /// (weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic)
+        #[arg(long, default_value = "weighted_ratio")]
+        scoring: ScoringFormula,
+
+        /// Business calendar file, or directory of calendar files, that rules may name
+        #[arg(long)]
+        calendars: Option<PathBuf>,
+
+        /// Organization policy file the rule must comply with
+        #[arg(long)]
+        policy: Option<PathBuf>,
     },
     /// Analyze LROL rules for potential issues and provide insights
     Analyze {
@@ -60,6 +104,181 @@ enum Commands {
         /// Output format (text or json)
         #[arg(short = 'o', long, default_value = "text")]
         output: String,
+
+        /// Event schema to type-check comparisons against (LROL or JSON Schema format)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+
+        /// Scoring formula used to check thresholds of rules without a `scoring` field
+        /// (weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic)
+        #[arg(long, default_value = "weighted_ratio")]
+        scoring: ScoringFormula,
+
+        /// Business calendar file, or directory of calendar files, that rules may name
+        #[arg(long)]
+        calendars: Option<PathBuf>,
+    },
+    /// Apply suggested fixes to a rule file in place, preserving its formatting
+    Fix {
+        /// Path to the LROL JSON file
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Also apply fixes that may change the rule's behavior
+        #[arg(long)]
+        unsafe_fixes: bool,
+
+        /// Print the fixed rule instead of writing it back
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Explain a diagnostic code in detail, or list all codes
+    Explain {
+        /// Code such as LROL0012
+        code: Option<String>,
+    },
+    /// Infer an event schema from sample JSON or NDJSON events
+    InferSchema {
+        /// Files with sample events (a JSON object, a JSON array or NDJSON)
+        #[arg(short, long, required = true, num_args = 1..)]
+        samples: Vec<PathBuf>,
+
+        /// LROL file whose referenced fields are checked against the samples
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Write the schema to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Compare all rules in a directory for duplicates, overlaps and conflicting actions
+    Library {
+        /// Directory containing LROL JSON files
+        #[arg(short, long)]
+        dir: PathBuf,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Run the configurable lints against a rule file or every rule in a directory
+    Lint {
+        /// LROL JSON file or directory of rule files
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Lint configuration (defaults to lrol-lints.json in the working directory)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Draw the dependencies between evaluations of a rule, or of every rule in a directory
+    Graph {
+        /// LROL JSON file or directory of rule files
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Graph format (dot, mermaid or json)
+        #[arg(long, default_value = "dot")]
+        format: GraphFormat,
+
+        /// Write the graph to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Compare two versions of a rule, matching evaluations by name
+    Diff {
+        /// Previous version of the LROL JSON file
+        old: PathBuf,
+
+        /// New version of the LROL JSON file
+        new: PathBuf,
+
+        /// Scoring formula weight changes are judged by when the new rule has no `scoring` field
+        /// (weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic)
+        #[arg(long, default_value = "weighted_ratio")]
+        scoring: ScoringFormula,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Estimate the streaming state each aggregation of a rule needs
+    Cost {
+        /// Path to the LROL JSON file
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Event rates, entity count and memory budget to estimate against
+        #[arg(short, long)]
+        config: PathBuf,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Report which rules read classified fields such as PII, directly or through other evaluations
+    Audit {
+        /// LROL JSON file or directory of rule files
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Field classification file mapping event fields to categories
+        #[arg(short, long)]
+        config: PathBuf,
+
+        /// Output format (text, json or csv)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Run a rule against an event and report the decision
+    Run {
+        /// Path to the LROL JSON file
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// JSON file holding the event
+        #[arg(short, long)]
+        event: PathBuf,
+
+        /// JSON file mapping aggregation evaluations to their values
+        #[arg(short, long)]
+        aggregates: Option<PathBuf>,
+
+        /// Time `now` refers to, instead of the system clock (RFC 3339 or date)
+        #[arg(long)]
+        now: Option<String>,
+
+        /// Scoring formula the weights combine with when the rule has no `scoring` field
+        /// (weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic)
+        #[arg(long, default_value = "weighted_ratio")]
+        scoring: ScoringFormula,
+
+        /// Business calendar file, or directory of calendar files, that rules may name
+        #[arg(long)]
+        calendars: Option<PathBuf>,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Find events that fire the rule's root evaluations, or explain why none exist
+    Witness {
+        /// Path to the LROL JSON file
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// Scoring formula the weights combine with when the rule has no `scoring` field
+        /// (weighted_ratio, root_weighted_ratio, max_weight, noisy_or or logistic)
+        #[arg(long, default_value = "weighted_ratio")]
+        scoring: ScoringFormula,
+
+        /// Output format (text or json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
     },
 }
 
@@ -72,12 +291,74 @@ fn main() -> Result<()> {
             output,
             verbose,
         } => handle_parse(file, &output, verbose),
-        Commands::Validate { file, verbose } => handle_validate(file, verbose),
+        Commands::Validate {
+            file,
+            verbose,
+            schema,
+            scoring,
+            calendars,
+            policy,
+        } => handle_validate(file, verbose, schema, scoring, calendars, policy),
         Commands::Analyze {
             file,
             verbose,
             output,
-        } => handle_analyze(file, verbose, &output),
+            schema,
+            scoring,
+            calendars,
+        } => handle_analyze(file, verbose, &output, schema, scoring, calendars),
+        Commands::Fix {
+            file,
+            unsafe_fixes,
+            dry_run,
+        } => handle_fix(file, unsafe_fixes, dry_run),
+        Commands::Explain { code } => handle_explain(code),
+        Commands::InferSchema {
+            samples,
+            file,
+            output,
+        } => handle_infer_schema(samples, file, output),
+        Commands::Library { dir, output } => handle_library(dir, &output),
+        Commands::Lint {
+            path,
+            config,
+            output,
+        } => handle_lint(path, config, &output),
+        Commands::Graph {
+            path,
+            format,
+            output,
+        } => handle_graph(path, format, output),
+        Commands::Diff {
+            old,
+            new,
+            scoring,
+            output,
+        } => handle_diff(old, new, scoring, &output),
+        Commands::Cost {
+            file,
+            config,
+            output,
+        } => handle_cost(file, config, &output),
+        Commands::Audit {
+            path,
+            config,
+            output,
+        } => handle_audit(path, config, &output),
+        Commands::Run {
+            file,
+            event,
+            aggregates,
+            now,
+            scoring,
+            calendars,
+            output,
+        } => handle_run(file, event, aggregates, now, scoring, calendars, &output),
+        Commands::Witness {
+            file,
+            scoring,
+            output,
+        } => handle_witness(file, scoring, &output),
     }
 }
 
@@ -91,7 +372,7 @@ fn handle_parse(file: PathBuf, output: &str, verbose: bool) -> Result<()> {
                 serde_json::to_string_pretty(&result).context("Failed to serialize to JSON")?;
             println!("{}", json);
         }
-        "text" | _ => {
+        _ => {
             print_model_summary(&result, verbose);
         }
     }
@@ -99,11 +380,44 @@ fn handle_parse(file: PathBuf, output: &str, verbose: bool) -> Result<()> {
     Ok(())
 }
 
-fn handle_validate(file: PathBuf, verbose: bool) -> Result<()> {
+/// Validator type-checking against the event schema at `schema`, checking
+/// calendar names against the calendars at `calendars` and rules against the
+/// policies at `policy`, if given
+pub(crate) fn build_validator(
+    schema: Option<PathBuf>,
+    scoring: ScoringFormula,
+    calendars: Option<PathBuf>,
+    policy: Option<PathBuf>,
+) -> Result<RuleValidator> {
+    let mut analyzer = match schema {
+        Some(path) => {
+            let schema = EventSchema::from_file(&path).map_err(anyhow::Error::msg)?;
+            RuleAnalyzer::with_event_schema(schema)
+        }
+        None => RuleAnalyzer::new(),
+    };
+    analyzer.set_scoring_formula(scoring);
+    if let Some(path) = calendars {
+        analyzer.set_calendars(CalendarRegistry::from_path(&path).map_err(anyhow::Error::msg)?);
+    }
+    if let Some(path) = policy {
+        analyzer.set_policies(PolicySet::from_file(&path).map_err(anyhow::Error::msg)?);
+    }
+    Ok(RuleValidator::with_analyzer(analyzer))
+}
+
+fn handle_validate(
+    file: PathBuf,
+    verbose: bool,
+    schema: Option<PathBuf>,
+    scoring: ScoringFormula,
+    calendars: Option<PathBuf>,
+    policy: Option<PathBuf>,
+) -> Result<()> {
     println!("{}", "Validating LROL file...".cyan());
     let file_path = file.display();
 
-    let mut validator = RuleValidator::new();
+    let validator = build_validator(schema, scoring, calendars, policy)?;
     match validator.validate_with_report_from_file(&file) {
         Ok(report) => {
             print_validation_success(&report, verbose);
@@ -146,6 +460,8 @@ fn handle_validate(file: PathBuf, verbose: bool) -> Result<()> {
 fn print_validation_success(report: &ValidationReport, verbose: bool) {
     println!("{}", "✓ File is valid LROL".green().bold());
 
+    print_diagnostics(report, Some(Severity::Warning));
+
     // Print model summary if available
     if let Some(ref model) = report.model {
         print_model_summary(model, verbose);
@@ -173,94 +489,7 @@ fn print_validation_failure(report: &ValidationReport, verbose: bool) {
         println!("\nFile: {}", file_path.cyan());
     }
 
-    if let Some(ref parser_error) = report.parser_error {
-        println!("\n{}", "Parser Errors:".yellow().bold());
-        match parser_error {
-            ParserError::InvalidSyntax {
-                line,
-                column,
-                message,
-            } => {
-                println!(
-                    "  Line {}, Column {}: {}",
-                    line.to_string().cyan(),
-                    column.to_string().cyan(),
-                    message
-                );
-            }
-            ParserError::MissingField { field } => {
-                println!("  Missing required field: {}", field.cyan());
-            }
-            ParserError::InvalidValue {
-                field,
-                expected,
-                found,
-            } => {
-                println!(
-                    "  Invalid value for {}: expected {}, found {}",
-                    field.cyan(),
-                    expected.yellow(),
-                    found.red()
-                );
-            }
-        }
-    }
-
-    if !report.analyzer_errors.is_empty() {
-        println!("\n{}", "Analyzer Errors:".yellow().bold());
-        for (i, error) in report.analyzer_errors.iter().enumerate() {
-            let error_message = match error {
-                AnalyzerError::InvalidThreshold { value, reason } => {
-                    format!("Invalid threshold {}: {}", value.to_string().red(), reason)
-                }
-                AnalyzerError::DuplicateEvaluationName(name) => {
-                    format!("Duplicate evaluation name: {}", name.red())
-                }
-                AnalyzerError::MissingOperandReference {
-                    evaluation_name,
-                    missing_operand,
-                } => {
-                    format!(
-                        "Missing operand reference in {}: {}",
-                        evaluation_name.cyan(),
-                        missing_operand.red()
-                    )
-                }
-                AnalyzerError::CircularDependency {
-                    evaluation_name,
-                    dependency_chain,
-                } => {
-                    format!(
-                        "Circular dependency detected in {}: {}",
-                        evaluation_name.cyan(),
-                        dependency_chain.join(" → ").red()
-                    )
-                }
-                AnalyzerError::InvalidDateTimeExpression {
-                    evaluation_name,
-                    field_name,
-                    expression,
-                    reason,
-                } => {
-                    format!(
-                        "Invalid datetime in {} ({}): {} - {}",
-                        evaluation_name.cyan(),
-                        field_name,
-                        expression.red(),
-                        reason
-                    )
-                }
-                // Add other error type formatting as needed...
-                _ if verbose => {
-                    format!("{:?}", error)
-                }
-                _ => {
-                    format!("{:?}", error)
-                }
-            };
-            println!("  {}. {}", (i + 1), error_message);
-        }
-    }
+    print_diagnostics(report, None);
 
     if verbose {
         println!("\n{}", "Full Validation Report:".yellow().bold());
@@ -270,6 +499,35 @@ fn print_validation_failure(report: &ValidationReport, verbose: bool) {
     }
 }
 
+/// Prints the report's diagnostics with source snippets, optionally only those of `severity`
+pub(crate) fn print_diagnostics(report: &ValidationReport, severity: Option<Severity>) {
+    let diagnostics: Vec<Diagnostic> = report
+        .diagnostics()
+        .into_iter()
+        .filter(|diagnostic| severity.is_none_or(|severity| diagnostic.severity == severity))
+        .collect();
+
+    for diagnostic in &diagnostics {
+        let rendered = diagnostic.render(report.source.as_deref(), report.file_path.as_deref());
+        // The heading is rendered again with colors, the snippet below it as is
+        let snippet = rendered.split_once('\n').map_or("", |(_, snippet)| snippet);
+        let prefix = format!("{}[{}]", diagnostic.severity, diagnostic.code);
+        let prefix = match diagnostic.severity {
+            Severity::Error => prefix.red().bold(),
+            Severity::Warning => prefix.yellow().bold(),
+        };
+        println!("\n{}{}", prefix, format!(": {}", diagnostic.message).bold());
+        print!("{}", snippet);
+    }
+
+    if !diagnostics.is_empty() {
+        println!(
+            "\n{}",
+            "For more information about a code, try `lrol explain <code>`".dimmed()
+        );
+    }
+}
+
 fn print_model_summary(model: &lrol_parser::parser::LrolModel, verbose: bool) {
     println!("{}", "LROL Model Summary".green().bold());
     println!("Model ID: {}", model.model_id);
@@ -0,0 +1,94 @@
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use lrol_analyzer::calendar::CalendarRegistry;
+use lrol_analyzer::scoring::ScoringFormula;
+use lrol_engine::{Engine, FixedClock};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn handle_run(
+    file: PathBuf,
+    event: PathBuf,
+    aggregates: Option<PathBuf>,
+    now: Option<String>,
+    scoring: ScoringFormula,
+    calendars: Option<PathBuf>,
+    output: &str,
+) -> Result<()> {
+    let model = lrol_parser::parse_file(&file)
+        .with_context(|| format!("Failed to parse file: {}", file.display()))?;
+    let event: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(&event)
+            .with_context(|| format!("Failed to read event: {}", event.display()))?,
+    )
+    .with_context(|| format!("Invalid event JSON: {}", event.display()))?;
+    let aggregates: BTreeMap<String, f64> = match aggregates {
+        Some(ref path) => serde_json::from_str(
+            &fs::read_to_string(path)
+                .with_context(|| format!("Failed to read aggregates: {}", path.display()))?,
+        )
+        .with_context(|| {
+            format!(
+                "Aggregates must map evaluation names to numbers: {}",
+                path.display()
+            )
+        })?,
+        None => BTreeMap::new(),
+    };
+
+    let mut engine = Engine::new(model);
+    engine.set_scoring_formula(scoring);
+    if let Some(ref path) = calendars {
+        engine.set_calendars(CalendarRegistry::from_path(path).map_err(anyhow::Error::msg)?);
+    }
+    if let Some(ref now) = now {
+        let Some(clock) = FixedClock::at(now) else {
+            bail!("Invalid --now timestamp '{}'", now);
+        };
+        engine.set_clock(clock);
+    }
+    let decision = engine
+        .evaluate_with_aggregates(&event, &aggregates)
+        .map_err(anyhow::Error::msg)?;
+
+    if output == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&decision).context("Failed to serialize decision")?
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Evaluations:".cyan().bold());
+    for result in &decision.evaluations {
+        let weight = result
+            .weight
+            .map(|weight| format!(" (weight {})", weight))
+            .unwrap_or_default();
+        let value = result.value.to_json().to_string();
+        let value = match result.value.to_json() {
+            serde_json::Value::Bool(true) => value.green(),
+            serde_json::Value::Bool(false) => value.dimmed(),
+            _ => value.normal(),
+        };
+        println!("  {}: {}{}", result.name, value, weight.dimmed());
+    }
+    println!(
+        "{} {:.3} (threshold {})",
+        "Score:".cyan().bold(),
+        decision.score,
+        decision.threshold
+    );
+
+    if decision.triggered {
+        println!("{}", "⚠ Rule triggered".yellow().bold());
+        for action in &decision.actions {
+            println!("  {}: {}", action.action_type.bold(), action.reason);
+        }
+    } else {
+        println!("{}", "✓ Rule not triggered".green().bold());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::schema_inference::{unobserved_fields, SchemaInference};
+use std::fs;
+use std::path::PathBuf;
+
+pub fn handle_infer_schema(
+    samples: Vec<PathBuf>,
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let mut inference = SchemaInference::new();
+    for path in &samples {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read samples: {}", path.display()))?;
+        inference
+            .add_samples_from_str(&content)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("Invalid samples in {}", path.display()))?;
+    }
+
+    let schema = inference.schema();
+    let json = serde_json::to_string_pretty(&schema).context("Failed to serialize event schema")?;
+
+    match output {
+        Some(ref path) => {
+            fs::write(path, json)
+                .with_context(|| format!("Failed to write schema: {}", path.display()))?;
+            eprintln!(
+                "{}",
+                format!(
+                    "✓ Inferred {} fields from {} events into {}",
+                    schema.fields.len(),
+                    inference.sample_count(),
+                    path.display()
+                )
+                .green()
+                .bold()
+            );
+        }
+        None => println!("{}", json),
+    }
+
+    // The schema may be on stdout, so the field report goes to stderr
+    if let Some(file) = file {
+        let model = lrol_parser::parse_file(&file)
+            .with_context(|| format!("Failed to parse file: {}", file.display()))?;
+        let missing = unobserved_fields(&model, &schema);
+
+        if missing.is_empty() {
+            eprintln!(
+                "{}",
+                "✓ Every field referenced by the rule appears in the samples".green()
+            );
+        } else {
+            eprintln!(
+                "\n{}",
+                "Referenced fields missing from the samples:"
+                    .yellow()
+                    .bold()
+            );
+            for reference in missing {
+                eprintln!(
+                    "  {} in {} ({})",
+                    reference.field.red(),
+                    reference.evaluation_name.cyan(),
+                    reference.field_name
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
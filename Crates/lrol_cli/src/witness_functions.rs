@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use lrol_analyzer::scoring::ScoringFormula;
+use lrol_analyzer::witness::{solve_model, Satisfiability, Witness};
+use std::path::PathBuf;
+
+pub fn handle_witness(file: PathBuf, scoring: ScoringFormula, output: &str) -> Result<()> {
+    let model = lrol_parser::parse_file(&file)
+        .with_context(|| format!("Failed to parse file: {}", file.display()))?;
+    let scoring = ScoringFormula::for_model(&model, scoring);
+    let results = solve_model(&model, scoring);
+
+    if output == "json" {
+        let json = serde_json::to_string_pretty(&results)
+            .context("Failed to serialize witnesses to JSON")?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    if results.is_empty() {
+        println!("{}", "No root evaluations to solve".yellow());
+    }
+
+    for result in &results {
+        match &result.outcome {
+            Satisfiability::Satisfiable { firing, near_miss } => {
+                println!(
+                    "\n{} {}",
+                    "✓".green().bold(),
+                    format!("{} can fire and trigger the rule", result.evaluation_name)
+                        .green()
+                        .bold()
+                );
+                print_witness("Firing event", firing)?;
+                match near_miss {
+                    Some(near_miss) => print_witness("Near miss", near_miss)?,
+                    None => println!("  {}", "No single-field near miss found".dimmed()),
+                }
+            }
+            Satisfiability::Unsatisfiable { explanation } => {
+                println!(
+                    "\n{} {}",
+                    "✗".red().bold(),
+                    format!("{} can never trigger the rule", result.evaluation_name)
+                        .red()
+                        .bold()
+                );
+                for line in explanation {
+                    println!("  • {}", line);
+                }
+            }
+            Satisfiability::Unknown { reason } => {
+                println!(
+                    "\n{} {}: {}",
+                    "?".yellow().bold(),
+                    result.evaluation_name.yellow().bold(),
+                    reason
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_witness(heading: &str, witness: &Witness) -> Result<()> {
+    match &witness.flipped {
+        Some(flipped) => println!("  {} (fails {}):", heading.cyan(), flipped),
+        None => println!("  {}:", heading.cyan()),
+    }
+    let json = serde_json::to_string_pretty(&witness.event)
+        .context("Failed to serialize witness event")?;
+    for line in json.lines() {
+        println!("    {}", line);
+    }
+    if !witness.assumptions.is_empty() {
+        let verb = if witness.assumptions.len() == 1 {
+            "holds"
+        } else {
+            "hold"
+        };
+        println!("    assuming {} {}", witness.assumptions.join(", "), verb);
+    }
+    Ok(())
+}
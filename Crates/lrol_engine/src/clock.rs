@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+
+/// Source of the time `now` refers to in a rule
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock stopped at one instant, for tests and for replaying past events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    /// Clock stopped at an RFC 3339 timestamp or date, see [`parse_timestamp`]
+    ///
+    /// [`parse_timestamp`]: lrol_analyzer::datetime::parse_timestamp
+    pub fn at(timestamp: &str) -> Option<Self> {
+        lrol_analyzer::datetime::parse_timestamp(timestamp).map(FixedClock)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -0,0 +1,666 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use lrol_analyzer::calendar::CalendarRegistry;
+use lrol_analyzer::datetime::{parse_timestamp, DateTimeContext, DateTimeExpr, DateTimeValue};
+use lrol_analyzer::operand::{split_arguments, split_arithmetic, Operand};
+use lrol_analyzer::scoring::{reaches, ScoringFormula};
+use lrol_analyzer::timezone::Timezones;
+use lrol_parser::{parser::LrolModel, Action, Evaluation, EvaluationType, Value};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::EngineError;
+use crate::value::{Operator, RuntimeValue};
+
+/// Runs a validated rule against events.
+///
+/// Comparisons read event fields by dotted path, so `profile.country` is the
+/// `country` key of the event's `profile` object. A comparison whose operands
+/// are missing or cannot be compared does not fire. Aggregations need event
+/// history, so their values are passed in with
+/// [`Engine::evaluate_with_aggregates`].
+pub struct Engine {
+    model: LrolModel,
+    clock: Box<dyn Clock>,
+    scoring_formula: ScoringFormula,
+    timezones: Timezones,
+    calendars: CalendarRegistry,
+}
+
+/// Outcome of running a rule against one event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Decision {
+    pub model_id: String,
+    /// Time `now` referred to, in RFC 3339
+    pub evaluated_at: String,
+    /// Value of every evaluation, in declaration order
+    pub evaluations: Vec<EvaluationResult>,
+    /// Strategy the weights were combined with
+    pub scoring: ScoringFormula,
+    pub score: f64,
+    pub threshold: f64,
+    pub triggered: bool,
+    /// The rule's actions when it triggered, otherwise none
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvaluationResult {
+    pub name: String,
+    /// Whether a comparison or logical evaluation fired, or an aggregation's value
+    pub value: RuntimeValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i32>,
+}
+
+impl Decision {
+    pub fn evaluation(&self, name: &str) -> Option<&RuntimeValue> {
+        self.evaluations
+            .iter()
+            .find(|result| result.name == name)
+            .map(|result| &result.value)
+    }
+}
+
+impl Engine {
+    pub fn new(model: LrolModel) -> Self {
+        // A validated model only names known zones; unknown ones read as UTC
+        let (timezones, _) = Timezones::from_model(&model);
+        Engine {
+            model,
+            clock: Box::new(SystemClock),
+            scoring_formula: ScoringFormula::default(),
+            timezones,
+            calendars: CalendarRegistry::new(),
+        }
+    }
+
+    pub fn model(&self) -> &LrolModel {
+        &self.model
+    }
+
+    /// Sets the clock `now` is read from, the system clock by default
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Sets the formula weights combine with for models without a `scoring`
+    /// field, weighted ratio by default
+    pub fn set_scoring_formula(&mut self, formula: ScoringFormula) {
+        self.scoring_formula = formula;
+    }
+
+    /// Sets the business calendars `is_business_day` and the other calendar
+    /// checks look up by name
+    pub fn set_calendars(&mut self, calendars: CalendarRegistry) {
+        self.calendars = calendars;
+    }
+
+    /// Runs the rule against an event; fails on rules with aggregations
+    pub fn evaluate(&self, event: &JsonValue) -> Result<Decision, EngineError> {
+        self.evaluate_with_aggregates(event, &BTreeMap::new())
+    }
+
+    /// Runs the rule against an event, taking the value of each aggregation
+    /// evaluation from `aggregates` by name
+    pub fn evaluate_with_aggregates(
+        &self,
+        event: &JsonValue,
+        aggregates: &BTreeMap<String, f64>,
+    ) -> Result<Decision, EngineError> {
+        let now = self.clock.now();
+        let mut run = Run {
+            engine: self,
+            event,
+            aggregates,
+            now,
+            evaluations: HashMap::new(),
+            values: HashMap::new(),
+            visiting: Vec::new(),
+        };
+        for evaluation in &self.model.evaluations {
+            run.evaluations
+                .entry(evaluation.name.as_str())
+                .or_insert(evaluation);
+        }
+
+        // Duplicate names resolve to the first evaluation, as in scoring
+        let mut evaluations = Vec::new();
+        for evaluation in &self.model.evaluations {
+            if evaluations
+                .iter()
+                .any(|result: &EvaluationResult| result.name == evaluation.name)
+            {
+                continue;
+            }
+            evaluations.push(EvaluationResult {
+                name: evaluation.name.clone(),
+                value: run.value(&evaluation.name)?,
+                weight: evaluation.weight,
+            });
+        }
+
+        let formula = ScoringFormula::for_model(&self.model, self.scoring_formula);
+        let score = formula.score_model(&self.model, |name| {
+            evaluations
+                .iter()
+                .any(|result| result.name == name && result.value.is_truthy())
+        });
+        let triggered = reaches(score, self.model.threshold);
+
+        Ok(Decision {
+            model_id: self.model.model_id.clone(),
+            evaluated_at: now.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            evaluations,
+            scoring: formula,
+            score,
+            threshold: self.model.threshold,
+            triggered,
+            actions: if triggered {
+                self.model.actions.clone()
+            } else {
+                Vec::new()
+            },
+        })
+    }
+}
+
+/// Reads the event field at a dotted path, or a top-level key named by the whole path
+pub fn event_field<'a>(event: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    path.split('.')
+        .try_fold(event, |value, segment| value.get(segment))
+        .or_else(|| event.get(path))
+        .filter(|value| !value.is_null())
+}
+
+// State of one run of a rule over an event
+struct Run<'a> {
+    engine: &'a Engine,
+    event: &'a JsonValue,
+    aggregates: &'a BTreeMap<String, f64>,
+    now: DateTime<Utc>,
+    evaluations: HashMap<&'a str, &'a Evaluation>,
+    values: HashMap<&'a str, RuntimeValue>,
+    visiting: Vec<&'a str>,
+}
+
+impl<'a> Run<'a> {
+    fn value(&mut self, name: &str) -> Result<RuntimeValue, EngineError> {
+        let Some(evaluation) = self.evaluations.get(name.trim()).copied() else {
+            return Err(EngineError::UnknownEvaluation {
+                name: name.to_string(),
+            });
+        };
+        let name = evaluation.name.as_str();
+        if let Some(value) = self.values.get(name) {
+            return Ok(value.clone());
+        }
+        if let Some(start) = self.visiting.iter().position(|visiting| *visiting == name) {
+            let mut chain: Vec<String> = self.visiting[start..]
+                .iter()
+                .map(|name| name.to_string())
+                .collect();
+            chain.push(name.to_string());
+            return Err(EngineError::CircularReference { chain });
+        }
+
+        self.visiting.push(name);
+        let value = self.evaluate(evaluation);
+        self.visiting.pop();
+        let value = value?;
+        self.values.insert(name, value.clone());
+        Ok(value)
+    }
+
+    fn evaluate(&mut self, evaluation: &'a Evaluation) -> Result<RuntimeValue, EngineError> {
+        let unsupported = |message: String| EngineError::Unsupported {
+            evaluation_name: evaluation.name.clone(),
+            message,
+        };
+
+        match evaluation.evaluation_type {
+            EvaluationType::Comparison => {
+                let (Some(left), Some(operator), Some(right)) = (
+                    evaluation.left.as_deref(),
+                    evaluation.operator.as_deref(),
+                    evaluation.right.as_ref(),
+                ) else {
+                    return Err(unsupported(
+                        "a comparison needs left, operator and right".to_string(),
+                    ));
+                };
+                let operator = Operator::parse(operator)
+                    .ok_or_else(|| unsupported(format!("unknown operator '{}'", operator)))?;
+                let left = self.expression(evaluation, left)?;
+                let right = self.right_operand(evaluation, right)?;
+                Ok(RuntimeValue::Bool(match (left, right) {
+                    (Some(left), Some(right)) => operator.apply(&left, &right),
+                    _ => false,
+                }))
+            }
+            EvaluationType::Logical => {
+                let conjunction = match evaluation.operator.as_deref() {
+                    Some("AND") => true,
+                    Some("OR") => false,
+                    operator => {
+                        return Err(unsupported(format!(
+                            "unknown logical operator '{}'",
+                            operator.unwrap_or_default()
+                        )))
+                    }
+                };
+                let mut values = Vec::new();
+                for operand in evaluation.operands.iter().flatten() {
+                    values.push(self.value(operand)?.is_truthy());
+                }
+                Ok(RuntimeValue::Bool(if conjunction {
+                    !values.is_empty() && values.iter().all(|v| *v)
+                } else {
+                    values.iter().any(|v| *v)
+                }))
+            }
+            EvaluationType::Aggregation => self
+                .aggregates
+                .get(&evaluation.name)
+                .map(|value| RuntimeValue::Number(*value))
+                .ok_or_else(|| EngineError::MissingAggregate {
+                    evaluation_name: evaluation.name.clone(),
+                }),
+            EvaluationType::TimeBased | EvaluationType::Conditional => Err(unsupported(format!(
+                "{} evaluations are not supported",
+                evaluation.evaluation_type
+            ))),
+        }
+    }
+
+    // The right side of a comparison, where plain strings are literals unless
+    // the event has a field by that name or they are dotted paths
+    fn right_operand(
+        &mut self,
+        evaluation: &'a Evaluation,
+        value: &Value,
+    ) -> Result<Option<RuntimeValue>, EngineError> {
+        let event = self.event;
+        match Operand::from_value(value, |path| event_field(event, path).is_some()) {
+            Operand::Literal(literal) => Ok(RuntimeValue::from_literal(&literal)),
+            Operand::Field(path) => Ok(self.field(&path)),
+            _ => match value {
+                Value::String(expr) => self.expression(evaluation, expr),
+                _ => Ok(RuntimeValue::from_literal(value)),
+            },
+        }
+    }
+
+    fn expression(
+        &mut self,
+        evaluation: &'a Evaluation,
+        expr: &str,
+    ) -> Result<Option<RuntimeValue>, EngineError> {
+        let expr = expr.trim();
+        let invalid = |message: String| EngineError::InvalidExpression {
+            evaluation_name: evaluation.name.clone(),
+            expression: expr.to_string(),
+            message,
+        };
+
+        if DateTimeExpr::is_call(expr) {
+            return self.datetime(expr).map_err(invalid);
+        }
+        match Operand::parse(expr) {
+            Operand::Field(path) => Ok(self.field(&path)),
+            Operand::Reference(name) => self.value(&name).map(Some),
+            Operand::Now => Ok(Some(RuntimeValue::DateTime(self.now))),
+            Operand::Literal(literal) => Ok(RuntimeValue::from_literal(&literal)),
+            Operand::Call { ref name, .. } if name == "timediff" => {
+                let inner = &expr[expr.find('(').unwrap_or_default() + 1..expr.len() - 1];
+                let args = split_arguments(inner);
+                if args.len() != 2 {
+                    return Err(invalid("timediff() requires 2 arguments".to_string()));
+                }
+                let to = self.expression(evaluation, args[0])?;
+                let from = self.expression(evaluation, args[1])?;
+                Ok(match (to.and_then(datetime), from.and_then(datetime)) {
+                    (Some(to), Some(from)) => Some(RuntimeValue::Duration(
+                        (to - from).num_milliseconds() as f64 / 1000.0,
+                    )),
+                    _ => None,
+                })
+            }
+            Operand::Call { name, .. } => Err(invalid(format!("unknown function {}()", name))),
+            Operand::Expression(_) => self.arithmetic(evaluation, expr),
+        }
+    }
+
+    // `@Average * 2` and other arithmetic on numbers; see `split_arithmetic`
+    // for how the expression is split
+    fn arithmetic(
+        &mut self,
+        evaluation: &'a Evaluation,
+        expr: &str,
+    ) -> Result<Option<RuntimeValue>, EngineError> {
+        let Some((left, operator, right)) = split_arithmetic(expr) else {
+            // A sign before a reference or field, e.g. `-@Balance_Change`
+            if let Some(operand) = expr.strip_prefix('-') {
+                return Ok(match self.expression(evaluation, operand)? {
+                    Some(RuntimeValue::Number(n)) => Some(RuntimeValue::Number(-n)),
+                    _ => None,
+                });
+            }
+            return Err(EngineError::InvalidExpression {
+                evaluation_name: evaluation.name.clone(),
+                expression: expr.to_string(),
+                message: "not a field, reference, literal or function call".to_string(),
+            });
+        };
+
+        let left = self.expression(evaluation, left)?;
+        let right = self.expression(evaluation, right)?;
+        let (Some(RuntimeValue::Number(left)), Some(RuntimeValue::Number(right))) = (left, right)
+        else {
+            return Ok(None);
+        };
+        let result = match operator {
+            '+' => left + right,
+            '-' => left - right,
+            '*' => left * right,
+            _ if right == 0.0 => return Ok(None),
+            _ => left / right,
+        };
+        Ok(Some(RuntimeValue::Number(result)))
+    }
+
+    fn datetime(&self, expr: &str) -> Result<Option<RuntimeValue>, String> {
+        let parsed = DateTimeExpr::parse(expr)?;
+        let event = self.event;
+        let field = |path: &str| event_field(event, path).map(json_text);
+        // Missing or malformed datetime fields make the comparison not fire
+        if parsed
+            .fields()
+            .iter()
+            .any(|path| field(path).as_deref().and_then(parse_timestamp).is_none())
+        {
+            return Ok(None);
+        }
+        let context = DateTimeContext {
+            now: self.now,
+            field: &field,
+            timezones: &self.engine.timezones,
+            calendars: &self.engine.calendars,
+        };
+        let value = parsed.evaluate(&context)?;
+        Ok(Some(match value {
+            DateTimeValue::DateTime(datetime) => RuntimeValue::DateTime(datetime),
+            DateTimeValue::Text(text) => RuntimeValue::Text(text),
+            DateTimeValue::Number(number) => RuntimeValue::Number(number as f64),
+            DateTimeValue::Bool(b) => RuntimeValue::Bool(b),
+        }))
+    }
+
+    fn field(&self, path: &str) -> Option<RuntimeValue> {
+        event_field(self.event, path).and_then(RuntimeValue::from_json)
+    }
+}
+
+fn datetime(value: RuntimeValue) -> Option<DateTime<Utc>> {
+    match value {
+        RuntimeValue::DateTime(datetime) => Some(datetime),
+        RuntimeValue::Text(text) => parse_timestamp(&text),
+        _ => None,
+    }
+}
+
+fn json_text(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use serde_json::json;
+
+    const RULE: &str = r#"{
+  "model_id": "E001",
+  "name": "Engine",
+  "threshold": 0.6,
+  "evaluations": [
+    {"name": "Large_Amount", "type": "comparison", "left": "transaction.amount", "operator": ">", "right": "@Average_Amount * 3", "weight": 3},
+    {"name": "Risky_Country", "type": "comparison", "left": "geo_location", "operator": "IN", "right": ["NG", "RU"], "weight": 2},
+    {"name": "Gift_Card", "type": "comparison", "left": "merchant.name", "operator": "LIKE", "right": "%gift%"},
+    {"name": "New_Account", "type": "comparison", "left": "timediff(now, profile.account_created)", "operator": "<=", "right": "30 days"},
+    {"name": "Night_Time", "type": "comparison", "left": "hour_of_day(transaction.timestamp)", "operator": "<", "right": 6},
+    {"name": "Recent", "type": "comparison", "left": "transaction.timestamp", "operator": ">=", "right": "datetime(now, '-1 hour')"},
+    {"name": "Average_Amount", "type": "aggregation", "aggregation": "AVG", "field": "amount"},
+    {"name": "Suspicious_Account", "type": "logical", "operator": "AND", "operands": ["New_Account", "Gift_Card"], "weight": 5}
+  ],
+  "actions": [{"type": "block_transaction", "reason": "Test"}]
+}"#;
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new(lrol_parser::parse_str(RULE).unwrap());
+        engine.set_clock(FixedClock::at("2024-06-01T03:30:00Z").unwrap());
+        engine
+    }
+
+    fn event() -> JsonValue {
+        json!({
+            "transaction": {"amount": 900, "timestamp": "2024-06-01T03:00:00Z"},
+            "geo_location": "GB",
+            "merchant": {"name": "Acme gift cards"},
+            "profile": {"account_created": "2024-05-20"}
+        })
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let aggregates = BTreeMap::from([("Average_Amount".to_string(), 100.0)]);
+        let decision = engine()
+            .evaluate_with_aggregates(&event(), &aggregates)
+            .unwrap();
+
+        let fired: Vec<&str> = decision
+            .evaluations
+            .iter()
+            .filter(|result| result.value.is_truthy())
+            .map(|result| result.name.as_str())
+            .collect();
+        assert_eq!(
+            fired,
+            vec![
+                "Large_Amount",
+                "Gift_Card",
+                "New_Account",
+                "Night_Time",
+                "Recent",
+                "Suspicious_Account"
+            ]
+        );
+        assert_eq!(
+            decision.evaluation("Average_Amount"),
+            Some(&RuntimeValue::Number(100.0))
+        );
+        assert_eq!(decision.score, 0.8);
+        assert!(decision.triggered);
+        assert_eq!(decision.actions[0].action_type, "block_transaction");
+        assert_eq!(decision.evaluated_at, "2024-06-01T03:30:00Z");
+
+        // Missing fields do not fire, and a lower score leaves the rule untriggered
+        let decision = engine()
+            .evaluate_with_aggregates(&json!({"geo_location": "NG"}), &aggregates)
+            .unwrap();
+        assert_eq!(decision.score, 0.2);
+        assert!(!decision.triggered);
+        assert!(decision.actions.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_datetime_does_not_fire() {
+        let aggregates = BTreeMap::from([("Average_Amount".to_string(), 100.0)]);
+        let mut event = event();
+        event["transaction"]["timestamp"] = json!("yesterday");
+        event["profile"]["account_created"] = json!(42);
+
+        let decision = engine()
+            .evaluate_with_aggregates(&event, &aggregates)
+            .unwrap();
+        for name in ["Night_Time", "Recent", "New_Account"] {
+            assert!(
+                !decision.evaluation(name).unwrap().is_truthy(),
+                "{} fired",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_negative_operands() {
+        let rule = r#"{
+  "model_id": "E002",
+  "name": "Negative Operands",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "Balance", "type": "aggregation", "aggregation": "SUM", "field": "amount"},
+    {"name": "Scaled", "type": "comparison", "left": "@Balance * -2", "operator": "==", "right": 50, "weight": 1},
+    {"name": "Offset", "type": "comparison", "left": "amount - -5", "operator": "==", "right": 15, "weight": 1},
+    {"name": "Negated", "type": "comparison", "left": "-@Balance", "operator": ">", "right": 20, "weight": 1}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Test"}]
+}"#;
+        let aggregates = BTreeMap::from([("Balance".to_string(), -25.0)]);
+        let decision = Engine::new(lrol_parser::parse_str(rule).unwrap())
+            .evaluate_with_aggregates(&json!({"amount": 10}), &aggregates)
+            .unwrap();
+
+        for name in ["Scaled", "Offset", "Negated"] {
+            assert_eq!(
+                decision.evaluation(name),
+                Some(&RuntimeValue::Bool(true)),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_model_scoring() {
+        let aggregates = BTreeMap::from([("Average_Amount".to_string(), 100.0)]);
+        let event = json!({"geo_location": "NG"});
+
+        let mut model = lrol_parser::parse_str(RULE).unwrap();
+        model.scoring = Some("max_weight".to_string());
+        let mut engine = Engine::new(model);
+        engine.set_scoring_formula(ScoringFormula::NoisyOr);
+        let decision = engine
+            .evaluate_with_aggregates(&event, &aggregates)
+            .unwrap();
+        assert_eq!(decision.scoring, ScoringFormula::MaxWeight);
+        assert_eq!(decision.score, 0.4);
+
+        engine.model.scoring = None;
+        let decision = engine
+            .evaluate_with_aggregates(&event, &aggregates)
+            .unwrap();
+        assert_eq!(decision.scoring, ScoringFormula::NoisyOr);
+        assert!((decision.score - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_library_rules_trigger() {
+        // Weighted aggregations must not keep a rule below its threshold
+        let rules = [
+            (
+                include_str!("../../../Library/R005.json"),
+                json!({
+                    "amount": 500,
+                    "profile": {"amountThreshold": 100, "dailyVolumeLimit": 1000}
+                }),
+                ("Daily_Volume_Limit_Check", 5000.0),
+            ),
+            (
+                include_str!("../../../Library/R012.json"),
+                json!({
+                    "profile": {"account_created": "2024-05-20", "last_active": "2023-01-01"}
+                }),
+                ("High_Transaction_Volume_Check", 25000.0),
+            ),
+        ];
+
+        for (rule, event, (aggregation, value)) in rules {
+            let model = lrol_parser::parse_str(rule).unwrap();
+            let analysis =
+                lrol_analyzer::scoring::analyze_scoring(&model, ScoringFormula::default()).unwrap();
+            let mut engine = Engine::new(model);
+            engine.set_clock(FixedClock::at("2024-06-01T03:30:00Z").unwrap());
+            let aggregates = BTreeMap::from([(aggregation.to_string(), value)]);
+            let decision = engine
+                .evaluate_with_aggregates(&event, &aggregates)
+                .unwrap();
+
+            assert!(decision.triggered, "{} did not trigger", decision.model_id);
+            assert_eq!(decision.score, 1.0);
+            assert_eq!(decision.score, analysis.max_score);
+        }
+    }
+
+    #[test]
+    fn test_clock() {
+        let aggregates = BTreeMap::from([("Average_Amount".to_string(), 100.0)]);
+        let mut engine = engine();
+        engine.set_clock(FixedClock::at("2024-08-01T12:00:00Z").unwrap());
+        let decision = engine
+            .evaluate_with_aggregates(&event(), &aggregates)
+            .unwrap();
+        assert_eq!(
+            decision.evaluation("New_Account"),
+            Some(&RuntimeValue::Bool(false))
+        );
+        assert_eq!(
+            decision.evaluation("Recent"),
+            Some(&RuntimeValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            engine().evaluate(&event()),
+            Err(EngineError::MissingAggregate {
+                evaluation_name: "Average_Amount".to_string()
+            })
+        );
+
+        let model = lrol_parser::parse_str(
+            r#"{
+  "model_id": "E002",
+  "name": "Loop",
+  "threshold": 0.5,
+  "evaluations": [
+    {"name": "A", "type": "logical", "operator": "OR", "operands": ["B"], "weight": 1},
+    {"name": "B", "type": "comparison", "left": "@A", "operator": "==", "right": true}
+  ],
+  "actions": [{"type": "flag_transaction", "reason": "Test"}]
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Engine::new(model).evaluate(&json!({})),
+            Err(EngineError::CircularReference {
+                chain: vec!["A".to_string(), "B".to_string(), "A".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_event_field() {
+        let event = json!({"profile": {"country": "NG"}, "card.bin": "411111", "empty": null});
+        assert_eq!(event_field(&event, "profile.country"), Some(&json!("NG")));
+        assert_eq!(event_field(&event, "card.bin"), Some(&json!("411111")));
+        assert_eq!(event_field(&event, "empty"), None);
+        assert_eq!(event_field(&event, "profile.city"), None);
+    }
+}
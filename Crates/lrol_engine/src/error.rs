@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// Reasons a rule cannot be evaluated against an event.
+///
+/// Event data that does not fit a comparison, such as a missing field, is not
+/// an error: the comparison simply does not fire.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    /// An operand or `@` reference names an evaluation the rule does not define
+    UnknownEvaluation { name: String },
+    /// Evaluations reference each other in a loop
+    CircularReference { chain: Vec<String> },
+    /// The evaluation's type or operator has no runtime semantics
+    Unsupported {
+        evaluation_name: String,
+        message: String,
+    },
+    /// An aggregation was evaluated without a value for it
+    MissingAggregate { evaluation_name: String },
+    /// An expression could not be evaluated, e.g. an unknown business calendar
+    InvalidExpression {
+        evaluation_name: String,
+        expression: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::UnknownEvaluation { name } => {
+                write!(f, "Unknown evaluation '{}'", name)
+            }
+            EngineError::CircularReference { chain } => {
+                write!(f, "Circular reference: {}", chain.join(" -> "))
+            }
+            EngineError::Unsupported {
+                evaluation_name,
+                message,
+            } => write!(f, "Cannot evaluate {}: {}", evaluation_name, message),
+            EngineError::MissingAggregate { evaluation_name } => write!(
+                f,
+                "No value was provided for aggregation {}",
+                evaluation_name
+            ),
+            EngineError::InvalidExpression {
+                evaluation_name,
+                expression,
+                message,
+            } => write!(
+                f,
+                "Invalid expression '{}' in {}: {}",
+                expression, evaluation_name, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
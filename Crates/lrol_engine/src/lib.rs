@@ -0,0 +1,9 @@
+pub mod clock;
+pub mod engine;
+pub mod error;
+pub mod value;
+
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use engine::{Decision, Engine, EvaluationResult};
+pub use error::EngineError;
+pub use value::RuntimeValue;
@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use lrol_analyzer::datetime::{parse_timestamp, Duration};
+use lrol_analyzer::state_cost::duration_seconds;
+use serde::{Serialize, Serializer};
+use serde_json::Value as JsonValue;
+
+/// Value of an operand or evaluation while a rule runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    DateTime(DateTime<Utc>),
+    /// Length of time in seconds, as `timediff` returns it
+    Duration(f64),
+    List(Vec<RuntimeValue>),
+}
+
+impl RuntimeValue {
+    /// Converts an event value; null and objects have no runtime value
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Bool(b) => Some(RuntimeValue::Bool(*b)),
+            JsonValue::Number(n) => n.as_f64().map(RuntimeValue::Number),
+            JsonValue::String(s) => Some(RuntimeValue::Text(s.clone())),
+            JsonValue::Array(items) => Some(RuntimeValue::List(
+                items.iter().filter_map(RuntimeValue::from_json).collect(),
+            )),
+            JsonValue::Null | JsonValue::Object(_) => None,
+        }
+    }
+
+    /// Converts a literal written in the rule
+    pub fn from_literal(value: &lrol_parser::Value) -> Option<Self> {
+        match value {
+            lrol_parser::Value::Bool(b) => Some(RuntimeValue::Bool(*b)),
+            lrol_parser::Value::Number(n) => Some(RuntimeValue::Number(*n)),
+            lrol_parser::Value::String(s) => Some(RuntimeValue::Text(s.clone())),
+            lrol_parser::Value::Array(items) => Some(RuntimeValue::List(
+                items
+                    .iter()
+                    .filter_map(RuntimeValue::from_literal)
+                    .collect(),
+            )),
+            lrol_parser::Value::Object(_) => None,
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            RuntimeValue::Bool(b) => JsonValue::from(*b),
+            RuntimeValue::Number(n) | RuntimeValue::Duration(n) => JsonValue::from(*n),
+            RuntimeValue::Text(s) => JsonValue::from(s.clone()),
+            RuntimeValue::DateTime(datetime) => {
+                JsonValue::from(datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            }
+            RuntimeValue::List(items) => {
+                JsonValue::Array(items.iter().map(RuntimeValue::to_json).collect())
+            }
+        }
+    }
+
+    /// Whether the value counts as firing when a logical evaluation combines it
+    pub fn is_truthy(&self) -> bool {
+        matches!(self, RuntimeValue::Bool(true))
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            RuntimeValue::Number(n) => Some(*n),
+            RuntimeValue::Text(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            RuntimeValue::DateTime(datetime) => Some(*datetime),
+            RuntimeValue::Text(s) => parse_timestamp(s),
+            _ => None,
+        }
+    }
+
+    // Durations compare with `30 days` as well as with a number of seconds
+    fn as_seconds(&self) -> Option<f64> {
+        match self {
+            RuntimeValue::Duration(seconds) | RuntimeValue::Number(seconds) => Some(*seconds),
+            RuntimeValue::Text(s) => Duration::parse(s).ok().map(|d| duration_seconds(&d)),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for RuntimeValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+/// Comparison operator of a comparison evaluation or aggregation condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    NotIn,
+    Like,
+    NotLike,
+}
+
+impl Operator {
+    pub fn parse(operator: &str) -> Option<Operator> {
+        match operator.trim() {
+            "==" => Some(Operator::Eq),
+            "!=" => Some(Operator::Ne),
+            "<" => Some(Operator::Lt),
+            "<=" => Some(Operator::Le),
+            ">" => Some(Operator::Gt),
+            ">=" => Some(Operator::Ge),
+            "IN" => Some(Operator::In),
+            "NOT IN" => Some(Operator::NotIn),
+            "LIKE" => Some(Operator::Like),
+            "NOT LIKE" => Some(Operator::NotLike),
+            _ => None,
+        }
+    }
+
+    /// Applies the operator. Values that cannot be compared, such as text
+    /// ordered against a number, never satisfy it, whatever the operator.
+    pub fn apply(self, left: &RuntimeValue, right: &RuntimeValue) -> bool {
+        match self {
+            Operator::Eq => equals(left, right) == Some(true),
+            Operator::Ne => equals(left, right) == Some(false),
+            Operator::Lt => order(left, right) == Some(Ordering::Less),
+            Operator::Le => order(left, right).is_some_and(Ordering::is_le),
+            Operator::Gt => order(left, right) == Some(Ordering::Greater),
+            Operator::Ge => order(left, right).is_some_and(Ordering::is_ge),
+            Operator::In | Operator::NotIn => {
+                let RuntimeValue::List(items) = right else {
+                    return false;
+                };
+                let found = items.iter().any(|item| equals(left, item) == Some(true));
+                found == (self == Operator::In)
+            }
+            Operator::Like | Operator::NotLike => match (left, right) {
+                (RuntimeValue::Text(text), RuntimeValue::Text(pattern)) => {
+                    like(text, pattern) == (self == Operator::Like)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+fn equals(left: &RuntimeValue, right: &RuntimeValue) -> Option<bool> {
+    match (left, right) {
+        (RuntimeValue::Bool(a), RuntimeValue::Bool(b)) => Some(a == b),
+        (RuntimeValue::Text(a), RuntimeValue::Text(b)) => Some(a == b),
+        (RuntimeValue::List(a), RuntimeValue::List(b)) => Some(a == b),
+        _ => order(left, right).map(Ordering::is_eq),
+    }
+}
+
+fn order(left: &RuntimeValue, right: &RuntimeValue) -> Option<Ordering> {
+    match (left, right) {
+        (RuntimeValue::Text(a), RuntimeValue::Text(b)) => Some(a.cmp(b)),
+        (RuntimeValue::DateTime(_), _) | (_, RuntimeValue::DateTime(_)) => {
+            Some(left.as_datetime()?.cmp(&right.as_datetime()?))
+        }
+        (RuntimeValue::Duration(_), _) | (_, RuntimeValue::Duration(_)) => {
+            left.as_seconds()?.partial_cmp(&right.as_seconds()?)
+        }
+        (RuntimeValue::Number(_), _) | (_, RuntimeValue::Number(_)) => {
+            left.as_number()?.partial_cmp(&right.as_number()?)
+        }
+        _ => None,
+    }
+}
+
+/// SQL `LIKE` matching: `%` matches any run of characters and `_` any one
+/// character, case-sensitively
+pub fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    // matches[j]: whether the text read so far matches the first j pattern characters
+    let mut matches = vec![false; pattern.len() + 1];
+    matches[0] = true;
+    for j in 1..=pattern.len() {
+        matches[j] = matches[j - 1] && pattern[j - 1] == '%';
+    }
+    for c in text {
+        let mut next = vec![false; pattern.len() + 1];
+        for j in 1..=pattern.len() {
+            next[j] = match pattern[j - 1] {
+                '%' => next[j - 1] || matches[j],
+                '_' => matches[j - 1],
+                p => matches[j - 1] && p == c,
+            };
+        }
+        matches = next;
+    }
+    matches[pattern.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> RuntimeValue {
+        RuntimeValue::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_operators() {
+        let number = RuntimeValue::Number(150.0);
+        assert!(Operator::Gt.apply(&number, &RuntimeValue::Number(100.0)));
+        assert!(Operator::Eq.apply(&number, &text("150")));
+        assert!(!Operator::Lt.apply(&number, &text("abc")));
+        assert!(!Operator::Ne.apply(&number, &RuntimeValue::Bool(true)));
+
+        let countries = RuntimeValue::List(vec![text("NG"), text("GB")]);
+        assert!(Operator::In.apply(&text("GB"), &countries));
+        assert!(Operator::NotIn.apply(&text("US"), &countries));
+        assert!(!Operator::NotIn.apply(&text("US"), &text("NG")));
+
+        let created = text("2024-03-01T12:00:00Z");
+        assert!(Operator::Ge.apply(&created, &text("2024-03-01")));
+        let age = RuntimeValue::Duration(86_400.0 * 10.0);
+        assert!(Operator::Le.apply(&age, &text("30 days")));
+        assert!(Operator::Gt.apply(&age, &RuntimeValue::Number(3_600.0)));
+
+        assert_eq!(Operator::parse("NOT LIKE"), Some(Operator::NotLike));
+        assert_eq!(Operator::parse("=<"), None);
+    }
+
+    #[test]
+    fn test_like() {
+        assert!(like("2024-03-01", "2024%"));
+        assert!(like("card", "c_rd"));
+        assert!(like("", "%"));
+        assert!(like("a%b", "a%b"));
+        assert!(!like("Card", "card"));
+        assert!(!like("cards", "c_rd"));
+        assert!(Operator::NotLike.apply(&text("gift card"), &text("%crypto%")));
+    }
+}
@@ -45,16 +45,25 @@ impl fmt::Display for ParserError {
     }
 }
 
+impl ParserError {
+    /// Stable diagnostic code, explained by `lrol explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserError::InvalidSyntax { .. } => "LROL0001",
+            ParserError::MissingField { .. } => "LROL0002",
+            ParserError::InvalidValue { .. } => "LROL0003",
+        }
+    }
+}
+
 impl std::error::Error for ParserError {}
 
 pub type ParserResult<T> = Result<T, ParserError>;
 
-
 pub(crate) fn get_error_position(full_input: &str, error_input: &str) -> (usize, usize) {
     // Calculate the offset where the error occurred
     let offset = full_input.len() - error_input.len();
     let mut line = 1;
-    let mut column = 1;
     let mut current_line_start = 0;
 
     // Count lines and track last line start
@@ -65,7 +74,7 @@ pub(crate) fn get_error_position(full_input: &str, error_input: &str) -> (usize,
         }
     }
 
-    column = offset - current_line_start + 1;
+    let column = offset - current_line_start + 1;
 
     (line, column)
 }
@@ -103,13 +112,15 @@ pub fn syntax_error<'a, T>(
     }))
 }
 
-pub (crate) const INVALID_EVAL_TYPE: &'static str = "Invalid evaluation type";
-pub (crate) const INVALID_LOGICAL_OP: &'static str = "Invalid logical operator, expected 'AND' or 'OR'";
-pub (crate) const INVALID_OPERANDS: &'static str = "Operands must be an array of strings";
-pub (crate) const INVALID_WEIGHT: &'static str = "Weight must be a number";
-pub (crate) const MISSING_NAME: &'static str = "Missing required field 'name'";
-pub (crate) const MISSING_TYPE: &'static str = "Missing required field 'type'";
-pub (crate) const MISSING_OPERANDS: &'static str = "Logical evaluation requires 'operands' field";
-pub (crate) const MISSING_OPERATOR: &'static str = "Logical evaluation requires 'operator' field";
-pub (crate) const MISSING_LEFT: &'static str = "Comparison evaluation requires 'left' field";
-pub (crate) const MISSING_RIGHT: &'static str = "Comparison evaluation requires 'right' field";
+pub(crate) const INVALID_EVAL_TYPE: &str = "Invalid evaluation type";
+pub(crate) const INVALID_OPERANDS: &str = "Operands must be an array of strings";
+pub(crate) const INVALID_CONDITIONS: &str = "Conditions must be an object or an array of objects";
+pub(crate) const INVALID_WEIGHT: &str = "Weight must be a number";
+pub(crate) const INVALID_AGGREGATION: &str =
+    "Invalid aggregation, expected one of SUM, COUNT, AVG, MIN, MAX, STDDEV";
+pub(crate) const MISSING_NAME: &str = "Missing required field 'name'";
+pub(crate) const MISSING_TYPE: &str = "Missing required field 'type'";
+pub(crate) const MISSING_OPERANDS: &str = "Logical evaluation requires 'operands' field";
+pub(crate) const MISSING_OPERATOR: &str = "Logical evaluation requires 'operator' field";
+pub(crate) const MISSING_LEFT: &str = "Comparison evaluation requires 'left' field";
+pub(crate) const MISSING_RIGHT: &str = "Comparison evaluation requires 'right' field";
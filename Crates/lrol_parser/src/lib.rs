@@ -1,5 +1,6 @@
-pub mod parser;
 pub mod error;
+pub mod parser;
+pub mod span;
 pub mod types;
 
 use std::fs::File;
@@ -8,7 +9,8 @@ use std::path::Path;
 
 pub use error::{ParserError, ParserResult};
 pub use parser::LrolParser;
-pub use types::{Action, Evaluation, EvaluationType, Value};
+pub use span::{SourceMap, Span};
+pub use types::{Action, Condition, Evaluation, EvaluationType, TimezoneSpec, Value};
 
 /// Parses LROL content from a string
 pub fn parse_str(content: &str) -> ParserResult<parser::LrolModel> {
@@ -22,13 +24,14 @@ pub fn parse_file<P: AsRef<Path>>(path: P) -> ParserResult<parser::LrolModel> {
         column: 0,
         message: format!("Failed to open file: {}", e),
     })?;
-    
+
     let mut content = String::new();
-    file.read_to_string(&mut content).map_err(|e| ParserError::InvalidSyntax {
-        line: 0,
-        column: 0,
-        message: format!("Failed to read file: {}", e),
-    })?;
-    
+    file.read_to_string(&mut content)
+        .map_err(|e| ParserError::InvalidSyntax {
+            line: 0,
+            column: 0,
+            message: format!("Failed to read file: {}", e),
+        })?;
+
     parse_str(&content)
-}
\ No newline at end of file
+}
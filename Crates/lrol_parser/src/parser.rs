@@ -1,4 +1,4 @@
-
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 use nom::bytes::complete::tag;
@@ -7,19 +7,19 @@ use nom::{
     bytes::complete::take_while1,
     character::complete::{char, digit1, multispace0},
     combinator::{map, map_res, opt, recognize},
-    error::{context, convert_error,  VerboseError},
+    error::{context, convert_error, VerboseError},
     multi::separated_list0,
     sequence::{delimited, pair, tuple},
     Err as NomErr, IResult,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::types::{Aggregation, Metadata};
+use crate::types::{Aggregation, Condition, Metadata, TimezoneSpec};
 use crate::{
     error::{
-        convert_nom_error, syntax_error, ParserError, INVALID_EVAL_TYPE,
-        INVALID_OPERANDS, INVALID_WEIGHT, MISSING_LEFT, MISSING_NAME, MISSING_OPERANDS,
-        MISSING_OPERATOR, MISSING_RIGHT, MISSING_TYPE,
+        convert_nom_error, syntax_error, ParserError, INVALID_AGGREGATION, INVALID_CONDITIONS,
+        INVALID_EVAL_TYPE, INVALID_OPERANDS, INVALID_WEIGHT, MISSING_LEFT, MISSING_NAME,
+        MISSING_OPERANDS, MISSING_OPERATOR, MISSING_RIGHT, MISSING_TYPE,
     },
     types::{Action, Evaluation, EvaluationType, Value},
 };
@@ -33,8 +33,20 @@ pub struct LrolModel {
     pub evaluations: Vec<Evaluation>,
     pub actions: Vec<Action>,
     pub metadata: Option<Metadata>,
+    /// Inline declaration of the fields available on incoming events
+    pub event_schema: Option<Value>,
+    /// Timezone local times are read in, unless a field has its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<TimezoneSpec>,
+    /// Timezones of individual datetime fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field_timezones: Option<BTreeMap<String, TimezoneSpec>>,
+    /// Strategy combining evaluation weights into the score compared with `threshold`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<String>,
 }
 
+#[derive(Default)]
 pub struct LrolParser;
 
 impl LrolParser {
@@ -74,6 +86,10 @@ impl LrolParser {
         let mut evaluations = None;
         let mut actions = None;
         let mut metadata = None;
+        let mut event_schema = None;
+        let mut timezone = None;
+        let mut field_timezones = None;
+        let mut scoring = None;
 
         loop {
             let (new_input, _) = multispace0(input)?;
@@ -138,7 +154,32 @@ impl LrolParser {
                             if let Value::Object(v) = value {
                                 let mut _metadata = Metadata::default();
                                 for (key, value) in v {
-                                    if let Value::String(val) = value {
+                                    if key == "allow_lints" {
+                                        match value {
+                                            Value::Array(items) => {
+                                                let lints = items
+                                                    .into_iter()
+                                                    .map(|item| match item {
+                                                        Value::String(lint) => Some(lint),
+                                                        _ => None,
+                                                    })
+                                                    .collect::<Option<Vec<_>>>();
+                                                match lints {
+                                                    Some(lints) => _metadata.allow_lints = Some(lints),
+                                                    None => {
+                                                        return syntax_error(
+                                                            new_input,
+                                                            "Invalid allow_lints: expected array of strings",
+                                                        )
+                                                    }
+                                                }
+                                            }
+                                            _ => return syntax_error(
+                                                new_input,
+                                                "Invalid allow_lints: expected array of strings",
+                                            ),
+                                        }
+                                    } else if let Value::String(val) = value {
                                         match key.clone().as_str() {
                                             "created_by" => _metadata.created_by = Some(val),
                                             "created_at" => _metadata.created_at = Some(val),
@@ -149,7 +190,7 @@ impl LrolParser {
                                                 return syntax_error(
                                                     new_input,
                                                     "Invalid metadata field",
-                                                )
+                                                );
                                             }
                                         }
                                     }
@@ -162,6 +203,53 @@ impl LrolParser {
                                 );
                             }
                         }
+                        "event_schema" => {
+                            if let Value::Object(_) = value {
+                                event_schema = Some(value);
+                            } else {
+                                return syntax_error(
+                                    new_input,
+                                    "Invalid event_schema type: expected object",
+                                );
+                            }
+                        }
+                        "timezone" => match Self::parse_timezone_spec(value) {
+                            Some(spec) => timezone = Some(spec),
+                            None => return syntax_error(
+                                new_input,
+                                "Invalid timezone: expected a zone name or an object with a field",
+                            ),
+                        },
+                        "field_timezones" => {
+                            let specs = match value {
+                                Value::Object(fields) => fields
+                                    .into_iter()
+                                    .map(|(field, spec)| {
+                                        Some((field, Self::parse_timezone_spec(spec)?))
+                                    })
+                                    .collect::<Option<BTreeMap<_, _>>>(),
+                                _ => None,
+                            };
+                            match specs {
+                                Some(specs) => field_timezones = Some(specs),
+                                None => {
+                                    return syntax_error(
+                                        new_input,
+                                        "Invalid field_timezones: expected an object of timezones",
+                                    )
+                                }
+                            }
+                        }
+                        "scoring" => {
+                            if let Value::String(v) = value {
+                                scoring = Some(v)
+                            } else {
+                                return syntax_error(
+                                    new_input,
+                                    "Invalid scoring: expected a strategy name",
+                                );
+                            }
+                        }
                         _ => {}
                     }
 
@@ -188,10 +276,47 @@ impl LrolParser {
                 evaluations: Self::parse_evaluations_array(input, evaluations.unwrap_or_default()),
                 actions: Self::parse_actions_array(actions.unwrap_or_default()),
                 metadata,
+                event_schema,
+                timezone,
+                field_timezones,
+                scoring,
             },
         ))
     }
 
+    // `"Europe/Paris"` or `{"field": "merchant_country", "zones": {...}, "default": "UTC"}`
+    fn parse_timezone_spec(value: Value) -> Option<TimezoneSpec> {
+        let entries = match value {
+            Value::String(zone) => return Some(TimezoneSpec::Zone(zone)),
+            Value::Object(entries) => entries,
+            _ => return None,
+        };
+
+        let mut field = None;
+        let mut zones = BTreeMap::new();
+        let mut default = None;
+        for (key, value) in entries {
+            match (key.as_str(), value) {
+                ("field", Value::String(name)) => field = Some(name),
+                ("default", Value::String(zone)) => default = Some(zone),
+                ("zones", Value::Object(entries)) => {
+                    for (key, zone) in entries {
+                        match zone {
+                            Value::String(zone) => zones.insert(key, zone),
+                            _ => return None,
+                        };
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(TimezoneSpec::Field {
+            field: field?,
+            zones,
+            default,
+        })
+    }
+
     // Parse a single field
     fn parse_field(input: &str) -> IResult<&str, (&str, Value), VerboseError<&str>> {
         let (input, key) = delimited(char('"'), take_while1(|c| c != '"'), char('"'))(input)?;
@@ -292,7 +417,7 @@ impl LrolParser {
             .into_iter()
             .filter_map(|v| {
                 if let Value::Object(fields) = v {
-                    if let Some((_, ev)) = Self::parse_evaluation_from_fields(input, &fields).ok() {
+                    if let Ok((_, ev)) = Self::parse_evaluation_from_fields(input, &fields) {
                         Some(ev)
                     } else {
                         None
@@ -317,6 +442,8 @@ impl LrolParser {
         let mut operands = None;
         let mut weight = None;
         let mut aggregation = None;
+        let mut field = None;
+        let mut conditions = None;
 
         for (key, value) in fields {
             match (key.as_str(), value) {
@@ -331,8 +458,11 @@ impl LrolParser {
                     operator = Some(v.clone());
                 }
                 ("aggregation", Value::String(v)) => {
-                    aggregation =
-                        Some(Aggregation::from_str(v).expect("expected valid aggregation type"));
+                    // Aggregations are canonically upper case; the analyzer flags others
+                    aggregation = match Aggregation::from_str(&v.to_ascii_uppercase()) {
+                        Ok(a) => Some(a),
+                        Err(_) => return syntax_error(current_input, INVALID_AGGREGATION),
+                    };
                 }
                 ("operands", Value::Array(arr)) => {
                     operands = match arr.iter().try_fold(Vec::new(), |mut acc, v| {
@@ -347,6 +477,23 @@ impl LrolParser {
                         Err(_) => return syntax_error(current_input, INVALID_OPERANDS),
                     };
                 }
+                ("field", Value::String(v)) => field = Some(v.clone()),
+                ("conditions", Value::Object(v)) => {
+                    conditions = Some(vec![Self::parse_condition_from_fields(v)]);
+                }
+                ("conditions", Value::Array(arr)) => {
+                    conditions = match arr.iter().try_fold(Vec::new(), |mut acc, v| {
+                        if let Value::Object(v) = v {
+                            acc.push(Self::parse_condition_from_fields(v));
+                            Ok(acc)
+                        } else {
+                            Err(())
+                        }
+                    }) {
+                        Ok(conds) => Some(conds),
+                        Err(_) => return syntax_error(current_input, INVALID_CONDITIONS),
+                    };
+                }
                 ("left", Value::String(v)) => left = Some(v.clone()),
                 ("right", v) => right = Some(v.clone()),
                 ("weight", Value::Number(v)) => weight = Some(*v as i32),
@@ -400,11 +547,30 @@ impl LrolParser {
                 operands,
                 weight,
                 aggregation,
+                field,
+                conditions,
             },
         ))
     }
 
+    // Parse an aggregation condition from fields
+    fn parse_condition_from_fields(fields: &[(String, Value)]) -> Condition {
+        let mut condition = Condition::default();
+
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("left", Value::String(v)) => condition.left = Some(v.clone()),
+                ("operator", Value::String(v)) => condition.operator = Some(v.clone()),
+                ("right", v) => condition.right = Some(v.clone()),
+                _ => {}
+            }
+        }
+
+        condition
+    }
+
     // Helper method to parse a single evaluation
+    #[cfg(test)]
     fn parse_single_evaluation(input: &str) -> IResult<&str, Evaluation, VerboseError<&str>> {
         let (remaining, fields) = context("evaluation object", Self::parse_object_value)(input)?;
 
@@ -630,6 +796,179 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_parse_aggregation_conditions() {
+        let input = r#"{
+            "model_id": "M502",
+            "name": "Aggregation Model",
+            "threshold": 0.9,
+            "event_schema": { "fields": { "amount": "number" } },
+            "evaluations": [
+                {
+                    "name": "Single_Condition",
+                    "type": "aggregation",
+                    "aggregation": "COUNT",
+                    "field": "amount",
+                    "conditions": { "type": "comparison", "left": "amount", "operator": "<", "right": 200 }
+                },
+                {
+                    "name": "Many_Conditions",
+                    "type": "aggregation",
+                    "aggregation": "SUM",
+                    "field": "amount",
+                    "conditions": [
+                        { "left": "amount", "operator": ">", "right": 10 },
+                        { "left": "currency", "operator": "==", "right": "USD" }
+                    ]
+                }
+            ],
+            "actions": []
+        }"#;
+
+        let model = LrolParser::parse(input).unwrap();
+        assert!(matches!(model.event_schema, Some(Value::Object(_))));
+
+        let single = &model.evaluations[0];
+        assert_eq!(single.field.as_deref(), Some("amount"));
+        let conditions = single.conditions.as_ref().unwrap();
+        assert_eq!(conditions.len(), 1);
+        assert_eq!(conditions[0].operator.as_deref(), Some("<"));
+        assert_eq!(conditions[0].right, Some(Value::Number(200.0)));
+
+        let many = model.evaluations[1].conditions.as_ref().unwrap();
+        assert_eq!(many.len(), 2);
+        assert_eq!(many[1].left.as_deref(), Some("currency"));
+    }
+
+    #[test]
+    fn test_parse_aggregation_case() {
+        let input = r#"{
+            "model_id": "M505",
+            "name": "Test Model",
+            "threshold": 0.9,
+            "evaluations": [
+                {"name": "Total", "type": "aggregation", "aggregation": "sum", "field": "amount"},
+                {"name": "Median", "type": "aggregation", "aggregation": "MEDIAN", "field": "amount"}
+            ]
+        }"#;
+
+        let model = LrolParser::parse(input).unwrap();
+        assert_eq!(model.evaluations.len(), 1);
+        assert_eq!(model.evaluations[0].aggregation, Some(Aggregation::SUM));
+    }
+
+    #[test]
+    fn test_parse_invalid_event_schema() {
+        let input = r#"{
+            "model_id": "M503",
+            "name": "Test Model",
+            "threshold": 0.9,
+            "event_schema": "amount: number",
+            "evaluations": []
+        }"#;
+
+        assert!(matches!(
+            LrolParser::parse(input),
+            Err(ParserError::InvalidSyntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_metadata_allow_lints() {
+        let input = r#"{
+            "model_id": "M504",
+            "name": "Test Model",
+            "threshold": 0.9,
+            "evaluations": [],
+            "metadata": {
+                "created_by": "risk-team",
+                "allow_lints": ["magic-number", "naming-convention:amount_check"]
+            }
+        }"#;
+
+        let metadata = LrolParser::parse(input).unwrap().metadata.unwrap();
+        assert_eq!(metadata.created_by.as_deref(), Some("risk-team"));
+        assert_eq!(
+            metadata.allow_lints,
+            Some(vec![
+                "magic-number".to_string(),
+                "naming-convention:amount_check".to_string()
+            ])
+        );
+
+        let invalid = input.replace(
+            r#"["magic-number", "naming-convention:amount_check"]"#,
+            "[1]",
+        );
+        assert!(matches!(
+            LrolParser::parse(&invalid),
+            Err(ParserError::InvalidSyntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_timezones() {
+        let input = r#"{
+            "model_id": "M505",
+            "name": "Test Model",
+            "threshold": 0.9,
+            "evaluations": [],
+            "timezone": "Europe/London",
+            "field_timezones": {
+                "merchant_time": {
+                    "field": "merchant_country",
+                    "zones": {"US": "America/New_York", "FR": "Europe/Paris"},
+                    "default": "UTC"
+                },
+                "settled_at": "UTC"
+            }
+        }"#;
+
+        let model = LrolParser::parse(input).unwrap();
+        assert_eq!(
+            model.timezone,
+            Some(TimezoneSpec::Zone("Europe/London".to_string()))
+        );
+        let field_timezones = model.field_timezones.unwrap();
+        assert_eq!(
+            field_timezones["merchant_time"],
+            TimezoneSpec::Field {
+                field: "merchant_country".to_string(),
+                zones: BTreeMap::from([
+                    ("FR".to_string(), "Europe/Paris".to_string()),
+                    ("US".to_string(), "America/New_York".to_string()),
+                ]),
+                default: Some("UTC".to_string()),
+            }
+        );
+
+        let invalid = input.replace(r#""timezone": "Europe/London""#, r#""timezone": 1"#);
+        assert!(matches!(
+            LrolParser::parse(&invalid),
+            Err(ParserError::InvalidSyntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_scoring() {
+        let input = r#"{
+            "model_id": "M506",
+            "name": "Test Model",
+            "threshold": 0.9,
+            "scoring": "noisy_or",
+            "evaluations": []
+        }"#;
+
+        let model = LrolParser::parse(input).unwrap();
+        assert_eq!(model.scoring.as_deref(), Some("noisy_or"));
+
+        let invalid = input.replace(r#""noisy_or""#, "[]");
+        assert!(matches!(
+            LrolParser::parse(&invalid),
+            Err(ParserError::InvalidSyntax { .. })
+        ));
+    }
+
     #[test]
     fn test_parse_with_syntax_error() {
         let input = r#"{
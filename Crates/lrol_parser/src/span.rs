@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Byte range of a value in the rule source, with the 1-based line and
+/// column of its first character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Positions of every JSON value in a rule source.
+///
+/// Values are keyed by JSON pointer, e.g. `/evaluations/2/right`. Building the
+/// map never fails: scanning stops at the first malformed token and whatever
+/// was located up to that point is kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    spans: HashMap<String, Span>,
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    pub fn from_source(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|(_, b)| *b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+
+        let mut map = SourceMap {
+            spans: HashMap::new(),
+            line_starts,
+        };
+
+        let mut scanner = Scanner {
+            bytes: source.as_bytes(),
+            pos: 0,
+        };
+        let mut ranges = Vec::new();
+        scanner.skip_trivia();
+        let _ = scanner.value(String::new(), &mut ranges);
+
+        for (pointer, start, end) in ranges {
+            let span = map.span(start, end);
+            map.spans.insert(pointer, span);
+        }
+
+        map
+    }
+
+    /// Span for a byte range of the source
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let (line, column) = self.line_col(start);
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// 1-based line and column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        (line + 1, offset - self.line_starts[line] + 1)
+    }
+
+    /// Byte offset of a 1-based line and column, if it lies within the source
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let line_start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let offset = line_start + column.checked_sub(1)?;
+        match self.line_starts.get(line) {
+            Some(&next_line) if offset >= next_line => None,
+            _ => Some(offset),
+        }
+    }
+
+    /// Span of the value at a JSON pointer
+    pub fn get(&self, pointer: &str) -> Option<Span> {
+        self.spans.get(pointer).copied()
+    }
+
+    /// Span of the evaluation at `index`
+    pub fn evaluation(&self, index: usize) -> Option<Span> {
+        self.get(&format!("/evaluations/{}", index))
+    }
+
+    /// Span of a field of the evaluation at `index`.
+    ///
+    /// `field` may address nested values with dots and brackets, e.g.
+    /// `conditions[0].left`. A `[0]` index also matches a single object
+    /// written without the surrounding array.
+    pub fn evaluation_field(&self, index: usize, field: &str) -> Option<Span> {
+        let mut pointer = format!("/evaluations/{}", index);
+        let mut fallback = pointer.clone();
+
+        for part in field.split('.') {
+            let (name, item) = match part.split_once('[') {
+                Some((name, rest)) => (name, rest.strip_suffix(']')),
+                None => (part, None),
+            };
+            pointer.push('/');
+            pointer.push_str(name);
+            fallback.push('/');
+            fallback.push_str(name);
+            if let Some(item) = item {
+                pointer.push('/');
+                pointer.push_str(item);
+                if item != "0" {
+                    fallback.push('/');
+                    fallback.push_str(item);
+                }
+            }
+        }
+
+        self.get(&pointer).or_else(|| self.get(&fallback))
+    }
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    // Skip whitespace and `//` line comments
+    fn skip_trivia(&mut self) {
+        while let Some(b) = self.peek() {
+            if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else if b == b'/' && self.bytes.get(self.pos + 1) == Some(&b'/') {
+                while let Some(b) = self.peek() {
+                    if b == b'\n' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: String, ranges: &mut Vec<(String, usize, usize)>) -> Option<()> {
+        let start = self.pos;
+        match self.peek()? {
+            b'{' => self.object(&pointer, ranges)?,
+            b'[' => self.array(&pointer, ranges)?,
+            b'"' => {
+                self.string()?;
+            }
+            _ => {
+                while let Some(b) = self.peek() {
+                    if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.') {
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                if self.pos == start {
+                    return None;
+                }
+            }
+        }
+        ranges.push((pointer, start, self.pos));
+        Some(())
+    }
+
+    fn object(&mut self, pointer: &str, ranges: &mut Vec<(String, usize, usize)>) -> Option<()> {
+        self.pos += 1;
+        loop {
+            self.skip_trivia();
+            match self.peek()? {
+                b'}' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b',' => {
+                    self.pos += 1;
+                }
+                b'"' => {
+                    let key = self.string()?;
+                    self.skip_trivia();
+                    if self.peek()? != b':' {
+                        return None;
+                    }
+                    self.pos += 1;
+                    self.skip_trivia();
+                    self.value(format!("{}/{}", pointer, key), ranges)?;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &str, ranges: &mut Vec<(String, usize, usize)>) -> Option<()> {
+        self.pos += 1;
+        let mut index = 0;
+        loop {
+            self.skip_trivia();
+            match self.peek()? {
+                b']' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b',' => {
+                    self.pos += 1;
+                }
+                _ => {
+                    self.value(format!("{}/{}", pointer, index), ranges)?;
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            match b {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    let content = String::from_utf8_lossy(&self.bytes[start..self.pos]);
+                    self.pos += 1;
+                    return Some(content.into_owned());
+                }
+                _ => self.pos += 1,
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"{
+    "model_id": "M1",
+    "evaluations": [
+        {
+            "name": "Amount_Check",
+            "left": "amount",
+            "right": 100
+        },
+        {
+            "name": "Count",
+            "conditions": {
+                "left": "industry",
+                "right": ["A", "B"]
+            }
+        }
+    ]
+}"#;
+
+    #[test]
+    fn test_locate_values() {
+        let map = SourceMap::from_source(SOURCE);
+
+        let span = map.get("/model_id").unwrap();
+        assert_eq!(&SOURCE[span.start..span.end], "\"M1\"");
+        assert_eq!((span.line, span.column), (2, 17));
+
+        let span = map.evaluation_field(0, "right").unwrap();
+        assert_eq!(&SOURCE[span.start..span.end], "100");
+        assert_eq!(span.line, 7);
+        assert_eq!(map.offset(span.line, span.column), Some(span.start));
+        assert_eq!(map.offset(2, 200), None);
+    }
+
+    #[test]
+    fn test_locate_nested_condition() {
+        let map = SourceMap::from_source(SOURCE);
+
+        let span = map.evaluation_field(1, "conditions[0].right").unwrap();
+        assert_eq!(&SOURCE[span.start..span.end], "[\"A\", \"B\"]");
+        assert!(map.evaluation_field(1, "conditions[1].right").is_none());
+    }
+
+    #[test]
+    fn test_malformed_source_keeps_prefix() {
+        let map = SourceMap::from_source("{\n \"model_id\": \"M1\",\n \"name\" \"oops\" }");
+        assert!(map.get("/model_id").is_some());
+        assert!(map.get("/name").is_none());
+    }
+}
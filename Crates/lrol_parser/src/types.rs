@@ -1,13 +1,37 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Metadata {
-    pub created_by:Option<String>,
-    pub created_at:Option<String>,
-    pub last_updated:Option<String>,
-    pub notes:Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<String>,
+    pub last_updated: Option<String>,
+    pub notes: Option<String>,
+    /// Lints suppressed for this rule, as `lint-id` or `lint-id:Evaluation_Name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_lints: Option<Vec<String>>,
+}
+
+/// Timezone in which local times, such as the hour of day, are read
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum TimezoneSpec {
+    /// IANA zone name such as `America/New_York`
+    Zone(String),
+    /// Zone chosen per event from a field such as `merchant_country`.
+    ///
+    /// The field's value is looked up in `zones`; without `zones` the field
+    /// holds zone names itself. `default` applies to values with no zone.
+    Field {
+        field: String,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        zones: BTreeMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,6 +44,18 @@ pub struct Evaluation {
     pub operands: Option<Vec<String>>,
     pub weight: Option<i32>,
     pub aggregation: Option<Aggregation>,
+    /// Field aggregated by an aggregation evaluation
+    pub field: Option<String>,
+    /// Filters applied to the events an aggregation evaluation covers
+    pub conditions: Option<Vec<Condition>>,
+}
+
+/// Comparison used to filter the events covered by an aggregation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Condition {
+    pub left: Option<String>,
+    pub operator: Option<String>,
+    pub right: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -46,15 +82,16 @@ impl FromStr for EvaluationType {
     }
 }
 
-impl ToString for EvaluationType {
-    fn to_string(&self) -> String {
-        match self {
-            EvaluationType::Comparison => "comparison".to_string(),
-            EvaluationType::Logical => "logical".to_string(),
-            EvaluationType::Aggregation => "aggregation".to_string(),
-            EvaluationType::TimeBased => "time-based".to_string(),
-            EvaluationType::Conditional => "conditional".to_string(),
-        }
+impl fmt::Display for EvaluationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EvaluationType::Comparison => "comparison",
+            EvaluationType::Logical => "logical",
+            EvaluationType::Aggregation => "aggregation",
+            EvaluationType::TimeBased => "time-based",
+            EvaluationType::Conditional => "conditional",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -84,16 +121,17 @@ impl FromStr for Aggregation {
     }
 }
 
-impl ToString for Aggregation{
-    fn to_string(&self) -> String {
-        match self {
-            Aggregation::SUM => "SUM".to_string(),
-            Aggregation::COUNT => "COUNT".to_string(),
-            Aggregation::AVG => "AVG".to_string(),
-            Aggregation::MIN => "MIN".to_string(),
-            Aggregation::MAX => "MAX".to_string(),
-            Aggregation::STDDEV => "STDDEV".to_string(),
-        }
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Aggregation::SUM => "SUM",
+            Aggregation::COUNT => "COUNT",
+            Aggregation::AVG => "AVG",
+            Aggregation::MIN => "MIN",
+            Aggregation::MAX => "MAX",
+            Aggregation::STDDEV => "STDDEV",
+        };
+        write!(f, "{}", name)
     }
 }
 